@@ -0,0 +1,19 @@
+fn main() {
+    // Links the Node.js module (see the node feature).
+    #[cfg(feature = "node")]
+    napi_build::setup();
+
+    // Generates the gRPC services (see the grpc feature), with the protoc of
+    // protoc-bin-vendored unless PROTOC points to another one.
+    #[cfg(feature = "grpc")]
+    {
+        if std::env::var_os("PROTOC").is_none() {
+            let protoc = protoc_bin_vendored::protoc_bin_path()
+                .expect("No vendored protoc for this platform, set PROTOC");
+            // SAFETY: The build script doesn't spawn threads.
+            unsafe { std::env::set_var("PROTOC", protoc) };
+        }
+        tonic_build::compile_protos("proto/hrdf.proto")
+            .expect("Failed to compile proto/hrdf.proto");
+    }
+}
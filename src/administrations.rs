@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    models::{Model, TransportCompany},
+    storage::DataStorage,
+};
+
+// ------------------------------------------------------------------------------------------------
+// --- Administration
+// ------------------------------------------------------------------------------------------------
+
+/// Administration (operator) code used by the journeys of FPLAN (see DataStorage::administrations).
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Administration {
+    code: String,
+    transport_company_id: Option<i32>,
+    journey_count: usize,
+}
+
+impl Administration {
+    pub fn new(code: String, transport_company_id: Option<i32>, journey_count: usize) -> Self {
+        Self {
+            code,
+            transport_company_id,
+            journey_count,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// None if no transport company of BETRIEB lists the administration (unknown operator).
+    pub fn transport_company_id(&self) -> Option<i32> {
+        self.transport_company_id
+    }
+
+    pub fn journey_count(&self) -> usize {
+        self.journey_count
+    }
+
+    // Functions
+
+    pub fn transport_company<'a>(
+        &self,
+        data_storage: &'a DataStorage,
+    ) -> Option<&'a TransportCompany> {
+        data_storage
+            .transport_companies()
+            .find(self.transport_company_id?)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Serves DataStorage::administrations.
+pub(crate) fn administrations(data_storage: &DataStorage) -> Vec<Administration> {
+    let journey_counts = data_storage.journeys().data().values().fold(
+        BTreeMap::new(),
+        |mut journey_counts, journey| {
+            *journey_counts.entry(journey.administration()).or_insert(0) += 1;
+            journey_counts
+        },
+    );
+
+    journey_counts
+        .into_iter()
+        .map(|(code, journey_count)| {
+            // The lowest ID if several transport companies list the administration.
+            let transport_company_id = data_storage
+                .transport_companies()
+                .data()
+                .values()
+                .filter(|company| {
+                    company
+                        .administrations()
+                        .iter()
+                        .any(|administration| administration == code)
+                })
+                .map(|company| company.id())
+                .min();
+            Administration::new(code.to_string(), transport_company_id, journey_count)
+        })
+        .collect()
+}
@@ -0,0 +1,153 @@
+use std::{
+    fs::{self, File},
+    path::Path,
+};
+
+use memmap2::Mmap;
+use rkyv::Deserialize;
+
+use crate::{
+    error::{HResult, HrdfError},
+    hrdf::{ArchivedHrdf, Hrdf, SCHEMA_VERSION},
+    storage::ArchivedDataStorage,
+};
+
+const ARCHIVE_MAGIC: &[u8; 8] = b"HRDFRKYV";
+
+// The magic followed by the schema version and 4 bytes of padding. 16 bytes keep the archive
+// aligned in the memory map.
+const HEADER_LEN: usize = 16;
+
+/// Serves Hrdf::save_archive.
+pub(crate) fn save_archive(hrdf: &Hrdf, path: &Path) -> HResult<()> {
+    let archive =
+        rkyv::to_bytes::<_, 4096>(hrdf).map_err(|error| HrdfError::Archive(error.to_string()))?;
+
+    let mut data = Vec::with_capacity(HEADER_LEN + archive.len());
+    data.extend_from_slice(ARCHIVE_MAGIC);
+    data.extend_from_slice(&SCHEMA_VERSION.to_le_bytes());
+    data.extend_from_slice(&[0; 4]);
+    data.extend_from_slice(&archive);
+    fs::write(path, data)?;
+    Ok(())
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- MappedHrdf
+// ------------------------------------------------------------------------------------------------
+
+/// An archive written by Hrdf::save_archive, memory-mapped. The data is queried in place, without
+/// deserialization, and only the pages actually accessed are read from the disk.
+/// The file must not be modified while it is mapped.
+pub struct MappedHrdf {
+    mmap: Mmap,
+}
+
+impl MappedHrdf {
+    /// Maps the archive and validates it. The validation reads the whole file but allocates
+    /// nothing, hence is much faster than Hrdf::load_from_cache.
+    pub fn open(path: &Path) -> HResult<Self> {
+        let mmap = map(path)?;
+        rkyv::check_archived_root::<Hrdf>(&mmap[HEADER_LEN..]).map_err(|error| {
+            HrdfError::IncompatibleSnapshot(format!("the archive is invalid: {error}"))
+        })?;
+        Ok(Self { mmap })
+    }
+
+    /// Maps the archive without validating it, only the header is checked.
+    ///
+    /// # Safety
+    ///
+    /// The file must have been written by Hrdf::save_archive, e.g. by the same deployment.
+    pub unsafe fn open_unchecked(path: &Path) -> HResult<Self> {
+        Ok(Self { mmap: map(path)? })
+    }
+
+    // Getters/Setters
+
+    pub fn archived(&self) -> &ArchivedHrdf {
+        // SAFETY: The archive has been validated by open, or the caller of open_unchecked
+        // guarantees that it is valid.
+        unsafe { rkyv::archived_root::<Hrdf>(&self.mmap[HEADER_LEN..]) }
+    }
+
+    pub fn data_storage(&self) -> &ArchivedDataStorage {
+        self.archived().data_storage()
+    }
+
+    // Functions
+
+    /// Deserializes the archive, to use the functions only available on DataStorage.
+    pub fn to_hrdf(&self) -> Hrdf {
+        // unwrap: Deserializing with rkyv::Infallible cannot fail.
+        self.archived().deserialize(&mut rkyv::Infallible).unwrap()
+    }
+}
+
+fn map(path: &Path) -> HResult<Mmap> {
+    let file = File::open(path)?;
+    // SAFETY: The file is not modified while it is mapped, see MappedHrdf.
+    let mmap = unsafe { Mmap::map(&file) }?;
+
+    let schema_version = mmap
+        .get(..HEADER_LEN)
+        .and_then(|header| header.strip_prefix(ARCHIVE_MAGIC))
+        .map(|rest| {
+            // unwrap: The schema version is 4 bytes long.
+            u32::from_le_bytes(rest[..4].try_into().unwrap())
+        })
+        .ok_or_else(|| {
+            HrdfError::IncompatibleSnapshot("the file is not an HRDF archive".to_string())
+        })?;
+    if schema_version != SCHEMA_VERSION {
+        return Err(HrdfError::IncompatibleSnapshot(format!(
+            "the archive has been written with schema {schema_version}, this is hrdf-parser {} (schema {SCHEMA_VERSION})",
+            env!("CARGO_PKG_VERSION"),
+        )));
+    }
+    Ok(mmap)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+    use crate::models::Version;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn query_mapped_archive() {
+        let hrdf = Hrdf::from_directory(
+            Version::V_5_40_41_2_0_7,
+            Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/mini")),
+        )
+        .unwrap();
+        let path = env::temp_dir().join("hrdf-parser-query-mapped-archive.rkyv");
+        hrdf.save_archive(&path).unwrap();
+
+        let mapped = MappedHrdf::open(&path).unwrap();
+        let stop = mapped.data_storage().stops().find(&8500101).unwrap();
+        assert_eq!(stop.name(), "Aarstadt");
+        assert_eq!(mapped.data_storage().journeys().len(), 6);
+        assert_eq!(
+            mapped.to_hrdf().data_storage().stops().data().len(),
+            hrdf.data_storage().stops().data().len()
+        );
+
+        let mut data = fs::read(&path).unwrap();
+        data[8..12].copy_from_slice(&(SCHEMA_VERSION + 1).to_le_bytes());
+        fs::write(&path, data).unwrap();
+        assert!(matches!(
+            MappedHrdf::open(&path),
+            Err(HrdfError::IncompatibleSnapshot(_))
+        ));
+
+        fs::write(&path, b"not an archive").unwrap();
+        assert!(matches!(
+            MappedHrdf::open(&path),
+            Err(HrdfError::IncompatibleSnapshot(_))
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+}
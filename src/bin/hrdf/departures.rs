@@ -0,0 +1,73 @@
+use std::error::Error;
+
+use chrono::{NaiveDateTime, TimeDelta};
+use hrdf_parser::Model;
+
+use crate::{DatasetArgs, find_stop, parse_datetime};
+
+#[derive(clap::Args)]
+pub struct DeparturesArgs {
+    #[command(flatten)]
+    dataset: DatasetArgs,
+
+    /// Name or ID of the stop.
+    stop: String,
+
+    /// First departure time, e.g. 2026-03-02T08:00.
+    #[arg(long, value_parser = parse_datetime)]
+    at: NaiveDateTime,
+
+    /// Length of the board in minutes.
+    #[arg(long, default_value_t = 60)]
+    minutes: i64,
+
+    /// Leaves out the stops grouped under the stop in METABHF.
+    #[arg(long)]
+    no_meta_stops: bool,
+}
+
+pub fn run(args: &DeparturesArgs) -> Result<(), Box<dyn Error>> {
+    let hrdf = args.dataset.load()?;
+    let data_storage = hrdf.data_storage();
+    let stop = find_stop(data_storage, &args.stop)?;
+    let station_board = data_storage.station_board(
+        stop.id(),
+        args.at,
+        args.at + TimeDelta::minutes(args.minutes),
+        !args.no_meta_stops,
+    )?;
+
+    let mut rows: Vec<_> = station_board
+        .platforms()
+        .iter()
+        .flat_map(|platform| {
+            platform
+                .entries()
+                .iter()
+                .map(move |entry| (entry, platform.name()))
+        })
+        .collect();
+    rows.sort_by_key(|(entry, _)| (entry.departure_at(), entry.journey_id()));
+
+    println!("Departures from {} ({})", stop.name(), stop.id());
+    println!();
+    println!(
+        "{:<6} {:<8} {:<32} {:<8} Attributes",
+        "Time", "Line", "Destination", "Platform"
+    );
+    for (entry, platform) in rows {
+        let destination = entry
+            .destination_stop_id()
+            .and_then(|stop_id| data_storage.stops().find(stop_id))
+            .map_or("", |stop| stop.name());
+        println!(
+            "{:<6} {:<8} {:<32} {:<8} {}",
+            entry.departure_at().format("%H:%M"),
+            entry.line_name().unwrap_or(""),
+            destination,
+            platform.unwrap_or(""),
+            entry.attributes().join(", ")
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use std::error::Error;
+
+use hrdf_parser::{Journey, Model, Version};
+use serde_json::json;
+
+use crate::load;
+
+#[derive(clap::Args)]
+pub struct DiffArgs {
+    /// Previous export (directory, archive or URL).
+    old: String,
+
+    /// New export of the same timetable period.
+    new: String,
+
+    /// Version of the HRDF format of both exports, e.g. V_5_40_41_2_0_7.
+    #[arg(long, default_value = "V_5_40_41_2_0_7")]
+    hrdf_version: Version,
+
+    /// Prints the differences as JSON.
+    #[arg(long)]
+    json: bool,
+}
+
+fn label(journey: &Journey) -> String {
+    format!("{} ({})", journey.legacy_id(), journey.administration())
+}
+
+pub fn run(args: &DiffArgs) -> Result<(), Box<dyn Error>> {
+    let old = load(&args.old, args.hrdf_version)?;
+    let new = load(&args.new, args.hrdf_version)?;
+    let old_storage = old.data_storage();
+    let new_storage = new.data_storage();
+    let delta = old_storage.journey_delta(new_storage);
+
+    let removed: Vec<_> = delta
+        .removed()
+        .iter()
+        .filter_map(|&journey_id| old_storage.journeys().find(journey_id))
+        .map(label)
+        .collect();
+    let added: Vec<_> = delta
+        .added()
+        .iter()
+        .map(|(journey, _)| label(journey))
+        .collect();
+    // The stop times changed, or only the metadata or the operating days.
+    let changed: Vec<_> = delta
+        .changed()
+        .iter()
+        .map(|(old_id, journey, _)| {
+            let stop_times_changed = old_storage
+                .journeys()
+                .find(*old_id)
+                .is_none_or(|old_journey| old_journey.route() != journey.route());
+            (label(journey), stop_times_changed)
+        })
+        .collect();
+
+    let mut renamed_stops: Vec<_> = old_storage
+        .stops()
+        .entries()
+        .into_iter()
+        .filter_map(|old_stop| {
+            let new_stop = new_storage.stops().find(old_stop.id())?;
+            (old_stop.name() != new_stop.name()).then(|| {
+                (
+                    old_stop.id(),
+                    old_stop.name().to_string(),
+                    new_stop.name().to_string(),
+                )
+            })
+        })
+        .collect();
+    renamed_stops.sort();
+
+    if args.json {
+        let output = json!({
+            "removed_journeys": removed,
+            "added_journeys": added,
+            "changed_journeys": changed
+                .iter()
+                .map(|(journey, stop_times_changed)| json!({
+                    "journey": journey,
+                    "stop_times_changed": stop_times_changed,
+                }))
+                .collect::<Vec<_>>(),
+            "renamed_stops": renamed_stops
+                .iter()
+                .map(|(id, old_name, new_name)| json!({
+                    "id": id,
+                    "old_name": old_name,
+                    "new_name": new_name,
+                }))
+                .collect::<Vec<_>>(),
+        });
+        println!("{output}");
+        return Ok(());
+    }
+
+    println!("Removed journeys: {}", removed.len());
+    for journey in &removed {
+        println!("  - {journey}");
+    }
+    println!("Added journeys: {}", added.len());
+    for journey in &added {
+        println!("  + {journey}");
+    }
+    println!("Changed journeys: {}", changed.len());
+    for (journey, stop_times_changed) in &changed {
+        let change = if *stop_times_changed {
+            "stop times"
+        } else {
+            "metadata or operating days"
+        };
+        println!("  ~ {journey}: {change}");
+    }
+    println!("Renamed stops: {}", renamed_stops.len());
+    for (id, old_name, new_name) in &renamed_stops {
+        println!("  {id}: {old_name} -> {new_name}");
+    }
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use std::{error::Error, fs, path::PathBuf};
+
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use hrdf_parser::DataStorage;
+
+use crate::DatasetArgs;
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// agency.txt, stops.txt, routes.txt, trips.txt, stop_times.txt and calendar_dates.txt, a
+    /// GTFS feed.
+    Gtfs,
+    /// lines.geojson, the geometries of the lines.
+    Geojson,
+    /// network.graphml, the stops and the connections between them.
+    Graphml,
+    /// stops.csv, journeys.csv and, with --date, stop_times.csv (requires the polars feature).
+    Csv,
+    /// hrdf.sqlite, the stops, the journeys and their stop times (requires the sqlite feature).
+    Sqlite,
+}
+
+#[derive(clap::Args)]
+pub struct ExportArgs {
+    #[command(flatten)]
+    dataset: DatasetArgs,
+
+    #[arg(long, value_enum)]
+    format: Format,
+
+    /// Directory where the files are written, created if needed.
+    #[arg(long)]
+    out: PathBuf,
+
+    /// Day of the stop times of the csv format, e.g. 2026-03-02.
+    #[arg(long)]
+    date: Option<NaiveDate>,
+}
+
+pub fn run(args: &ExportArgs) -> Result<(), Box<dyn Error>> {
+    let hrdf = args.dataset.load()?;
+    let data_storage = hrdf.data_storage();
+    fs::create_dir_all(&args.out)?;
+
+    match args.format {
+        Format::Gtfs => {
+            for (file_name, content) in data_storage.to_gtfs() {
+                fs::write(args.out.join(file_name), content)?;
+            }
+        }
+        Format::Geojson => fs::write(
+            args.out.join("lines.geojson"),
+            data_storage.line_geometries_to_geojson()?,
+        )?,
+        Format::Graphml => fs::write(args.out.join("network.graphml"), data_storage.to_graphml())?,
+        Format::Csv => write_csv(data_storage, args)?,
+        Format::Sqlite => write_sqlite(data_storage, args)?,
+    }
+    println!("Exported to {}", args.out.display());
+    Ok(())
+}
+
+#[cfg(feature = "polars")]
+fn write_csv(data_storage: &DataStorage, args: &ExportArgs) -> Result<(), Box<dyn Error>> {
+    use polars::prelude::{CsvWriter, SerWriter};
+
+    let mut dataframes = vec![
+        ("stops.csv", data_storage.stops_to_dataframe()?),
+        ("journeys.csv", data_storage.journeys_to_dataframe()?),
+    ];
+    if let Some(date) = args.date {
+        data_storage.check_date(date)?;
+        dataframes.push((
+            "stop_times.csv",
+            data_storage.stop_times_to_dataframe(date)?,
+        ));
+    }
+    for (file_name, mut dataframe) in dataframes {
+        let mut file = fs::File::create(args.out.join(file_name))?;
+        CsvWriter::new(&mut file).finish(&mut dataframe)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "polars"))]
+fn write_csv(_: &DataStorage, _: &ExportArgs) -> Result<(), Box<dyn Error>> {
+    Err("The csv format requires the polars feature".into())
+}
+
+#[cfg(feature = "sqlite")]
+fn write_sqlite(data_storage: &DataStorage, args: &ExportArgs) -> Result<(), Box<dyn Error>> {
+    Ok(data_storage.to_sqlite(&args.out.join("hrdf.sqlite"))?)
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn write_sqlite(_: &DataStorage, _: &ExportArgs) -> Result<(), Box<dyn Error>> {
+    Err("The sqlite format requires the sqlite feature".into())
+}
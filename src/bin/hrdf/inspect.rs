@@ -0,0 +1,115 @@
+use std::{collections::BTreeMap, error::Error};
+
+use hrdf_parser::{Issue, Language, Model};
+
+use crate::DatasetArgs;
+
+#[derive(clap::Args)]
+pub struct InspectArgs {
+    #[command(flatten)]
+    dataset: DatasetArgs,
+
+    /// Lists every parse warning instead of their number per kind.
+    #[arg(long)]
+    verbose: bool,
+}
+
+pub fn run(args: &InspectArgs) -> Result<(), Box<dyn Error>> {
+    let hrdf = args.dataset.load()?;
+    let data_storage = hrdf.data_storage();
+
+    println!("Version: {}", hrdf.version());
+    let timetable_period = hrdf.timetable_period();
+    println!(
+        "Timetable period: {} - {}",
+        timetable_period.start(),
+        timetable_period.end()
+    );
+    let mut metadata = data_storage.timetable_metadata().entries();
+    metadata.sort_by_key(|entry| entry.id());
+    for entry in metadata {
+        println!("{}: {}", entry.key(), entry.value());
+    }
+
+    println!();
+    println!("Resources:");
+    for (name, count) in [
+        ("Stops", data_storage.stops().data().len()),
+        (
+            "Stop connections",
+            data_storage.stop_connections().data().len(),
+        ),
+        ("Platforms", data_storage.platforms().data().len()),
+        ("Journeys", data_storage.journeys().data().len()),
+        (
+            "Journey platforms",
+            data_storage.journey_platform().data().len(),
+        ),
+        (
+            "Through services",
+            data_storage.through_service().data().len(),
+        ),
+        ("Bit fields", data_storage.bit_fields().data().len()),
+        ("Lines", data_storage.lines().data().len()),
+        ("Directions", data_storage.directions().data().len()),
+        ("Attributes", data_storage.attributes().data().len()),
+        (
+            "Information texts",
+            data_storage.information_texts().data().len(),
+        ),
+        (
+            "Transport types",
+            data_storage.transport_types().data().len(),
+        ),
+        (
+            "Exchange times (administrations)",
+            data_storage.exchange_times_administration().data().len(),
+        ),
+        (
+            "Exchange times (journeys)",
+            data_storage.exchange_times_journey().data().len(),
+        ),
+        (
+            "Exchange times (lines)",
+            data_storage.exchange_times_line().data().len(),
+        ),
+    ] {
+        println!("  {name:<34}{count:>10}");
+    }
+
+    println!();
+    println!("Administrations:");
+    for administration in data_storage.administrations() {
+        let operator = administration
+            .transport_company(data_storage)
+            .and_then(|company| company.short_name(Language::German))
+            .unwrap_or("unknown");
+        println!(
+            "  {} {operator:<27}{:>10}",
+            administration.code(),
+            administration.journey_count()
+        );
+    }
+
+    println!();
+    let issues = data_storage.parse_report().issues();
+    if issues.is_empty() {
+        println!("Parse warnings: none");
+        return Ok(());
+    }
+    println!("Parse warnings: {}", issues.len());
+    if args.verbose {
+        for issue in issues {
+            println!("  [{:?}] {}: {issue:?}", issue.severity(), issue.code());
+        }
+    } else {
+        let mut counts = BTreeMap::new();
+        for issue in issues {
+            *counts.entry((issue.code(), issue.severity())).or_insert(0) += 1;
+        }
+        for ((code, severity), count) in counts {
+            println!("  [{severity:?}] {code:<32}{count:>10}");
+        }
+    }
+    Ok(())
+}
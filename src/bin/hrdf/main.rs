@@ -0,0 +1,108 @@
+//! Command-line tool to check HRDF exports without writing code.
+
+mod departures;
+mod diff;
+mod export;
+mod inspect;
+mod route;
+mod validate;
+
+use std::{error::Error, path::Path, process::ExitCode};
+
+use chrono::NaiveDateTime;
+use clap::{Parser, Subcommand};
+use hrdf_parser::{DataStorage, Hrdf, Stop, Version};
+
+#[derive(Parser)]
+#[command(name = "hrdf", about = "Inspect and query HRDF timetables")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Prints the departures from a stop.
+    Departures(departures::DeparturesArgs),
+    /// Compares two exports: journeys added, removed and changed, stops renamed.
+    Diff(diff::DiffArgs),
+    /// Converts a dataset to GTFS, GeoJSON, GraphML, CSV or SQLite files.
+    Export(export::ExportArgs),
+    /// Prints the metadata, the resource counts and the parse warnings of a dataset.
+    Inspect(inspect::InspectArgs),
+    /// Prints the earliest arrival from a stop to another.
+    Route(route::RouteArgs),
+    /// Checks the integrity of a dataset, exits with 1 if errors have been found.
+    Validate(validate::ValidateArgs),
+}
+
+/// The dataset queried by a command.
+#[derive(clap::Args)]
+struct DatasetArgs {
+    /// Directory of the decompressed HRDF files, HRDF archive (ZIP file) or URL of an archive.
+    source: String,
+
+    /// Version of the HRDF format, e.g. V_5_40_41_2_0_7.
+    #[arg(long, default_value = "V_5_40_41_2_0_7")]
+    hrdf_version: Version,
+}
+
+impl DatasetArgs {
+    fn load(&self) -> Result<Hrdf, Box<dyn Error>> {
+        load(&self.source, self.hrdf_version)
+    }
+}
+
+/// A directory is parsed directly, an archive or an URL is loaded with Hrdf::new, i.e. through
+/// the cache.
+fn load(source: &str, version: Version) -> Result<Hrdf, Box<dyn Error>> {
+    let path = Path::new(source);
+    if path.is_dir() {
+        return Ok(Hrdf::from_directory(version, path)?);
+    }
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    let hrdf = runtime.block_on(Hrdf::new(version, source, false, None))?;
+    Ok(hrdf)
+}
+
+/// The stop with the ID, or the best match of the stop search.
+fn find_stop<'a>(data_storage: &'a DataStorage, query: &str) -> Result<&'a Stop, Box<dyn Error>> {
+    let stop = query
+        .trim()
+        .parse()
+        .ok()
+        .and_then(|stop_id| data_storage.stops().find(stop_id))
+        .or_else(|| data_storage.search_stops(query, false).first().copied());
+    stop.ok_or_else(|| format!("No stop matches \"{query}\"").into())
+}
+
+/// Accepts "2026-03-02T08:00", "2026-03-02 08:00" and the same with seconds.
+fn parse_datetime(value: &str) -> Result<NaiveDateTime, String> {
+    [
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+    .ok_or_else(|| format!("invalid date and time \"{value}\", expected e.g. 2026-03-02T08:00"))
+}
+
+/// Exits with 2 if the command fails, e.g. if the dataset cannot be loaded.
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Departures(args) => departures::run(&args).map(|()| ExitCode::SUCCESS),
+        Command::Diff(args) => diff::run(&args).map(|()| ExitCode::SUCCESS),
+        Command::Export(args) => export::run(&args).map(|()| ExitCode::SUCCESS),
+        Command::Inspect(args) => inspect::run(&args).map(|()| ExitCode::SUCCESS),
+        Command::Route(args) => route::run(&args).map(|()| ExitCode::SUCCESS),
+        Command::Validate(args) => validate::run(&args),
+    };
+    result.unwrap_or_else(|error| {
+        eprintln!("Error: {error}");
+        ExitCode::from(2)
+    })
+}
@@ -0,0 +1,115 @@
+use std::error::Error;
+
+use chrono::NaiveDateTime;
+use hrdf_parser::{
+    Model,
+    dto::{ItineraryDto, LegDto},
+};
+
+use crate::{DatasetArgs, find_stop, parse_datetime};
+
+#[derive(clap::Args)]
+pub struct RouteArgs {
+    #[command(flatten)]
+    dataset: DatasetArgs,
+
+    /// Name or ID of the origin stop.
+    from: String,
+
+    /// Name or ID of the destination stop.
+    to: String,
+
+    /// Departure time, e.g. 2026-03-02T08:00.
+    #[arg(long, value_parser = parse_datetime)]
+    depart: NaiveDateTime,
+
+    #[arg(long, default_value_t = 4)]
+    max_transfers: usize,
+}
+
+/// With every limit on the number of transfers, the itinerary shows what each additional
+/// transfer gains.
+pub fn run(args: &RouteArgs) -> Result<(), Box<dyn Error>> {
+    let hrdf = args.dataset.load()?;
+    let data_storage = hrdf.data_storage();
+    // Fails before printing the header.
+    data_storage.check_date(args.depart.date())?;
+
+    let from = find_stop(data_storage, &args.from)?;
+    let to = find_stop(data_storage, &args.to)?;
+    println!(
+        "{} ({}) -> {} ({}), departure at {}",
+        from.name(),
+        from.id(),
+        to.name(),
+        to.id(),
+        args.depart.format("%Y-%m-%d %H:%M")
+    );
+
+    let mut previous_arrival = None;
+    for max_transfers in 0..=args.max_transfers {
+        let Some(legs) =
+            data_storage.earliest_itinerary(from.id(), to.id(), args.depart, max_transfers)?
+        else {
+            continue;
+        };
+        let Some(itinerary) = ItineraryDto::from_legs(&legs, data_storage) else {
+            continue;
+        };
+        if previous_arrival == Some(itinerary.arrival_at) {
+            continue;
+        }
+        previous_arrival = Some(itinerary.arrival_at);
+        let travel_time = (itinerary.arrival_at - args.depart).num_minutes();
+        println!(
+            "  at most {max_transfers} transfer(s): arrival at {} ({} h {:02} min)",
+            itinerary.arrival_at.format("%Y-%m-%d %H:%M"),
+            travel_time / 60,
+            travel_time % 60
+        );
+        print_legs(&itinerary);
+    }
+    if previous_arrival.is_none() {
+        println!("  The destination cannot be reached.");
+    }
+    Ok(())
+}
+
+fn print_legs(itinerary: &ItineraryDto) {
+    let mut previous_leg: Option<&LegDto> = None;
+    for leg in &itinerary.legs {
+        if let Some(previous_leg) = previous_leg {
+            let transfer_stop = stop_label(leg.from_stop_id, &leg.from_stop_name);
+            if previous_leg.to_stop_id == leg.from_stop_id {
+                println!("      transfer at {transfer_stop}");
+            } else {
+                // A transfer on foot between two stops of a meta stop (METABHF).
+                println!(
+                    "      transfer on foot from {} to {transfer_stop}",
+                    stop_label(previous_leg.to_stop_id, &previous_leg.to_stop_name)
+                );
+            }
+        }
+        println!(
+            "    {} {}{} -> {} {}{}  {}",
+            leg.departure_at.format("%H:%M"),
+            stop_label(leg.from_stop_id, &leg.from_stop_name),
+            platform_label(&leg.departure_platform),
+            leg.arrival_at.format("%H:%M"),
+            stop_label(leg.to_stop_id, &leg.to_stop_name),
+            platform_label(&leg.arrival_platform),
+            leg.line_name.as_deref().unwrap_or(""),
+        );
+        previous_leg = Some(leg);
+    }
+}
+
+fn stop_label(stop_id: i32, stop_name: &Option<String>) -> String {
+    stop_name.clone().unwrap_or_else(|| stop_id.to_string())
+}
+
+fn platform_label(platform: &Option<String>) -> String {
+    platform
+        .as_ref()
+        .map_or(String::new(), |platform| format!(" (platform {platform})"))
+}
@@ -0,0 +1,75 @@
+use std::{error::Error, fmt::Debug, process::ExitCode};
+
+use clap::ValueEnum;
+use hrdf_parser::{Issue, Severity};
+
+use crate::DatasetArgs;
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+}
+
+#[derive(clap::Args)]
+pub struct ValidateArgs {
+    #[command(flatten)]
+    dataset: DatasetArgs,
+
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    /// Fails on warnings too, not only on errors.
+    #[arg(long)]
+    deny_warnings: bool,
+}
+
+/// Exits with 1 if an issue is at least an error (or a warning with --deny-warnings), so that a
+/// CI job can gate a new timetable release on it.
+pub fn run(args: &ValidateArgs) -> Result<ExitCode, Box<dyn Error>> {
+    let hrdf = args.dataset.load()?;
+    let data_storage = hrdf.data_storage();
+    let parse_report = data_storage.parse_report();
+    let validation_report = data_storage.validate();
+
+    match args.format {
+        Format::Text => {
+            print_issues("Parse issues", parse_report.issues());
+            println!();
+            print_issues("Validation issues", validation_report.issues());
+        }
+        Format::Json => println!(
+            "{{\"parse_issues\":{},\"validation_issues\":{}}}",
+            parse_report.to_json()?,
+            validation_report.to_json()?
+        ),
+    }
+
+    let threshold = if args.deny_warnings {
+        Severity::Warning
+    } else {
+        Severity::Error
+    };
+    let failed = parse_report
+        .issues()
+        .iter()
+        .map(Issue::severity)
+        .chain(validation_report.issues().iter().map(Issue::severity))
+        .any(|severity| severity >= threshold);
+    Ok(if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+fn print_issues<T: Issue + Debug>(title: &str, issues: &[T]) {
+    if issues.is_empty() {
+        println!("{title}: none");
+        return;
+    }
+    println!("{title}: {}", issues.len());
+    for issue in issues {
+        println!("  [{:?}] {}: {issue:?}", issue.severity(), issue.code());
+    }
+}
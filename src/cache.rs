@@ -0,0 +1,169 @@
+use std::{
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use bincode::config;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{HResult, HrdfError},
+    hrdf::SCHEMA_VERSION,
+    models::Version,
+    storage::DataStorage,
+};
+
+const CACHE_MAGIC: &[u8; 8] = b"HRDFSEGS";
+
+// ------------------------------------------------------------------------------------------------
+// --- CacheSegment
+// ------------------------------------------------------------------------------------------------
+
+/// The parts of the cache which can be loaded independently (see Hrdf::load_from_cache_segments).
+/// Each segment also contains the lookup maps built from its resources.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum CacheSegment {
+    /// Timetable metadata, holidays, default exchange times and parse report. Always loaded.
+    Core,
+    BitFields,
+    Attributes,
+    InformationTexts,
+    Directions,
+    Lines,
+    TransportCompanies,
+    TransportTypes,
+    Stops,
+    StopConnections,
+    Journeys,
+    JourneyPlatform,
+    Platforms,
+    ThroughService,
+    /// The exchange times between administrations, journeys and lines.
+    ExchangeTimes,
+}
+
+impl CacheSegment {
+    pub const ALL: &[Self] = &[
+        Self::Core,
+        Self::BitFields,
+        Self::Attributes,
+        Self::InformationTexts,
+        Self::Directions,
+        Self::Lines,
+        Self::TransportCompanies,
+        Self::TransportTypes,
+        Self::Stops,
+        Self::StopConnections,
+        Self::Journeys,
+        Self::JourneyPlatform,
+        Self::Platforms,
+        Self::ThroughService,
+        Self::ExchangeTimes,
+    ];
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- File format
+// ------------------------------------------------------------------------------------------------
+//
+// The magic, the length of the header (u64, little endian), the header, then the segments one
+// after the other, each encoded on its own by DataStorage::encode_segment.
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheHeader {
+    crate_version: String,
+    schema_version: u32,
+    hrdf_version: Version,
+    segments: Vec<SegmentEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SegmentEntry {
+    segment: CacheSegment,
+    // Relative to the end of the header.
+    offset: u64,
+    len: u64,
+}
+
+/// Serves Hrdf::build_cache.
+pub(crate) fn write_cache(
+    path: &Path,
+    version: Version,
+    data_storage: &DataStorage,
+) -> HResult<()> {
+    let mut segments = Vec::new();
+    let mut body = Vec::new();
+    for &segment in CacheSegment::ALL {
+        let data = data_storage.encode_segment(segment)?;
+        segments.push(SegmentEntry {
+            segment,
+            offset: body.len() as u64,
+            len: data.len() as u64,
+        });
+        body.extend(data);
+    }
+
+    let header = bincode::serde::encode_to_vec(
+        CacheHeader {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            schema_version: SCHEMA_VERSION,
+            hrdf_version: version,
+            segments,
+        },
+        config::standard(),
+    )?;
+
+    let mut data = CACHE_MAGIC.to_vec();
+    data.extend((header.len() as u64).to_le_bytes());
+    data.extend(header);
+    data.extend(body);
+    fs::write(path, data)?;
+    Ok(())
+}
+
+/// Serves Hrdf::load_from_cache_segments. Only the requested segments are read from the file,
+/// the other resources are left empty.
+pub(crate) fn read_cache(
+    path: &Path,
+    segments: &[CacheSegment],
+) -> HResult<(Version, DataStorage)> {
+    let mut file = File::open(path)?;
+
+    let mut prefix = [0; 16];
+    file.read_exact(&mut prefix)
+        .ok()
+        .filter(|_| prefix.starts_with(CACHE_MAGIC))
+        .ok_or_else(|| {
+            HrdfError::IncompatibleSnapshot("the file is not an HRDF cache".to_string())
+        })?;
+    // unwrap: The length of the header is 8 bytes long.
+    let header_len = u64::from_le_bytes(prefix[8..].try_into().unwrap());
+
+    let mut header = vec![0; header_len as usize];
+    file.read_exact(&mut header)?;
+    let (header, _): (CacheHeader, _) =
+        bincode::serde::decode_from_slice(&header, config::standard())?;
+    if header.schema_version != SCHEMA_VERSION {
+        return Err(HrdfError::IncompatibleSnapshot(format!(
+            "the cache has been written by hrdf-parser {} (schema {}), this is hrdf-parser {} (schema {SCHEMA_VERSION})",
+            header.crate_version,
+            header.schema_version,
+            env!("CARGO_PKG_VERSION"),
+        )));
+    }
+
+    let body_start = prefix.len() as u64 + header_len;
+    let mut data_storage = DataStorage::default();
+    for entry in header
+        .segments
+        .iter()
+        .filter(|entry| entry.segment == CacheSegment::Core || segments.contains(&entry.segment))
+    {
+        file.seek(SeekFrom::Start(body_start + entry.offset))?;
+        let mut data = vec![0; entry.len as usize];
+        file.read_exact(&mut data)?;
+        data_storage.decode_segment(entry.segment, &data)?;
+    }
+    Ok((header.hrdf_version, data_storage))
+}
@@ -0,0 +1,319 @@
+use chrono::NaiveDate;
+use rustc_hash::FxHashMap;
+
+use crate::{
+    models::{BitField, Journey, JourneyMetadataEntry, JourneyMetadataType, Model, ServiceTime},
+    storage::DataStorage,
+};
+
+// ------------------------------------------------------------------------------------------------
+// --- JourneyDelta
+// ------------------------------------------------------------------------------------------------
+
+/// The journeys added, removed and changed between two exports of the same timetable period
+/// (see DataStorage::journey_delta), to update the previous export in memory instead of parsing
+/// the new one (see DataStorage::apply_delta).
+/// The journeys are identified by their number, administration and first departure. The other
+/// resources (stops, lines, attributes, etc.) are expected to be unchanged, the platforms and the
+/// through services of the added journeys are not part of the delta.
+#[derive(Debug, Default)]
+pub struct JourneyDelta {
+    timetable_period: (NaiveDate, NaiveDate),
+    // The IDs are those of the previous export.
+    removed: Vec<i32>,
+    // The journeys come with their bit field, None if they operate every day.
+    added: Vec<(Journey, Option<BitField>)>,
+    changed: Vec<(i32, Journey, Option<BitField>)>,
+}
+
+impl JourneyDelta {
+    // Getters/Setters
+
+    pub fn timetable_period(&self) -> (NaiveDate, NaiveDate) {
+        self.timetable_period
+    }
+
+    pub fn removed(&self) -> &[i32] {
+        &self.removed
+    }
+
+    pub fn added(&self) -> &[(Journey, Option<BitField>)] {
+        &self.added
+    }
+
+    /// (ID in the previous export, new journey, new bit field).
+    pub fn changed(&self) -> &[(i32, Journey, Option<BitField>)] {
+        &self.changed
+    }
+
+    // Functions
+
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty() && self.changed.is_empty()
+    }
+}
+
+// (legacy_id, administration, first stop, first departure)
+type JourneyKey = (i32, String, Option<i32>, Option<ServiceTime>);
+
+fn journey_key(journey: &Journey) -> JourneyKey {
+    let first_entry = journey.route().first();
+    (
+        journey.legacy_id(),
+        journey.administration().to_string(),
+        first_entry.map(|route_entry| route_entry.stop_id()),
+        first_entry.and_then(|route_entry| route_entry.departure()),
+    )
+}
+
+/// None if the journey operates every day.
+fn bit_field<'a>(journey: &Journey, data_storage: &'a DataStorage) -> Option<&'a BitField> {
+    journey
+        .bit_field_id()
+        .ok()
+        .flatten()
+        .filter(|&bit_field_id| bit_field_id != 0)
+        .and_then(|bit_field_id| data_storage.bit_fields().find(bit_field_id))
+}
+
+fn metadata_without_bit_field(
+    journey: &Journey,
+) -> FxHashMap<&JourneyMetadataType, &Vec<JourneyMetadataEntry>> {
+    journey
+        .metadata()
+        .iter()
+        .filter(|(k, _)| **k != JourneyMetadataType::BitField)
+        .collect()
+}
+
+/// Same route, same metadata and same operating days. The bit fields are compared by their bits,
+/// since their IDs differ between exports.
+fn is_unchanged(
+    old: &Journey,
+    old_storage: &DataStorage,
+    new: &Journey,
+    new_storage: &DataStorage,
+) -> bool {
+    old.route() == new.route()
+        && metadata_without_bit_field(old) == metadata_without_bit_field(new)
+        && bit_field(old, old_storage).map(BitField::bits)
+            == bit_field(new, new_storage).map(BitField::bits)
+}
+
+/// Sorted by ID, i.e. in the order of the files.
+fn sorted_journeys(data_storage: &DataStorage) -> Vec<&Journey> {
+    let mut journeys = data_storage.journeys().entries();
+    journeys.sort_by_key(|journey| journey.id());
+    journeys
+}
+
+/// Serves DataStorage::journey_delta.
+pub(crate) fn journey_delta(old_storage: &DataStorage, new_storage: &DataStorage) -> JourneyDelta {
+    // Several journeys can have the same key, they are then matched in the order of the files.
+    let mut old_journeys: FxHashMap<JourneyKey, Vec<&Journey>> = FxHashMap::default();
+    for journey in sorted_journeys(old_storage) {
+        old_journeys
+            .entry(journey_key(journey))
+            .or_default()
+            .push(journey);
+    }
+    for journeys in old_journeys.values_mut() {
+        journeys.reverse();
+    }
+
+    let mut delta = JourneyDelta {
+        timetable_period: (
+            *new_storage.timetable_period().start(),
+            *new_storage.timetable_period().end(),
+        ),
+        ..Default::default()
+    };
+    for journey in sorted_journeys(new_storage) {
+        let bit_field = bit_field(journey, new_storage).cloned();
+        match old_journeys
+            .get_mut(&journey_key(journey))
+            .and_then(|journeys| journeys.pop())
+        {
+            Some(old) if is_unchanged(old, old_storage, journey, new_storage) => {}
+            Some(old) => delta.changed.push((old.id(), journey.clone(), bit_field)),
+            None => delta.added.push((journey.clone(), bit_field)),
+        }
+    }
+
+    delta.removed = old_journeys
+        .into_values()
+        .flatten()
+        .map(|journey| journey.id())
+        .collect();
+    delta.removed.sort();
+    delta
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::Path};
+
+    use chrono::NaiveDateTime;
+
+    use super::*;
+    use crate::{hrdf::Hrdf, models::Version};
+    use pretty_assertions::assert_eq;
+
+    const NEW_JOURNEY: &str = "\
+*Z 000007 000001   101         %
+*G S   8500101 8500104 %
+*A VE 8500101 8500104 000002 %
+*L #0000001 8500101 8500104               %
+*R H R000001 8500101 8500104               %
+8500101 Aarstadt                     00800 000007 000001 %
+8500102 Aarstadt Nord         00803  00804 000007 000001 %
+8500103 Bergdorf              00810  00811 000007 000001 %
+8500104 Seeburg               00820        000007 000001 %
+";
+
+    fn departure_times(data_storage: &DataStorage) -> Vec<NaiveDateTime> {
+        let from = NaiveDate::from_ymd_opt(2025, 12, 15)
+            .unwrap()
+            .and_hms_opt(5, 0, 0)
+            .unwrap();
+        data_storage
+            .departures(8500101, from, from + chrono::TimeDelta::hours(5))
+            .unwrap()
+            .iter()
+            .map(|departure| departure.departure_at())
+            .collect()
+    }
+
+    /// Writes the mini dataset to new_path, without journey 3, with journey 2 stopping longer in
+    /// Bergdorf and with journey 7.
+    fn write_new_export(source: &Path, new_path: &Path) {
+        fs::create_dir_all(new_path).unwrap();
+        for entry in fs::read_dir(source).unwrap() {
+            let entry = entry.unwrap();
+            let content = fs::read_to_string(entry.path()).unwrap();
+            // The platforms of journey 3 are removed with it.
+            let content: Vec<_> = content
+                .lines()
+                .filter(|line| !line.contains(" 000003 000001 "))
+                .collect();
+            fs::write(new_path.join(entry.file_name()), content.join("\n") + "\n").unwrap();
+        }
+        let fplan = fs::read_to_string(source.join("FPLAN")).unwrap();
+        let mut lines: Vec<_> = fplan.lines().collect();
+        // unwrap: The mini dataset contains journey 3.
+        let start = lines
+            .iter()
+            .position(|line| line.starts_with("*Z 000003"))
+            .unwrap();
+        let end = start
+            + 1
+            + lines[start + 1..]
+                .iter()
+                .position(|line| line.starts_with("*Z"))
+                .unwrap();
+        lines.drain(start..end);
+        let fplan = lines.join("\n").replace(
+            "8500103 Bergdorf              00649  00650",
+            "8500103 Bergdorf              00649  00652",
+        );
+        fs::write(new_path.join("FPLAN"), format!("{fplan}\n{NEW_JOURNEY}")).unwrap();
+    }
+
+    #[test]
+    fn journey_delta_of_the_same_export_is_empty() {
+        let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/mini");
+        let old = Hrdf::from_directory(Version::V_5_40_41_2_0_7, &source).unwrap();
+        let new = Hrdf::from_directory(Version::V_5_40_41_2_0_7, &source).unwrap();
+
+        let delta = old.data_storage().journey_delta(new.data_storage());
+        assert!(delta.is_empty());
+
+        let mut updated = old.into_data_storage();
+        updated.apply_delta(&delta).unwrap();
+        assert_eq!(
+            updated.journeys().data().len(),
+            new.data_storage().journeys().data().len()
+        );
+    }
+
+    #[test]
+    fn apply_delta_between_exports() {
+        let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/mini");
+        let old = Hrdf::from_directory(Version::V_5_40_41_2_0_7, &source).unwrap();
+
+        let new_path = env::temp_dir().join("hrdf-parser-apply-delta-between-exports");
+        write_new_export(&source, &new_path);
+        let new = Hrdf::from_directory(Version::V_5_40_41_2_0_7, &new_path).unwrap();
+
+        let delta = old.data_storage().journey_delta(new.data_storage());
+        assert_eq!(delta.removed().len(), 1);
+        assert_eq!(delta.changed().len(), 1);
+        assert_eq!(delta.added().len(), 1);
+        assert_eq!(delta.changed()[0].1.legacy_id(), 2);
+        assert_eq!(delta.added()[0].0.legacy_id(), 7);
+
+        let mut updated = old.into_data_storage();
+        assert!(updated.contains_journey(3, "000001"));
+        updated.apply_delta(&delta).unwrap();
+        assert_eq!(updated.journeys().data().len(), 6);
+        assert!(!updated.contains_journey(3, "000001"));
+        assert!(updated.contains_journey(2, "000001"));
+        assert!(updated.contains_journey(7, "000001"));
+        assert!(
+            updated
+                .journey_platform()
+                .entries()
+                .iter()
+                .all(|journey_platform| journey_platform.journey_legacy_id() != 3)
+        );
+        assert_eq!(
+            updated.journey_platform().data().len(),
+            new.data_storage().journey_platform().data().len()
+        );
+        assert_eq!(
+            departure_times(&updated),
+            departure_times(new.data_storage())
+        );
+        assert!(updated.journey_delta(new.data_storage()).is_empty());
+        fs::remove_dir_all(&new_path).unwrap();
+    }
+
+    #[test]
+    fn legacy_code_lookups_after_apply_delta() {
+        let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/mini");
+        let old = Hrdf::from_directory(Version::V_5_40_41_2_0_7, &source).unwrap();
+        let new_path = env::temp_dir().join("hrdf-parser-legacy-code-lookups-after-apply-delta");
+        write_new_export(&source, &new_path);
+        let new = Hrdf::from_directory(Version::V_5_40_41_2_0_7, &new_path).unwrap();
+        let new = new.data_storage();
+
+        let delta = old.data_storage().journey_delta(new);
+        let mut updated = old.into_data_storage();
+        updated.apply_delta(&delta).unwrap();
+
+        for (data_storage, other) in [(&updated, new), (new, &updated)] {
+            for journey in data_storage.journeys().entries() {
+                assert!(other.contains_journey(journey.legacy_id(), journey.administration()));
+            }
+        }
+        for code in ["NF", "VR"] {
+            assert_eq!(
+                updated.attribute_id_by_code(code),
+                new.attribute_id_by_code(code)
+            );
+        }
+        for code in ["R000001", "R000002", "R000003"] {
+            assert_eq!(
+                updated.direction_id_by_code(code),
+                new.direction_id_by_code(code)
+            );
+        }
+        for designation in ["S", "B"] {
+            assert_eq!(
+                updated.transport_type_id_by_designation(designation),
+                new.transport_type_id_by_designation(designation)
+            );
+        }
+        fs::remove_dir_all(&new_path).unwrap();
+    }
+}
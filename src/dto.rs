@@ -0,0 +1,226 @@
+//! Flat data transfer objects for web APIs, serialized in camelCase. Unlike the models, their
+//! fields are stable and only contain what is shown to the passengers.
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    models::{Color, Journey, Model, Stop},
+    routing::ItineraryLeg,
+    station_board::StationBoard,
+    storage::DataStorage,
+};
+
+/// Largest number of origins, and of destinations, of an earliest arrivals request to the server
+/// and grpc APIs.
+pub const MAX_MATRIX_STOPS: usize = 100;
+
+/// Longest from..until window of a departures request to the server and grpc APIs.
+pub const MAX_DEPARTURES_WINDOW: TimeDelta = TimeDelta::hours(24);
+
+/// CSS notation of the color (e.g. "#0066CC").
+fn to_hex(color: Color) -> String {
+    format!("#{:02X}{:02X}{:02X}", color.r(), color.g(), color.b())
+}
+
+fn stop_name(data_storage: &DataStorage, stop_id: i32) -> Option<String> {
+    data_storage
+        .stops()
+        .find(stop_id)
+        .map(|stop| stop.name().to_string())
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- StopDto
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StopDto {
+    pub id: i32,
+    pub name: String,
+    /// WGS84, converted from LV95 if needed.
+    pub latitude: Option<f64>,
+    /// WGS84, converted from LV95 if needed.
+    pub longitude: Option<f64>,
+}
+
+impl From<&Stop> for StopDto {
+    fn from(stop: &Stop) -> Self {
+        let coordinates = stop.wgs84_position();
+        Self {
+            id: stop.id(),
+            name: stop.name().to_string(),
+            latitude: coordinates.and_then(|coordinates| coordinates.latitude()),
+            longitude: coordinates.and_then(|coordinates| coordinates.longitude()),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- DepartureDto
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepartureDto {
+    pub journey_id: i32,
+    pub stop_id: i32,
+    pub platform: Option<String>,
+    /// Local date and time (e.g. "2025-12-15T06:00:00").
+    pub departure_at: NaiveDateTime,
+    pub destination_stop_id: Option<i32>,
+    pub destination_name: Option<String>,
+    pub line_name: Option<String>,
+    pub text_color: Option<String>,
+    pub background_color: Option<String>,
+    /// Designations of the attributes (e.g. "VR").
+    pub attributes: Vec<String>,
+}
+
+impl DepartureDto {
+    /// The departures of the station board, sorted by departure time.
+    pub fn from_station_board(
+        station_board: &StationBoard,
+        data_storage: &DataStorage,
+    ) -> Vec<Self> {
+        let mut departures: Vec<_> = station_board
+            .platforms()
+            .iter()
+            .flat_map(|platform| {
+                platform.entries().iter().map(move |entry| Self {
+                    journey_id: entry.journey_id(),
+                    stop_id: platform.stop_id(),
+                    platform: platform.name().map(str::to_string),
+                    departure_at: entry.departure_at(),
+                    destination_stop_id: entry.destination_stop_id(),
+                    destination_name: entry
+                        .destination_stop_id()
+                        .and_then(|stop_id| stop_name(data_storage, stop_id)),
+                    line_name: entry.line_name().map(str::to_string),
+                    text_color: entry.text_color().map(to_hex),
+                    background_color: entry.background_color().map(to_hex),
+                    attributes: entry.attributes().to_vec(),
+                })
+            })
+            .collect();
+        departures.sort_by_key(|departure| (departure.departure_at, departure.journey_id));
+        departures
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- LegDto
+// ------------------------------------------------------------------------------------------------
+
+/// A ride on a journey between two of its stops.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LegDto {
+    pub journey_id: i32,
+    pub line_name: Option<String>,
+    pub from_stop_id: i32,
+    pub from_stop_name: Option<String>,
+    pub to_stop_id: i32,
+    pub to_stop_name: Option<String>,
+    /// Platform at the from stop (GLEISE).
+    pub departure_platform: Option<String>,
+    /// Platform at the to stop (GLEISE).
+    pub arrival_platform: Option<String>,
+    /// Local date and time.
+    pub departure_at: NaiveDateTime,
+    /// Local date and time.
+    pub arrival_at: NaiveDateTime,
+}
+
+impl LegDto {
+    /// The journey leaves its first stop on the service day. None if the journey does not serve
+    /// the from stop, then the to stop.
+    pub fn new(
+        journey: &Journey,
+        from_stop_id: i32,
+        to_stop_id: i32,
+        service_day: NaiveDate,
+        data_storage: &DataStorage,
+    ) -> Option<Self> {
+        let route = journey.route();
+        let from = route
+            .iter()
+            .position(|route_entry| route_entry.stop_id() == from_stop_id)?;
+        let to = from
+            + route[from..]
+                .iter()
+                .position(|route_entry| route_entry.stop_id() == to_stop_id)?;
+        let to_datetime = |minutes: u32| {
+            service_day.and_time(NaiveTime::MIN) + TimeDelta::minutes(i64::from(minutes))
+        };
+        let platform_name = |stop_id: i32| {
+            journey
+                .platform(stop_id, service_day, data_storage)
+                .map(|platform| platform.name().to_string())
+        };
+
+        Some(Self {
+            journey_id: journey.id(),
+            line_name: journey
+                .line_id()
+                .and_then(|line_id| data_storage.lines().find(line_id))
+                .map(|line| line.display_name().to_string()),
+            from_stop_id,
+            from_stop_name: stop_name(data_storage, from_stop_id),
+            to_stop_id,
+            to_stop_name: stop_name(data_storage, to_stop_id),
+            departure_platform: platform_name(from_stop_id),
+            arrival_platform: platform_name(to_stop_id),
+            departure_at: to_datetime(route[from].departure()?.minutes()),
+            arrival_at: to_datetime(route[to].arrival()?.minutes()),
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- ItineraryDto
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItineraryDto {
+    pub departure_at: NaiveDateTime,
+    pub arrival_at: NaiveDateTime,
+    /// In minutes.
+    pub duration: i64,
+    pub transfers: usize,
+    pub legs: Vec<LegDto>,
+}
+
+impl ItineraryDto {
+    /// None if there are no legs.
+    pub fn new(legs: Vec<LegDto>) -> Option<Self> {
+        let departure_at = legs.first()?.departure_at;
+        let arrival_at = legs.last()?.arrival_at;
+        Some(Self {
+            departure_at,
+            arrival_at,
+            duration: (arrival_at - departure_at).num_minutes(),
+            transfers: legs.len() - 1,
+            legs,
+        })
+    }
+
+    /// The itinerary of DataStorage::earliest_itinerary, None if it has no legs.
+    pub fn from_legs(legs: &[ItineraryLeg], data_storage: &DataStorage) -> Option<Self> {
+        let legs = legs
+            .iter()
+            .map(|leg| {
+                let journey = data_storage.journeys().find(leg.journey_id())?;
+                LegDto::new(
+                    journey,
+                    leg.from_stop_id(),
+                    leg.to_stop_id(),
+                    leg.service_day(),
+                    data_storage,
+                )
+            })
+            .collect::<Option<_>>()?;
+        Self::new(legs)
+    }
+}
@@ -1,7 +1,9 @@
 use crate::{JourneyError, Version, parsing::error::ParsingError};
+#[cfg(feature = "serde")]
 use bincode::error::{DecodeError, EncodeError};
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveDateTime};
 use thiserror::Error;
+#[cfg(feature = "fetch")]
 use zip::result::ZipError;
 
 #[derive(Debug, Error)]
@@ -13,6 +15,9 @@ pub enum HrdfError {
         line: String,
         line_number: usize,
     },
+    /// The errors of all the lines which could not be parsed, see ParsingOptions::collect_errors.
+    #[error("{} lines could not be parsed", .0.len())]
+    ParsingErrors(Vec<HrdfError>),
     #[error("Io error: {0}")]
     Io(#[from] std::io::Error),
     #[error("Missing key \"start_date\"")]
@@ -27,14 +32,30 @@ pub enum HrdfError {
     FailedToSubDays(NaiveDate, u64),
     #[error("BitFieldId {0} not found")]
     BitFieldIdNotFound(i32),
+    #[cfg(feature = "serde")]
     #[error("Failed to read cache: {0}")]
     ReadCache(#[from] DecodeError),
+    #[cfg(feature = "serde")]
     #[error("Failed to write cache: {0}")]
     WriteCacher(#[from] EncodeError),
+    #[error("The HRDF files are not available, the data has been loaded from a cache")]
+    MissingDataPath,
+    #[error("Invalid file name: {0}")]
+    InvalidFileName(String),
+    #[error("Incompatible snapshot: {0}")]
+    IncompatibleSnapshot(String),
+    #[cfg(feature = "rkyv")]
+    #[error("Failed to write archive: {0}")]
+    Archive(String),
+    #[cfg(feature = "fetch")]
     #[error("Failed decompress data: {0}")]
     Decompress(#[from] ZipError),
+    #[cfg(feature = "fetch")]
     #[error("Failed to download data: {0}")]
     Download(#[from] reqwest::Error),
+    #[cfg(feature = "grpc")]
+    #[error("gRPC server error: {0}")]
+    Grpc(#[from] tonic::transport::Error),
     #[error("Missing stop id: {0}")]
     MissingStopId(i32),
     #[error("Missing departure time at index: {0}")]
@@ -45,10 +66,33 @@ pub enum HrdfError {
     MissingRoute,
     #[error("Out of rage date: {0}")]
     OutOfRangeDate(NaiveDate),
+    #[error("Date {date} is outside of the timetable period ({start} to {end})")]
+    OutOfTimetablePeriod {
+        date: NaiveDate,
+        start: NaiveDate,
+        end: NaiveDate,
+    },
+    #[error("The delta is for the timetable period {found:?}, not {expected:?}")]
+    IncompatibleDelta {
+        expected: (NaiveDate, NaiveDate),
+        found: (NaiveDate, NaiveDate),
+    },
+    #[error("No timetable covers {0}")]
+    NoDatasetForDate(NaiveDate),
     #[error("Invalid year provided")]
     InvalidYear,
+    #[error("Invalid local date-time: {0}")]
+    InvalidLocalDateTime(NaiveDateTime),
     #[error("Version not supported: {0}")]
     SupportedVersion(Version),
+    #[error("Failed to serialize to JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "polars")]
+    #[error("Failed to build a DataFrame: {0}")]
+    Polars(#[from] polars::error::PolarsError),
+    #[cfg(feature = "sqlite")]
+    #[error("SQLite export failed: {0}")]
+    Sqlite(#[from] rusqlite::Error),
 }
 
 pub type HResult<T> = Result<T, HrdfError>;
@@ -0,0 +1,607 @@
+use std::{borrow::Cow, collections::BTreeMap, fmt::Write};
+#[cfg(feature = "sqlite")]
+use std::{collections::BTreeSet, path::Path};
+
+use chrono::NaiveDate;
+#[cfg(feature = "polars")]
+use chrono::{NaiveTime, TimeDelta};
+#[cfg(feature = "polars")]
+use polars::{df, frame::DataFrame};
+use rustc_hash::FxHashSet;
+use serde_json::json;
+
+#[cfg(feature = "polars")]
+use crate::models::ServiceTime;
+use crate::{
+    error::HResult,
+    models::{Journey, LanguagePreference, Model},
+    storage::DataStorage,
+};
+
+// ------------------------------------------------------------------------------------------------
+// --- GeoJSON
+// ------------------------------------------------------------------------------------------------
+
+/// Serves DataStorage::line_geometries_to_geojson.
+pub(crate) fn line_geometries_geojson(data_storage: &DataStorage) -> HResult<String> {
+    let features: Vec<_> = data_storage
+        .line_geometries()
+        .iter()
+        .map(|line_geometry| {
+            let line_strings: Vec<Vec<[f64; 2]>> = line_geometry
+                .coordinates(data_storage)
+                .into_iter()
+                .map(|coordinates| {
+                    coordinates
+                        .iter()
+                        // unwrap: The coordinates are in WGS84.
+                        .map(|point| [point.longitude().unwrap(), point.latitude().unwrap()])
+                        .collect()
+                })
+                .filter(|line_string: &Vec<_>| line_string.len() >= 2)
+                .collect();
+            let name = data_storage
+                .lines()
+                .find(line_geometry.line_id())
+                .map(|line| line.name());
+
+            json!({
+                "type": "Feature",
+                "geometry": {
+                    "type": "MultiLineString",
+                    "coordinates": line_strings,
+                },
+                "properties": {
+                    "line_id": line_geometry.line_id(),
+                    "name": name,
+                },
+            })
+        })
+        .collect();
+
+    Ok(serde_json::to_string(&json!({
+        "type": "FeatureCollection",
+        "features": features,
+    }))?)
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- GraphML
+// ------------------------------------------------------------------------------------------------
+
+/// Serves DataStorage::to_graphml.
+pub(crate) fn graphml(data_storage: &DataStorage) -> String {
+    let mut stops = data_storage.stops().entries();
+    stops.sort_by_key(|stop| stop.id());
+
+    let mut output = String::new();
+    output.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    output.push('\n');
+    output.push_str(r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#);
+    output.push('\n');
+    for (id, target, name, kind) in [
+        ("name", "node", "name", "string"),
+        ("latitude", "node", "latitude", "double"),
+        ("longitude", "node", "longitude", "double"),
+        ("journeys", "edge", "journeys", "int"),
+        ("min_travel_time", "edge", "min_travel_time", "int"),
+        ("mean_travel_time", "edge", "mean_travel_time", "double"),
+    ] {
+        // unwrap: Writing into a String cannot fail.
+        writeln!(
+            output,
+            r#"  <key id="{id}" for="{target}" attr.name="{name}" attr.type="{kind}"/>"#
+        )
+        .unwrap();
+    }
+    output.push_str(r#"  <graph id="network" edgedefault="directed">"#);
+    output.push('\n');
+
+    for stop in &stops {
+        writeln!(output, r#"    <node id="{}">"#, stop.id()).unwrap();
+        writeln!(
+            output,
+            r#"      <data key="name">{}</data>"#,
+            escape_xml(stop.name())
+        )
+        .unwrap();
+        if let Some(coordinates) = stop.wgs84_position() {
+            // unwrap: The coordinates are in WGS84.
+            let (latitude, longitude) = (
+                coordinates.latitude().unwrap(),
+                coordinates.longitude().unwrap(),
+            );
+            writeln!(output, r#"      <data key="latitude">{latitude}</data>"#).unwrap();
+            writeln!(output, r#"      <data key="longitude">{longitude}</data>"#).unwrap();
+        }
+        output.push_str("    </node>\n");
+    }
+
+    for segment_statistics in data_storage.segment_statistics() {
+        let (from_stop_id, to_stop_id) = (
+            segment_statistics.from_stop_id(),
+            segment_statistics.to_stop_id(),
+        );
+        let journeys = segment_statistics.service_count();
+        let min_travel_time = segment_statistics.min_travel_time();
+        let mean_travel_time = segment_statistics.mean_travel_time();
+
+        writeln!(
+            output,
+            r#"    <edge source="{from_stop_id}" target="{to_stop_id}">"#
+        )
+        .unwrap();
+        writeln!(output, r#"      <data key="journeys">{journeys}</data>"#).unwrap();
+        writeln!(
+            output,
+            r#"      <data key="min_travel_time">{min_travel_time}</data>"#
+        )
+        .unwrap();
+        writeln!(
+            output,
+            r#"      <data key="mean_travel_time">{mean_travel_time}</data>"#
+        )
+        .unwrap();
+        output.push_str("    </edge>\n");
+    }
+
+    output.push_str("  </graph>\n");
+    output.push_str("</graphml>\n");
+    output
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- GTFS
+// ------------------------------------------------------------------------------------------------
+
+// route_type of GTFS, the extended type 1700 (miscellaneous) covering the cable cars, the
+// funiculars and the other classes.
+const GTFS_ROUTE_TYPE_RAIL: u16 = 2;
+const GTFS_ROUTE_TYPE_BUS: u16 = 3;
+const GTFS_ROUTE_TYPE_FERRY: u16 = 4;
+const GTFS_ROUTE_TYPE_MISCELLANEOUS: u16 = 1700;
+// The HRDF has no URL for the transport companies, which GTFS requires.
+const GTFS_AGENCY_URL: &str = "https://opentransportdata.swiss";
+
+/// Serves DataStorage::to_gtfs.
+pub(crate) fn gtfs(data_storage: &DataStorage) -> Vec<(&'static str, String)> {
+    let mut journeys = data_storage.journeys().entries();
+    journeys.sort_by_key(|journey| journey.id());
+
+    // The service of a journey is its bit field, "0" if it operates every day.
+    let service_id = |journey: &Journey| match journey.bit_field_id().ok().flatten() {
+        None | Some(0) => 0,
+        Some(bit_field_id) => bit_field_id,
+    };
+    let mut service_dates = BTreeMap::new();
+    for journey in &journeys {
+        let service_id = service_id(journey);
+        service_dates
+            .entry(service_id)
+            .or_insert_with(|| active_dates(data_storage, Some(service_id)));
+    }
+
+    let mut agencies = String::from("agency_id,agency_name,agency_url,agency_timezone\n");
+    for administration in data_storage.administrations() {
+        let name = administration
+            .transport_company(data_storage)
+            .and_then(|company| company.short_name(LanguagePreference::default()))
+            .unwrap_or(administration.code());
+        writeln!(
+            agencies,
+            "{},{},{GTFS_AGENCY_URL},Europe/Zurich",
+            escape_csv(administration.code()),
+            escape_csv(name),
+        )
+        .unwrap();
+    }
+
+    let mut stops = data_storage.stops().entries();
+    stops.sort_by_key(|stop| stop.id());
+    let mut stops_txt = String::from("stop_id,stop_name,stop_lat,stop_lon\n");
+    // GTFS requires the coordinates of the stops, the stop times must only reference these.
+    let mut exported_stop_ids = FxHashSet::default();
+    for stop in stops {
+        let Some(coordinates) = stop.wgs84_position() else {
+            log::warn!(
+                "Stop {} ({}) has no coordinates, it and its stop times are left out of the GTFS feed.",
+                stop.id(),
+                stop.name(),
+            );
+            continue;
+        };
+        exported_stop_ids.insert(stop.id());
+        // unwrap: The coordinates are in WGS84.
+        writeln!(
+            stops_txt,
+            "{},{},{},{}",
+            stop.id(),
+            escape_csv(stop.name()),
+            coordinates.latitude().unwrap(),
+            coordinates.longitude().unwrap(),
+        )
+        .unwrap();
+    }
+
+    // A route per administration and line, or per administration and transport type for the
+    // journeys without a line.
+    let mut routes = BTreeMap::new();
+    let mut trips = String::from("route_id,service_id,trip_id\n");
+    let mut stop_times = String::from(
+        "trip_id,arrival_time,departure_time,stop_id,stop_sequence,pickup_type,drop_off_type\n",
+    );
+    for journey in journeys {
+        let service_id = service_id(journey);
+        if service_dates[&service_id].is_empty() {
+            continue;
+        }
+
+        let transport_type = journey.transport_type(data_storage).ok();
+        let line = journey
+            .line_id()
+            .and_then(|line_id| data_storage.lines().find(line_id));
+        let designation = transport_type.map_or("", |transport_type| transport_type.designation());
+        let (route_id, route_short_name) = match line {
+            Some(line) => (
+                format!("{}:{}", journey.administration(), line.id()),
+                line.name(),
+            ),
+            None => (
+                format!("{}:{designation}", journey.administration()),
+                designation,
+            ),
+        };
+        let route_type = match transport_type {
+            Some(transport_type) if transport_type.is_rail() => GTFS_ROUTE_TYPE_RAIL,
+            Some(transport_type) if transport_type.is_boat() => GTFS_ROUTE_TYPE_FERRY,
+            Some(transport_type) if transport_type.is_bus() => GTFS_ROUTE_TYPE_BUS,
+            _ => GTFS_ROUTE_TYPE_MISCELLANEOUS,
+        };
+        routes.entry(route_id.clone()).or_insert((
+            journey.administration(),
+            route_short_name,
+            route_type,
+        ));
+
+        // The repetitions of a cyclical journey are trips of their own.
+        for (cycle, offset) in [0].into_iter().chain(journey.cycle_offsets()).enumerate() {
+            let trip_id = if cycle == 0 {
+                journey.id().to_string()
+            } else {
+                format!("{}-{cycle}", journey.id())
+            };
+            writeln!(trips, "{},{service_id},{trip_id}", escape_csv(&route_id)).unwrap();
+
+            for (sequence, route_entry) in journey.route().iter().enumerate() {
+                // The first stop has no arrival time and the last one no departure time.
+                let (Some(arrival), Some(departure)) = (
+                    route_entry.arrival().or(route_entry.departure()),
+                    route_entry.departure().or(route_entry.arrival()),
+                ) else {
+                    continue;
+                };
+                if !exported_stop_ids.contains(&route_entry.stop_id()) {
+                    continue;
+                }
+                writeln!(
+                    stop_times,
+                    "{trip_id},{},{},{},{sequence},{},{}",
+                    gtfs_time(arrival.minutes() + offset),
+                    gtfs_time(departure.minutes() + offset),
+                    route_entry.stop_id(),
+                    if route_entry.boarding_allowed() { 0 } else { 1 },
+                    if route_entry.alighting_allowed() {
+                        0
+                    } else {
+                        1
+                    },
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    let mut routes_txt = String::from("route_id,agency_id,route_short_name,route_type\n");
+    for (route_id, (agency_id, route_short_name, route_type)) in routes {
+        writeln!(
+            routes_txt,
+            "{},{},{},{route_type}",
+            escape_csv(&route_id),
+            escape_csv(agency_id),
+            escape_csv(route_short_name),
+        )
+        .unwrap();
+    }
+
+    let mut calendar_dates = String::from("service_id,date,exception_type\n");
+    for (service_id, dates) in service_dates {
+        for date in dates {
+            writeln!(calendar_dates, "{service_id},{},1", date.format("%Y%m%d")).unwrap();
+        }
+    }
+
+    vec![
+        ("agency.txt", agencies),
+        ("stops.txt", stops_txt),
+        ("routes.txt", routes_txt),
+        ("trips.txt", trips),
+        ("stop_times.txt", stop_times),
+        ("calendar_dates.txt", calendar_dates),
+    ]
+}
+
+/// The days of the timetable period on which the bit field is active.
+fn active_dates(data_storage: &DataStorage, bit_field_id: Option<i32>) -> Vec<NaiveDate> {
+    let timetable_period = data_storage.timetable_period();
+    timetable_period
+        .start()
+        .iter_days()
+        .take_while(|date| date <= timetable_period.end())
+        .filter(|&date| data_storage.is_bit_field_active(bit_field_id, date))
+        .collect()
+}
+
+/// HH:MM:SS from the minutes since the start of the operating day, beyond 24:00:00 for the times
+/// of the following days.
+fn gtfs_time(minutes: u32) -> String {
+    format!("{:02}:{:02}:00", minutes / 60, minutes % 60)
+}
+
+fn escape_csv(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Polars
+// ------------------------------------------------------------------------------------------------
+
+/// Serves DataStorage::stops_to_dataframe.
+#[cfg(feature = "polars")]
+pub(crate) fn stops_dataframe(data_storage: &DataStorage) -> HResult<DataFrame> {
+    let mut stops = data_storage.stops().entries();
+    stops.sort_by_key(|stop| stop.id());
+
+    let coordinates: Vec<_> = stops.iter().map(|stop| stop.wgs84_position()).collect();
+    Ok(df!(
+        "id" => stops.iter().map(|stop| stop.id()).collect::<Vec<_>>(),
+        "name" => stops.iter().map(|stop| stop.name()).collect::<Vec<_>>(),
+        "latitude" => coordinates
+            .iter()
+            .map(|coordinates| coordinates.and_then(|coordinates| coordinates.latitude()))
+            .collect::<Vec<_>>(),
+        "longitude" => coordinates
+            .iter()
+            .map(|coordinates| coordinates.and_then(|coordinates| coordinates.longitude()))
+            .collect::<Vec<_>>(),
+    )?)
+}
+
+/// Serves DataStorage::journeys_to_dataframe.
+#[cfg(feature = "polars")]
+pub(crate) fn journeys_dataframe(data_storage: &DataStorage) -> HResult<DataFrame> {
+    let mut journeys = data_storage.journeys().entries();
+    journeys.sort_by_key(|journey| journey.id());
+
+    Ok(df!(
+        "id" => journeys.iter().map(|journey| journey.id()).collect::<Vec<_>>(),
+        "legacy_id" => journeys.iter().map(|journey| journey.legacy_id()).collect::<Vec<_>>(),
+        "administration" => journeys
+            .iter()
+            .map(|journey| journey.administration())
+            .collect::<Vec<_>>(),
+        "line_id" => journeys.iter().map(|journey| journey.line_id()).collect::<Vec<_>>(),
+        "transport_type" => journeys
+            .iter()
+            .map(|journey| {
+                journey
+                    .transport_type(data_storage)
+                    .ok()
+                    .map(|transport_type| transport_type.designation())
+            })
+            .collect::<Vec<_>>(),
+        "first_stop_id" => journeys
+            .iter()
+            .map(|journey| journey.first_stop_id().ok())
+            .collect::<Vec<_>>(),
+        "last_stop_id" => journeys
+            .iter()
+            .map(|journey| journey.last_stop_id().ok())
+            .collect::<Vec<_>>(),
+    )?)
+}
+
+/// Serves DataStorage::stop_times_to_dataframe.
+#[cfg(feature = "polars")]
+pub(crate) fn stop_times_dataframe(
+    data_storage: &DataStorage,
+    date: NaiveDate,
+) -> HResult<DataFrame> {
+    let mut journeys: Vec<_> = data_storage
+        .journeys()
+        .entries()
+        .into_iter()
+        .filter(|journey| journey.operates_on(date, data_storage).unwrap_or(false))
+        .collect();
+    journeys.sort_by_key(|journey| journey.id());
+
+    let start_of_day = date.and_time(NaiveTime::MIN);
+    let (mut journey_ids, mut sequences, mut stop_ids) = (Vec::new(), Vec::new(), Vec::new());
+    let (mut arrivals, mut departures) = (Vec::new(), Vec::new());
+    for journey in journeys {
+        for (sequence, route_entry) in journey.route().iter().enumerate() {
+            let to_datetime = |service_time: ServiceTime| {
+                start_of_day + TimeDelta::minutes(i64::from(service_time.minutes()))
+            };
+            journey_ids.push(journey.id());
+            // unwrap: A journey has less than 2^32 stops.
+            sequences.push(u32::try_from(sequence).unwrap());
+            stop_ids.push(route_entry.stop_id());
+            arrivals.push(route_entry.arrival().map(to_datetime));
+            departures.push(route_entry.departure().map(to_datetime));
+        }
+    }
+
+    Ok(df!(
+        "journey_id" => journey_ids,
+        "sequence" => sequences,
+        "stop_id" => stop_ids,
+        "arrival_at" => arrivals,
+        "departure_at" => departures,
+    )?)
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- SQLite
+// ------------------------------------------------------------------------------------------------
+
+/// Serves DataStorage::to_sqlite.
+#[cfg(feature = "sqlite")]
+pub(crate) fn sqlite(data_storage: &DataStorage, path: &Path) -> HResult<()> {
+    let mut connection = rusqlite::Connection::open(path)?;
+    let transaction = connection.transaction()?;
+    transaction.execute_batch(
+        "DROP TABLE IF EXISTS stop_times;
+        DROP TABLE IF EXISTS stops;
+        DROP TABLE IF EXISTS journeys;
+        DROP TABLE IF EXISTS bit_field_dates;
+        CREATE TABLE stops (
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            latitude REAL,
+            longitude REAL
+        );
+        CREATE TABLE journeys (
+            id INTEGER PRIMARY KEY,
+            legacy_id INTEGER NOT NULL,
+            administration TEXT NOT NULL,
+            line_id INTEGER,
+            transport_type TEXT,
+            bit_field_id INTEGER,
+            num_cycles INTEGER,
+            cycle_duration INTEGER
+        );
+        CREATE TABLE stop_times (
+            journey_id INTEGER NOT NULL REFERENCES journeys (id),
+            sequence INTEGER NOT NULL,
+            stop_id INTEGER NOT NULL,
+            arrival_minutes INTEGER,
+            departure_minutes INTEGER,
+            pickup_allowed INTEGER NOT NULL,
+            drop_off_allowed INTEGER NOT NULL,
+            PRIMARY KEY (journey_id, sequence)
+        );
+        CREATE TABLE bit_field_dates (
+            bit_field_id INTEGER NOT NULL,
+            date TEXT NOT NULL,
+            PRIMARY KEY (bit_field_id, date)
+        );",
+    )?;
+
+    {
+        let mut insert_stop = transaction.prepare("INSERT INTO stops VALUES (?1, ?2, ?3, ?4)")?;
+        for stop in data_storage.stops().entries() {
+            let coordinates = stop.wgs84_position();
+            insert_stop.execute(rusqlite::params![
+                stop.id(),
+                stop.name(),
+                coordinates.and_then(|coordinates| coordinates.latitude()),
+                coordinates.and_then(|coordinates| coordinates.longitude()),
+            ])?;
+        }
+
+        let mut insert_journey =
+            transaction.prepare("INSERT INTO journeys VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)")?;
+        let mut insert_stop_time =
+            transaction.prepare("INSERT INTO stop_times VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)")?;
+        let mut bit_field_ids = BTreeSet::new();
+        for journey in data_storage.journeys().entries() {
+            let bit_field_id = journey
+                .bit_field_id()
+                .ok()
+                .flatten()
+                .filter(|&bit_field_id| bit_field_id != 0);
+            bit_field_ids.extend(bit_field_id);
+            insert_journey.execute(rusqlite::params![
+                journey.id(),
+                journey.legacy_id(),
+                journey.administration(),
+                journey.line_id(),
+                journey
+                    .transport_type(data_storage)
+                    .ok()
+                    .map(|transport_type| transport_type.designation()),
+                bit_field_id,
+                journey.num_cycles(),
+                journey.cycle_duration(),
+            ])?;
+            for (sequence, route_entry) in journey.route().iter().enumerate() {
+                insert_stop_time.execute(rusqlite::params![
+                    journey.id(),
+                    sequence,
+                    route_entry.stop_id(),
+                    route_entry.arrival().map(|arrival| arrival.minutes()),
+                    route_entry.departure().map(|departure| departure.minutes()),
+                    route_entry.boarding_allowed(),
+                    route_entry.alighting_allowed(),
+                ])?;
+            }
+        }
+
+        let mut insert_bit_field_date =
+            transaction.prepare("INSERT INTO bit_field_dates VALUES (?1, ?2)")?;
+        for bit_field_id in bit_field_ids {
+            for date in active_dates(data_storage, Some(bit_field_id)) {
+                insert_bit_field_date.execute(rusqlite::params![bit_field_id, date.to_string()])?;
+            }
+        }
+    }
+
+    transaction.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn xml_escaping() {
+        assert_eq!(
+            escape_xml(r#"Bahnhof <Süd> & "Nord" 'Ost'"#),
+            "Bahnhof &lt;Süd&gt; &amp; &quot;Nord&quot; &apos;Ost&apos;"
+        );
+    }
+
+    #[test]
+    fn csv_escaping() {
+        assert_eq!(escape_csv("Bern"), "Bern");
+        assert_eq!(escape_csv("Basel, Bahnhof"), r#""Basel, Bahnhof""#);
+        assert_eq!(escape_csv(r#"Zug "Nord""#), r#""Zug ""Nord""""#);
+    }
+
+    #[test]
+    fn gtfs_times_beyond_midnight() {
+        assert_eq!(gtfs_time(6 * 60 + 5), "06:05:00");
+        assert_eq!(gtfs_time(25 * 60 + 30), "25:30:00");
+    }
+}
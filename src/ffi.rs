@@ -0,0 +1,328 @@
+use std::{
+    ffi::{CStr, CString, c_char},
+    path::Path,
+    ptr,
+};
+
+use chrono::{DateTime, NaiveDateTime};
+
+use crate::{
+    hrdf::Hrdf,
+    models::{Model, Stop, Version},
+};
+
+// The dates and times are exchanged as the number of seconds between 1970-01-01 00:00 and the
+// local (Swiss) date and time, i.e. as if the local time were UTC.
+
+fn to_naive_date_time(seconds: i64) -> Option<NaiveDateTime> {
+    DateTime::from_timestamp(seconds, 0).map(|datetime| datetime.naive_utc())
+}
+
+fn to_seconds(datetime: NaiveDateTime) -> i64 {
+    datetime.and_utc().timestamp()
+}
+
+/// # Safety
+///
+/// value must be null or a valid nul-terminated string.
+unsafe fn to_str<'a>(value: *const c_char) -> Option<&'a str> {
+    if value.is_null() {
+        return None;
+    }
+    // SAFETY: The caller guarantees that value is a valid nul-terminated string.
+    unsafe { CStr::from_ptr(value) }.to_str().ok()
+}
+
+fn to_c_string(value: &str) -> *mut c_char {
+    // A string containing a nul character is truncated before it.
+    let value = value.split('\0').next().unwrap_or_default();
+    // unwrap: The string does not contain any nul character.
+    CString::new(value).unwrap().into_raw()
+}
+
+/// # Safety
+///
+/// value must be null or have been returned by to_c_string.
+unsafe fn free_c_string(value: *mut c_char) {
+    if !value.is_null() {
+        // SAFETY: The caller guarantees that value has been allocated by CString::into_raw.
+        drop(unsafe { CString::from_raw(value) });
+    }
+}
+
+/// # Safety
+///
+/// items and len must have been returned by into_raw_parts.
+unsafe fn from_raw_parts<T>(items: *mut T, len: usize) -> Vec<T> {
+    if items.is_null() {
+        return Vec::new();
+    }
+    // SAFETY: The caller guarantees that the slice has been allocated by into_raw_parts.
+    unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(items, len)) }.into_vec()
+}
+
+fn into_raw_parts<T>(items: Vec<T>) -> (*mut T, usize) {
+    let len = items.len();
+    let items = Box::into_raw(items.into_boxed_slice()).cast::<T>();
+    (items, len)
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Hrdf
+// ------------------------------------------------------------------------------------------------
+
+/// Parses an HRDF archive already decompressed into a directory. The version is the name of a
+/// Version variant (e.g. "V_5_40_41_2_0_7"). Returns null if the data cannot be loaded.
+/// The result must be released with hrdf_free.
+///
+/// # Safety
+///
+/// version and path must be valid nul-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn hrdf_load_directory(
+    version: *const c_char,
+    path: *const c_char,
+) -> *mut Hrdf {
+    // SAFETY: The caller guarantees that the arguments are valid strings.
+    let (version, path) = unsafe { (to_str(version), to_str(path)) };
+    let hrdf = version
+        .and_then(|version| version.parse::<Version>().ok())
+        .zip(path)
+        .and_then(|(version, path)| Hrdf::from_directory(version, Path::new(path)).ok());
+    hrdf.map_or(ptr::null_mut(), |hrdf| Box::into_raw(Box::new(hrdf)))
+}
+
+/// # Safety
+///
+/// hrdf must be null or have been returned by hrdf_load_directory, and not released yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn hrdf_free(hrdf: *mut Hrdf) {
+    if !hrdf.is_null() {
+        // SAFETY: The caller guarantees that hrdf has been allocated by hrdf_load_directory.
+        drop(unsafe { Box::from_raw(hrdf) });
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Stops
+// ------------------------------------------------------------------------------------------------
+
+#[repr(C)]
+pub struct HrdfStop {
+    pub id: i32,
+    pub name: *mut c_char,
+    /// NaN if the stop has no coordinates.
+    pub latitude: f64,
+    /// NaN if the stop has no coordinates.
+    pub longitude: f64,
+}
+
+impl HrdfStop {
+    fn new(stop: &Stop) -> Self {
+        let coordinates = stop.wgs84_position();
+        Self {
+            id: stop.id(),
+            name: to_c_string(stop.name()),
+            latitude: coordinates
+                .and_then(|coordinates| coordinates.latitude())
+                .unwrap_or(f64::NAN),
+            longitude: coordinates
+                .and_then(|coordinates| coordinates.longitude())
+                .unwrap_or(f64::NAN),
+        }
+    }
+}
+
+#[repr(C)]
+pub struct HrdfStopList {
+    pub items: *mut HrdfStop,
+    pub len: usize,
+}
+
+/// Stops whose names contain the query (see DataStorage::search_stops). The result must be
+/// released with hrdf_stop_list_free.
+///
+/// # Safety
+///
+/// hrdf must have been returned by hrdf_load_directory and query must be a valid nul-terminated
+/// string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn hrdf_search_stops(
+    hrdf: *const Hrdf,
+    query: *const c_char,
+    expand_meta_stops: bool,
+) -> HrdfStopList {
+    // SAFETY: The caller guarantees that the arguments are valid.
+    let (hrdf, query) = unsafe { (&*hrdf, to_str(query).unwrap_or_default()) };
+    let stops = hrdf
+        .data_storage()
+        .search_stops(query, expand_meta_stops)
+        .into_iter()
+        .map(HrdfStop::new)
+        .collect();
+    let (items, len) = into_raw_parts(stops);
+    HrdfStopList { items, len }
+}
+
+/// Looks up a stop by ID. The result has an ID of 0 and a null name if the stop does not exist,
+/// and must be released with hrdf_stop_free.
+///
+/// # Safety
+///
+/// hrdf must have been returned by hrdf_load_directory.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn hrdf_find_stop(hrdf: *const Hrdf, stop_id: i32) -> HrdfStop {
+    // SAFETY: The caller guarantees that hrdf is valid.
+    let hrdf = unsafe { &*hrdf };
+    hrdf.data_storage().stops().find(stop_id).map_or(
+        HrdfStop {
+            id: 0,
+            name: ptr::null_mut(),
+            latitude: f64::NAN,
+            longitude: f64::NAN,
+        },
+        HrdfStop::new,
+    )
+}
+
+/// # Safety
+///
+/// stop must have been returned by hrdf_find_stop, and not released yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn hrdf_stop_free(stop: HrdfStop) {
+    // SAFETY: The caller guarantees that the name has been allocated by this module.
+    unsafe { free_c_string(stop.name) };
+}
+
+/// # Safety
+///
+/// stops must have been returned by hrdf_search_stops, and not released yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn hrdf_stop_list_free(stops: HrdfStopList) {
+    // SAFETY: The caller guarantees that the list has been allocated by this module.
+    for stop in unsafe { from_raw_parts(stops.items, stops.len) } {
+        unsafe { free_c_string(stop.name) };
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Departures
+// ------------------------------------------------------------------------------------------------
+
+#[repr(C)]
+pub struct HrdfDeparture {
+    pub journey_id: i32,
+    pub stop_id: i32,
+    /// Seconds since 1970-01-01 00:00, local time.
+    pub departure_at: i64,
+    /// 0 if unknown.
+    pub destination_stop_id: i32,
+    /// Null if unknown.
+    pub line_name: *mut c_char,
+    /// Null if unknown.
+    pub platform: *mut c_char,
+}
+
+#[repr(C)]
+pub struct HrdfDepartureList {
+    pub items: *mut HrdfDeparture,
+    pub len: usize,
+}
+
+/// Departures from the stop between from and until (both included, in seconds since
+/// 1970-01-01 00:00, local time), sorted by departure time (see DataStorage::station_board).
+/// The list is empty if from is not covered by the timetable. The result must be released with
+/// hrdf_departure_list_free.
+///
+/// # Safety
+///
+/// hrdf must have been returned by hrdf_load_directory.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn hrdf_departures(
+    hrdf: *const Hrdf,
+    stop_id: i32,
+    from: i64,
+    until: i64,
+    expand_meta_stops: bool,
+) -> HrdfDepartureList {
+    // SAFETY: The caller guarantees that hrdf is valid.
+    let hrdf = unsafe { &*hrdf };
+    let mut departures = Vec::new();
+    let station_board = to_naive_date_time(from)
+        .zip(to_naive_date_time(until))
+        .and_then(|(from, until)| {
+            hrdf.data_storage()
+                .station_board(stop_id, from, until, expand_meta_stops)
+                .ok()
+        });
+    if let Some(station_board) = station_board {
+        for platform in station_board.platforms() {
+            for entry in platform.entries() {
+                departures.push(HrdfDeparture {
+                    journey_id: entry.journey_id(),
+                    stop_id: platform.stop_id(),
+                    departure_at: to_seconds(entry.departure_at()),
+                    destination_stop_id: entry.destination_stop_id().unwrap_or_default(),
+                    line_name: entry.line_name().map_or(ptr::null_mut(), to_c_string),
+                    platform: platform.name().map_or(ptr::null_mut(), to_c_string),
+                });
+            }
+        }
+    }
+    departures.sort_by_key(|departure| (departure.departure_at, departure.journey_id));
+
+    let (items, len) = into_raw_parts(departures);
+    HrdfDepartureList { items, len }
+}
+
+/// # Safety
+///
+/// departures must have been returned by hrdf_departures, and not released yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn hrdf_departure_list_free(departures: HrdfDepartureList) {
+    // SAFETY: The caller guarantees that the list has been allocated by this module.
+    for departure in unsafe { from_raw_parts(departures.items, departures.len) } {
+        unsafe {
+            free_c_string(departure.line_name);
+            free_c_string(departure.platform);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn departures_through_the_c_interface() {
+        let version = CString::new("V_5_40_41_2_0_7").unwrap();
+        let path = CString::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/mini")).unwrap();
+        let hrdf = unsafe { hrdf_load_directory(version.as_ptr(), path.as_ptr()) };
+        assert!(!hrdf.is_null());
+
+        let query = CString::new("aarstadt nord").unwrap();
+        let stops = unsafe { hrdf_search_stops(hrdf, query.as_ptr(), false) };
+        assert_eq!(stops.len, 1);
+        let stop = unsafe { &*stops.items };
+        assert_eq!(stop.id, 8500102);
+        assert_eq!(
+            unsafe { CStr::from_ptr(stop.name) }.to_str().unwrap(),
+            "Aarstadt Nord"
+        );
+        unsafe { hrdf_stop_list_free(stops) };
+
+        // 15.12.2025 06:00 and 07:30.
+        let departures = unsafe { hrdf_departures(hrdf, 8500101, 1765778400, 1765783800, false) };
+        let departure_times: Vec<_> =
+            unsafe { std::slice::from_raw_parts(departures.items, departures.len) }
+                .iter()
+                .map(|departure| departure.departure_at)
+                .collect();
+        assert_eq!(departure_times, vec![1765778400, 1765782000, 1765782900]);
+        unsafe {
+            hrdf_departure_list_free(departures);
+            hrdf_free(hrdf);
+        }
+    }
+}
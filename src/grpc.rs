@@ -0,0 +1,337 @@
+//! gRPC API over a loaded timetable, built with tonic from proto/hrdf.proto: StopService,
+//! BoardService and RoutingService. The build uses the protoc of protoc-bin-vendored, or the one
+//! set in PROTOC.
+// tonic::Status is the error type of the services.
+#![allow(clippy::result_large_err)]
+use std::net::SocketAddr;
+
+use chrono::NaiveDateTime;
+use tonic::{Request, Response, Status, transport::Server};
+
+use crate::{
+    dto::{DepartureDto, MAX_DEPARTURES_WINDOW, MAX_MATRIX_STOPS, StopDto},
+    error::{HResult, HrdfError},
+    shared_storage::SharedStorage,
+};
+
+pub mod proto {
+    tonic::include_proto!("hrdf.v1");
+}
+
+use proto::{
+    board_service_server::{BoardService, BoardServiceServer},
+    routing_service_server::{RoutingService, RoutingServiceServer},
+    stop_service_server::{StopService, StopServiceServer},
+};
+
+fn parse_date_time(value: &str) -> Result<NaiveDateTime, Status> {
+    value
+        .parse()
+        .map_err(|_| Status::invalid_argument(format!("Invalid date and time: {value}")))
+}
+
+fn format_date_time(value: NaiveDateTime) -> String {
+    value.format("%Y-%m-%dT%H:%M:%S").to_string()
+}
+
+fn to_status(error: HrdfError) -> Status {
+    match error {
+        HrdfError::OutOfTimetablePeriod { .. } => Status::out_of_range(error.to_string()),
+        _ => Status::internal(error.to_string()),
+    }
+}
+
+/// The station boards and the routing are CPU-bound, they run on the blocking threads of tokio
+/// instead of the workers polling the requests.
+async fn spawn_query<T: Send + 'static>(
+    query: impl FnOnce() -> Result<T, Status> + Send + 'static,
+) -> Result<T, Status> {
+    tokio::task::spawn_blocking(query)
+        .await
+        .map_err(|error| Status::internal(error.to_string()))?
+}
+
+impl From<StopDto> for proto::Stop {
+    fn from(stop: StopDto) -> Self {
+        Self {
+            id: stop.id,
+            name: stop.name,
+            latitude: stop.latitude,
+            longitude: stop.longitude,
+        }
+    }
+}
+
+impl From<DepartureDto> for proto::Departure {
+    fn from(departure: DepartureDto) -> Self {
+        Self {
+            journey_id: departure.journey_id,
+            stop_id: departure.stop_id,
+            platform: departure.platform,
+            departure_at: format_date_time(departure.departure_at),
+            destination_stop_id: departure.destination_stop_id,
+            destination_name: departure.destination_name,
+            line_name: departure.line_name,
+            text_color: departure.text_color,
+            background_color: departure.background_color,
+            attributes: departure.attributes,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- HrdfService
+// ------------------------------------------------------------------------------------------------
+
+/// Implements the three services over the same storage. The departures and the earliest arrivals
+/// are limited by dto::MAX_DEPARTURES_WINDOW and dto::MAX_MATRIX_STOPS.
+#[derive(Clone, Debug)]
+pub struct HrdfService {
+    storage: SharedStorage,
+}
+
+impl HrdfService {
+    pub fn new(storage: SharedStorage) -> Self {
+        Self { storage }
+    }
+
+    // Functions
+
+    /// Serves the three services on the address until the process is stopped.
+    pub async fn serve(self, address: SocketAddr) -> HResult<()> {
+        log::info!("Serving the HRDF gRPC services on {address}...");
+        Server::builder()
+            .add_service(StopServiceServer::new(self.clone()))
+            .add_service(BoardServiceServer::new(self.clone()))
+            .add_service(RoutingServiceServer::new(self))
+            .serve(address)
+            .await?;
+        Ok(())
+    }
+}
+
+#[tonic::async_trait]
+impl StopService for HrdfService {
+    async fn search_stops(
+        &self,
+        request: Request<proto::SearchStopsRequest>,
+    ) -> Result<Response<proto::SearchStopsResponse>, Status> {
+        let request = request.into_inner();
+        let stops = self
+            .storage
+            .search_stops(&request.query, request.expand_meta_stops)
+            .into_iter()
+            .map(|stop| StopDto::from(stop).into())
+            .collect();
+        Ok(Response::new(proto::SearchStopsResponse { stops }))
+    }
+
+    async fn get_stop(
+        &self,
+        request: Request<proto::GetStopRequest>,
+    ) -> Result<Response<proto::Stop>, Status> {
+        let id = request.into_inner().id;
+        self.storage
+            .stop(id)
+            .map(|stop| Response::new(StopDto::from(stop).into()))
+            .ok_or_else(|| Status::not_found(format!("Stop {id} not found")))
+    }
+}
+
+#[tonic::async_trait]
+impl BoardService for HrdfService {
+    async fn get_departures(
+        &self,
+        request: Request<proto::GetDeparturesRequest>,
+    ) -> Result<Response<proto::GetDeparturesResponse>, Status> {
+        let request = request.into_inner();
+        let from = parse_date_time(&request.from)?;
+        let until = parse_date_time(&request.until)?;
+        if self.storage.stop(request.stop_id).is_none() {
+            return Err(Status::not_found(format!(
+                "Stop {} not found",
+                request.stop_id
+            )));
+        }
+        if until - from > MAX_DEPARTURES_WINDOW {
+            return Err(Status::invalid_argument(format!(
+                "The window cannot exceed {} hours",
+                MAX_DEPARTURES_WINDOW.num_hours()
+            )));
+        }
+
+        let storage = self.storage.clone();
+        let departures = spawn_query(move || {
+            let station_board = storage
+                .station_board(request.stop_id, from, until, request.expand_meta_stops)
+                .map_err(to_status)?;
+            Ok(DepartureDto::from_station_board(&station_board, &storage))
+        })
+        .await?
+        .into_iter()
+        .map(proto::Departure::from)
+        .collect();
+        Ok(Response::new(proto::GetDeparturesResponse { departures }))
+    }
+}
+
+#[tonic::async_trait]
+impl RoutingService for HrdfService {
+    async fn get_earliest_arrivals(
+        &self,
+        request: Request<proto::GetEarliestArrivalsRequest>,
+    ) -> Result<Response<proto::GetEarliestArrivalsResponse>, Status> {
+        let request = request.into_inner();
+        let departure_at = parse_date_time(&request.departure_at)?;
+        if request.origin_stop_ids.len() > MAX_MATRIX_STOPS
+            || request.destination_stop_ids.len() > MAX_MATRIX_STOPS
+        {
+            return Err(Status::invalid_argument(format!(
+                "At most {MAX_MATRIX_STOPS} origins and {MAX_MATRIX_STOPS} destinations"
+            )));
+        }
+
+        let storage = self.storage.clone();
+        let matrix = spawn_query(move || {
+            storage
+                .earliest_arrival_matrix(
+                    &request.origin_stop_ids,
+                    &request.destination_stop_ids,
+                    departure_at,
+                    request.max_transfers.unwrap_or(2) as usize,
+                )
+                .map_err(to_status)
+        })
+        .await?;
+        let arrivals = matrix
+            .origin_stop_ids()
+            .iter()
+            .flat_map(|&origin_stop_id| {
+                matrix
+                    .destination_stop_ids()
+                    .iter()
+                    .map(move |&destination_stop_id| (origin_stop_id, destination_stop_id))
+            })
+            .map(
+                |(origin_stop_id, destination_stop_id)| proto::EarliestArrival {
+                    origin_stop_id,
+                    destination_stop_id,
+                    arrival_at: matrix
+                        .arrival_at(origin_stop_id, destination_stop_id)
+                        .map(format_date_time),
+                },
+            )
+            .collect();
+        Ok(Response::new(proto::GetEarliestArrivalsResponse {
+            arrivals,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::{hrdf::Hrdf, models::Version};
+    use pretty_assertions::assert_eq;
+
+    fn service() -> HrdfService {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/mini");
+        let hrdf = Hrdf::from_directory(Version::V_5_40_41_2_0_7, &path).unwrap();
+        HrdfService::new(SharedStorage::new(hrdf.into_data_storage()))
+    }
+
+    #[tokio::test]
+    async fn services() {
+        let service = service();
+
+        let stop = service
+            .get_stop(Request::new(proto::GetStopRequest { id: 8500101 }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(stop.name, "Aarstadt");
+
+        let departures = service
+            .get_departures(Request::new(proto::GetDeparturesRequest {
+                stop_id: 8500101,
+                from: "2025-12-15T05:00:00".to_string(),
+                until: "2025-12-15T10:00:00".to_string(),
+                expand_meta_stops: false,
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .departures;
+        assert!(!departures.is_empty());
+
+        let arrivals = service
+            .get_earliest_arrivals(Request::new(proto::GetEarliestArrivalsRequest {
+                origin_stop_ids: vec![8500101],
+                destination_stop_ids: vec![8500101, 8500104],
+                departure_at: "2025-12-15T05:00:00".to_string(),
+                max_transfers: None,
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .arrivals;
+        assert_eq!(arrivals.len(), 2);
+        assert_eq!(
+            arrivals[0].arrival_at.as_deref(),
+            Some("2025-12-15T05:00:00")
+        );
+
+        let status = service
+            .get_departures(Request::new(proto::GetDeparturesRequest {
+                stop_id: 8500101,
+                from: "not a date".to_string(),
+                until: "2025-12-15T10:00:00".to_string(),
+                expand_meta_stops: false,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn request_limits() {
+        let service = service();
+
+        let status = service
+            .get_departures(Request::new(proto::GetDeparturesRequest {
+                stop_id: 8500101,
+                from: "2025-12-15T05:00:00".to_string(),
+                until: "2025-12-16T05:01:00".to_string(),
+                expand_meta_stops: false,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        let status = service
+            .get_earliest_arrivals(Request::new(proto::GetEarliestArrivalsRequest {
+                origin_stop_ids: vec![8500101],
+                destination_stop_ids: vec![8500104; MAX_MATRIX_STOPS + 1],
+                departure_at: "2025-12-15T05:00:00".to_string(),
+                max_transfers: None,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        let arrivals = service
+            .get_earliest_arrivals(Request::new(proto::GetEarliestArrivalsRequest {
+                origin_stop_ids: vec![8500101],
+                destination_stop_ids: vec![8500104; MAX_MATRIX_STOPS],
+                departure_at: "2025-12-15T05:00:00".to_string(),
+                max_transfers: None,
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .arrivals;
+        assert_eq!(arrivals.len(), MAX_MATRIX_STOPS);
+    }
+}
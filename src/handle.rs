@@ -0,0 +1,99 @@
+use std::{
+    sync::{Arc, RwLock},
+    thread::{self, JoinHandle},
+};
+
+use crate::{error::HResult, hrdf::Hrdf};
+
+// ------------------------------------------------------------------------------------------------
+// --- HrdfHandle
+// ------------------------------------------------------------------------------------------------
+
+/// Shared access to a timetable which can be replaced while it is in use, e.g. by the weekly
+/// export in a long-running API server. The clones of a handle share the same timetable.
+/// The readers get the current timetable as an Arc, which stays valid after a reload has swapped
+/// the next one in, so a query never sees two different timetables.
+#[derive(Clone, Debug)]
+pub struct HrdfHandle {
+    current: Arc<RwLock<Arc<Hrdf>>>,
+}
+
+impl HrdfHandle {
+    pub fn new(hrdf: Hrdf) -> Self {
+        Self {
+            current: Arc::new(RwLock::new(Arc::new(hrdf))),
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn current(&self) -> Arc<Hrdf> {
+        // unwrap: The lock is never held while panicking.
+        Arc::clone(&self.current.read().unwrap())
+    }
+
+    // Functions
+
+    /// Replaces the timetable and returns the previous one.
+    pub fn swap(&self, hrdf: Hrdf) -> Arc<Hrdf> {
+        let hrdf = Arc::new(hrdf);
+        // unwrap: The lock is never held while panicking.
+        let mut current = self.current.write().unwrap();
+        std::mem::replace(&mut *current, hrdf)
+    }
+
+    /// Parses the HRDF files of the current timetable again in a background thread (see
+    /// Hrdf::reload), then swaps the result in.
+    pub fn reload(&self) -> JoinHandle<HResult<()>> {
+        let current = self.current();
+        self.reload_with(move || current.reload())
+    }
+
+    /// Loads the next timetable with load in a background thread (e.g. Hrdf::new with the URL of
+    /// the new export), then swaps it in. Until then, and if the loading fails, the current
+    /// timetable stays in use.
+    pub fn reload_with(
+        &self,
+        load: impl FnOnce() -> HResult<Hrdf> + Send + 'static,
+    ) -> JoinHandle<HResult<()>> {
+        let handle = self.clone();
+        thread::spawn(move || {
+            let hrdf = load()?;
+            log::info!("Swapping in the reloaded HRDF data...");
+            handle.swap(hrdf);
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::{error::HrdfError, models::Version};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn reload_swaps_the_timetable() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/mini");
+        let handle =
+            HrdfHandle::new(Hrdf::from_directory(Version::V_5_40_41_2_0_7, &path).unwrap());
+        let previous = handle.current();
+
+        handle.reload().join().unwrap().unwrap();
+        let current = handle.current();
+        assert!(!Arc::ptr_eq(&previous, &current));
+        assert_eq!(
+            previous.data_storage().journeys().data().len(),
+            current.data_storage().journeys().data().len()
+        );
+
+        let result = handle
+            .reload_with(|| Err(HrdfError::MissingDataPath))
+            .join()
+            .unwrap();
+        assert!(result.is_err());
+        assert!(Arc::ptr_eq(&current, &handle.current()));
+    }
+}
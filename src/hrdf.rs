@@ -1,54 +1,158 @@
+#[cfg(feature = "fetch")]
 use std::{
-    env,
-    fs::{self, File},
-    io::{BufReader, Cursor},
+    env, fs, process,
+    sync::{LazyLock, Mutex},
+    time::{Instant, SystemTime},
+};
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    ops::RangeInclusive,
     path::{Path, PathBuf},
-    time::Instant,
 };
 
+#[cfg(feature = "serde")]
+use crate::cache::{self, CacheSegment};
+#[cfg(feature = "fetch")]
+use crate::instrumentation;
+#[cfg(feature = "rkyv")]
+use crate::{archive, storage::ArchivedDataStorage};
 use crate::{
     error::{HResult, HrdfError},
     models::Version,
+    parsing::ParsingOptions,
     storage::DataStorage,
 };
+#[cfg(feature = "serde")]
 use bincode::config;
 use chrono::NaiveDate;
+#[cfg(feature = "fetch")]
+use reqwest::{
+    StatusCode,
+    header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+};
+#[cfg(feature = "fetch")]
+use rustc_hash::FxHashMap;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "fetch")]
 use sha2::{Digest, Sha256};
+#[cfg(feature = "fetch")]
 use url::Url;
+#[cfg(feature = "fetch")]
 use zip::ZipArchive;
 
+/// Written at the start of the snapshots (see Hrdf::to_bytes).
+#[cfg(feature = "serde")]
+const SNAPSHOT_MAGIC: &[u8; 8] = b"HRDFSNAP";
+
+/// Version of the layout of the serialized structures. A snapshot, cache or archive can only be
+/// loaded by a build with the same version, since neither bincode nor rkyv describe the
+/// structures they encode. Must be incremented by every change to a serialized structure.
+#[cfg(any(feature = "serde", feature = "rkyv"))]
+pub(crate) const SCHEMA_VERSION: u32 = 1;
+
+#[cfg(feature = "serde")]
 #[derive(Debug, Serialize, Deserialize)]
+struct SnapshotHeader {
+    crate_version: String,
+    schema_version: u32,
+    hrdf_version: Version,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct Hrdf {
+    version: Version,
     data_storage: DataStorage,
+    // The directory of the HRDF files, None if the data has been loaded from a cache.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "rkyv", with(rkyv::with::Skip))]
+    data_path: Option<PathBuf>,
+    // The options the HRDF files have been parsed with (see reload).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "rkyv", with(rkyv::with::Skip))]
+    options: ParsingOptions,
 }
 
 impl Hrdf {
-    /// Loads and parses an HRDF archive.
+    /// Loads and parses an HRDF archive (requires the fetch feature).
     /// If an URL is provided, the HRDF archive (ZIP file) is downloaded automatically. If a path is provided, it must absolutely point to an HRDF archive (ZIP file).
-    /// The ZIP archive is automatically decompressed into the temp_dir of the OS folder.
+    /// The ZIP archive is automatically decompressed into the temp_dir of the OS folder, where the
+    /// other processes and Hrdf instances reuse it (see decompressed_archive).
+    #[cfg(feature = "fetch")]
     pub async fn new(
         version: Version,
         url_or_path: &str,
         force_rebuild_cache: bool,
         cache_prefix: Option<String>,
+    ) -> HResult<Self> {
+        Self::new_with_options(
+            version,
+            url_or_path,
+            force_rebuild_cache,
+            cache_prefix,
+            ParsingOptions::default(),
+        )
+        .await
+    }
+
+    /// Same as new, but the parsing can be configured with options.
+    /// The options are part of the cache key, so data parsed with different options is cached separately.
+    /// So is the content of the archive, so that a new export behind the same URL is parsed again.
+    /// Without the serde feature, the cache is neither used nor built.
+    #[cfg(feature = "fetch")]
+    #[cfg_attr(not(feature = "serde"), allow(unused_variables))]
+    pub async fn new_with_options(
+        version: Version,
+        url_or_path: &str,
+        force_rebuild_cache: bool,
+        cache_prefix: Option<String>,
+        options: ParsingOptions,
     ) -> HResult<Self> {
         let now = Instant::now();
 
-        let unique_filename = format!("{:x}", Sha256::digest(url_or_path.as_bytes()));
+        let archive = local_archive(url_or_path).await?;
+
+        #[cfg(feature = "serde")]
+        let cache_filename = format!(
+            "{:x}",
+            Sha256::digest(format!("{}{}", archive.key, options.cache_key()).as_bytes())
+        );
+        #[cfg(feature = "serde")]
         let cache_path = PathBuf::from(&cache_prefix.unwrap_or(String::from("./")))
-            .join(format!("{unique_filename}.cache"));
+            .join(format!("{cache_filename}.cache"));
 
+        #[cfg(feature = "serde")]
         let hrdf = if cache_path.exists() && !force_rebuild_cache {
             // Loading from cache.
             log::info!("Loading HRDF data from cache ({cache_path:?})...");
 
-            // If loading from cache fails, None is returned.
-            Self::load_from_cache(&cache_path).ok()
+            // If loading from cache fails or the cache is of another HRDF version, None is returned.
+            Self::load_from_cache(&cache_path)
+                .inspect_err(|error| log::warn!("The cache cannot be used: {error}"))
+                .ok()
+                .filter(|hrdf| hrdf.version == version)
+                .map(|mut hrdf| {
+                    // The HRDF files stay available (e.g. to raw_file) as long as the
+                    // decompressed archive is kept in the temp_dir.
+                    hrdf.data_path =
+                        Some(archive.decompressed_data_path()).filter(|path| path.exists());
+                    hrdf
+                })
         } else {
             // No loading from cache.
             None
         };
+        #[cfg(feature = "serde")]
+        instrumentation::record_cache(hrdf.is_some());
+        #[cfg(not(feature = "serde"))]
+        let hrdf = None;
 
         let hrdf = if let Some(hrdf) = hrdf {
             // The cache has been loaded without error.
@@ -56,41 +160,26 @@ impl Hrdf {
         } else {
             // The cache must be built.
             // If cache loading has failed, the cache must be rebuilt.
-            let compressed_data_path = if Url::parse(url_or_path).is_ok() {
-                let compressed_data_path = env::temp_dir().join(format!("{unique_filename}.zip"));
-
-                if !compressed_data_path.exists() {
-                    // The data must be downloaded.
-                    log::info!("Downloading HRDF data to {compressed_data_path:?}...");
-                    let response = reqwest::get(url_or_path).await?.error_for_status()?;
-                    let mut file = std::fs::File::create(&compressed_data_path)?;
-                    let mut content = Cursor::new(response.bytes().await?);
-                    std::io::copy(&mut content, &mut file)?;
-                }
-
-                compressed_data_path
-            } else {
-                PathBuf::from(url_or_path)
-            };
-
-            let decompressed_data_path = env::temp_dir().join(unique_filename);
-
-            if !decompressed_data_path.exists() {
-                // The data must be decompressed.
-                log::info!("Unzipping HRDF archive into {decompressed_data_path:?}...");
-                let file = File::open(&compressed_data_path)?;
-                let mut archive = ZipArchive::new(BufReader::new(file))?;
-                archive.extract(&decompressed_data_path)?;
-            }
+            let decompressed_data_path = decompress(&archive)?;
 
             log::info!("Parsing HRDF data from {decompressed_data_path:?}...");
 
             let hrdf = Self {
-                data_storage: DataStorage::new(version, &decompressed_data_path)?,
+                version,
+                data_storage: DataStorage::new_with_options(
+                    version,
+                    &decompressed_data_path,
+                    &options,
+                )?,
+                data_path: Some(decompressed_data_path),
+                options,
             };
 
-            log::info!("Building cache...");
-            hrdf.build_cache(&cache_path)?;
+            #[cfg(feature = "serde")]
+            {
+                log::info!("Building cache...");
+                hrdf.build_cache(&cache_path)?;
+            }
             hrdf
         };
 
@@ -104,6 +193,7 @@ impl Hrdf {
     /// Tries to load an HRDF archive for a specific date by picking the archive which
     /// date range contains the date.
     /// `force_rebuild_cache` and `cache_prefix` are option related to the caching of data.
+    #[cfg(feature = "fetch")]
     pub async fn try_from_date(
         date: NaiveDate,
         force_rebuild_cache: bool,
@@ -118,6 +208,7 @@ impl Hrdf {
     /// Tries to load an HRDF archive for a specific year (which is understood as the validity year).
     /// For example year 2026 ranes from (15.12.2025 to 14.12.2026).
     /// `force_rebuild_cache` and `cache_prefix` are option related to the caching of data.
+    #[cfg(feature = "fetch")]
     pub async fn try_from_year(
         year: i32,
         force_rebuild_cache: bool,
@@ -127,21 +218,446 @@ impl Hrdf {
         Self::try_from_date(date, force_rebuild_cache, cache_prefix).await
     }
 
+    /// Parses an HRDF archive which is already decompressed into a directory.
+    /// The cache is neither used nor built.
+    pub fn from_directory(version: Version, path: &Path) -> HResult<Self> {
+        Self::from_directory_with_options(version, path, ParsingOptions::default())
+    }
+
+    /// Same as from_directory, but the parsing can be configured with options.
+    pub fn from_directory_with_options(
+        version: Version,
+        path: &Path,
+        options: ParsingOptions,
+    ) -> HResult<Self> {
+        log::info!("Parsing HRDF data from {path:?}...");
+        Ok(Self {
+            version,
+            data_storage: DataStorage::new_with_options(version, path, &options)?,
+            data_path: Some(path.to_path_buf()),
+            options,
+        })
+    }
+
     // Getters/Setters
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
     pub fn data_storage(&self) -> &DataStorage {
         &self.data_storage
     }
 
+    pub fn into_data_storage(self) -> DataStorage {
+        self.data_storage
+    }
+
+    /// The directory of the HRDF files. None if the data has been loaded from bytes or from a
+    /// cache, unless the cache has been loaded by new and the decompressed archive still exists.
+    pub fn data_path(&self) -> Option<&Path> {
+        self.data_path.as_deref()
+    }
+
+    /// First and last day (inclusive) covered by the timetable, as defined in ECKDATEN.
+    pub fn timetable_period(&self) -> RangeInclusive<NaiveDate> {
+        self.data_storage.timetable_period()
+    }
+
     // Functions
+
+    /// Parses the HRDF files again, with the same version and options, e.g. after the directory
+    /// has been updated in place. The current data is left untouched (see HrdfHandle::reload).
+    pub fn reload(&self) -> HResult<Self> {
+        let data_path = self.data_path.as_ref().ok_or(HrdfError::MissingDataPath)?;
+        Self::from_directory_with_options(self.version, data_path, self.options.clone())
+    }
+
+    /// Lines of any file of the dataset (e.g. "BAHNHOF"), including the files not modeled by the
+    /// crate, so that they can be parsed without decompressing the archive again.
+    /// The lines are read lazily and the iteration stops at the first unreadable line.
+    /// Fails with HrdfError::MissingDataPath if the HRDF files are not available (see data_path).
+    pub fn raw_file(&self, name: &str) -> HResult<impl Iterator<Item = String>> {
+        let data_path = self.data_path.as_ref().ok_or(HrdfError::MissingDataPath)?;
+        // Only the files of the dataset can be read, not e.g. "../secret".
+        if Path::new(name)
+            .file_name()
+            .is_none_or(|file_name| file_name != name)
+        {
+            return Err(HrdfError::InvalidFileName(name.to_string()));
+        }
+
+        let file = File::open(data_path.join(name))?;
+        Ok(BufReader::new(file).lines().map_while(io::Result::ok))
+    }
+
+    /// Writes the cache, with one segment per resource (see load_from_cache_segments).
+    #[cfg(feature = "serde")]
     pub fn build_cache(&self, path: &Path) -> HResult<()> {
-        let data = bincode::serde::encode_to_vec(self, config::standard())?;
-        fs::write(path, data)?;
-        Ok(())
+        cache::write_cache(path, self.version, &self.data_storage)
     }
 
+    #[cfg(feature = "serde")]
     pub fn load_from_cache(path: &Path) -> HResult<Self> {
-        let data = fs::read(path)?;
-        let (hrdf, _) = bincode::serde::decode_from_slice(&data, config::standard())?;
+        Self::load_from_cache_segments(path, CacheSegment::ALL)
+    }
+
+    /// Loads only some segments of the cache, the resources of the other segments are empty.
+    /// E.g. a geocoder only needs CacheSegment::Stops. The core segment is always loaded.
+    #[cfg(feature = "serde")]
+    pub fn load_from_cache_segments(path: &Path, segments: &[CacheSegment]) -> HResult<Self> {
+        let (version, data_storage) = cache::read_cache(path, segments)?;
+        Ok(Self {
+            version,
+            data_storage,
+            data_path: None,
+            options: ParsingOptions::default(),
+        })
+    }
+
+    /// Encodes the data as a single snapshot, e.g. to ship a pre-parsed dataset to a
+    /// browser (see from_bytes). The snapshot starts with a header giving the crate version,
+    /// the HRDF version and the version of the schema of the data.
+    #[cfg(feature = "serde")]
+    pub fn to_bytes(&self) -> HResult<Vec<u8>> {
+        let header = SnapshotHeader {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            schema_version: SCHEMA_VERSION,
+            hrdf_version: self.version,
+        };
+        let mut data = SNAPSHOT_MAGIC.to_vec();
+        data.extend(bincode::serde::encode_to_vec(&header, config::standard())?);
+        data.extend(bincode::serde::encode_to_vec(self, config::standard())?);
+        Ok(data)
+    }
+
+    /// Decodes data encoded by to_bytes. Unlike the other constructors,
+    /// it needs no file system, hence works in wasm32-unknown-unknown.
+    /// Fails with HrdfError::IncompatibleSnapshot if the snapshot has been written by a build
+    /// with another schema.
+    #[cfg(feature = "serde")]
+    pub fn from_bytes(data: &[u8]) -> HResult<Self> {
+        let data = data.strip_prefix(SNAPSHOT_MAGIC).ok_or_else(|| {
+            HrdfError::IncompatibleSnapshot("the data is not an HRDF snapshot".to_string())
+        })?;
+        let (header, header_len): (SnapshotHeader, _) =
+            bincode::serde::decode_from_slice(data, config::standard())?;
+        if header.schema_version != SCHEMA_VERSION {
+            return Err(HrdfError::IncompatibleSnapshot(format!(
+                "the snapshot has been written by hrdf-parser {} (schema {}), this is hrdf-parser {} (schema {SCHEMA_VERSION})",
+                header.crate_version,
+                header.schema_version,
+                env!("CARGO_PKG_VERSION"),
+            )));
+        }
+
+        let (hrdf, _): (Self, _) =
+            bincode::serde::decode_from_slice(&data[header_len..], config::standard())?;
+        if hrdf.version != header.hrdf_version {
+            return Err(HrdfError::IncompatibleSnapshot(format!(
+                "the header announces {} but the data is {}",
+                header.hrdf_version, hrdf.version
+            )));
+        }
         Ok(hrdf)
     }
+
+    /// Writes the data as an rkyv archive, which MappedHrdf memory-maps and queries without
+    /// deserializing it. The archive has the same compatibility checks as the snapshots.
+    #[cfg(feature = "rkyv")]
+    pub fn save_archive(&self, path: &Path) -> HResult<()> {
+        archive::save_archive(self, path)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl ArchivedHrdf {
+    // Getters/Setters
+    pub fn data_storage(&self) -> &ArchivedDataStorage {
+        &self.data_storage
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Archives
+// ------------------------------------------------------------------------------------------------
+
+/// A zipped HRDF archive on the file system, with a key derived from its URL or path and from its
+/// content.
+#[cfg(feature = "fetch")]
+struct LocalArchive {
+    path: PathBuf,
+    key: String,
+}
+
+#[cfg(feature = "fetch")]
+impl LocalArchive {
+    /// The directory into which the archive is decompressed, in the temp_dir of the OS.
+    fn decompressed_data_path(&self) -> PathBuf {
+        env::temp_dir().join(&self.key)
+    }
+}
+
+/// The modification time and the length of a file.
+#[cfg(feature = "fetch")]
+type FileVersion = (SystemTime, u64);
+
+/// The content hashes of the archives hashed by this process, by path, with the version of the
+/// file they have been computed from.
+#[cfg(feature = "fetch")]
+static CONTENT_HASHES: LazyLock<Mutex<FxHashMap<PathBuf, (FileVersion, String)>>> =
+    LazyLock::new(Default::default);
+
+/// Returns the archive of the URL or path. An archive is downloaded into the temp_dir of the OS,
+/// under a key derived from the URL, so that the processes of a host share it. The copy
+/// downloaded before is revalidated with its ETag and Last-Modified, so that the new content of
+/// a permalink is picked up, and is used as is if the server cannot be reached.
+#[cfg(feature = "fetch")]
+async fn local_archive(url_or_path: &str) -> HResult<LocalArchive> {
+    let path = if Url::parse(url_or_path).is_ok() {
+        let url_key = format!("{:x}", Sha256::digest(url_or_path.as_bytes()));
+        let path = env::temp_dir().join(format!("{url_key}.zip"));
+        match download(url_or_path, &path).await {
+            Ok(()) => {}
+            Err(error) if path.exists() => {
+                log::warn!("The HRDF archive cannot be revalidated, {path:?} is used: {error}")
+            }
+            Err(error) => return Err(error),
+        }
+        path
+    } else {
+        PathBuf::from(url_or_path)
+    };
+
+    let key = format!(
+        "{:x}",
+        Sha256::digest(format!("{url_or_path}{}", content_hash(&path)?).as_bytes())
+    );
+    Ok(LocalArchive { path, key })
+}
+
+/// Downloads the archive to path, unless the copy already there is still current.
+#[cfg(feature = "fetch")]
+async fn download(url: &str, path: &Path) -> HResult<()> {
+    // The conditional headers of the copy, one per line (e.g. "if-none-match: \"1a2b\"").
+    let conditions_path = path.with_extension("conditions");
+    let mut request = reqwest::Client::new().get(url);
+    if path.exists() {
+        for line in fs::read_to_string(&conditions_path)
+            .unwrap_or_default()
+            .lines()
+        {
+            if let Some((name, value)) = line.split_once(": ") {
+                request = request.header(name, value);
+            }
+        }
+    }
+
+    let started_at = Instant::now();
+    let response = request.send().await?.error_for_status()?;
+    if response.status() == StatusCode::NOT_MODIFIED {
+        log::info!("The HRDF archive {path:?} is up to date");
+        return Ok(());
+    }
+    let conditions: String = [(ETAG, IF_NONE_MATCH), (LAST_MODIFIED, IF_MODIFIED_SINCE)]
+        .into_iter()
+        .filter_map(|(validator, condition)| {
+            let value = response.headers().get(validator)?.to_str().ok()?;
+            Some(format!("{condition}: {value}\n"))
+        })
+        .collect();
+
+    log::info!("Downloading HRDF data to {path:?}...");
+    let data = response.bytes().await?;
+    instrumentation::record_download(data.len(), started_at);
+    // Written under other names first, so that the other processes never see a partial archive.
+    // The conditions come last, so that they never validate an older archive.
+    let partial_path = path.with_extension(format!("{}", process::id()));
+    fs::write(&partial_path, data)?;
+    fs::rename(&partial_path, path)?;
+    fs::write(&partial_path, conditions)?;
+    fs::rename(&partial_path, &conditions_path)?;
+    Ok(())
+}
+
+/// Hashes the content of the archive, once per version of the file in this process.
+#[cfg(feature = "fetch")]
+fn content_hash(path: &Path) -> HResult<String> {
+    let metadata = fs::metadata(path)?;
+    let version = (metadata.modified()?, metadata.len());
+    // unwrap: The lock is never held while panicking.
+    if let Some((_, hash)) = CONTENT_HASHES
+        .lock()
+        .unwrap()
+        .get(path)
+        .filter(|(known_version, _)| *known_version == version)
+    {
+        return Ok(hash.clone());
+    }
+
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    let hash = format!("{:x}", hasher.finalize());
+    // unwrap: The lock is never held while panicking.
+    CONTENT_HASHES
+        .lock()
+        .unwrap()
+        .insert(path.to_path_buf(), (version, hash.clone()));
+    Ok(hash)
+}
+
+/// Decompresses the archive, unless a process of the host already has. Returns the directory of
+/// the HRDF files.
+#[cfg(feature = "fetch")]
+fn decompress(archive: &LocalArchive) -> HResult<PathBuf> {
+    let decompressed_data_path = archive.decompressed_data_path();
+
+    if !decompressed_data_path.exists() {
+        // The data must be decompressed.
+        log::info!("Unzipping HRDF archive into {decompressed_data_path:?}...");
+        let partial_path = decompressed_data_path.with_extension(format!("{}", process::id()));
+        let file = File::open(&archive.path)?;
+        let mut archive = ZipArchive::new(BufReader::new(file))?;
+        archive.extract(&partial_path)?;
+        if fs::rename(&partial_path, &decompressed_data_path).is_err() {
+            // Another process has decompressed the archive in the meantime.
+            fs::remove_dir_all(&partial_path)?;
+        }
+    }
+
+    Ok(decompressed_data_path)
+}
+
+#[cfg(all(test, feature = "fetch"))]
+mod tests {
+    use std::{
+        io::Write,
+        net::TcpListener,
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread,
+    };
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use test_log::test;
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    /// Zips the mini dataset into the temp_dir.
+    fn zip_mini_dataset(name: &str) -> PathBuf {
+        let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/mini");
+        let zip_path = env::temp_dir().join(name);
+        let mut writer = ZipWriter::new(File::create(&zip_path).unwrap());
+        for entry in fs::read_dir(&source).unwrap() {
+            let entry = entry.unwrap();
+            writer
+                .start_file(
+                    entry.file_name().to_string_lossy(),
+                    SimpleFileOptions::default(),
+                )
+                .unwrap();
+            writer.write_all(&fs::read(entry.path()).unwrap()).unwrap();
+        }
+        writer.finish().unwrap();
+        zip_path
+    }
+
+    #[test(tokio::test)]
+    async fn archives_are_decompressed_once() {
+        let zip_path = zip_mini_dataset("hrdf-parser-archives-are-decompressed-once.zip");
+        let url_or_path = zip_path.to_str().unwrap();
+        let first = decompress(&local_archive(url_or_path).await.unwrap()).unwrap();
+        let second = decompress(&local_archive(url_or_path).await.unwrap()).unwrap();
+        assert_eq!(first, second);
+        assert!(first.join("BAHNHOF").exists());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test(tokio::test)]
+    async fn raw_file_after_a_cached_load() {
+        let zip_path = zip_mini_dataset("hrdf-parser-raw-file-after-a-cached-load.zip");
+        let url_or_path = zip_path.to_str().unwrap();
+        let cache_prefix = env::temp_dir().join("hrdf-parser-raw-file-after-a-cached-load");
+        fs::create_dir_all(&cache_prefix).unwrap();
+        let cache_prefix = cache_prefix.to_str().map(String::from);
+
+        Hrdf::new(
+            Version::V_5_40_41_2_0_7,
+            url_or_path,
+            true,
+            cache_prefix.clone(),
+        )
+        .await
+        .unwrap();
+        let hrdf = Hrdf::new(Version::V_5_40_41_2_0_7, url_or_path, false, cache_prefix)
+            .await
+            .unwrap();
+        assert!(hrdf.data_path().is_some());
+        assert_eq!(
+            hrdf.raw_file("BAHNHOF").unwrap().count(),
+            fs::read_to_string(hrdf.data_path().unwrap().join("BAHNHOF"))
+                .unwrap()
+                .lines()
+                .count()
+        );
+    }
+
+    /// Serves the content with its hash as ETag, answers 304 to the requests which already have
+    /// it. Returns the URL and the number of 200 responses.
+    fn serve(content: Arc<Mutex<Vec<u8>>>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hrdf.zip", listener.local_addr().unwrap());
+        let downloads = Arc::new(AtomicUsize::new(0));
+        let served = Arc::clone(&downloads);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let request: Vec<_> = BufReader::new(&stream)
+                    .lines()
+                    .map(|line| line.unwrap().to_lowercase())
+                    .take_while(|line| !line.is_empty())
+                    .collect();
+                let body = content.lock().unwrap().clone();
+                let etag = format!("\"{:x}\"", Sha256::digest(&body));
+                if request.contains(&format!("if-none-match: {etag}")) {
+                    write!(
+                        stream,
+                        "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n"
+                    )
+                    .unwrap();
+                } else {
+                    served.fetch_add(1, Ordering::SeqCst);
+                    write!(
+                        stream,
+                        "HTTP/1.1 200 OK\r\nETag: {etag}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        body.len()
+                    )
+                    .unwrap();
+                    stream.write_all(&body).unwrap();
+                }
+            }
+        });
+        (url, downloads)
+    }
+
+    #[test(tokio::test)]
+    async fn downloads_are_revalidated() {
+        let content = Arc::new(Mutex::new(b"first export".to_vec()));
+        let (url, downloads) = serve(Arc::clone(&content));
+        // The port may have been used by an earlier run.
+        let url_key = format!("{:x}", Sha256::digest(url.as_bytes()));
+        let _ = fs::remove_file(env::temp_dir().join(format!("{url_key}.zip")));
+
+        let first = local_archive(&url).await.unwrap();
+        let second = local_archive(&url).await.unwrap();
+        assert_eq!(first.key, second.key);
+        assert_eq!(downloads.load(Ordering::SeqCst), 1);
+
+        *content.lock().unwrap() = b"second export".to_vec();
+        let third = local_archive(&url).await.unwrap();
+        assert_ne!(first.key, third.key);
+        assert_eq!(downloads.load(Ordering::SeqCst), 2);
+        assert_eq!(fs::read(&third.path).unwrap(), b"second export");
+    }
 }
@@ -0,0 +1,254 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::{
+    error::{HResult, HrdfError},
+    hrdf::Hrdf,
+    models::{Arrival, Departure},
+    routing::ArrivalMatrix,
+    station_board::StationBoard,
+};
+
+// ------------------------------------------------------------------------------------------------
+// --- HrdfSet
+// ------------------------------------------------------------------------------------------------
+
+/// Several loaded timetables (e.g. 2025 and 2026, or weekly exports), the queries being routed to
+/// the timetable covering their date. Where the timetable periods overlap, the timetable starting
+/// last, i.e. the most recent export, is used.
+#[derive(Debug, Default)]
+pub struct HrdfSet {
+    // Sorted by start of the timetable period.
+    datasets: Vec<Hrdf>,
+}
+
+impl HrdfSet {
+    pub fn new(datasets: Vec<Hrdf>) -> Self {
+        let mut hrdf_set = Self::default();
+        for hrdf in datasets {
+            hrdf_set.insert(hrdf);
+        }
+        hrdf_set
+    }
+
+    // Getters/Setters
+
+    pub fn datasets(&self) -> &[Hrdf] {
+        &self.datasets
+    }
+
+    // Functions
+
+    pub fn insert(&mut self, hrdf: Hrdf) {
+        let index = self.datasets.partition_point(|other| {
+            other.timetable_period().start() <= hrdf.timetable_period().start()
+        });
+        self.datasets.insert(index, hrdf);
+    }
+
+    /// The timetable used for the date.
+    pub fn for_date(&self, date: NaiveDate) -> HResult<&Hrdf> {
+        self.index_for_date(date)
+            .map(|index| &self.datasets[index])
+            .ok_or(HrdfError::NoDatasetForDate(date))
+    }
+
+    fn index_for_date(&self, date: NaiveDate) -> Option<usize> {
+        self.datasets
+            .iter()
+            .rposition(|hrdf| hrdf.timetable_period().contains(&date))
+    }
+
+    /// Same as DataStorage::departures, over all the timetables. Each departure comes with the
+    /// timetable of its service day, to which its journey ID refers. Fails with NoDatasetForDate
+    /// if no timetable covers the date of from.
+    pub fn departures(
+        &self,
+        stop_id: i32,
+        from: NaiveDateTime,
+        until: NaiveDateTime,
+    ) -> HResult<Vec<(&Hrdf, Departure)>> {
+        let mut departures = self.merge(
+            from,
+            until,
+            |hrdf, from| hrdf.data_storage().departures(stop_id, from, until),
+            Departure::service_day,
+        )?;
+        departures.sort_by_key(|(_, departure)| (departure.departure_at(), departure.journey_id()));
+        Ok(departures)
+    }
+
+    /// Same as DataStorage::arrivals, over all the timetables. Each arrival comes with the
+    /// timetable of its service day, to which its journey ID refers. Fails with NoDatasetForDate
+    /// if no timetable covers the date of from.
+    pub fn arrivals(
+        &self,
+        stop_id: i32,
+        from: NaiveDateTime,
+        until: NaiveDateTime,
+    ) -> HResult<Vec<(&Hrdf, Arrival)>> {
+        let mut arrivals = self.merge(
+            from,
+            until,
+            |hrdf, from| hrdf.data_storage().arrivals(stop_id, from, until),
+            Arrival::service_day,
+        )?;
+        arrivals.sort_by_key(|(_, arrival)| (arrival.arrival_at(), arrival.journey_id()));
+        Ok(arrivals)
+    }
+
+    /// Runs the query on every timetable whose period overlaps from..=until, from being moved to
+    /// the start of the period if it is earlier, and keeps the results whose service day is
+    /// covered by the timetable which returned them.
+    fn merge<T>(
+        &self,
+        from: NaiveDateTime,
+        until: NaiveDateTime,
+        query: impl Fn(&Hrdf, NaiveDateTime) -> HResult<Vec<T>>,
+        service_day: impl Fn(&T) -> NaiveDate,
+    ) -> HResult<Vec<(&Hrdf, T)>> {
+        self.for_date(from.date())?;
+
+        let mut results = Vec::new();
+        for (index, hrdf) in self.datasets.iter().enumerate() {
+            let timetable_period = hrdf.timetable_period();
+            let from = from.max(timetable_period.start().and_time(NaiveTime::MIN));
+            if from > until || !timetable_period.contains(&from.date()) {
+                continue;
+            }
+
+            results.extend(
+                query(hrdf, from)?
+                    .into_iter()
+                    .filter(|result| self.index_for_date(service_day(result)) == Some(index))
+                    .map(|result| (hrdf, result)),
+            );
+        }
+        Ok(results)
+    }
+
+    /// Same as DataStorage::station_board, in the timetable of the date of from.
+    pub fn station_board(
+        &self,
+        stop_id: i32,
+        from: NaiveDateTime,
+        until: NaiveDateTime,
+        expand_meta_stops: bool,
+    ) -> HResult<(&Hrdf, StationBoard)> {
+        let hrdf = self.for_date(from.date())?;
+        let station_board =
+            hrdf.data_storage()
+                .station_board(stop_id, from, until, expand_meta_stops)?;
+        Ok((hrdf, station_board))
+    }
+
+    /// Same as DataStorage::earliest_arrival_matrix, in the timetable of the date of departure_at.
+    pub fn earliest_arrival_matrix(
+        &self,
+        origin_stop_ids: &[i32],
+        destination_stop_ids: &[i32],
+        departure_at: NaiveDateTime,
+        max_transfers: usize,
+    ) -> HResult<ArrivalMatrix> {
+        self.for_date(departure_at.date())?
+            .data_storage()
+            .earliest_arrival_matrix(
+                origin_stop_ids,
+                destination_stop_ids,
+                departure_at,
+                max_transfers,
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::Path};
+
+    use super::*;
+    use crate::models::Version;
+    use pretty_assertions::assert_eq;
+
+    fn date(day: u32, month: u32, year: i32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn queries_across_timetables() {
+        let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/mini");
+        let current = Hrdf::from_directory(Version::V_5_40_41_2_0_7, &source).unwrap();
+
+        // The same data, for the next timetable period.
+        let next_path = env::temp_dir().join("hrdf-parser-queries-across-timetables");
+        fs::create_dir_all(&next_path).unwrap();
+        for entry in fs::read_dir(&source).unwrap() {
+            let entry = entry.unwrap();
+            fs::copy(entry.path(), next_path.join(entry.file_name())).unwrap();
+        }
+        fs::write(
+            next_path.join("ECKDATEN"),
+            "13.12.2026\n11.12.2027\nNext timetable$01.06.2026$1.00$hrdf-parser\n",
+        )
+        .unwrap();
+        let next = Hrdf::from_directory(Version::V_5_40_41_2_0_7, &next_path).unwrap();
+
+        let hrdf_set = HrdfSet::new(vec![next, current]);
+        assert_eq!(
+            *hrdf_set
+                .for_date(date(12, 12, 2026))
+                .unwrap()
+                .timetable_period()
+                .start(),
+            date(14, 12, 2025)
+        );
+        assert_eq!(
+            *hrdf_set
+                .for_date(date(13, 12, 2026))
+                .unwrap()
+                .timetable_period()
+                .start(),
+            date(13, 12, 2026)
+        );
+        assert!(matches!(
+            hrdf_set.for_date(date(1, 1, 2028)),
+            Err(HrdfError::NoDatasetForDate(_))
+        ));
+
+        let departures = hrdf_set
+            .departures(
+                8500101,
+                date(12, 12, 2026).and_hms_opt(5, 0, 0).unwrap(),
+                date(13, 12, 2026).and_hms_opt(8, 0, 0).unwrap(),
+            )
+            .unwrap();
+        assert!(
+            departures
+                .iter()
+                .any(|(_, departure)| departure.service_day() == date(12, 12, 2026))
+        );
+        assert!(
+            departures
+                .iter()
+                .any(|(_, departure)| departure.service_day() == date(13, 12, 2026))
+        );
+        for (hrdf, departure) in departures {
+            assert!(hrdf.timetable_period().contains(&departure.service_day()));
+            assert_eq!(
+                hrdf.timetable_period().start(),
+                hrdf_set
+                    .for_date(departure.service_day())
+                    .unwrap()
+                    .timetable_period()
+                    .start()
+            );
+        }
+        assert!(matches!(
+            hrdf_set.departures(
+                8500101,
+                date(1, 1, 2028).and_hms_opt(5, 0, 0).unwrap(),
+                date(1, 1, 2028).and_hms_opt(8, 0, 0).unwrap(),
+            ),
+            Err(HrdfError::NoDatasetForDate(_))
+        ));
+        fs::remove_dir_all(&next_path).unwrap();
+    }
+}
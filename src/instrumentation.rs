@@ -0,0 +1,52 @@
+//! Metrics recorded through the metrics facade (requires the metrics feature), exported by the
+//! recorder installed by the application, e.g. metrics-exporter-prometheus. Without the feature,
+//! the functions only log or do nothing.
+//!
+//! - hrdf_download_bytes_total (counter) and hrdf_download_duration_seconds (histogram), the
+//!   downloads of the HRDF archives.
+//! - hrdf_parse_duration_seconds (histogram, labeled by resource), the parsing of each file.
+//! - hrdf_cache_hits_total and hrdf_cache_misses_total (counters), the cache of Hrdf::new.
+//! - hrdf_query_duration_seconds (histogram, labeled by query), the queries of DataStorage.
+use std::time::Instant;
+
+#[cfg(feature = "fetch")]
+pub(crate) fn record_download(bytes: usize, started_at: Instant) {
+    let elapsed = started_at.elapsed();
+    log::info!("Downloaded {bytes} bytes in {elapsed:.2?}");
+    #[cfg(feature = "metrics")]
+    {
+        metrics::counter!("hrdf_download_bytes_total").increment(bytes as u64);
+        metrics::histogram!("hrdf_download_duration_seconds").record(elapsed.as_secs_f64());
+    }
+}
+
+pub(crate) fn record_parse(resource: &'static str, started_at: Instant) {
+    let elapsed = started_at.elapsed();
+    log::info!("Time elapsed for {resource} parsing: {elapsed:?}");
+    #[cfg(feature = "metrics")]
+    metrics::histogram!("hrdf_parse_duration_seconds", "resource" => resource)
+        .record(elapsed.as_secs_f64());
+}
+
+#[cfg(all(feature = "fetch", feature = "serde"))]
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_cache(hit: bool) {
+    #[cfg(feature = "metrics")]
+    if hit {
+        metrics::counter!("hrdf_cache_hits_total").increment(1);
+    } else {
+        metrics::counter!("hrdf_cache_misses_total").increment(1);
+    }
+}
+
+/// Runs the query and records its duration.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn timed_query<T>(query: &'static str, run: impl FnOnce() -> T) -> T {
+    #[cfg(feature = "metrics")]
+    let started_at = Instant::now();
+    let result = run();
+    #[cfg(feature = "metrics")]
+    metrics::histogram!("hrdf_query_duration_seconds", "query" => query)
+        .record(started_at.elapsed().as_secs_f64());
+    result
+}
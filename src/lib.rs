@@ -1,19 +1,85 @@
 #![doc = include_str!("../README.md")]
+mod administrations;
+#[cfg(feature = "rkyv")]
+mod archive;
+#[cfg(feature = "serde")]
+mod cache;
+mod delta;
+#[cfg(feature = "serde")]
+pub mod dto;
 mod error;
+mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+mod handle;
 mod hrdf;
+mod hrdf_set;
+mod instrumentation;
 mod models;
+#[cfg(feature = "node")]
+mod node;
 mod parsing;
+#[cfg(feature = "python")]
+mod python;
+mod routing;
+#[cfg(feature = "server")]
+pub mod server;
+mod shared_storage;
+#[cfg(feature = "spatial")]
+mod spatial;
+mod station_board;
+mod stop_hierarchy;
 mod storage;
+#[cfg(feature = "synthetic")]
+mod synthetic;
+#[cfg(feature = "time")]
+mod time_conversion;
+mod transfers;
 mod utils;
+mod validation;
 
+pub use administrations::Administration;
+#[cfg(feature = "rkyv")]
+pub use archive::MappedHrdf;
+#[cfg(feature = "serde")]
+pub use cache::CacheSegment;
+pub use delta::JourneyDelta;
 pub use error::HrdfError as Error;
+pub use handle::HrdfHandle;
+#[cfg(feature = "rkyv")]
+pub use hrdf::ArchivedHrdf;
 pub use hrdf::Hrdf;
+pub use hrdf_set::HrdfSet;
 pub use models::*;
+pub use parsing::{
+    DuplicatePlatformPolicy, FileRules, ParseIssue, ParseReport, ParsingMode, ParsingOptions,
+    RouteTimeCheck, Visitor, visit,
+};
+pub use routing::{ArrivalMatrix, ItineraryLeg};
+pub use shared_storage::SharedStorage;
+#[cfg(feature = "spatial")]
+pub use spatial::{PlatformPoint, StopPoint};
+pub use station_board::{StationBoard, StationBoardEntry, StationBoardPlatform};
+pub use stop_hierarchy::{BoardingArea, LocationType, StopHierarchy};
 pub use storage::DataStorage;
+#[cfg(feature = "rkyv")]
+pub use storage::{ArchivedDataStorage, ArchivedResourceStorage};
+#[cfg(feature = "synthetic")]
+pub use synthetic::{SAMPLE_DATASET_VERSION, generate_sample_dataset};
+#[cfg(feature = "time")]
+pub use time_conversion::{ToChrono, ToTime};
+pub use transfers::{Transfer, TransferStatistics};
+pub use utils::swiss_local_to_fixed_offset;
 pub use utils::timetable_end_date;
 pub use utils::timetable_start_date;
+pub use validation::{
+    CoordinateProblem, Issue, ReferenceKind, ResourceKind, Severity, ValidationIssue,
+    ValidationReport,
+};
 
-#[cfg(test)]
+#[cfg(all(test, feature = "fetch"))]
 mod tests {
     use super::*;
     use chrono::NaiveDate;
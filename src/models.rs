@@ -1,10 +1,12 @@
 use std::{
     collections::BTreeSet,
     hash::{DefaultHasher, Hash, Hasher},
+    ops::RangeInclusive,
 };
 
-use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike};
 use rustc_hash::FxHashMap;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use strum_macros::{self, Display, EnumString};
 
@@ -13,7 +15,7 @@ use thiserror::Error;
 use crate::{
     error::{HResult, HrdfError},
     storage::DataStorage,
-    utils::{add_1_day, sub_1_day},
+    utils::{add_days, bit_index_of_date, encode_polyline, sub_days, swiss_local_to_fixed_offset},
 };
 
 pub(crate) type JourneyId = (i32, String); // (legacy_id, administration)
@@ -24,7 +26,10 @@ pub(crate) type JourneyId = (i32, String); // (legacy_id, administration)
 
 pub trait Model<M: Model<M>> {
     // Primary key type.
-    type K: Copy + Eq + Hash + Serialize + for<'a> Deserialize<'a>;
+    #[cfg(feature = "serde")]
+    type K: Clone + Eq + Hash + Serialize + for<'a> Deserialize<'a>;
+    #[cfg(not(feature = "serde"))]
+    type K: Clone + Eq + Hash;
 
     fn id(&self) -> M::K;
 }
@@ -41,11 +46,72 @@ macro_rules! impl_Model {
     };
 }
 
+// ------------------------------------------------------------------------------------------------
+// --- Arrival
+// ------------------------------------------------------------------------------------------------
+
+/// A journey reaching a stop at a given date and time (see DataStorage::arrivals).
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Arrival {
+    journey_id: i32,
+    stop_id: i32,
+    service_day: NaiveDate,
+    arrival_at: NaiveDateTime,
+}
+
+impl Arrival {
+    pub fn new(
+        journey_id: i32,
+        stop_id: i32,
+        service_day: NaiveDate,
+        arrival_at: NaiveDateTime,
+    ) -> Self {
+        Self {
+            journey_id,
+            stop_id,
+            service_day,
+            arrival_at,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn journey_id(&self) -> i32 {
+        self.journey_id
+    }
+
+    pub fn stop_id(&self) -> i32 {
+        self.stop_id
+    }
+
+    /// The day the journey leaves its first stop (see Departure::service_day).
+    pub fn service_day(&self) -> NaiveDate {
+        self.service_day
+    }
+
+    pub fn arrival_at(&self) -> NaiveDateTime {
+        self.arrival_at
+    }
+
+    // Functions
+
+    pub fn journey<'a>(&self, data_storage: &'a DataStorage) -> Option<&'a Journey> {
+        data_storage.journeys().find(self.journey_id)
+    }
+}
+
 // ------------------------------------------------------------------------------------------------
 // --- Attribute
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct Attribute {
     id: i32,
     designation: String,
@@ -77,6 +143,26 @@ impl Attribute {
 
     // Getters/Setters
 
+    pub fn designation(&self) -> &str {
+        &self.designation
+    }
+
+    pub fn stop_scope(&self) -> i16 {
+        self.stop_scope
+    }
+
+    pub fn main_sorting_priority(&self) -> i16 {
+        self.main_sorting_priority
+    }
+
+    pub fn secondary_sorting_priority(&self) -> i16 {
+        self.secondary_sorting_priority
+    }
+
+    pub fn description(&self, language: impl Into<LanguagePreference>) -> Option<&str> {
+        language.into().resolve(&self.description)
+    }
+
     pub fn set_description(&mut self, language: Language, value: &str) {
         self.description.insert(language, value.to_string());
     }
@@ -86,7 +172,13 @@ impl Attribute {
 // --- BitField
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct BitField {
     id: i32,
     bits: Vec<u8>,
@@ -104,13 +196,88 @@ impl BitField {
     pub fn bits(&self) -> &Vec<u8> {
         &self.bits
     }
+
+    // Functions
+
+    /// Returns true if the bit corresponding to the date is set.
+    pub fn is_active_on(&self, date: NaiveDate, timetable_start_date: NaiveDate) -> bool {
+        bit_index_of_date(date, timetable_start_date)
+            .and_then(|index| self.bits.get(index))
+            .is_some_and(|&bit| bit == 1)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- BoundingBox
+// ------------------------------------------------------------------------------------------------
+
+/// Smallest WGS84 rectangle containing a set of coordinates.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BoundingBox {
+    south_west: Coordinates,
+    north_east: Coordinates,
+}
+
+impl BoundingBox {
+    pub fn new(south_west: Coordinates, north_east: Coordinates) -> Self {
+        Self {
+            south_west,
+            north_east,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn south_west(&self) -> Coordinates {
+        self.south_west
+    }
+
+    pub fn north_east(&self) -> Coordinates {
+        self.north_east
+    }
+
+    // Functions
+
+    /// Returns true if the coordinates are inside the rectangle, borders included.
+    /// LV95 coordinates are converted to WGS84 first.
+    pub fn contains(&self, coordinates: &Coordinates) -> bool {
+        let coordinates = coordinates.to_wgs84();
+        let (south_west, north_east) = (self.south_west.to_wgs84(), self.north_east.to_wgs84());
+        (south_west.x..=north_east.x).contains(&coordinates.x)
+            && (south_west.y..=north_east.y).contains(&coordinates.y)
+    }
+}
+
+/// The x axis of geo is the longitude, the y axis the latitude.
+#[cfg(feature = "geo")]
+impl From<geo::Rect<f64>> for BoundingBox {
+    fn from(rect: geo::Rect<f64>) -> Self {
+        Self::new(rect.min().into(), rect.max().into())
+    }
+}
+
+#[cfg(feature = "geo")]
+impl From<BoundingBox> for geo::Rect<f64> {
+    fn from(bounding_box: BoundingBox) -> Self {
+        geo::Rect::new(
+            geo::Point::from(bounding_box.south_west),
+            geo::Point::from(bounding_box.north_east),
+        )
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
 // --- Color
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct Color {
     r: i16,
     g: i16,
@@ -138,11 +305,70 @@ impl Color {
     }
 }
 
+// ------------------------------------------------------------------------------------------------
+// --- CompositeJourney
+// ------------------------------------------------------------------------------------------------
+
+/// Journeys linked by through services (DURCHBI), in which the passengers stay seated
+/// (see DataStorage::composite_journeys).
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CompositeJourney {
+    journey_ids: Vec<i32>,
+    through_stop_ids: Vec<i32>,
+}
+
+impl CompositeJourney {
+    pub fn new(journey_ids: Vec<i32>, through_stop_ids: Vec<i32>) -> Self {
+        Self {
+            journey_ids,
+            through_stop_ids,
+        }
+    }
+
+    // Getters/Setters
+
+    /// The legs, in order.
+    pub fn journey_ids(&self) -> &[i32] {
+        &self.journey_ids
+    }
+
+    /// The stop where each leg continues as the next one, one less than the legs.
+    pub fn through_stop_ids(&self) -> &[i32] {
+        &self.through_stop_ids
+    }
+
+    // Functions
+
+    pub fn journeys<'a>(&self, data_storage: &'a DataStorage) -> Vec<&'a Journey> {
+        self.journey_ids
+            .iter()
+            .filter_map(|&journey_id| data_storage.journeys().find(journey_id))
+            .collect()
+    }
+
+    /// Last stop of the last leg, to be shown as the destination of the whole run.
+    pub fn destination_stop_id(&self, data_storage: &DataStorage) -> Option<i32> {
+        let journey_id = *self.journey_ids.last()?;
+        data_storage
+            .journeys()
+            .find(journey_id)?
+            .last_stop_id()
+            .ok()
+    }
+}
+
 // ------------------------------------------------------------------------------------------------
 // --- CoordinateSystem
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Clone, Copy, Debug, Default, Display, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Default, Display, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub enum CoordinateSystem {
     #[default]
     LV95,
@@ -153,7 +379,16 @@ pub enum CoordinateSystem {
 // --- Coordinates
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+/// Mean radius of the Earth.
+const EARTH_RADIUS_M: f64 = 6_371_008.8;
+
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct Coordinates {
     coordinate_system: CoordinateSystem,
     x: f64,
@@ -172,6 +407,10 @@ impl Coordinates {
 
     // Getters/Setters
 
+    pub fn coordinate_system(&self) -> CoordinateSystem {
+        self.coordinate_system
+    }
+
     pub fn easting(&self) -> Option<f64> {
         match self.coordinate_system {
             CoordinateSystem::LV95 => Some(self.x),
@@ -199,13 +438,186 @@ impl Coordinates {
             CoordinateSystem::LV95 => None,
         }
     }
+
+    // Functions
+
+    /// Converts the coordinates to WGS84 with the approximation formulas of swisstopo (accuracy of about 1 meter).
+    pub fn to_wgs84(&self) -> Coordinates {
+        match self.coordinate_system {
+            CoordinateSystem::WGS84 => *self,
+            CoordinateSystem::LV95 => {
+                // Projection coordinates relative to Bern, in 1000 km.
+                let y = (self.x - 2_600_000.0) / 1_000_000.0;
+                let x = (self.y - 1_200_000.0) / 1_000_000.0;
+
+                // Longitude and latitude in 10000".
+                let longitude =
+                    2.6779094 + 4.728982 * y + 0.791484 * y * x + 0.1306 * y * x.powi(2)
+                        - 0.0436 * y.powi(3);
+                let latitude = 16.9023892 + 3.238272 * x
+                    - 0.270978 * y.powi(2)
+                    - 0.002528 * x.powi(2)
+                    - 0.0447 * y.powi(2) * x
+                    - 0.0140 * x.powi(3);
+
+                Coordinates::new(
+                    CoordinateSystem::WGS84,
+                    latitude * 100.0 / 36.0,
+                    longitude * 100.0 / 36.0,
+                )
+            }
+        }
+    }
+
+    /// Great-circle distance in meters (haversine formula).
+    /// LV95 coordinates are converted to WGS84 first.
+    pub fn distance_m(&self, other: &Coordinates) -> f64 {
+        let (latitude_1, longitude_1) = self.wgs84_radians();
+        let (latitude_2, longitude_2) = other.wgs84_radians();
+
+        let a = ((latitude_2 - latitude_1) / 2.0).sin().powi(2)
+            + latitude_1.cos()
+                * latitude_2.cos()
+                * ((longitude_2 - longitude_1) / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+    }
+
+    /// Initial bearing towards the other coordinates, in degrees clockwise from the north ([0, 360[).
+    /// LV95 coordinates are converted to WGS84 first.
+    pub fn bearing(&self, other: &Coordinates) -> f64 {
+        let (latitude_1, longitude_1) = self.wgs84_radians();
+        let (latitude_2, longitude_2) = other.wgs84_radians();
+        let delta_longitude = longitude_2 - longitude_1;
+
+        let y = delta_longitude.sin() * latitude_2.cos();
+        let x = latitude_1.cos() * latitude_2.sin()
+            - latitude_1.sin() * latitude_2.cos() * delta_longitude.cos();
+        y.atan2(x).to_degrees().rem_euclid(360.0)
+    }
+
+    /// WGS84 latitude and longitude in radians.
+    fn wgs84_radians(&self) -> (f64, f64) {
+        let wgs84 = self.to_wgs84();
+        (wgs84.x.to_radians(), wgs84.y.to_radians())
+    }
+
+    /// Converts the coordinates to LV95 with the approximation formulas of swisstopo (accuracy of about 1 meter).
+    pub fn to_lv95(&self) -> Coordinates {
+        match self.coordinate_system {
+            CoordinateSystem::LV95 => *self,
+            CoordinateSystem::WGS84 => {
+                // Latitude and longitude relative to Bern, in 10000".
+                let latitude = (self.x * 3600.0 - 169_028.66) / 10_000.0;
+                let longitude = (self.y * 3600.0 - 26_782.5) / 10_000.0;
+
+                let easting = 2_600_072.37 + 211_455.93 * longitude
+                    - 10_938.51 * longitude * latitude
+                    - 0.36 * longitude * latitude.powi(2)
+                    - 44.54 * longitude.powi(3);
+                let northing = 1_200_147.07
+                    + 308_807.95 * latitude
+                    + 3_745.25 * longitude.powi(2)
+                    + 76.63 * latitude.powi(2)
+                    - 194.56 * longitude.powi(2) * latitude
+                    + 119.79 * latitude.powi(3);
+
+                Coordinates::new(CoordinateSystem::LV95, easting, northing)
+            }
+        }
+    }
+}
+
+/// The point is in WGS84, LV95 coordinates are converted first. The x axis of geo is the longitude,
+/// the y axis the latitude.
+#[cfg(feature = "geo")]
+impl From<Coordinates> for geo::Point<f64> {
+    fn from(coordinates: Coordinates) -> Self {
+        let coordinates = coordinates.to_wgs84();
+        geo::Point::new(coordinates.y, coordinates.x)
+    }
+}
+
+#[cfg(feature = "geo")]
+impl From<geo::Point<f64>> for Coordinates {
+    fn from(point: geo::Point<f64>) -> Self {
+        Coordinates::new(CoordinateSystem::WGS84, point.y(), point.x())
+    }
+}
+
+#[cfg(feature = "geo")]
+impl From<geo::Coord<f64>> for Coordinates {
+    fn from(coord: geo::Coord<f64>) -> Self {
+        Coordinates::new(CoordinateSystem::WGS84, coord.y, coord.x)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Departure
+// ------------------------------------------------------------------------------------------------
+
+/// A journey leaving a stop at a given date and time (see DataStorage::departures).
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Departure {
+    journey_id: i32,
+    stop_id: i32,
+    service_day: NaiveDate,
+    departure_at: NaiveDateTime,
+}
+
+impl Departure {
+    pub fn new(
+        journey_id: i32,
+        stop_id: i32,
+        service_day: NaiveDate,
+        departure_at: NaiveDateTime,
+    ) -> Self {
+        Self {
+            journey_id,
+            stop_id,
+            service_day,
+            departure_at,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn journey_id(&self) -> i32 {
+        self.journey_id
+    }
+
+    pub fn stop_id(&self) -> i32 {
+        self.stop_id
+    }
+
+    /// The day the journey leaves its first stop, which differs from the date of departure_at
+    /// for a journey running past midnight.
+    pub fn service_day(&self) -> NaiveDate {
+        self.service_day
+    }
+
+    pub fn departure_at(&self) -> NaiveDateTime {
+        self.departure_at
+    }
+
+    // Functions
+
+    pub fn journey<'a>(&self, data_storage: &'a DataStorage) -> Option<&'a Journey> {
+        data_storage.journeys().find(self.journey_id)
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
 // --- Direction
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct Direction {
     id: i32,
     name: String,
@@ -217,14 +629,24 @@ impl Direction {
     pub fn new(id: i32, name: String) -> Self {
         Self { id, name }
     }
+
+    // Getters/Setters
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
 // --- DirectionType
 // ------------------------------------------------------------------------------------------------
 
-#[derive(
-    Clone, Copy, Debug, Default, Display, Eq, Hash, PartialEq, EnumString, Serialize, Deserialize,
+#[derive(Clone, Copy, Debug, Default, Display, Eq, Hash, PartialEq, EnumString)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
 )]
 pub enum DirectionType {
     #[default]
@@ -239,7 +661,13 @@ pub enum DirectionType {
 // --- Holiday
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct Holiday {
     id: i32,
     date: NaiveDate,
@@ -252,13 +680,29 @@ impl Holiday {
     pub fn new(id: i32, date: NaiveDate, name: FxHashMap<Language, String>) -> Self {
         Self { id, date, name }
     }
+
+    // Getters/Setters
+
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    pub fn name(&self, language: impl Into<LanguagePreference>) -> Option<&str> {
+        language.into().resolve(&self.name)
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
 // --- ExchangeTimeAdministration
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct ExchangeTimeAdministration {
     id: i32,
     stop_id: Option<i32>, // A None value means that the exchange time applies to all stops if there is no specific entry for the stop and the 2 administrations.
@@ -309,7 +753,13 @@ impl ExchangeTimeAdministration {
 // --- ExchangeTimeJourney
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct ExchangeTimeJourney {
     id: i32,
     stop_id: i32,
@@ -373,6 +823,10 @@ impl ExchangeTimeJourney {
         self.duration
     }
 
+    pub fn is_guaranteed(&self) -> bool {
+        self.is_guaranteed
+    }
+
     pub fn bit_field_id(&self) -> Option<i32> {
         self.bit_field_id
     }
@@ -382,7 +836,13 @@ impl ExchangeTimeJourney {
 // --- ExchangeTimeLine
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct ExchangeTimeLine {
     id: i32,
     stop_id: Option<i32>,
@@ -394,18 +854,26 @@ pub struct ExchangeTimeLine {
 
 impl_Model!(ExchangeTimeLine);
 
-#[derive(Debug, Serialize, Deserialize)]
-pub(crate) struct LineInfo {
-    administration: String,
-    transport_type_id: i32,
+/// Side of a line exchange time (UMSTEIGL). The "*" wildcards of the file are None and match
+/// every journey.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
+pub struct LineInfo {
+    administration: Option<String>,
+    transport_type_id: Option<i32>,
     line_id: Option<String>,
     direction: Option<DirectionType>,
 }
 
 impl LineInfo {
-    pub(crate) fn new(
-        administration: String,
-        transport_type_id: i32,
+    pub fn new(
+        administration: Option<String>,
+        transport_type_id: Option<i32>,
         line_id: Option<String>,
         direction: Option<DirectionType>,
     ) -> Self {
@@ -416,10 +884,57 @@ impl LineInfo {
             direction,
         }
     }
+
+    // Getters/Setters
+
+    pub fn administration(&self) -> Option<&str> {
+        self.administration.as_deref()
+    }
+
+    pub fn transport_type_id(&self) -> Option<i32> {
+        self.transport_type_id
+    }
+
+    /// The name of the line, or its ID in LINIE written #0000482.
+    pub fn line_id(&self) -> Option<&str> {
+        self.line_id.as_deref()
+    }
+
+    pub fn direction(&self) -> Option<DirectionType> {
+        self.direction
+    }
+
+    // Functions
+
+    /// Returns true if the journey is of the administration, the transport type, the line and the
+    /// direction. A line written #0000482 is compared with the ID of the line of the journey,
+    /// otherwise with its name and its short name.
+    pub fn matches(&self, journey: &Journey, data_storage: &DataStorage) -> bool {
+        self.administration
+            .as_ref()
+            .is_none_or(|administration| administration == journey.administration())
+            && self.transport_type_id.is_none_or(|id| {
+                journey
+                    .transport_type_id()
+                    .is_ok_and(|transport_type_id| transport_type_id == id)
+            })
+            && self.line_id.as_ref().is_none_or(|line_id| {
+                match line_id.strip_prefix('#').map(str::parse::<i32>) {
+                    Some(Ok(id)) => journey.line_id() == Some(id),
+                    _ => journey
+                        .line_id()
+                        .and_then(|id| data_storage.lines().find(id))
+                        .is_some_and(|line| line.name() == line_id || line.short_name() == line_id),
+                }
+            })
+            && self
+                .direction
+                .is_none_or(|direction| journey.direction_type() == Some(direction))
+    }
 }
 
 impl ExchangeTimeLine {
-    pub(crate) fn new(
+    pub fn new(
         id: i32,
         stop_id: Option<i32>,
         line_1: LineInfo,
@@ -436,13 +951,60 @@ impl ExchangeTimeLine {
             is_guaranteed,
         }
     }
+
+    // Getters/Setters
+
+    /// None if the exchange time applies at all the stops.
+    pub fn stop_id(&self) -> Option<i32> {
+        self.stop_id
+    }
+
+    /// Exchange time from line 1 to line 2 in minutes.
+    pub fn duration(&self) -> i16 {
+        self.duration
+    }
+
+    pub fn is_guaranteed(&self) -> bool {
+        self.is_guaranteed
+    }
+
+    /// The arriving line.
+    pub fn line_1(&self) -> &LineInfo {
+        &self.line_1
+    }
+
+    /// The departing line.
+    pub fn line_2(&self) -> &LineInfo {
+        &self.line_2
+    }
+
+    // Functions
+
+    /// Returns true if the exchange time applies at the stop from journey 1 to journey 2.
+    pub fn applies_to(
+        &self,
+        stop_id: i32,
+        journey_1: &Journey,
+        journey_2: &Journey,
+        data_storage: &DataStorage,
+    ) -> bool {
+        self.stop_id.is_none_or(|id| id == stop_id)
+            && self.line_1.matches(journey_1, data_storage)
+            && self.line_2.matches(journey_2, data_storage)
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
 // --- InformationText
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct InformationText {
     id: i32,
     content: FxHashMap<Language, String>,
@@ -460,6 +1022,10 @@ impl InformationText {
 
     // Getters/Setters
 
+    pub fn content(&self, language: impl Into<LanguagePreference>) -> Option<&str> {
+        language.into().resolve(&self.content)
+    }
+
     pub fn set_content(&mut self, language: Language, value: &str) {
         self.content.insert(language, value.to_string());
     }
@@ -469,13 +1035,26 @@ impl InformationText {
 // --- Journey
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct Journey {
     id: i32,
     legacy_id: i32,
     administration: String,
     metadata: FxHashMap<JourneyMetadataType, Vec<JourneyMetadataEntry>>,
     route: Vec<JourneyRouteEntry>,
+    // Of the *Z row: the journey is repeated num_cycles times, every cycle_duration minutes.
+    num_cycles: Option<i32>,
+    cycle_duration: Option<i32>,
+    number_changes: Vec<JourneyNumberChange>,
+    // The texts of the *I JY and *I RN rows, resolved when the data storage is built.
+    swiss_journey_id: Option<String>,
+    region: Option<String>,
 }
 
 impl_Model!(Journey);
@@ -488,6 +1067,11 @@ impl Journey {
             administration,
             metadata: FxHashMap::default(),
             route: Vec::new(),
+            num_cycles: None,
+            cycle_duration: None,
+            number_changes: Vec::new(),
+            swiss_journey_id: None,
+            region: None,
         }
     }
 
@@ -501,7 +1085,7 @@ impl Journey {
         self.legacy_id
     }
 
-    fn metadata(&self) -> &FxHashMap<JourneyMetadataType, Vec<JourneyMetadataEntry>> {
+    pub fn metadata(&self) -> &FxHashMap<JourneyMetadataType, Vec<JourneyMetadataEntry>> {
         &self.metadata
     }
 
@@ -509,6 +1093,52 @@ impl Journey {
         &self.route
     }
 
+    /// Number of repetitions of the journey after this one (*Z row), None if it is not cyclical or
+    /// the repetitions were created by the parser (see ParsingOptions::set_expand_cycles).
+    pub fn num_cycles(&self) -> Option<i32> {
+        self.num_cycles
+    }
+
+    /// Minutes between two repetitions of the journey.
+    pub fn cycle_duration(&self) -> Option<i32> {
+        self.cycle_duration
+    }
+
+    pub(crate) fn set_cycle(&mut self, num_cycles: Option<i32>, cycle_duration: Option<i32>) {
+        self.num_cycles = num_cycles;
+        self.cycle_duration = cycle_duration;
+    }
+
+    /// The stops from which the journey runs with another journey number or administration (route
+    /// rows of FPLAN), in the order of the route. Empty if the journey keeps the number of its *Z
+    /// row, e.g. a cross-border train is often renumbered at the border.
+    pub fn number_changes(&self) -> &[JourneyNumberChange] {
+        &self.number_changes
+    }
+
+    /// Swiss Journey ID (SJYID) of the journey, e.g. ch:1:sjyid:100001:3995-001.
+    pub fn swiss_journey_id(&self) -> Option<&str> {
+        self.swiss_journey_id.as_deref()
+    }
+
+    pub(crate) fn set_swiss_journey_id(&mut self, value: Option<String>) {
+        self.swiss_journey_id = value;
+    }
+
+    /// Region of the journey (*I RN row). The journeys of the administration 801 (DB Regio) are
+    /// only unique with their region, their numbers being reused from one region to another.
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    pub(crate) fn set_region(&mut self, value: Option<String>) {
+        self.region = value;
+    }
+
+    pub(crate) fn set_id(&mut self, value: i32) {
+        self.id = value;
+    }
+
     // Functions
 
     pub fn add_metadata_entry(&mut self, k: JourneyMetadataType, v: JourneyMetadataEntry) {
@@ -519,18 +1149,105 @@ impl Journey {
         self.route.push(entry);
     }
 
-    pub(crate) fn bit_field_id(&self) -> JResult<Option<i32>> {
-        let entry = self
-            .metadata()
-            .get(&JourneyMetadataType::BitField)
-            .ok_or(JourneyError::MissingBitFieldMetadata)?;
-
+    pub fn add_number_change(&mut self, number_change: JourneyNumberChange) {
+        self.number_changes.push(number_change);
+    }
+
+    /// Journey number and administration with which the journey departs from the stop (the
+    /// number of the *Z row until the first number change).
+    pub fn number_at(&self, stop_id: i32) -> (i32, &str) {
+        let mut number = (self.legacy_id, self.administration.as_str());
+        for route_entry in &self.route {
+            if let Some(number_change) = self
+                .number_changes
+                .iter()
+                .find(|number_change| number_change.stop_id == route_entry.stop_id)
+            {
+                number = (
+                    number_change.legacy_id,
+                    number_change.administration.as_str(),
+                );
+            }
+            if route_entry.stop_id == stop_id {
+                break;
+            }
+        }
+        number
+    }
+
+    /// Minutes between the journey and each of its repetitions (e.g. [60, 120] for 2 cycles of 60
+    /// minutes), empty if it is not cyclical.
+    pub fn cycle_offsets(&self) -> Vec<u32> {
+        match (self.num_cycles, self.cycle_duration) {
+            (Some(num_cycles), Some(cycle_duration)) if num_cycles > 0 && cycle_duration > 0 => (1
+                ..=num_cycles as u32)
+                .map(|cycle| cycle * cycle_duration as u32)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// A copy of the journey with another ID, all its times later by the minutes and no cycle,
+    /// e.g. to create the repetitions of a cyclical journey (see cycle_offsets).
+    pub fn shifted(&self, id: i32, minutes: u32) -> Journey {
+        let shift =
+            |time: Option<ServiceTime>| time.map(|time| ServiceTime::new(time.minutes() + minutes));
+
+        let mut journey = self.clone();
+        journey.id = id;
+        journey.set_cycle(None, None);
+        for entry in journey.route.iter_mut() {
+            entry.arrival = shift(entry.arrival);
+            entry.departure = shift(entry.departure);
+        }
+        for entry in journey.metadata.values_mut().flatten() {
+            entry.departure_time = shift(entry.departure_time);
+            entry.arrival_time = shift(entry.arrival_time);
+        }
+        journey
+    }
+
+    pub(crate) fn bit_field_id(&self) -> JResult<Option<i32>> {
+        let entry = self
+            .metadata()
+            .get(&JourneyMetadataType::BitField)
+            .ok_or(JourneyError::MissingBitFieldMetadata)?;
+
         Ok(entry
             .first()
             .ok_or(JourneyError::EmptyJourneyMetadata)?
             .bit_field_id)
     }
 
+    pub(crate) fn set_bit_field_id(&mut self, bit_field_id: Option<i32>) {
+        for entry in self
+            .metadata
+            .get_mut(&JourneyMetadataType::BitField)
+            .into_iter()
+            .flatten()
+        {
+            entry.bit_field_id = bit_field_id;
+        }
+    }
+
+    /// ID of the line (*L with a #-reference), if the journey has one.
+    pub fn line_id(&self) -> Option<i32> {
+        self.metadata
+            .get(&JourneyMetadataType::Line)?
+            .first()?
+            .resource_id
+    }
+
+    /// Direction (*R H or *R R) of the journey, if it has one.
+    pub fn direction_type(&self) -> Option<DirectionType> {
+        self.metadata
+            .get(&JourneyMetadataType::Direction)?
+            .first()?
+            .extra_field_1
+            .as_deref()
+            .and_then(|direction| direction.parse().ok())
+    }
+
     pub fn transport_type_id(&self) -> HResult<i32> {
         let entry = self
             .metadata()
@@ -554,6 +1271,162 @@ impl Journey {
             .ok_or(JourneyError::TransportIdNotFound(transport_id).into())
     }
 
+    /// Returns true if the journey operates on the given date (i.e. the day its first stop is served).
+    /// An error is returned if the date is not covered by the timetable.
+    pub fn operates_on(&self, date: NaiveDate, data_storage: &DataStorage) -> HResult<bool> {
+        data_storage.check_date(date)?;
+
+        match self.bit_field_id()? {
+            // If the journey has no bit_field_id or a bit_field_id of 0, the journey operates every day.
+            None | Some(0) => Ok(true),
+            Some(bit_field_id) => Ok(data_storage
+                .bit_fields()
+                .find(bit_field_id)
+                .ok_or(HrdfError::BitFieldIdNotFound(bit_field_id))?
+                .is_active_on(date, *data_storage.timetable_period().start())),
+        }
+    }
+
+    /// Returns true if the journey serves the platform on the service day. The platform assignments
+    /// (GLEISE) of the journey are restricted to some days by their bit field.
+    pub fn serves_platform(
+        &self,
+        platform: &Platform,
+        service_day: NaiveDate,
+        data_storage: &DataStorage,
+    ) -> bool {
+        data_storage
+            .journey_platform()
+            .find((
+                self.legacy_id(),
+                self.administration().to_string(),
+                platform.id(),
+            ))
+            .is_some_and(|journey_platform| {
+                data_storage.is_bit_field_active(journey_platform.bit_field_id(), service_day)
+            })
+    }
+
+    /// The platform at which the journey serves the stop on the service day, the first one by name
+    /// if there are several. None if GLEISE assigns none.
+    pub fn platform<'a>(
+        &self,
+        stop_id: i32,
+        service_day: NaiveDate,
+        data_storage: &'a DataStorage,
+    ) -> Option<&'a Platform> {
+        data_storage
+            .platforms()
+            .data()
+            .values()
+            .filter(|platform| platform.stop_id() == stop_id)
+            .filter(|platform| self.serves_platform(platform, service_day, data_storage))
+            .min_by(|platform_1, platform_2| {
+                platform_1
+                    .name()
+                    .cmp(platform_2.name())
+                    .then(platform_1.id().cmp(&platform_2.id()))
+            })
+    }
+
+    /// IDs of the information texts referenced by the *I rows with the code, e.g. "JY".
+    pub fn information_text_ids(&self, code: &str) -> Vec<i32> {
+        self.metadata
+            .get(&JourneyMetadataType::InformationText)
+            .into_iter()
+            .flatten()
+            .filter(|entry| entry.extra_field_1.as_deref() == Some(code))
+            .filter_map(|entry| entry.resource_id)
+            .collect()
+    }
+
+    /// The attributes (*A rows) of the journey which apply on the given date, the date of the
+    /// journey. An attribute with a bit field applies only on the days of its bit field.
+    pub fn attributes_on<'a>(
+        &self,
+        date: NaiveDate,
+        data_storage: &'a DataStorage,
+    ) -> Vec<&'a Attribute> {
+        self.metadata
+            .get(&JourneyMetadataType::Attribute)
+            .into_iter()
+            .flatten()
+            .filter(|entry| data_storage.is_bit_field_active(entry.bit_field_id, date))
+            .filter_map(|entry| data_storage.attributes().find(entry.resource_id?))
+            .collect()
+    }
+
+    /// Returns false if the stop is a seasonal stop (*SH row) which the journey does not serve on
+    /// the given date, the date of the journey. The other stops of the route are always served.
+    pub fn serves_stop_on(
+        &self,
+        stop_id: i32,
+        date: NaiveDate,
+        data_storage: &DataStorage,
+    ) -> bool {
+        self.metadata
+            .get(&JourneyMetadataType::SeasonalStop)
+            .into_iter()
+            .flatten()
+            .filter(|entry| entry.from_stop_id == Some(stop_id))
+            .all(|entry| data_storage.is_bit_field_active(entry.bit_field_id, date))
+    }
+
+    /// The star-rows of the journey whose type is not parsed, as (type, rest of the row), e.g.
+    /// ("XY", "1234 %") for "*XY 1234 %".
+    pub fn unknown_rows(&self) -> Vec<(&str, &str)> {
+        self.metadata
+            .get(&JourneyMetadataType::Unknown)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| Some((entry.raw_code.as_deref()?, entry.extra_field_1.as_deref()?)))
+            .collect()
+    }
+
+    /// Returns true if the journey has left its first stop and has not yet reached its last stop at the given date and time.
+    /// The journey is also considered on the previous service days, so that a journey running after midnight is found.
+    /// The service days which are not covered by the timetable are ignored.
+    pub fn is_active_at(&self, datetime: NaiveDateTime, data_storage: &DataStorage) -> bool {
+        let (Some(first_departure), Some(last_arrival)) = (
+            self.route
+                .first()
+                .and_then(|route_entry| route_entry.departure()),
+            self.route
+                .last()
+                .and_then(|route_entry| route_entry.arrival()),
+        ) else {
+            return false;
+        };
+
+        (0..=last_arrival.day_offset()).any(|day_offset| {
+            let service_time = ServiceTime::from_time_and_day_offset(datetime.time(), day_offset);
+            if service_time < first_departure || service_time > last_arrival {
+                return false;
+            }
+
+            sub_days(datetime.date(), day_offset.into())
+                .and_then(|service_day| self.operates_on(service_day, data_storage))
+                .unwrap_or(false)
+        })
+    }
+
+    /// Returns the WGS84 coordinates of the stops of the route, in order.
+    /// The stops which are unknown or have no coordinates are skipped.
+    pub fn polyline(&self, data_storage: &DataStorage) -> Vec<Coordinates> {
+        self.route
+            .iter()
+            .filter_map(|route_entry| {
+                let stop = data_storage.stops().find(route_entry.stop_id())?;
+                stop.wgs84_position()
+            })
+            .collect()
+    }
+
+    /// Same as polyline, but encoded with the Encoded Polyline Algorithm Format of Google.
+    pub fn encoded_polyline(&self, data_storage: &DataStorage) -> String {
+        encode_polyline(&self.polyline(data_storage))
+    }
+
     pub fn first_stop_id(&self) -> HResult<i32> {
         Ok(self
             .route
@@ -607,27 +1480,30 @@ impl Journey {
         Some(hasher.finish())
     }
 
-    /// unwrap: Do not call this function if the stop is not part of the route.
-    /// unwrap: Do not call this function if the stop has no departure time (only the last stop has no departure time).
+    /// Returns the departure time at the stop and whether it is on the day after the departure from the first stop.
+    /// Do not call this function if the stop is not part of the route.
+    /// Do not call this function if the stop has no departure time (only the last stop has no departure time).
     pub fn departure_time_of(&self, stop_id: i32) -> HResult<(NaiveTime, bool)> {
+        let (departure_time, day_offset) = self.departure_time_and_day_offset_of(stop_id)?;
+        Ok((departure_time, day_offset > 0))
+    }
+
+    /// Returns the departure time at the stop and the number of days elapsed since the departure from the first stop.
+    pub fn departure_time_and_day_offset_of(&self, stop_id: i32) -> HResult<(NaiveTime, u8)> {
         let route = self.route();
         let index = route
             .iter()
             .position(|route_entry| route_entry.stop_id() == stop_id)
-            .ok_or_else(|| HrdfError::MissingStopId(stop_id))?;
+            .ok_or(HrdfError::MissingStopId(stop_id))?;
         let departure_time = route[index]
             .departure_time()
-            .ok_or_else(|| HrdfError::MissingDepartureTime(index))?;
+            .ok_or(HrdfError::MissingDepartureTime(index))?;
 
         Ok((
             departure_time,
-            // The departure time is on the next day if this evaluates to true.
-            departure_time
-                < route
-                    .first()
-                    .ok_or(HrdfError::MissingRoute)?
-                    .departure_time()
-                    .ok_or(HrdfError::MissingDepartureTime(0))?,
+            route[index]
+                .departure_day_offset()
+                .saturating_sub(self.first_departure_day_offset()?),
         ))
     }
 
@@ -635,10 +1511,11 @@ impl Journey {
     /// Do not call this function if the stop is not part of the route.
     /// Do not call this function if the stop has no departure time (only the last stop has no departure time).
     pub fn departure_at_of(&self, stop_id: i32, date: NaiveDate) -> HResult<NaiveDateTime> {
-        match self.departure_time_of(stop_id)? {
-            (departure_time, false) => Ok(NaiveDateTime::new(date, departure_time)),
-            (departure_time, true) => Ok(NaiveDateTime::new(add_1_day(date)?, departure_time)),
-        }
+        let (departure_time, day_offset) = self.departure_time_and_day_offset_of(stop_id)?;
+        Ok(NaiveDateTime::new(
+            add_days(date, day_offset.into())?,
+            departure_time,
+        ))
     }
 
     /// The date must be associated with the origin_stop_id.
@@ -651,31 +1528,34 @@ impl Journey {
         is_departure_date: bool,
         origin_stop_id: i32,
     ) -> HResult<NaiveDateTime> {
-        let (departure_time, is_next_day) = self.departure_time_of(stop_id)?;
-        let (_, origin_is_next_day) = if is_departure_date {
-            self.departure_time_of(origin_stop_id)?
-        } else {
-            self.arrival_time_of(origin_stop_id)?
-        };
+        let (departure_time, day_offset) = self.departure_time_and_day_offset_of(stop_id)?;
+        let origin_day_offset = self.day_offset_of_origin(origin_stop_id, is_departure_date)?;
 
-        match (is_next_day, origin_is_next_day) {
-            (true, false) => Ok(NaiveDateTime::new(add_1_day(date)?, departure_time)),
-            (false, true) => Ok(NaiveDateTime::new(sub_1_day(date)?, departure_time)),
-            _ => Ok(NaiveDateTime::new(date, departure_time)),
-        }
+        Ok(NaiveDateTime::new(
+            shift_date(date, origin_day_offset, day_offset)?,
+            departure_time,
+        ))
     }
 
     /// The date must correspond to the route's first entry.
     /// Do not call this function if the stop is not part of the route.
     /// Do not call this function if the stop has no arrival time (only the first stop has no arrival time).
     pub fn arrival_at_of(&self, stop_id: i32, date: NaiveDate) -> HResult<NaiveDateTime> {
-        match self.arrival_time_of(stop_id)? {
-            (arrival_time, false) => Ok(NaiveDateTime::new(date, arrival_time)),
-            (arrival_time, true) => Ok(NaiveDateTime::new(add_1_day(date)?, arrival_time)),
-        }
+        let (arrival_time, day_offset) = self.arrival_time_and_day_offset_of(stop_id)?;
+        Ok(NaiveDateTime::new(
+            add_days(date, day_offset.into())?,
+            arrival_time,
+        ))
     }
 
+    /// Returns the arrival time at the stop and whether it is on the day after the departure from the first stop.
     pub fn arrival_time_of(&self, stop_id: i32) -> HResult<(NaiveTime, bool)> {
+        let (arrival_time, day_offset) = self.arrival_time_and_day_offset_of(stop_id)?;
+        Ok((arrival_time, day_offset > 0))
+    }
+
+    /// Returns the arrival time at the stop and the number of days elapsed since the departure from the first stop.
+    pub fn arrival_time_and_day_offset_of(&self, stop_id: i32) -> HResult<(NaiveTime, u8)> {
         let route = self.route();
         let index = route
             .iter()
@@ -683,20 +1563,16 @@ impl Journey {
             .skip(1)
             .position(|route_entry| route_entry.stop_id() == stop_id)
             .map(|i| i + 1)
-            .ok_or_else(|| HrdfError::MissingStopId(stop_id))?;
+            .ok_or(HrdfError::MissingStopId(stop_id))?;
         let arrival_time = route[index]
             .arrival_time()
-            .ok_or_else(|| HrdfError::MissingArrivalTime(index))?;
+            .ok_or(HrdfError::MissingArrivalTime(index))?;
 
         Ok((
             arrival_time,
-            // The arrival time is on the next day if this evaluates to true.
-            arrival_time
-                < route
-                    .first()
-                    .ok_or(HrdfError::MissingRoute)?
-                    .departure_time()
-                    .ok_or(HrdfError::MissingDepartureTime(0))?,
+            route[index]
+                .arrival_day_offset()
+                .saturating_sub(self.first_departure_day_offset()?),
         ))
     }
 
@@ -709,18 +1585,188 @@ impl Journey {
         is_departure_date: bool,
         origin_stop_id: i32,
     ) -> HResult<NaiveDateTime> {
-        let (arrival_time, is_next_day) = self.arrival_time_of(stop_id)?;
-        let (_, origin_is_next_day) = if is_departure_date {
-            self.departure_time_of(origin_stop_id)?
+        let (arrival_time, day_offset) = self.arrival_time_and_day_offset_of(stop_id)?;
+        let origin_day_offset = self.day_offset_of_origin(origin_stop_id, is_departure_date)?;
+
+        Ok(NaiveDateTime::new(
+            shift_date(date, origin_day_offset, day_offset)?,
+            arrival_time,
+        ))
+    }
+
+    /// The coupled runs of the journey (*KW and *KWZ rows), in the order of FPLAN.
+    pub fn couplings(&self) -> Vec<JourneyCoupling> {
+        self.metadata
+            .get(&JourneyMetadataType::Coupling)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let coupling_type = if entry.raw_code.as_deref() == Some("KWZ") {
+                    CouplingType::Train
+                } else {
+                    CouplingType::ThroughCoach
+                };
+                Some(JourneyCoupling::new(
+                    coupling_type,
+                    entry.extra_field_2?,
+                    entry.extra_field_1.clone()?,
+                    entry.from_stop_id,
+                    entry.until_stop_id,
+                ))
+            })
+            .collect()
+    }
+
+    /// The hops between consecutive stops with times, the times counted from the day of the
+    /// departure from the first stop (the date of the journey, see operates_on). The stops
+    /// without times are skipped.
+    pub fn segments(&self) -> Vec<JourneySegment> {
+        let first_day_start =
+            u32::from(self.first_departure_day_offset().unwrap_or(0)) * MINUTES_PER_DAY;
+        let shift = |service_time: ServiceTime| {
+            ServiceTime::new(service_time.minutes().saturating_sub(first_day_start))
+        };
+        let timed_route_entries: Vec<_> = self
+            .route
+            .iter()
+            .filter(|route_entry| {
+                route_entry.arrival().is_some() || route_entry.departure().is_some()
+            })
+            .collect();
+
+        timed_route_entries
+            .windows(2)
+            .filter_map(|route_entries| {
+                let (from, to) = (route_entries[0], route_entries[1]);
+                let departure = from.departure().or(from.arrival())?;
+                let arrival = to.arrival().or(to.departure())?;
+                let dwell_time = from.arrival().map_or(0, |arrival| {
+                    departure.minutes().saturating_sub(arrival.minutes())
+                });
+                Some(JourneySegment::new(
+                    from.stop_id(),
+                    to.stop_id(),
+                    shift(departure),
+                    shift(arrival),
+                    dwell_time,
+                ))
+            })
+            .collect()
+    }
+
+    fn first_departure_day_offset(&self) -> HResult<u8> {
+        Ok(self
+            .route
+            .first()
+            .ok_or(HrdfError::MissingRoute)?
+            .departure_day_offset())
+    }
+
+    fn day_offset_of_origin(&self, origin_stop_id: i32, is_departure_date: bool) -> HResult<u8> {
+        let (_, day_offset) = if is_departure_date {
+            self.departure_time_and_day_offset_of(origin_stop_id)?
         } else {
-            self.arrival_time_of(origin_stop_id)?
+            self.arrival_time_and_day_offset_of(origin_stop_id)?
         };
+        Ok(day_offset)
+    }
+
+    // The zoned variants resolve the local times in Swiss local time (Europe/Zurich).
+    // Unlike NaiveDateTime, the difference between two of these instants is correct
+    // on the nights when the clocks are changed (last Sundays of March and October).
+
+    /// See departure_at_of.
+    pub fn zoned_departure_at_of(
+        &self,
+        stop_id: i32,
+        date: NaiveDate,
+    ) -> HResult<DateTime<FixedOffset>> {
+        swiss_local_to_fixed_offset(self.departure_at_of(stop_id, date)?)
+    }
 
-        match (is_next_day, origin_is_next_day) {
-            (true, false) => Ok(NaiveDateTime::new(add_1_day(date)?, arrival_time)),
-            (false, true) => Ok(NaiveDateTime::new(sub_1_day(date)?, arrival_time)),
-            _ => Ok(NaiveDateTime::new(date, arrival_time)),
+    /// See departure_at_of_with_origin.
+    pub fn zoned_departure_at_of_with_origin(
+        &self,
+        stop_id: i32,
+        date: NaiveDate,
+        is_departure_date: bool,
+        origin_stop_id: i32,
+    ) -> HResult<DateTime<FixedOffset>> {
+        swiss_local_to_fixed_offset(self.departure_at_of_with_origin(
+            stop_id,
+            date,
+            is_departure_date,
+            origin_stop_id,
+        )?)
+    }
+
+    /// See arrival_at_of.
+    pub fn zoned_arrival_at_of(
+        &self,
+        stop_id: i32,
+        date: NaiveDate,
+    ) -> HResult<DateTime<FixedOffset>> {
+        swiss_local_to_fixed_offset(self.arrival_at_of(stop_id, date)?)
+    }
+
+    /// See arrival_at_of_with_origin.
+    pub fn zoned_arrival_at_of_with_origin(
+        &self,
+        stop_id: i32,
+        date: NaiveDate,
+        is_departure_date: bool,
+        origin_stop_id: i32,
+    ) -> HResult<DateTime<FixedOffset>> {
+        swiss_local_to_fixed_offset(self.arrival_at_of_with_origin(
+            stop_id,
+            date,
+            is_departure_date,
+            origin_stop_id,
+        )?)
+    }
+
+    /// Returns the index of the first route entry with an arrival or departure time
+    /// earlier than a previous time of the route, if there is one.
+    pub fn find_non_monotonic_route_entry(&self) -> Option<usize> {
+        let mut previous = None;
+
+        for (index, route_entry) in self.route.iter().enumerate() {
+            for current in [route_entry.arrival(), route_entry.departure()]
+                .into_iter()
+                .flatten()
+            {
+                if previous.is_some_and(|previous| current < previous) {
+                    return Some(index);
+                }
+                previous = Some(current);
+            }
         }
+
+        None
+    }
+
+    /// Replaces each arrival or departure time earlier than a previous time of the route by that previous time.
+    /// Returns the number of replaced times.
+    pub(crate) fn repair_route_times(&mut self) -> usize {
+        let mut previous: Option<ServiceTime> = None;
+        let mut count = 0;
+
+        for route_entry in self.route.iter_mut() {
+            for current in [&mut route_entry.arrival, &mut route_entry.departure]
+                .into_iter()
+                .filter_map(Option::as_mut)
+            {
+                match previous {
+                    Some(previous) if *current < previous => {
+                        *current = previous;
+                        count += 1;
+                    }
+                    _ => previous = Some(*current),
+                }
+            }
+        }
+
+        count
     }
 
     /// Excluding departure stop.
@@ -749,6 +1795,155 @@ impl Journey {
 
         result
     }
+
+    /// Human-readable summary of the journey over several lines: transport type and number,
+    /// line, operator, direction, attributes, operating days, then the times at every stop.
+    /// The texts are in the first language of the preference available.
+    pub fn describe(
+        &self,
+        data_storage: &DataStorage,
+        language: impl Into<LanguagePreference>,
+    ) -> String {
+        let language = language.into();
+        let stop_name = |stop_id: i32| {
+            data_storage
+                .stops()
+                .find(stop_id)
+                .map_or("?", |stop| stop.name())
+        };
+        let resources = |metadata_type: JourneyMetadataType| {
+            self.metadata
+                .get(&metadata_type)
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| entry.resource_id.map(|resource_id| (resource_id, entry)))
+        };
+        // The section of the route to which an entry applies, if it does not apply to all of it.
+        let section = |entry: &JourneyMetadataEntry| match (entry.from_stop_id, entry.until_stop_id)
+        {
+            (Some(from), Some(until))
+                if Some(from) != self.first_stop_id().ok()
+                    || Some(until) != self.last_stop_id().ok() =>
+            {
+                format!(" ({} - {})", stop_name(from), stop_name(until))
+            }
+            _ => String::new(),
+        };
+
+        let mut lines = Vec::new();
+        let transport_type = self
+            .transport_type(data_storage)
+            .map_or("?", |transport_type| transport_type.designation());
+        lines.push(format!(
+            "{transport_type} {} ({})",
+            self.legacy_id, self.administration
+        ));
+        if let Some(line) = self
+            .line_id()
+            .and_then(|line_id| data_storage.lines().find(line_id))
+        {
+            lines.push(format!("Line: {}", line.display_name()));
+        }
+        if let Some(operator) = data_storage
+            .transport_companies()
+            .data()
+            .values()
+            .find(|company| company.administrations().contains(&self.administration))
+            .and_then(|company| company.short_name(language.clone()))
+        {
+            lines.push(format!("Operator: {operator}"));
+        }
+        for (direction_id, entry) in resources(JourneyMetadataType::Direction) {
+            if let Some(direction) = data_storage.directions().find(direction_id) {
+                lines.push(format!("Direction: {}{}", direction.name(), section(entry)));
+            }
+        }
+        let attributes: Vec<_> = resources(JourneyMetadataType::Attribute)
+            .filter_map(|(attribute_id, entry)| {
+                let attribute = data_storage.attributes().find(attribute_id)?;
+                let description = attribute
+                    .description(language.clone())
+                    .map(|description| format!(" {description}"))
+                    .unwrap_or_default();
+                Some(format!(
+                    "{}{description}{}",
+                    attribute.designation(),
+                    section(entry)
+                ))
+            })
+            .collect();
+        if !attributes.is_empty() {
+            lines.push(format!("Attributes: {}", attributes.join(", ")));
+        }
+
+        let period = data_storage.timetable_period();
+        let operating_days: Vec<_> = period
+            .start()
+            .iter_days()
+            .take_while(|date| date <= period.end())
+            .filter(|&date| self.operates_on(date, data_storage).unwrap_or(false))
+            .collect();
+        let period_days = (*period.end() - *period.start()).num_days() + 1;
+        lines.push(match (operating_days.first(), operating_days.last()) {
+            (Some(_), Some(_)) if operating_days.len() as i64 == period_days => {
+                "Operates: every day of the timetable".to_string()
+            }
+            (Some(first), Some(last)) => format!(
+                "Operates: {} days between {first} and {last}",
+                operating_days.len()
+            ),
+            _ => "Operates: never".to_string(),
+        });
+
+        let format_time = |service_time: Option<ServiceTime>| {
+            service_time.map_or(String::new(), |service_time| {
+                let day_offset = match service_time.day_offset() {
+                    0 => String::new(),
+                    day_offset => format!("+{day_offset}"),
+                };
+                format!("{}{day_offset}", service_time.time().format("%H:%M"))
+            })
+        };
+        for route_entry in &self.route {
+            lines.push(format!(
+                "  {:<40}{:>8}{:>8}",
+                format!(
+                    "{} ({})",
+                    stop_name(route_entry.stop_id()),
+                    route_entry.stop_id()
+                ),
+                format_time(route_entry.arrival()),
+                format_time(route_entry.departure())
+            ));
+        }
+
+        lines.join("\n")
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl ArchivedJourney {
+    // Getters/Setters
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn legacy_id(&self) -> i32 {
+        self.legacy_id
+    }
+
+    pub fn administration(&self) -> &str {
+        &self.administration
+    }
+}
+
+/// Moves a date associated with a day offset to the date associated with another day offset.
+fn shift_date(date: NaiveDate, from_day_offset: u8, to_day_offset: u8) -> HResult<NaiveDate> {
+    if to_day_offset >= from_day_offset {
+        add_days(date, (to_day_offset - from_day_offset).into())
+    } else {
+        sub_days(date, (from_day_offset - to_day_offset).into())
+    }
 }
 
 type JResult<T> = Result<T, JourneyError>;
@@ -775,7 +1970,16 @@ pub enum JourneyError {
 // --- JourneyMetadataType
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Clone, Copy, Debug, Default, Display, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Default, Display, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes),
+    archive_attr(derive(Eq, Hash, PartialEq))
+)]
+// Same representation as the archived enum, so that both hash alike (keys of archived maps).
+#[repr(u8)]
 pub enum JourneyMetadataType {
     #[default]
     Attribute,
@@ -786,22 +1990,35 @@ pub enum JourneyMetadataType {
     ExchangeTimeBoarding,
     ExchangeTimeDisembarking,
     TransportType,
+    Coupling,
+    SeasonalStop,
+    // A star-row of FPLAN whose type is not parsed, see Journey::unknown_rows.
+    Unknown,
 }
 
 // ------------------------------------------------------------------------------------------------
 // --- JourneyMetadataEntry
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct JourneyMetadataEntry {
     from_stop_id: Option<i32>,
     until_stop_id: Option<i32>,
     resource_id: Option<i32>,
     bit_field_id: Option<i32>,
-    departure_time: Option<NaiveTime>,
-    arrival_time: Option<NaiveTime>,
+    departure_time: Option<ServiceTime>,
+    arrival_time: Option<ServiceTime>,
     extra_field_1: Option<String>,
     extra_field_2: Option<i32>,
+    // The reference code as written in FPLAN, when it matches no resource (e.g. a direction code
+    // without RICHTUNG entry or a non-numeric line reference), or the tag of a *KW/*KWZ row.
+    raw_code: Option<String>,
 }
 
 impl JourneyMetadataEntry {
@@ -811,8 +2028,8 @@ impl JourneyMetadataEntry {
         until_stop_id: Option<i32>,
         resource_id: Option<i32>,
         bit_field_id: Option<i32>,
-        departure_time: Option<NaiveTime>,
-        arrival_time: Option<NaiveTime>,
+        departure_time: Option<ServiceTime>,
+        arrival_time: Option<ServiceTime>,
         extra_field_1: Option<String>,
         extra_field_2: Option<i32>,
     ) -> Self {
@@ -825,31 +2042,75 @@ impl JourneyMetadataEntry {
             arrival_time,
             extra_field_1,
             extra_field_2,
+            raw_code: None,
         }
     }
+
+    // Getters/Setters
+
+    pub fn from_stop_id(&self) -> Option<i32> {
+        self.from_stop_id
+    }
+
+    pub fn until_stop_id(&self) -> Option<i32> {
+        self.until_stop_id
+    }
+
+    pub fn resource_id(&self) -> Option<i32> {
+        self.resource_id
+    }
+
+    pub fn bit_field_id(&self) -> Option<i32> {
+        self.bit_field_id
+    }
+
+    /// Counted from the start of the service day, e.g. 24:10 is 00:10 on the following day.
+    pub fn departure_time(&self) -> Option<ServiceTime> {
+        self.departure_time
+    }
+
+    /// Counted from the start of the service day, e.g. 24:10 is 00:10 on the following day.
+    pub fn arrival_time(&self) -> Option<ServiceTime> {
+        self.arrival_time
+    }
+
+    pub fn raw_code(&self) -> Option<&str> {
+        self.raw_code.as_deref()
+    }
+
+    pub(crate) fn set_raw_code(&mut self, value: String) {
+        self.raw_code = Some(value);
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
 // --- JourneyRouteEntry
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct JourneyRouteEntry {
     stop_id: i32,
-    arrival_time: Option<NaiveTime>,
-    departure_time: Option<NaiveTime>,
+    arrival: Option<ServiceTime>,
+    departure: Option<ServiceTime>,
+    // False when the time is negative in FPLAN.
+    boarding_allowed: bool,
+    alighting_allowed: bool,
 }
 
 impl JourneyRouteEntry {
-    pub fn new(
-        stop_id: i32,
-        arrival_time: Option<NaiveTime>,
-        departure_time: Option<NaiveTime>,
-    ) -> Self {
+    pub fn new(stop_id: i32, arrival: Option<ServiceTime>, departure: Option<ServiceTime>) -> Self {
         Self {
             stop_id,
-            arrival_time,
-            departure_time,
+            arrival,
+            departure,
+            boarding_allowed: true,
+            alighting_allowed: true,
         }
     }
 
@@ -859,16 +2120,52 @@ impl JourneyRouteEntry {
         self.stop_id
     }
 
-    pub fn arrival_time(&self) -> &Option<NaiveTime> {
-        &self.arrival_time
+    pub fn arrival(&self) -> Option<ServiceTime> {
+        self.arrival
+    }
+
+    pub fn departure(&self) -> Option<ServiceTime> {
+        self.departure
+    }
+
+    /// False if the passengers cannot board at the stop (negative departure time in FPLAN).
+    pub fn boarding_allowed(&self) -> bool {
+        self.boarding_allowed
+    }
+
+    pub fn set_boarding_allowed(&mut self, value: bool) {
+        self.boarding_allowed = value;
+    }
+
+    /// False if the passengers cannot alight at the stop (negative arrival time in FPLAN).
+    pub fn alighting_allowed(&self) -> bool {
+        self.alighting_allowed
     }
 
-    pub fn departure_time(&self) -> &Option<NaiveTime> {
-        &self.departure_time
+    pub fn set_alighting_allowed(&mut self, value: bool) {
+        self.alighting_allowed = value;
     }
 
     // Functions
 
+    pub fn arrival_time(&self) -> Option<NaiveTime> {
+        self.arrival.map(|arrival| arrival.time())
+    }
+
+    pub fn departure_time(&self) -> Option<NaiveTime> {
+        self.departure.map(|departure| departure.time())
+    }
+
+    /// Number of midnights passed since the start of the service day (0 if there is no arrival time).
+    pub fn arrival_day_offset(&self) -> u8 {
+        self.arrival.map_or(0, |arrival| arrival.day_offset())
+    }
+
+    /// Number of midnights passed since the start of the service day (0 if there is no departure time).
+    pub fn departure_day_offset(&self) -> u8 {
+        self.departure.map_or(0, |departure| departure.day_offset())
+    }
+
     pub fn stop<'a>(&'a self, data_storage: &'a DataStorage) -> HResult<&'a Stop> {
         let stop_id = self.stop_id();
         data_storage
@@ -879,167 +2176,946 @@ impl JourneyRouteEntry {
 }
 
 // ------------------------------------------------------------------------------------------------
-// --- JourneyPlatform
+// --- JourneySegment
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct JourneyPlatform {
-    journey_legacy_id: i32,
-    administration: String,
-    platform_id: i32,
-    time: Option<NaiveTime>,
-    bit_field_id: Option<i32>,
+/// Hop of a journey between two consecutive stops (see Journey::segments).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct JourneySegment {
+    from_stop_id: i32,
+    to_stop_id: i32,
+    departure: ServiceTime,
+    arrival: ServiceTime,
+    dwell_time: u32,
 }
 
-impl JourneyPlatform {
+impl JourneySegment {
     pub fn new(
-        journey_legacy_id: i32,
-        administration: String,
-        platform_id: i32,
-        time: Option<NaiveTime>,
-        bit_field_id: Option<i32>,
+        from_stop_id: i32,
+        to_stop_id: i32,
+        departure: ServiceTime,
+        arrival: ServiceTime,
+        dwell_time: u32,
     ) -> Self {
         Self {
-            journey_legacy_id,
-            administration,
-            platform_id,
-            time,
-            bit_field_id,
+            from_stop_id,
+            to_stop_id,
+            departure,
+            arrival,
+            dwell_time,
         }
     }
-}
 
-impl Model<JourneyPlatform> for JourneyPlatform {
-    type K = (i32, i32);
+    // Getters/Setters
 
-    fn id(&self) -> Self::K {
-        (self.journey_legacy_id, self.platform_id)
+    pub fn from_stop_id(&self) -> i32 {
+        self.from_stop_id
     }
-}
 
-// ------------------------------------------------------------------------------------------------
-// --- Language
-// ------------------------------------------------------------------------------------------------
+    pub fn to_stop_id(&self) -> i32 {
+        self.to_stop_id
+    }
 
-#[derive(
-    Clone, Copy, Debug, Default, Display, Eq, Hash, PartialEq, EnumString, Serialize, Deserialize,
-)]
-pub enum Language {
-    #[default]
-    #[strum(serialize = "deu", serialize = "DE")]
-    German,
+    /// Departure from the first stop, counted from the day of the journey.
+    pub fn departure(&self) -> ServiceTime {
+        self.departure
+    }
 
-    #[strum(serialize = "fra", serialize = "FR")]
-    French,
+    /// Arrival at the second stop, counted from the day of the journey.
+    pub fn arrival(&self) -> ServiceTime {
+        self.arrival
+    }
 
-    #[strum(serialize = "ita", serialize = "IT")]
-    Italian,
+    /// Minutes spent at the first stop before the departure (0 at the origin of the journey).
+    pub fn dwell_time(&self) -> u32 {
+        self.dwell_time
+    }
 
-    #[strum(serialize = "eng", serialize = "EN")]
-    English,
+    // Functions
+
+    /// Minutes between the departure and the arrival.
+    pub fn run_time(&self) -> u32 {
+        self.arrival
+            .minutes()
+            .saturating_sub(self.departure.minutes())
+    }
+
+    /// The date is the date of the journey.
+    pub fn departure_at(&self, date: NaiveDate) -> NaiveDateTime {
+        date.and_time(NaiveTime::MIN) + TimeDelta::minutes(i64::from(self.departure.minutes()))
+    }
+
+    /// The date is the date of the journey.
+    pub fn arrival_at(&self, date: NaiveDate) -> NaiveDateTime {
+        date.and_time(NaiveTime::MIN) + TimeDelta::minutes(i64::from(self.arrival.minutes()))
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
-// --- Line
+// --- JourneyCoupling
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Default, Serialize, Deserialize)]
-pub struct Line {
-    id: i32,
-    name: String,
-    short_name: String,
-    long_name: String,
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CouplingType {
+    /// *KW: the coupled journey runs as a through coach of the journey.
+    ThroughCoach,
+    /// *KWZ: the journey runs as a through coach of the coupled journey.
+    Train,
+}
+
+/// Coupled run of a journey (see Journey::couplings), e.g. the two parts of a wing train.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct JourneyCoupling {
+    coupling_type: CouplingType,
+    journey_legacy_id: i32,
+    administration: String,
+    from_stop_id: Option<i32>,
+    until_stop_id: Option<i32>,
+}
+
+impl JourneyCoupling {
+    pub fn new(
+        coupling_type: CouplingType,
+        journey_legacy_id: i32,
+        administration: String,
+        from_stop_id: Option<i32>,
+        until_stop_id: Option<i32>,
+    ) -> Self {
+        Self {
+            coupling_type,
+            journey_legacy_id,
+            administration,
+            from_stop_id,
+            until_stop_id,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn coupling_type(&self) -> CouplingType {
+        self.coupling_type
+    }
+
+    /// Journey number of the coupled journey.
+    pub fn journey_legacy_id(&self) -> i32 {
+        self.journey_legacy_id
+    }
+
+    /// Administration of the coupled journey.
+    pub fn administration(&self) -> &str {
+        &self.administration
+    }
+
+    /// None if the coupling starts at the first stop of the journey.
+    pub fn from_stop_id(&self) -> Option<i32> {
+        self.from_stop_id
+    }
+
+    /// None if the coupling ends at the last stop of the journey.
+    pub fn until_stop_id(&self) -> Option<i32> {
+        self.until_stop_id
+    }
+
+    // Functions
+
+    /// The coupled journeys with the journey number and administration, there can be several.
+    pub fn journeys<'a>(&self, data_storage: &'a DataStorage) -> Vec<&'a Journey> {
+        if !data_storage.contains_journey(self.journey_legacy_id, &self.administration) {
+            return Vec::new();
+        }
+        let mut journeys: Vec<_> = data_storage
+            .journeys()
+            .data()
+            .values()
+            .filter(|journey| {
+                journey.legacy_id() == self.journey_legacy_id
+                    && journey.administration() == self.administration
+            })
+            .collect();
+        journeys.sort_by_key(|journey| journey.id());
+        journeys
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- JourneyNumberChange
+// ------------------------------------------------------------------------------------------------
+
+/// From the stop on, the journey runs with the journey number and administration (see
+/// Journey::number_changes).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
+pub struct JourneyNumberChange {
+    stop_id: i32,
+    legacy_id: i32,
+    administration: String,
+}
+
+impl JourneyNumberChange {
+    pub fn new(stop_id: i32, legacy_id: i32, administration: String) -> Self {
+        Self {
+            stop_id,
+            legacy_id,
+            administration,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn stop_id(&self) -> i32 {
+        self.stop_id
+    }
+
+    pub fn legacy_id(&self) -> i32 {
+        self.legacy_id
+    }
+
+    pub fn administration(&self) -> &str {
+        &self.administration
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- JourneyPlatform
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
+pub struct JourneyPlatform {
+    journey_legacy_id: i32,
+    administration: String,
+    platform_id: i32,
+    time: Option<NaiveTime>,
+    bit_field_id: Option<i32>,
+}
+
+impl JourneyPlatform {
+    pub fn new(
+        journey_legacy_id: i32,
+        administration: String,
+        platform_id: i32,
+        time: Option<NaiveTime>,
+        bit_field_id: Option<i32>,
+    ) -> Self {
+        Self {
+            journey_legacy_id,
+            administration,
+            platform_id,
+            time,
+            bit_field_id,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn journey_legacy_id(&self) -> i32 {
+        self.journey_legacy_id
+    }
+
+    pub fn administration(&self) -> &str {
+        &self.administration
+    }
+
+    pub fn platform_id(&self) -> i32 {
+        self.platform_id
+    }
+
+    /// Time at the stop, to tell the platforms apart when the journey serves the stop twice.
+    pub fn time(&self) -> Option<NaiveTime> {
+        self.time
+    }
+
+    pub fn bit_field_id(&self) -> Option<i32> {
+        self.bit_field_id
+    }
+}
+
+impl Model<JourneyPlatform> for JourneyPlatform {
+    // The legacy ID of a journey is only unique within its administration.
+    type K = (i32, String, i32);
+
+    fn id(&self) -> Self::K {
+        (
+            self.journey_legacy_id,
+            self.administration.clone(),
+            self.platform_id,
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Language
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, Default, Display, Eq, Hash, PartialEq, EnumString)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes),
+    archive_attr(derive(Eq, Hash, PartialEq))
+)]
+// Same representation as the archived enum, so that both hash alike (keys of archived maps).
+#[repr(u8)]
+pub enum Language {
+    #[default]
+    #[strum(serialize = "deu", serialize = "DE")]
+    German,
+
+    #[strum(serialize = "fra", serialize = "FR")]
+    French,
+
+    #[strum(serialize = "ita", serialize = "IT")]
+    Italian,
+
+    #[strum(serialize = "eng", serialize = "EN")]
+    English,
+}
+
+impl Language {
+    pub const ALL: [Self; 4] = [Self::German, Self::French, Self::Italian, Self::English];
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- LanguagePreference
+// ------------------------------------------------------------------------------------------------
+
+/// Languages in order of preference, accepted by the getters of the translated texts, which
+/// return the text in the first language available. A single Language is preferred, then
+/// followed by the other languages (German, French, Italian, English).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LanguagePreference {
+    languages: Vec<Language>,
+}
+
+impl LanguagePreference {
+    pub fn new(languages: Vec<Language>) -> Self {
+        Self { languages }
+    }
+
+    // Getters/Setters
+
+    pub fn languages(&self) -> &[Language] {
+        &self.languages
+    }
+
+    // Functions
+
+    /// The text in the first language of the preference which has one.
+    pub fn resolve<'a>(&self, texts: &'a FxHashMap<Language, String>) -> Option<&'a str> {
+        self.languages
+            .iter()
+            .find_map(|language| texts.get(language))
+            .map(String::as_str)
+    }
+}
+
+impl Default for LanguagePreference {
+    fn default() -> Self {
+        Self::new(Language::ALL.to_vec())
+    }
+}
+
+impl From<Language> for LanguagePreference {
+    fn from(language: Language) -> Self {
+        let mut languages = vec![language];
+        languages.extend(Language::ALL.into_iter().filter(|&other| other != language));
+        Self::new(languages)
+    }
+}
+
+impl From<Vec<Language>> for LanguagePreference {
+    fn from(languages: Vec<Language>) -> Self {
+        Self::new(languages)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Line
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
+pub struct Line {
+    id: i32,
+    name: String,
+    short_name: String,
+    long_name: String,
     internal_designation: String,
+    region_name: String,
     description: String,
     text_color: Color,
     background_color: Color,
+    main_line_id: Option<i32>,
+    // (Code, ID of the information text) of the I rows, e.g. ("TU", 1).
+    info_text_refs: Vec<(String, i32)>,
+}
+
+impl_Model!(Line);
+
+impl Line {
+    pub fn new(id: i32, name: String) -> Self {
+        Self {
+            id,
+            name,
+            short_name: String::default(),
+            long_name: String::default(),
+            internal_designation: String::default(),
+            region_name: String::default(),
+            description: String::default(),
+            text_color: Color::default(),
+            background_color: Color::default(),
+            main_line_id: None,
+            info_text_refs: Vec::new(),
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn short_name(&self) -> &str {
+        &self.short_name
+    }
+
+    pub fn set_short_name(&mut self, value: String) {
+        self.short_name = value;
+    }
+
+    pub fn set_long_name(&mut self, value: String) {
+        self.long_name = value;
+    }
+
+    pub fn set_internal_designation(&mut self, value: String) {
+        self.internal_designation = value;
+    }
+
+    /// Reserved for the BAV ID.
+    pub fn region_name(&self) -> &str {
+        &self.region_name
+    }
+
+    pub fn set_region_name(&mut self, value: String) {
+        self.region_name = value;
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn set_description(&mut self, value: String) {
+        self.description = value;
+    }
+
+    pub fn text_color(&self) -> Color {
+        self.text_color
+    }
+
+    pub fn set_text_color(&mut self, value: Color) {
+        self.text_color = value;
+    }
+
+    pub fn background_color(&self) -> Color {
+        self.background_color
+    }
+
+    pub fn set_background_color(&mut self, value: Color) {
+        self.background_color = value;
+    }
+
+    /// ID of the main line, if the line is one of its variants.
+    pub fn main_line_id(&self) -> Option<i32> {
+        self.main_line_id
+    }
+
+    pub fn set_main_line_id(&mut self, value: i32) {
+        self.main_line_id = Some(value);
+    }
+
+    /// Code and ID (see INFOTEXT) of the information texts of the line.
+    pub fn info_text_refs(&self) -> &[(String, i32)] {
+        &self.info_text_refs
+    }
+
+    pub fn add_info_text_ref(&mut self, code: String, info_text_id: i32) {
+        self.info_text_refs.push((code, info_text_id));
+    }
+
+    // Functions
+
+    /// The name shown to the passengers: the short name (e.g. "S1"), or the name if there is none.
+    pub fn display_name(&self) -> &str {
+        if self.short_name.is_empty() {
+            &self.name
+        } else {
+            &self.short_name
+        }
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl ArchivedLine {
+    // Getters/Setters
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- LineGeometry
+// ------------------------------------------------------------------------------------------------
+
+/// Representative shape of a line: its longest stop sequence, plus the parts of the other stop
+/// sequences which are not already covered (e.g. a branch to another terminus).
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LineGeometry {
+    line_id: i32,
+    main_variant: Vec<i32>,
+    branches: Vec<Vec<i32>>,
+}
+
+impl LineGeometry {
+    pub fn new(line_id: i32, main_variant: Vec<i32>, branches: Vec<Vec<i32>>) -> Self {
+        Self {
+            line_id,
+            main_variant,
+            branches,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn line_id(&self) -> i32 {
+        self.line_id
+    }
+
+    /// Stop IDs of the longest stop sequence of the line.
+    pub fn main_variant(&self) -> &[i32] {
+        &self.main_variant
+    }
+
+    /// Stop IDs of the parts of the other stop sequences which are not covered by the main variant
+    /// or a previous branch. A branch starts and ends with a stop where it leaves and rejoins the
+    /// rest of the geometry, if it does.
+    pub fn branches(&self) -> &[Vec<i32>] {
+        &self.branches
+    }
+
+    // Functions
+
+    /// WGS84 coordinates of the main variant followed by the branches.
+    /// The stops which are unknown or have no coordinates are skipped.
+    pub fn coordinates(&self, data_storage: &DataStorage) -> Vec<Vec<Coordinates>> {
+        std::iter::once(&self.main_variant)
+            .chain(&self.branches)
+            .map(|stop_ids| {
+                stop_ids
+                    .iter()
+                    .filter_map(|&stop_id| {
+                        let stop = data_storage.stops().find(stop_id)?;
+                        stop.wgs84_position()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- LineOverview
+// ------------------------------------------------------------------------------------------------
+
+/// Summary of the journeys of a line (see DataStorage::lines_overview).
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LineOverview {
+    line_id: i32,
+    administrations: Vec<String>,
+    transport_type_ids: Vec<i32>,
+    terminal_stop_ids: Vec<i32>,
+    active_period: Option<(NaiveDate, NaiveDate)>,
+}
+
+impl LineOverview {
+    pub fn new(
+        line_id: i32,
+        administrations: Vec<String>,
+        transport_type_ids: Vec<i32>,
+        terminal_stop_ids: Vec<i32>,
+        active_period: Option<(NaiveDate, NaiveDate)>,
+    ) -> Self {
+        Self {
+            line_id,
+            administrations,
+            transport_type_ids,
+            terminal_stop_ids,
+            active_period,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn line_id(&self) -> i32 {
+        self.line_id
+    }
+
+    /// Operators of the journeys, sorted.
+    pub fn administrations(&self) -> &[String] {
+        &self.administrations
+    }
+
+    /// Sorted.
+    pub fn transport_type_ids(&self) -> &[i32] {
+        &self.transport_type_ids
+    }
+
+    /// First and last stops of the journeys, sorted by ID.
+    pub fn terminal_stop_ids(&self) -> &[i32] {
+        &self.terminal_stop_ids
+    }
+
+    /// First and last days (inclusive) on which at least one journey operates,
+    /// None if no journey of the line operates during the timetable period.
+    pub fn active_period(&self) -> Option<RangeInclusive<NaiveDate>> {
+        self.active_period.map(|(start, end)| start..=end)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Platform
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
+pub struct Platform {
+    id: i32,
+    name: String,
+    sectors: Vec<PlatformSector>,
+    stop_id: i32,
+    sloid: String,
+    lv95_coordinates: Option<Coordinates>,
+    wgs84_coordinates: Option<Coordinates>,
+    altitude: Option<f64>,
+}
+
+impl_Model!(Platform);
+
+impl Platform {
+    pub fn new(id: i32, name: String, sectors: Vec<PlatformSector>, stop_id: i32) -> Self {
+        Self {
+            id,
+            name,
+            sectors,
+            stop_id,
+            sloid: String::default(),
+            lv95_coordinates: None,
+            wgs84_coordinates: None,
+            altitude: None,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn set_name(&mut self, value: String) {
+        self.name = value;
+    }
+
+    /// Sectors of the platform, in the order of their definition.
+    pub fn sectors(&self) -> &[PlatformSector] {
+        &self.sectors
+    }
+
+    pub(crate) fn sectors_mut(&mut self) -> &mut Vec<PlatformSector> {
+        &mut self.sectors
+    }
+
+    pub(crate) fn set_sectors(&mut self, value: Vec<PlatformSector>) {
+        self.sectors = value;
+    }
+
+    /// Empty if GLEISE defines no SLOID for the platform.
+    pub fn sloid(&self) -> &str {
+        &self.sloid
+    }
+
+    pub fn set_sloid(&mut self, value: String) {
+        self.sloid = value;
+    }
+
+    pub fn lv95_coordinates(&self) -> Option<Coordinates> {
+        self.lv95_coordinates
+    }
+
+    pub fn set_lv95_coordinates(&mut self, value: Coordinates) {
+        self.lv95_coordinates = Some(value);
+    }
+
+    pub fn wgs84_coordinates(&self) -> Option<Coordinates> {
+        self.wgs84_coordinates
+    }
+
+    pub fn set_wgs84_coordinates(&mut self, value: Coordinates) {
+        self.wgs84_coordinates = Some(value);
+    }
+
+    /// Altitude in meters, None if the k row of GLEISE has none.
+    pub fn altitude(&self) -> Option<f64> {
+        self.altitude
+    }
+
+    pub fn set_altitude(&mut self, value: f64) {
+        self.altitude = Some(value);
+    }
+
+    pub fn stop_id(&self) -> i32 {
+        self.stop_id
+    }
+
+    // Functions
+
+    /// Returns the best available WGS84 coordinates of the platform: its own WGS84 coordinates,
+    /// otherwise those of its stop, otherwise its LV95 coordinates or those of its stop converted to WGS84.
+    pub fn best_coordinates(&self, data_storage: &DataStorage) -> Option<Coordinates> {
+        let stop = data_storage.stops().find(self.stop_id);
+
+        self.wgs84_coordinates
+            .or_else(|| stop.and_then(|stop| stop.wgs84_coordinates()))
+            .or_else(|| {
+                self.lv95_coordinates
+                    .or_else(|| stop.and_then(|stop| stop.lv95_coordinates()))
+                    .map(|coordinates| coordinates.to_wgs84())
+            })
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl ArchivedPlatform {
+    // Getters/Setters
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn stop_id(&self) -> i32 {
+        self.stop_id
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- PlatformSector
+// ------------------------------------------------------------------------------------------------
+
+/// A section of a platform (A row of GLEISE, or the A column of the G row in GLEIS), e.g. "AB".
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
+pub struct PlatformSector {
+    name: String,
+    sloid: Option<String>,
+    lv95_coordinates: Option<Coordinates>,
+    wgs84_coordinates: Option<Coordinates>,
+}
+
+impl PlatformSector {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            sloid: None,
+            lv95_coordinates: None,
+            wgs84_coordinates: None,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// None if the g A row following the section is missing.
+    pub fn sloid(&self) -> Option<&str> {
+        self.sloid.as_deref()
+    }
+
+    pub(crate) fn set_sloid(&mut self, value: String) {
+        self.sloid = Some(value);
+    }
+
+    pub fn lv95_coordinates(&self) -> Option<Coordinates> {
+        self.lv95_coordinates
+    }
+
+    pub(crate) fn set_lv95_coordinates(&mut self, value: Coordinates) {
+        self.lv95_coordinates = Some(value);
+    }
+
+    pub fn wgs84_coordinates(&self) -> Option<Coordinates> {
+        self.wgs84_coordinates
+    }
+
+    pub(crate) fn set_wgs84_coordinates(&mut self, value: Coordinates) {
+        self.wgs84_coordinates = Some(value);
+    }
 }
 
-impl_Model!(Line);
+// ------------------------------------------------------------------------------------------------
+// --- SegmentStatistics
+// ------------------------------------------------------------------------------------------------
+
+/// Travel times between two consecutive stops, over all the journeys serving them in this order.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SegmentStatistics {
+    from_stop_id: i32,
+    to_stop_id: i32,
+    service_count: usize,
+    min_travel_time: u32,
+    median_travel_time: f64,
+    mean_travel_time: f64,
+}
+
+impl SegmentStatistics {
+    /// The travel times are in minutes, there must be at least one.
+    pub fn new(from_stop_id: i32, to_stop_id: i32, mut travel_times: Vec<u32>) -> Self {
+        travel_times.sort_unstable();
+        let service_count = travel_times.len();
+        let middle = service_count / 2;
+        let median_travel_time = if service_count.is_multiple_of(2) {
+            f64::from(travel_times[middle - 1] + travel_times[middle]) / 2.0
+        } else {
+            f64::from(travel_times[middle])
+        };
 
-impl Line {
-    pub fn new(id: i32, name: String) -> Self {
         Self {
-            id,
-            name,
-            short_name: String::default(),
-            long_name: String::default(),
-            internal_designation: String::default(),
-            description: String::default(),
-            text_color: Color::default(),
-            background_color: Color::default(),
+            from_stop_id,
+            to_stop_id,
+            service_count,
+            min_travel_time: travel_times[0],
+            median_travel_time,
+            mean_travel_time: travel_times.iter().sum::<u32>() as f64 / service_count as f64,
         }
     }
 
     // Getters/Setters
 
-    pub fn set_short_name(&mut self, value: String) {
-        self.short_name = value;
+    pub fn from_stop_id(&self) -> i32 {
+        self.from_stop_id
     }
 
-    pub fn set_long_name(&mut self, value: String) {
-        self.long_name = value;
+    pub fn to_stop_id(&self) -> i32 {
+        self.to_stop_id
     }
 
-    pub fn set_internal_designation(&mut self, value: String) {
-        self.internal_designation = value;
+    /// Number of journeys serving the segment.
+    pub fn service_count(&self) -> usize {
+        self.service_count
     }
 
-    pub fn set_description(&mut self, value: String) {
-        self.description = value;
+    /// In minutes.
+    pub fn min_travel_time(&self) -> u32 {
+        self.min_travel_time
     }
 
-    pub fn set_text_color(&mut self, value: Color) {
-        self.text_color = value;
+    /// In minutes.
+    pub fn median_travel_time(&self) -> f64 {
+        self.median_travel_time
     }
 
-    pub fn set_background_color(&mut self, value: Color) {
-        self.background_color = value;
+    /// In minutes.
+    pub fn mean_travel_time(&self) -> f64 {
+        self.mean_travel_time
     }
 }
 
 // ------------------------------------------------------------------------------------------------
-// --- Platform
+// --- ServiceTime
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Platform {
-    id: i32,
-    name: String,
-    sectors: Option<String>,
-    stop_id: i32,
-    sloid: String,
-    lv95_coordinates: Coordinates,
-    wgs84_coordinates: Coordinates,
-}
+const MINUTES_PER_DAY: u32 = 1440;
 
-impl_Model!(Platform);
+/// Number of minutes since the start of the service day.
+/// Unlike NaiveTime, it can exceed 24 hours (e.g. 25:30 for 01:30 on the next day).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
+pub struct ServiceTime(u32);
 
-impl Platform {
-    pub fn new(id: i32, name: String, sectors: Option<String>, stop_id: i32) -> Self {
-        Self {
-            id,
-            name,
-            sectors,
-            stop_id,
-            sloid: String::default(),
-            lv95_coordinates: Coordinates::default(),
-            wgs84_coordinates: Coordinates::default(),
-        }
+impl ServiceTime {
+    pub fn new(minutes: u32) -> Self {
+        Self(minutes)
+    }
+
+    pub fn from_time_and_day_offset(time: NaiveTime, day_offset: u8) -> Self {
+        Self(u32::from(day_offset) * MINUTES_PER_DAY + time.num_seconds_from_midnight() / 60)
     }
 
     // Getters/Setters
 
-    pub fn set_sloid(&mut self, value: String) {
-        self.sloid = value;
+    pub fn minutes(&self) -> u32 {
+        self.0
     }
 
-    pub fn set_lv95_coordinates(&mut self, value: Coordinates) {
-        self.lv95_coordinates = value;
+    // Functions
+
+    /// The time of day, regardless of how many midnights have passed.
+    pub fn time(&self) -> NaiveTime {
+        NaiveTime::MIN + TimeDelta::minutes(i64::from(self.0 % MINUTES_PER_DAY))
     }
 
-    pub fn set_wgs84_coordinates(&mut self, value: Coordinates) {
-        self.wgs84_coordinates = value;
+    /// Number of midnights passed since the start of the service day.
+    pub fn day_offset(&self) -> u8 {
+        u8::try_from(self.0 / MINUTES_PER_DAY).unwrap_or(u8::MAX)
+    }
+}
+
+impl From<NaiveTime> for ServiceTime {
+    fn from(value: NaiveTime) -> Self {
+        Self::from_time_and_day_offset(value, 0)
     }
 }
 
@@ -1047,7 +3123,13 @@ impl Platform {
 // --- Stop
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct Stop {
     id: i32,
     name: String,
@@ -1056,12 +3138,15 @@ pub struct Stop {
     synonyms: Option<Vec<String>>,
     lv95_coordinates: Option<Coordinates>,
     wgs84_coordinates: Option<Coordinates>,
+    altitude: Option<f64>,
     exchange_priority: i16,
     exchange_flag: i16,
     exchange_time: Option<(i16, i16)>, // (InterCity exchange time, Exchange time for all other journey types)
     restrictions: i16,
     sloid: String,
     boarding_areas: Vec<String>,
+    country_code: Option<String>,
+    canton: Option<i32>,
 }
 
 impl_Model!(Stop);
@@ -1082,12 +3167,15 @@ impl Stop {
             synonyms,
             lv95_coordinates: None,
             wgs84_coordinates: None,
+            altitude: None,
             exchange_priority: 8, // 8 is the default priority.
             exchange_flag: 0,
             exchange_time: None,
             restrictions: 0,
             sloid: String::default(),
             boarding_areas: Vec::new(),
+            country_code: None,
+            canton: None,
         }
     }
 
@@ -1097,6 +3185,18 @@ impl Stop {
         &self.name
     }
 
+    pub fn long_name(&self) -> Option<&str> {
+        self.long_name.as_deref()
+    }
+
+    pub fn abbreviation(&self) -> Option<&str> {
+        self.abbreviation.as_deref()
+    }
+
+    pub fn synonyms(&self) -> &[String] {
+        self.synonyms.as_deref().unwrap_or_default()
+    }
+
     pub fn lv95_coordinates(&self) -> Option<Coordinates> {
         self.lv95_coordinates
     }
@@ -1113,6 +3213,15 @@ impl Stop {
         self.wgs84_coordinates = Some(value);
     }
 
+    /// Altitude in meters (BFKOORD).
+    pub fn altitude(&self) -> Option<f64> {
+        self.altitude
+    }
+
+    pub fn set_altitude(&mut self, value: f64) {
+        self.altitude = Some(value);
+    }
+
     pub fn set_exchange_priority(&mut self, value: i16) {
         self.exchange_priority = value;
     }
@@ -1137,26 +3246,126 @@ impl Stop {
         self.restrictions = value;
     }
 
+    pub fn sloid(&self) -> &str {
+        &self.sloid
+    }
+
     pub fn set_sloid(&mut self, value: String) {
         self.sloid = value;
     }
 
+    /// SLOIDs of the boarding areas of the stop (the "G a" rows of BHFART).
+    pub fn boarding_areas(&self) -> &[String] {
+        &self.boarding_areas
+    }
+
     // Functions
 
     pub fn add_boarding_area(&mut self, value: String) {
         self.boarding_areas.push(value);
     }
 
+    /// WGS84 coordinates of the stop, converted from LV95 if it has only those.
+    pub fn wgs84_position(&self) -> Option<Coordinates> {
+        self.wgs84_coordinates
+            .or(self.lv95_coordinates)
+            .map(|coordinates| coordinates.to_wgs84())
+    }
+
+    /// Country code of the stop (the L row of BHFART), e.g. "CH".
+    pub fn country_code(&self) -> Option<&str> {
+        self.country_code.as_deref()
+    }
+
+    pub fn set_country_code(&mut self, value: String) {
+        self.country_code = Some(value);
+    }
+
+    /// Number of the canton of the stop (the I KT row of BHFART), e.g. 1 for Zurich.
+    pub fn canton(&self) -> Option<i32> {
+        self.canton
+    }
+
+    pub fn set_canton(&mut self, value: i32) {
+        self.canton = Some(value);
+    }
+
     pub fn can_be_used_as_exchange_point(&self) -> bool {
         self.exchange_flag() != 0
     }
+
+    /// Exchange time in minutes at the stop (UMSTEIGB) for the journeys of the product class:
+    /// the InterCity exchange time for the InterCity classes (see TransportType::is_inter_city),
+    /// the other exchange time otherwise. The default exchange time (the 9999999 row) is used if
+    /// the stop has no exchange time.
+    pub fn effective_exchange_time(
+        &self,
+        product_class_id: i16,
+        data_storage: &DataStorage,
+    ) -> i16 {
+        let (inter_city_exchange_time, exchange_time) = self
+            .exchange_time
+            .unwrap_or(data_storage.default_exchange_time());
+        if PRODUCT_CLASSES_INTER_CITY.contains(&product_class_id) {
+            inter_city_exchange_time
+        } else {
+            exchange_time
+        }
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl ArchivedStop {
+    // Getters/Setters
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- StopCluster
+// ------------------------------------------------------------------------------------------------
+
+/// Stops which are physically co-located (e.g. a railway station and the adjacent bus stops).
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StopCluster {
+    stop_ids: Vec<i32>,
+    centroid: Option<Coordinates>,
+}
+
+impl StopCluster {
+    pub fn new(stop_ids: Vec<i32>, centroid: Option<Coordinates>) -> Self {
+        Self { stop_ids, centroid }
+    }
+
+    // Getters/Setters
+
+    pub fn stop_ids(&self) -> &[i32] {
+        &self.stop_ids
+    }
+
+    /// WGS84 centroid of the stops with coordinates.
+    pub fn centroid(&self) -> Option<Coordinates> {
+        self.centroid
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
 // --- StopConnection
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct StopConnection {
     id: i32,
     stop_id_1: i32,
@@ -1201,7 +3410,13 @@ impl StopConnection {
 // --- ThroughService
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct ThroughService {
     id: i32,
     journey_1_id: JourneyId,
@@ -1257,7 +3472,13 @@ impl ThroughService {
 // --- TimetableMetadataEntry
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct TimetableMetadataEntry {
     id: i32,
     key: String,
@@ -1291,13 +3512,20 @@ impl TimetableMetadataEntry {
 // --- TransportCompany
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct TransportCompany {
     id: i32,
     short_name: FxHashMap<Language, String>,
     long_name: FxHashMap<Language, String>,
     full_name: FxHashMap<Language, String>,
     administrations: Vec<String>,
+    sboid: Option<String>,
 }
 
 impl_Model!(TransportCompany);
@@ -1310,33 +3538,76 @@ impl TransportCompany {
             long_name: FxHashMap::default(),
             full_name: FxHashMap::default(),
             administrations: Vec::new(),
+            sboid: None,
         }
     }
 
     // Getters/Setters
 
+    pub fn administrations(&self) -> &[String] {
+        &self.administrations
+    }
+
     pub fn set_administrations(&mut self, administrations: Vec<String>) {
         self.administrations = administrations;
     }
 
+    pub fn short_name(&self, language: impl Into<LanguagePreference>) -> Option<&str> {
+        language.into().resolve(&self.short_name)
+    }
+
     pub fn set_short_name(&mut self, language: Language, value: &str) {
         self.short_name.insert(language, value.to_string());
     }
 
+    pub fn long_name(&self, language: impl Into<LanguagePreference>) -> Option<&str> {
+        language.into().resolve(&self.long_name)
+    }
+
     pub fn set_long_name(&mut self, language: Language, value: &str) {
         self.long_name.insert(language, value.to_string());
     }
 
+    pub fn full_name(&self, language: impl Into<LanguagePreference>) -> Option<&str> {
+        language.into().resolve(&self.full_name)
+    }
+
     pub fn set_full_name(&mut self, language: Language, value: &str) {
         self.full_name.insert(language, value.to_string());
     }
+
+    /// Swiss Business Organisation ID (N row of BETRIEB), e.g. ch:1:sboid:100001.
+    pub fn sboid(&self) -> Option<&str> {
+        self.sboid.as_deref()
+    }
+
+    pub fn set_sboid(&mut self, value: String) {
+        self.sboid = Some(value);
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
 // --- TransportType
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+// Product classes (class00 to class13 in ZUGART) as used in Switzerland.
+// ICE/EN/CNL/ES/NZ/TGV/THA/X2 (0), EuroCity/InterCity/ICN/InterCityNight/SuperCity (1),
+// InterRegio/PanoramaExpress (2).
+const PRODUCT_CLASSES_LONG_DISTANCE: [i16; 3] = [0, 1, 2];
+// The classes to which the InterCity exchange times of UMSTEIGB apply.
+const PRODUCT_CLASSES_INTER_CITY: [i16; 2] = [0, 1];
+// The long distance classes, Schnellzug/RegioExpress (3) and S-Bahn/StadtExpress/Regionalzug (5).
+const PRODUCT_CLASSES_RAIL: [i16; 5] = [0, 1, 2, 3, 5];
+const PRODUCT_CLASS_BOAT: i16 = 4;
+const PRODUCT_CLASS_BUS: i16 = 6;
+
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct TransportType {
     id: i32,
     designation: String,
@@ -1388,13 +3659,67 @@ impl TransportType {
         self.product_class_id
     }
 
+    pub fn tariff_group(&self) -> &str {
+        &self.tariff_group
+    }
+
+    pub fn output_control(&self) -> i16 {
+        self.output_control
+    }
+
+    pub fn short_name(&self) -> &str {
+        &self.short_name
+    }
+
+    pub fn surcharge(&self) -> i16 {
+        self.surcharge
+    }
+
+    /// N for local transport, B for ship, empty otherwise.
+    pub fn flag(&self) -> &str {
+        &self.flag
+    }
+
+    pub fn product_class_name(&self, language: impl Into<LanguagePreference>) -> Option<&str> {
+        language.into().resolve(&self.product_class_name)
+    }
+
     pub fn set_product_class_name(&mut self, language: Language, value: &str) {
         self.product_class_name.insert(language, value.to_string());
     }
 
+    pub fn category_name(&self, language: impl Into<LanguagePreference>) -> Option<&str> {
+        language.into().resolve(&self.category_name)
+    }
+
     pub fn set_category_name(&mut self, language: Language, value: &str) {
         self.category_name.insert(language, value.to_string());
     }
+
+    // Functions
+
+    /// ICE, EC, IC, IR, RE, S-Bahn and regional trains.
+    pub fn is_rail(&self) -> bool {
+        PRODUCT_CLASSES_RAIL.contains(&self.product_class_id)
+    }
+
+    /// ICE, EC and IC.
+    pub fn is_inter_city(&self) -> bool {
+        PRODUCT_CLASSES_INTER_CITY.contains(&self.product_class_id)
+    }
+
+    /// ICE, EC, IC and IR, unless flagged as local transport.
+    pub fn is_long_distance(&self) -> bool {
+        PRODUCT_CLASSES_LONG_DISTANCE.contains(&self.product_class_id) && self.flag != "N"
+    }
+
+    pub fn is_boat(&self) -> bool {
+        self.product_class_id == PRODUCT_CLASS_BOAT || self.flag == "B"
+    }
+
+    pub fn is_bus(&self) -> bool {
+        self.product_class_id == PRODUCT_CLASS_BUS && !self.is_boat()
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -1412,16 +3737,33 @@ impl NaiveDateRange {
     }
 }
 
-#[derive(Clone, Copy, Debug, Display, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub use version::Version;
+
+// The variants are named after the version numbers. The lint is allowed on the module so that
+// it covers the items generated by the derives too (e.g. VersionResolver of rkyv).
 #[allow(non_camel_case_types)]
-pub enum Version {
-    V_5_20_1_0,
-    V_5_40_41_2_0_2,
-    V_5_40_41_2_0_3,
-    V_5_40_41_2_0_4,
-    V_5_40_41_2_0_5,
-    V_5_40_41_2_0_6,
-    V_5_40_41_2_0_7,
+mod version {
+    #[cfg(feature = "serde")]
+    use serde::{Deserialize, Serialize};
+    use strum_macros::{Display, EnumString};
+
+    #[derive(Clone, Copy, Debug, Display, Eq, Hash, PartialEq, EnumString)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(
+        feature = "rkyv",
+        derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+        archive(check_bytes),
+        archive_attr(allow(non_camel_case_types))
+    )]
+    pub enum Version {
+        V_5_20_1_0,
+        V_5_40_41_2_0_2,
+        V_5_40_41_2_0_3,
+        V_5_40_41_2_0_4,
+        V_5_40_41_2_0_5,
+        V_5_40_41_2_0_6,
+        V_5_40_41_2_0_7,
+    }
 }
 
 impl Version {
@@ -1467,6 +3809,7 @@ impl Version {
     //         NaiveDate::from_ymd_opt(2021, 12, 12).unwrap(),
     //     )
     // }
+    #[cfg_attr(not(feature = "fetch"), allow(dead_code))]
     pub(crate) fn try_url(date: NaiveDate) -> HResult<String> {
         if Self::timetable_2026().contains(&date) {
             Ok(String::from(
@@ -1529,22 +3872,53 @@ mod tests {
     use super::*;
     use chrono::{NaiveDate, NaiveTime};
 
+    fn parse_time(value: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(value, "%H:%M").unwrap()
+    }
+
     fn build_route_entry(
         stop_id: i32,
         arrival: Option<&str>,
         departure: Option<&str>,
     ) -> JourneyRouteEntry {
-        let arrival_time = arrival.map(|value| NaiveTime::parse_from_str(value, "%H:%M").unwrap());
-        let departure_time =
-            departure.map(|value| NaiveTime::parse_from_str(value, "%H:%M").unwrap());
-        JourneyRouteEntry::new(stop_id, arrival_time, departure_time)
+        build_route_entry_with_day_offsets(stop_id, arrival, departure, 0, 0)
+    }
+
+    fn build_route_entry_with_day_offsets(
+        stop_id: i32,
+        arrival: Option<&str>,
+        departure: Option<&str>,
+        arrival_day_offset: u8,
+        departure_day_offset: u8,
+    ) -> JourneyRouteEntry {
+        JourneyRouteEntry::new(
+            stop_id,
+            arrival.map(|value| {
+                ServiceTime::from_time_and_day_offset(parse_time(value), arrival_day_offset)
+            }),
+            departure.map(|value| {
+                ServiceTime::from_time_and_day_offset(parse_time(value), departure_day_offset)
+            }),
+        )
     }
 
     fn build_midnight_journey() -> Journey {
         let mut journey = Journey::new(1, 100, "CH".to_string());
         journey.add_route_entry(build_route_entry(1, None, Some("23:50")));
-        journey.add_route_entry(build_route_entry(2, Some("00:10"), Some("00:15")));
-        journey.add_route_entry(build_route_entry(3, Some("00:30"), None));
+        journey.add_route_entry(build_route_entry_with_day_offsets(
+            2,
+            Some("00:10"),
+            Some("00:15"),
+            1,
+            1,
+        ));
+        journey.add_route_entry(build_route_entry_with_day_offsets(
+            3,
+            Some("00:30"),
+            None,
+            1,
+            0,
+        ));
         journey
     }
 
@@ -1627,6 +4001,229 @@ mod tests {
         );
     }
 
+    #[test]
+    fn transport_type_classification() {
+        let transport_type = |product_class_id, flag: &str| {
+            TransportType::new(
+                1,
+                "X".to_string(),
+                product_class_id,
+                "A".to_string(),
+                0,
+                "X".to_string(),
+                0,
+                flag.to_string(),
+            )
+        };
+
+        let inter_city = transport_type(1, "");
+        assert!(inter_city.is_rail());
+        assert!(inter_city.is_long_distance());
+        assert!(!inter_city.is_bus());
+
+        let s_bahn = transport_type(5, "N");
+        assert!(s_bahn.is_rail());
+        assert!(!s_bahn.is_long_distance());
+
+        assert!(transport_type(4, "").is_boat());
+        assert!(transport_type(6, "N").is_bus());
+        // Flagged as a ship.
+        let ship = transport_type(6, "B");
+        assert!(ship.is_boat());
+        assert!(!ship.is_bus());
+        assert!(!ship.is_rail());
+    }
+
+    #[test]
+    fn journey_time_calculations_cross_two_midnights() {
+        let mut journey = Journey::new(1, 100, "CH".to_string());
+        journey.add_route_entry(build_route_entry(1, None, Some("22:00")));
+        journey.add_route_entry(build_route_entry_with_day_offsets(
+            2,
+            Some("08:00"),
+            Some("08:30"),
+            1,
+            1,
+        ));
+        journey.add_route_entry(build_route_entry_with_day_offsets(
+            3,
+            Some("07:00"),
+            None,
+            2,
+            0,
+        ));
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+
+        assert_eq!(
+            journey.arrival_time_and_day_offset_of(3).unwrap(),
+            (NaiveTime::from_hms_opt(7, 0, 0).unwrap(), 2)
+        );
+        assert_eq!(
+            journey.arrival_at_of(3, date).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 3)
+                .unwrap()
+                .and_time(NaiveTime::from_hms_opt(7, 0, 0).unwrap())
+        );
+
+        // The date is associated with the departure from stop 2 (2024-01-02).
+        let date = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        assert_eq!(
+            journey.arrival_at_of_with_origin(3, date, true, 2).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 3)
+                .unwrap()
+                .and_time(NaiveTime::from_hms_opt(7, 0, 0).unwrap())
+        );
+        assert_eq!(
+            journey
+                .departure_at_of_with_origin(1, date, true, 2)
+                .unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 1)
+                .unwrap()
+                .and_time(NaiveTime::from_hms_opt(22, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn coordinates_conversion() {
+        // Reference point of swisstopo (old observatory of Bern).
+        let lv95 = Coordinates::new(CoordinateSystem::LV95, 2_600_000.0, 1_200_000.0);
+        let wgs84 = lv95.to_wgs84();
+        assert_eq!(wgs84.coordinate_system(), CoordinateSystem::WGS84);
+        assert!((wgs84.latitude().unwrap() - 46.95108).abs() < 1e-5);
+        assert!((wgs84.longitude().unwrap() - 7.43864).abs() < 1e-5);
+
+        let back = wgs84.to_lv95();
+        assert!((back.easting().unwrap() - 2_600_000.0).abs() < 1.0);
+        assert!((back.northing().unwrap() - 1_200_000.0).abs() < 1.0);
+
+        // Converting to the same coordinate system does nothing.
+        assert_eq!(lv95.to_lv95().easting(), Some(2_600_000.0));
+    }
+
+    #[test]
+    fn coordinates_distance_and_bearing() {
+        let bern = Coordinates::new(CoordinateSystem::WGS84, 46.948825, 7.439122);
+        let zurich = Coordinates::new(CoordinateSystem::WGS84, 47.378177, 8.540192);
+
+        // About 96 km as the crow flies.
+        assert!((bern.distance_m(&zurich) - 95_961.0).abs() < 1.0);
+        assert_eq!(bern.distance_m(&bern), 0.0);
+        // Zürich is north-east of Bern.
+        assert!((bern.bearing(&zurich) - 59.76).abs() < 0.01);
+        assert!((zurich.bearing(&bern) - 240.57).abs() < 0.01);
+
+        // LV95 coordinates are converted.
+        let bern_lv95 = bern.to_lv95();
+        assert!(bern_lv95.distance_m(&bern) < 2.0);
+    }
+
+    #[test]
+    fn service_time_exceeds_one_day() {
+        let service_time = ServiceTime::new(1530);
+        assert_eq!(
+            service_time.time(),
+            NaiveTime::from_hms_opt(1, 30, 0).unwrap()
+        );
+        assert_eq!(service_time.day_offset(), 1);
+        assert_eq!(
+            ServiceTime::from_time_and_day_offset(NaiveTime::from_hms_opt(1, 30, 0).unwrap(), 1),
+            service_time
+        );
+        assert!(ServiceTime::from(NaiveTime::from_hms_opt(23, 50, 0).unwrap()) < service_time);
+    }
+
+    #[test]
+    fn journey_non_monotonic_route_times_are_found_and_repaired() {
+        let mut journey = Journey::new(1, 100, "CH".to_string());
+        journey.add_route_entry(build_route_entry(1, None, Some("08:00")));
+        journey.add_route_entry(build_route_entry(2, Some("08:10"), Some("08:05")));
+        journey.add_route_entry(build_route_entry(3, Some("08:30"), None));
+        assert_eq!(journey.find_non_monotonic_route_entry(), Some(1));
+
+        assert_eq!(journey.repair_route_times(), 1);
+        assert_eq!(journey.find_non_monotonic_route_entry(), None);
+        assert_eq!(
+            journey.route()[1].departure_time(),
+            Some(NaiveTime::from_hms_opt(8, 10, 0).unwrap())
+        );
+
+        // Times after midnight are not going backwards.
+        assert_eq!(
+            build_midnight_journey().find_non_monotonic_route_entry(),
+            None
+        );
+    }
+
+    fn build_night_journey() -> Journey {
+        let mut journey = Journey::new(1, 100, "CH".to_string());
+        journey.add_route_entry(build_route_entry(1, None, Some("01:40")));
+        journey.add_route_entry(build_route_entry(2, Some("02:20"), Some("02:25")));
+        journey.add_route_entry(build_route_entry(3, Some("03:10"), None));
+        journey
+    }
+
+    #[test]
+    fn journey_zoned_times_spring_forward() {
+        let journey = build_night_journey();
+        // Last Sunday of March 2024, the clocks go from 02:00 to 03:00.
+        let date = NaiveDate::from_ymd_opt(2024, 3, 31).unwrap();
+
+        let departure_at = journey.zoned_departure_at_of(1, date).unwrap();
+        assert_eq!(departure_at.offset().local_minus_utc(), 3600);
+        let arrival_at = journey.zoned_arrival_at_of(3, date).unwrap();
+        assert_eq!(arrival_at.offset().local_minus_utc(), 7200);
+        // 01:40 CET -> 03:10 CEST is 30 minutes and not 90 minutes.
+        assert_eq!((arrival_at - departure_at).num_minutes(), 30);
+
+        // 02:20 does not exist and is moved one hour forward.
+        let arrival_at = journey
+            .zoned_arrival_at_of_with_origin(2, date, true, 1)
+            .unwrap();
+        assert_eq!(
+            arrival_at.naive_utc(),
+            date.and_time(NaiveTime::from_hms_opt(1, 20, 0).unwrap())
+        );
+        assert_eq!((arrival_at - departure_at).num_minutes(), 40);
+    }
+
+    #[test]
+    fn journey_zoned_times_fall_back() {
+        let journey = build_night_journey();
+        // Last Sunday of October 2024, the clocks go from 03:00 back to 02:00.
+        let date = NaiveDate::from_ymd_opt(2024, 10, 27).unwrap();
+
+        let departure_at = journey.zoned_departure_at_of(1, date).unwrap();
+        assert_eq!(departure_at.offset().local_minus_utc(), 7200);
+        // 02:20 exists twice and the earlier instant is used.
+        let arrival_at = journey
+            .zoned_arrival_at_of_with_origin(2, date, true, 1)
+            .unwrap();
+        assert_eq!(arrival_at.offset().local_minus_utc(), 7200);
+        let departure_at_2 = journey
+            .zoned_departure_at_of_with_origin(2, date, true, 1)
+            .unwrap();
+        assert_eq!((departure_at_2 - arrival_at).num_minutes(), 5);
+
+        let arrival_at = journey.zoned_arrival_at_of(3, date).unwrap();
+        assert_eq!(arrival_at.offset().local_minus_utc(), 3600);
+        // 01:40 CEST -> 03:10 CET is 150 minutes and not 90 minutes.
+        assert_eq!((arrival_at - departure_at).num_minutes(), 150);
+    }
+
+    #[test]
+    fn journey_zoned_times_regular_night() {
+        let journey = build_night_journey();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 14).unwrap();
+
+        let departure_at = journey.zoned_departure_at_of(1, date).unwrap();
+        let arrival_at = journey.zoned_arrival_at_of(3, date).unwrap();
+        assert_eq!(
+            (arrival_at - departure_at).num_minutes(),
+            (journey.arrival_at_of(3, date).unwrap() - journey.departure_at_of(1, date).unwrap())
+                .num_minutes()
+        );
+    }
+
     #[test]
     fn journey_bit_field_id_requires_metadata() {
         let journey = Journey::new(1, 100, "CH".to_string());
@@ -1705,4 +4302,34 @@ mod tests {
         let in_2021 = NaiveDate::from_ymd_opt(2021, 6, 1).unwrap();
         Version::try_url(in_2021).unwrap();
     }
+
+    #[test]
+    fn language_preference_falls_back() {
+        let mut attribute = Attribute::new(1, "VR".to_string(), 0, 5, 5);
+        attribute.set_description(Language::German, "Velos: Reservierung obligatorisch");
+        attribute.set_description(Language::French, "Vélos: réservation obligatoire");
+
+        assert_eq!(
+            attribute.description(Language::French),
+            Some("Vélos: réservation obligatoire")
+        );
+        assert_eq!(
+            attribute.description(Language::English),
+            Some("Velos: Reservierung obligatorisch")
+        );
+        assert_eq!(
+            attribute.description(vec![Language::Italian, Language::French]),
+            Some("Vélos: réservation obligatoire")
+        );
+        assert_eq!(attribute.description(vec![Language::English]), None);
+        assert_eq!(
+            LanguagePreference::from(Language::Italian).languages(),
+            &[
+                Language::Italian,
+                Language::German,
+                Language::French,
+                Language::English
+            ]
+        );
+    }
 }
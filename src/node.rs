@@ -0,0 +1,278 @@
+use chrono::NaiveDateTime;
+use napi::{Error, Result};
+use napi_derive::napi;
+
+use crate::{
+    hrdf::Hrdf,
+    models::{Model, Stop, Version},
+};
+
+// The dates and times are exchanged as local (Swiss) ISO 8601 strings without offset
+// (e.g. "2025-12-15T06:00:00").
+
+fn parse_date_time(value: &str) -> Result<NaiveDateTime> {
+    value
+        .parse()
+        .map_err(|_| Error::from_reason(format!("Invalid date and time: {value}")))
+}
+
+fn format_date_time(value: NaiveDateTime) -> String {
+    value.format("%Y-%m-%dT%H:%M:%S").to_string()
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- JsHrdf
+// ------------------------------------------------------------------------------------------------
+
+#[napi(js_name = "Hrdf")]
+pub struct JsHrdf {
+    hrdf: Hrdf,
+}
+
+#[napi]
+impl JsHrdf {
+    /// Parses an HRDF archive already decompressed into a directory. The version is the name of
+    /// a Version variant (e.g. "V_5_40_41_2_0_7").
+    #[napi(factory)]
+    pub fn from_directory(version: String, path: String) -> Result<Self> {
+        let version = version
+            .parse::<Version>()
+            .map_err(|_| Error::from_reason(format!("Unknown version: {version}")))?;
+        let hrdf = Hrdf::from_directory(version, path.as_ref())
+            .map_err(|error| Error::from_reason(error.to_string()))?;
+        Ok(Self { hrdf })
+    }
+
+    #[napi]
+    pub fn search_stops(&self, query: String, expand_meta_stops: Option<bool>) -> Vec<JsStop> {
+        self.hrdf
+            .data_storage()
+            .search_stops(&query, expand_meta_stops.unwrap_or(false))
+            .into_iter()
+            .map(JsStop::new)
+            .collect()
+    }
+
+    /// The departures of the station board, flattened and sorted by departure time.
+    #[napi]
+    pub fn station_board(
+        &self,
+        stop_id: i32,
+        from: String,
+        until: String,
+        expand_meta_stops: Option<bool>,
+    ) -> Result<Vec<JsDeparture>> {
+        let station_board = self
+            .hrdf
+            .data_storage()
+            .station_board(
+                stop_id,
+                parse_date_time(&from)?,
+                parse_date_time(&until)?,
+                expand_meta_stops.unwrap_or(false),
+            )
+            .map_err(|error| Error::from_reason(error.to_string()))?;
+
+        let mut departures: Vec<_> = station_board
+            .platforms()
+            .iter()
+            .flat_map(|platform| {
+                platform.entries().iter().map(|entry| {
+                    (
+                        entry.departure_at(),
+                        JsDeparture {
+                            journey_id: entry.journey_id(),
+                            stop_id: platform.stop_id(),
+                            platform: platform.name().map(str::to_string),
+                            departure_at: format_date_time(entry.departure_at()),
+                            destination_stop_id: entry.destination_stop_id(),
+                            line_name: entry.line_name().map(str::to_string),
+                            attributes: entry.attributes().to_vec(),
+                        },
+                    )
+                })
+            })
+            .collect();
+        departures.sort_by_key(|(departure_at, departure)| (*departure_at, departure.journey_id));
+        Ok(departures
+            .into_iter()
+            .map(|(_, departure)| departure)
+            .collect())
+    }
+
+    /// Earliest arrival times, one row per origin and one column per destination, null if the
+    /// destination cannot be reached.
+    #[napi]
+    pub fn earliest_arrival_matrix(
+        &self,
+        origin_stop_ids: Vec<i32>,
+        destination_stop_ids: Vec<i32>,
+        departure_at: String,
+        max_transfers: Option<u32>,
+    ) -> Result<Vec<Vec<Option<String>>>> {
+        let matrix = self
+            .hrdf
+            .data_storage()
+            .earliest_arrival_matrix(
+                &origin_stop_ids,
+                &destination_stop_ids,
+                parse_date_time(&departure_at)?,
+                max_transfers.unwrap_or(2) as usize,
+            )
+            .map_err(|error| Error::from_reason(error.to_string()))?;
+        Ok(matrix
+            .arrival_times()
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|arrival_at| arrival_at.map(format_date_time))
+                    .collect()
+            })
+            .collect())
+    }
+}
+
+// napi registers all the methods of an impl block, including those removed by cfg.
+#[cfg(feature = "serde")]
+#[napi]
+impl JsHrdf {
+    /// Decodes data encoded by Hrdf::to_bytes.
+    #[napi(factory)]
+    pub fn from_bytes(data: napi::bindgen_prelude::Buffer) -> Result<Self> {
+        let hrdf =
+            Hrdf::from_bytes(&data).map_err(|error| Error::from_reason(error.to_string()))?;
+        Ok(Self { hrdf })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- JsStop
+// ------------------------------------------------------------------------------------------------
+
+#[napi(object, js_name = "Stop")]
+pub struct JsStop {
+    pub id: i32,
+    pub name: String,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+impl JsStop {
+    fn new(stop: &Stop) -> Self {
+        let coordinates = stop.wgs84_position();
+        Self {
+            id: stop.id(),
+            name: stop.name().to_string(),
+            latitude: coordinates.and_then(|coordinates| coordinates.latitude()),
+            longitude: coordinates.and_then(|coordinates| coordinates.longitude()),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- JsDeparture
+// ------------------------------------------------------------------------------------------------
+
+#[napi(object, js_name = "Departure")]
+pub struct JsDeparture {
+    pub journey_id: i32,
+    pub stop_id: i32,
+    pub platform: Option<String>,
+    pub departure_at: String,
+    pub destination_stop_id: Option<i32>,
+    pub line_name: Option<String>,
+    pub attributes: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn load() -> JsHrdf {
+        JsHrdf::from_directory(
+            "V_5_40_41_2_0_7".to_string(),
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/mini").to_string(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn unknown_version() {
+        let error = JsHrdf::from_directory("V_1".to_string(), "tests/data/mini".to_string())
+            .err()
+            .unwrap();
+        assert_eq!(error.reason, "Unknown version: V_1");
+    }
+
+    #[test]
+    fn stops_with_wgs84_positions() {
+        let stops = load().search_stops("aarstadt nord".to_string(), None);
+        assert_eq!(stops.len(), 1);
+        assert_eq!(stops[0].id, 8500102);
+        assert_eq!(stops[0].name, "Aarstadt Nord");
+        assert!(stops[0].latitude.is_some() && stops[0].longitude.is_some());
+    }
+
+    #[test]
+    fn station_board_departures() {
+        let hrdf = load();
+        let departures = hrdf
+            .station_board(
+                8500101,
+                "2025-12-15T06:00:00".to_string(),
+                "2025-12-15T07:30:00".to_string(),
+                None,
+            )
+            .unwrap();
+        let departure_times: Vec<_> = departures
+            .iter()
+            .map(|departure| departure.departure_at.as_str())
+            .collect();
+        assert_eq!(
+            departure_times,
+            vec![
+                "2025-12-15T06:00:00",
+                "2025-12-15T07:00:00",
+                "2025-12-15T07:15:00"
+            ]
+        );
+
+        let error = hrdf
+            .station_board(8500101, "15.12.2025".to_string(), "".to_string(), None)
+            .err()
+            .unwrap();
+        assert_eq!(error.reason, "Invalid date and time: 15.12.2025");
+    }
+
+    #[test]
+    fn earliest_arrival_matrix() {
+        let hrdf = load();
+        let matrix = hrdf
+            .earliest_arrival_matrix(
+                vec![8500101],
+                vec![8500104, 8500105],
+                "2025-12-15T06:00:00".to_string(),
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            matrix,
+            vec![vec![
+                Some("2025-12-15T06:20:00".to_string()),
+                Some("2025-12-15T07:27:00".to_string())
+            ]]
+        );
+
+        // Outside of the timetable period.
+        assert!(
+            hrdf.earliest_arrival_matrix(
+                vec![8500101],
+                vec![8500104],
+                "2030-01-01T06:00:00".to_string(),
+                None
+            )
+            .is_err()
+        );
+    }
+}
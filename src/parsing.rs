@@ -10,13 +10,16 @@ mod holiday_parser;
 mod information_text_parser;
 mod journey_parser;
 mod line_parser;
+mod options;
 mod platform_parser;
+mod report;
 mod stop_connection_parser;
 mod stop_parser;
 mod through_service_parser;
 mod timetable_metadata_parser;
 mod transport_company_parser;
 mod transport_type_parser;
+mod visitor;
 
 pub use attribute_parser::parse as load_attributes;
 pub use bit_field_parser::parse as load_bit_fields;
@@ -24,19 +27,25 @@ pub use direction_parser::parse as load_directions;
 pub use exchange_administration_parser::parse as load_exchange_times_administration;
 pub use exchange_journey_parser::parse as load_exchange_times_journey;
 pub use exchange_line_parser::parse as load_exchange_times_line;
+pub(crate) use helpers::normalize_direction_code;
 pub use holiday_parser::parse as load_holidays;
 pub use information_text_parser::parse as load_information_texts;
 pub use journey_parser::parse as load_journeys;
 pub use line_parser::parse as load_lines;
+pub use options::{
+    DuplicatePlatformPolicy, FileRules, ParsingMode, ParsingOptions, RouteTimeCheck,
+};
 pub use platform_parser::parse as load_platforms;
+pub use report::{ParseIssue, ParseReport};
 pub use stop_connection_parser::parse as load_stop_connections;
 pub use stop_parser::parse as load_stops;
 pub use through_service_parser::parse as load_through_service;
 pub use timetable_metadata_parser::parse as load_timetable_metadata;
 pub use transport_company_parser::parse as load_transport_companies;
 pub use transport_type_parser::parse as load_transport_types;
+pub use visitor::{Visitor, visit};
 
-#[cfg(test)]
+#[cfg(all(test, feature = "serde"))]
 mod tests {
     use std::error::Error;
 
@@ -61,7 +61,8 @@ use crate::{
     error::{HResult, HrdfError},
     models::{Attribute, Language, Model},
     parsing::{
-        error::{PResult, ParsingError},
+        ParsingOptions,
+        error::{LineErrors, PResult, ParsingError},
         helpers::{
             i16_from_n_digits_parser, read_lines, string_from_n_chars_parser,
             string_till_eol_parser,
@@ -198,11 +199,15 @@ fn parse_line(
     Ok(())
 }
 
-pub fn parse(path: &Path) -> HResult<AttributeAndTypeConverter> {
+pub fn parse(
+    path: &Path,
+    options: &ParsingOptions,
+    errors: &mut LineErrors,
+) -> HResult<AttributeAndTypeConverter> {
     log::info!("Parsing ATTRIBUT...");
 
     let file = path.join("ATTRIBUT");
-    let lines = read_lines(&file, 0)?;
+    let lines = read_lines(&file, options)?;
 
     let auto_increment = AutoIncrement::new();
     let mut data = FxHashMap::default();
@@ -214,7 +219,7 @@ pub fn parse(path: &Path) -> HResult<AttributeAndTypeConverter> {
         .enumerate()
         .filter(|(_, line)| !line.trim().is_empty())
         .try_for_each(|(line_number, line)| {
-            parse_line(
+            let result = parse_line(
                 &line,
                 &mut data,
                 &mut pk_type_converter,
@@ -226,7 +231,8 @@ pub fn parse(path: &Path) -> HResult<AttributeAndTypeConverter> {
                 file: String::from(file.to_string_lossy()),
                 line,
                 line_number,
-            })
+            });
+            errors.handle(result).map(|_| ())
         })?;
 
     Ok((ResourceStorage::new(data), pk_type_converter))
@@ -236,6 +242,7 @@ pub fn parse(path: &Path) -> HResult<AttributeAndTypeConverter> {
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
+    #[cfg(feature = "serde")]
     use crate::parsing::tests::get_json_values;
     use pretty_assertions::assert_eq;
 
@@ -341,6 +348,7 @@ mod tests {
         assert_eq!("fre", language);
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn muti_line_parsing() {
         let rows = vec![
@@ -39,7 +39,8 @@ use crate::{
     error::{HResult, HrdfError},
     models::BitField,
     parsing::{
-        error::{PResult, ParsingError},
+        ParsingOptions,
+        error::{LineErrors, PResult, ParsingError},
         helpers::{i32_from_n_digits_parser, read_lines},
     },
     storage::ResourceStorage,
@@ -66,21 +67,26 @@ fn parse_line(line: &str) -> PResult<(i32, BitField)> {
     Ok((id, BitField::new(id, bits)))
 }
 
-pub fn parse(path: &Path) -> HResult<ResourceStorage<BitField>> {
+pub fn parse(
+    path: &Path,
+    options: &ParsingOptions,
+    errors: &mut LineErrors,
+) -> HResult<ResourceStorage<BitField>> {
     log::info!("Parsing BITFELD...");
     let file = path.join("BITFELD");
-    let lines = read_lines(&file, 0)?;
+    let lines = read_lines(&file, options)?;
     let bitfields = lines
         .into_iter()
         .enumerate()
         .filter(|(_, line)| !line.trim().is_empty())
-        .map(|(line_number, line)| {
-            parse_line(&line).map_err(|e| HrdfError::Parsing {
+        .filter_map(|(line_number, line)| {
+            let result = parse_line(&line).map_err(|e| HrdfError::Parsing {
                 error: e,
                 file: String::from(file.to_string_lossy()),
                 line,
                 line_number,
-            })
+            });
+            errors.handle(result).transpose()
         })
         .collect::<HResult<FxHashMap<i32, BitField>>>()?;
     Ok(ResourceStorage::new(bitfields))
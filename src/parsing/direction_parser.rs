@@ -20,7 +20,8 @@ use crate::{
     error::{HResult, HrdfError},
     models::Direction,
     parsing::{
-        error::PResult,
+        ParsingOptions,
+        error::{LineErrors, PResult},
         helpers::{direction_parser, read_lines, string_till_eol_parser},
     },
     storage::ResourceStorage,
@@ -48,23 +49,29 @@ fn parse_line(
     Ok((id, Direction::new(id, name)))
 }
 
-pub fn parse(path: &Path) -> HResult<DirectionAndTypeConverter> {
+pub fn parse(
+    path: &Path,
+    options: &ParsingOptions,
+    errors: &mut LineErrors,
+) -> HResult<DirectionAndTypeConverter> {
     log::info!("Parsing RICHTUNG...");
 
     let file = path.join("RICHTUNG");
-    let lines = read_lines(&file, 0)?;
+    let lines = read_lines(&file, options)?;
     let mut pk_type_converter = FxHashMap::default();
     let directions = lines
         .into_iter()
         .enumerate()
         .filter(|(_, line)| !line.trim().is_empty())
-        .map(|(line_number, line)| {
-            parse_line(&line, &mut pk_type_converter).map_err(|e| HrdfError::Parsing {
-                error: e,
-                file: String::from(file.to_string_lossy()),
-                line,
-                line_number,
-            })
+        .filter_map(|(line_number, line)| {
+            let result =
+                parse_line(&line, &mut pk_type_converter).map_err(|e| HrdfError::Parsing {
+                    error: e,
+                    file: String::from(file.to_string_lossy()),
+                    line,
+                    line_number,
+                });
+            errors.handle(result).transpose()
         })
         .collect::<HResult<FxHashMap<i32, Direction>>>()?;
     Ok((ResourceStorage::new(directions), pk_type_converter))
@@ -74,6 +81,7 @@ pub fn parse(path: &Path) -> HResult<DirectionAndTypeConverter> {
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
+    #[cfg(feature = "serde")]
     use crate::parsing::tests::get_json_values;
     use pretty_assertions::assert_eq;
 
@@ -98,6 +106,7 @@ mod tests {
         assert_eq!("Hégenheim - Collège des Trois Pays", name);
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn type_converter_v207() {
         let rows = vec![
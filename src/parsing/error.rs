@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::error::{HResult, HrdfError};
+
 pub type PResult<T> = Result<T, ParsingError>;
 
 #[derive(Debug, Error)]
@@ -24,6 +26,20 @@ pub enum ParsingError {
     ParseDate(#[from] chrono::ParseError),
     #[error("Unable to build NaiveTime from {0} hours, {1} minutes, {2} seconds")]
     UnableToBuildTime(u32, u32, u32),
+    #[error("Invalid time value {0}")]
+    InvalidTimeValue(i32),
+    #[error("Invalid time {value} of stop {stop_id}: {source}")]
+    InvalidRouteTime {
+        stop_id: i32,
+        value: i32,
+        source: Box<ParsingError>,
+    },
+    #[error("Duplicate transport type designation: {0}")]
+    DuplicateTransportType(String),
+    #[error("Duplicate platform (stop_id, index): ({0}, {1})")]
+    DuplicatePlatform(i32, i32),
+    #[error("Unknown language: {0}")]
+    UnknownLanguage(String),
 }
 
 impl From<nom::Err<nom::error::Error<&str>>> for ParsingError {
@@ -37,3 +53,47 @@ impl From<&str> for ParsingError {
         ParsingError::Unknown(value.to_string())
     }
 }
+
+// ------------------------------------------------------------------------------------------------
+// --- LineErrors
+// ------------------------------------------------------------------------------------------------
+
+/// The parsing errors of the lines, which either stop the parsing at the first one or are
+/// collected over all the files (see ParsingOptions::collect_errors).
+#[derive(Debug, Default)]
+pub(crate) struct LineErrors {
+    collect: bool,
+    errors: Vec<HrdfError>,
+}
+
+impl LineErrors {
+    pub(crate) fn new(collect: bool) -> Self {
+        Self {
+            collect,
+            errors: Vec::new(),
+        }
+    }
+
+    // Functions
+
+    /// Returns the result of a line. If the errors are collected, the parsing error of the line
+    /// is kept and Ok(None) is returned, the line is then skipped.
+    pub(crate) fn handle<T>(&mut self, result: HResult<T>) -> HResult<Option<T>> {
+        match result {
+            Err(error @ HrdfError::Parsing { .. }) if self.collect => {
+                self.errors.push(error);
+                Ok(None)
+            }
+            result => result.map(Some),
+        }
+    }
+
+    /// Returns all the collected errors at once.
+    pub(crate) fn into_result(self) -> HResult<()> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(HrdfError::ParsingErrors(self.errors))
+        }
+    }
+}
@@ -24,16 +24,28 @@ use std::path::Path;
 /// 1 file(s).
 /// File(s) read by the parser:
 /// UMSTEIGV
-use nom::{IResult, Parser, character::char, sequence::preceded};
+use nom::{
+    IResult, Parser,
+    branch::alt,
+    bytes::tag,
+    character::{
+        char,
+        complete::{i16, space0, space1},
+    },
+    combinator::{eof, map},
+    sequence::{preceded, terminated},
+};
 use rustc_hash::FxHashMap;
 
 use crate::{
     error::{HResult, HrdfError},
     models::ExchangeTimeAdministration,
     parsing::{
-        error::PResult,
+        ParsingOptions,
+        error::{LineErrors, PResult},
         helpers::{
-            i16_from_n_digits_parser, optional_i32_from_n_digits_parser, read_lines,
+            end_of_field_parser, field_parser, i16_from_n_digits_parser,
+            optional_i32_from_n_digits_parser, optional_stop_id_field_parser, read_lines,
             string_from_n_chars_parser,
         },
     },
@@ -41,9 +53,13 @@ use crate::{
     utils::AutoIncrement,
 };
 
-fn parse_exchange_administration_row(
-    input: &str,
-) -> IResult<&str, (Option<i32>, String, String, i16)> {
+type ExchangeTimeAdministrationRow = (Option<i32>, String, String, i16);
+
+fn parse_exchange_administration_row(input: &str) -> IResult<&str, ExchangeTimeAdministrationRow> {
+    alt((parse_fixed_width_row, parse_whitespace_separated_row)).parse(input)
+}
+
+fn parse_fixed_width_row(input: &str) -> IResult<&str, ExchangeTimeAdministrationRow> {
     let (res, (stop_id, administration_1, administration_2, duration)) = (
         optional_i32_from_n_digits_parser(7),
         preceded(char(' '), string_from_n_chars_parser(6)),
@@ -54,6 +70,42 @@ fn parse_exchange_administration_row(
     Ok((res, (stop_id, administration_1, administration_2, duration)))
 }
 
+fn parse_whitespace_separated_row(input: &str) -> IResult<&str, ExchangeTimeAdministrationRow> {
+    let administrations_and_duration = || {
+        (
+            terminated(field_parser, space1),
+            terminated(field_parser, space1),
+            terminated(i16, end_of_field_parser),
+        )
+    };
+    // Without the stop number, the row applies to all the stops and has no name of stop, only a
+    // comment can follow.
+    preceded(
+        space0,
+        alt((
+            map(
+                (
+                    terminated(optional_stop_id_field_parser, space1),
+                    administrations_and_duration(),
+                ),
+                |(stop_id, (administration_1, administration_2, duration))| {
+                    (stop_id, administration_1, administration_2, duration)
+                },
+            ),
+            map(
+                terminated(
+                    administrations_and_duration(),
+                    (space0, alt((eof, tag("%")))),
+                ),
+                |(administration_1, administration_2, duration)| {
+                    (None, administration_1, administration_2, duration)
+                },
+            ),
+        )),
+    )
+    .parse(input)
+}
+
 fn parse_line(
     line: &str,
     auto_increment: &AutoIncrement,
@@ -68,23 +120,35 @@ fn parse_line(
     ))
 }
 
-pub fn parse(path: &Path) -> HResult<ResourceStorage<ExchangeTimeAdministration>> {
+pub fn parse(
+    path: &Path,
+    options: &ParsingOptions,
+    errors: &mut LineErrors,
+) -> HResult<ResourceStorage<ExchangeTimeAdministration>> {
     log::info!("Parsing UMSTEIGV...");
 
     let file = path.join("UMSTEIGV");
-    let lines = read_lines(&file, 0)?;
+    let lines = read_lines(&file, options)?;
     let auto_increment = AutoIncrement::new();
     let exchanges = lines
         .into_iter()
         .enumerate()
         .filter(|(_, line)| !line.trim().is_empty())
-        .map(|(line_number, line)| {
-            parse_line(&line, &auto_increment).map_err(|e| HrdfError::Parsing {
+        .filter_map(|(line_number, line)| {
+            let result = parse_line(&line, &auto_increment).map_err(|e| HrdfError::Parsing {
                 error: e,
                 file: String::from(file.to_string_lossy()),
                 line,
                 line_number,
-            })
+            });
+            errors.handle(result).transpose()
+        })
+        .filter(|result| match result {
+            Ok((_, exchange)) => {
+                options.includes_administration(exchange.administration_1())
+                    && options.includes_administration(exchange.administration_2())
+            }
+            Err(_) => true,
         })
         .collect::<HResult<FxHashMap<i32, ExchangeTimeAdministration>>>()?;
 
@@ -95,6 +159,7 @@ pub fn parse(path: &Path) -> HResult<ResourceStorage<ExchangeTimeAdministration>
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
+    #[cfg(feature = "serde")]
     use crate::parsing::tests::get_json_values;
     use pretty_assertions::assert_eq;
 
@@ -130,6 +195,37 @@ mod tests {
         assert_eq!(2, duration);
     }
 
+    #[test]
+    fn row_parser_with_variable_whitespace() {
+        let line = "8101236\t81____\t007000\t02\tFeldkirch";
+        let (_, (stop_id, administration_1, administration_2, duration)) =
+            parse_exchange_administration_row(line).unwrap();
+        assert_eq!(Some(8101236), stop_id);
+        assert_eq!("81____", &administration_1);
+        assert_eq!("007000", &administration_2);
+        assert_eq!(2, duration);
+        let line = "8501008 85000 11 10 Genève";
+        let (_, (stop_id, administration_1, administration_2, duration)) =
+            parse_exchange_administration_row(line).unwrap();
+        assert_eq!(Some(8501008), stop_id);
+        assert_eq!("85000", &administration_1);
+        assert_eq!("11", &administration_2);
+        assert_eq!(10, duration);
+        let line = "000793 000873 2";
+        let (_, (stop_id, administration_1, administration_2, duration)) =
+            parse_exchange_administration_row(line).unwrap();
+        assert_eq!(None, stop_id);
+        assert_eq!("000793", &administration_1);
+        assert_eq!("000873", &administration_2);
+        assert_eq!(2, duration);
+        let (_, (stop_id, _, _, duration)) =
+            parse_exchange_administration_row("000793 000873 2 % Alle Haltestellen").unwrap();
+        assert_eq!((None, 2), (stop_id, duration));
+        // The leftover of the duration is not taken for the name of the stop.
+        assert!(parse_exchange_administration_row("8500065 000037 000037 0X Ettingen").is_err());
+    }
+
+    #[cfg(feature = "serde")]
     #[test]
     fn multiple_row_parsing() {
         let lines = vec![
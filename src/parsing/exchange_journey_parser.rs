@@ -26,7 +26,16 @@ use std::path::Path;
 /// 1 file(s).
 /// File(s) read by the parser:
 /// UMSTEIGZ
-use nom::{IResult, Parser, character::char, combinator::map, sequence::preceded};
+use nom::{
+    IResult, Parser,
+    branch::alt,
+    character::{
+        char,
+        complete::{i16, i32, space0, space1},
+    },
+    combinator::{map, map_res, opt},
+    sequence::{preceded, terminated},
+};
 use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::{
@@ -34,10 +43,11 @@ use crate::{
     error::{HResult, HrdfError},
     models::ExchangeTimeJourney,
     parsing::{
-        error::{PResult, ParsingError},
+        ParsingOptions,
+        error::{LineErrors, PResult, ParsingError},
         helpers::{
-            i16_from_n_digits_parser, i32_from_n_digits_parser, optional_i32_from_n_digits_parser,
-            read_lines, string_from_n_chars_parser,
+            end_of_field_parser, field_parser, i16_from_n_digits_parser, i32_from_n_digits_parser,
+            optional_i32_from_n_digits_parser, read_lines, string_from_n_chars_parser,
         },
     },
     storage::ResourceStorage,
@@ -47,6 +57,10 @@ use crate::{
 type ExchangeTimeJourneyLine = (i32, i32, String, i32, String, i16, bool, Option<i32>);
 
 fn parse_exchange_journey_row(input: &str) -> IResult<&str, ExchangeTimeJourneyLine> {
+    alt((parse_fixed_width_row, parse_whitespace_separated_row)).parse(input)
+}
+
+fn parse_fixed_width_row(input: &str) -> IResult<&str, ExchangeTimeJourneyLine> {
     // TODO: I haven't seen an is_guaranteed field in the doc. Check if this makes sense.
     // It is present in UMSTEIGL. Maybe a copy/paste leftover
     //
@@ -89,6 +103,30 @@ fn parse_exchange_journey_row(input: &str) -> IResult<&str, ExchangeTimeJourneyL
     ))
 }
 
+fn parse_whitespace_separated_row(input: &str) -> IResult<&str, ExchangeTimeJourneyLine> {
+    preceded(
+        space0,
+        (
+            i32,
+            preceded(space1, i32),
+            preceded(space1, field_parser),
+            preceded(space1, i32),
+            preceded(space1, field_parser),
+            preceded(space1, i16),
+            terminated(
+                map(opt(char('!')), |guaranteed| guaranteed.is_some()),
+                end_of_field_parser,
+            ),
+            // The bit field is optional, the name of the stop can follow directly.
+            opt(preceded(
+                space1,
+                map_res(field_parser, |bit_field_id| bit_field_id.parse::<i32>()),
+            )),
+        ),
+    )
+    .parse(input)
+}
+
 fn parse_line(
     line: &str,
     auto_increment: &AutoIncrement,
@@ -134,27 +172,42 @@ fn parse_line(
 pub fn parse(
     path: &Path,
     journeys_pk_type_converter: &FxHashSet<JourneyId>,
+    options: &ParsingOptions,
+    errors: &mut LineErrors,
 ) -> HResult<ResourceStorage<ExchangeTimeJourney>> {
     log::info!("Parsing UMSTEIGZ...");
 
     let file = path.join("UMSTEIGZ");
-    let lines = read_lines(&file, 0)?;
+    let lines = read_lines(&file, options)?;
     let auto_increment = AutoIncrement::new();
-    let exchanges = lines
-        .into_iter()
-        .enumerate()
-        .filter(|(_, line)| !line.trim().is_empty())
-        .map(|(line_number, line)| {
-            parse_line(&line, &auto_increment, journeys_pk_type_converter).map_err(|e| {
-                HrdfError::Parsing {
-                    error: e,
-                    file: String::from(file.to_string_lossy()),
-                    line,
-                    line_number,
-                }
+    let exchanges =
+        lines
+            .into_iter()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .filter(|(_, line)| {
+                // The journeys of the administrations which are not parsed are unknown.
+                options.administrations().is_none()
+                    || match parse_exchange_journey_row(line) {
+                        Ok((_, (_, _, administration_1, _, administration_2, ..))) => {
+                            options.includes_administration(&administration_1)
+                                && options.includes_administration(&administration_2)
+                        }
+                        // The error is reported by parse_line.
+                        Err(_) => true,
+                    }
+            })
+            .filter_map(|(line_number, line)| {
+                let result = parse_line(&line, &auto_increment, journeys_pk_type_converter)
+                    .map_err(|e| HrdfError::Parsing {
+                        error: e,
+                        file: String::from(file.to_string_lossy()),
+                        line,
+                        line_number,
+                    });
+                errors.handle(result).transpose()
             })
-        })
-        .collect::<HResult<FxHashMap<i32, ExchangeTimeJourney>>>()?;
+            .collect::<HResult<FxHashMap<i32, ExchangeTimeJourney>>>()?;
 
     Ok(ResourceStorage::new(exchanges))
 }
@@ -163,6 +216,7 @@ pub fn parse(
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
+    #[cfg(feature = "serde")]
     use crate::parsing::tests::get_json_values;
     use pretty_assertions::assert_eq;
 
@@ -236,6 +290,44 @@ mod tests {
         assert_eq!(Some(19), bit_field_id);
     }
 
+    #[test]
+    fn row_parser_with_variable_whitespace() {
+        let line = "8501008\t023057\t000011\t001671\t000011\t002\t000010\tGenève";
+        let (_, row) = parse_exchange_journey_row(line).unwrap();
+        assert_eq!(
+            (
+                8501008,
+                23057,
+                "000011".to_string(),
+                1671,
+                "000011".to_string(),
+                2,
+                false,
+                Some(10)
+            ),
+            row
+        );
+        let line = "8501120 1929 11 24256 11 999! Lausanne";
+        let (_, row) = parse_exchange_journey_row(line).unwrap();
+        assert_eq!(
+            (
+                8501120,
+                1929,
+                "11".to_string(),
+                24256,
+                "11".to_string(),
+                999,
+                true,
+                None
+            ),
+            row
+        );
+        // The leftover of the duration is not taken for the name of the stop.
+        assert!(parse_exchange_journey_row("8501120 1929 11 24256 11 9X Lausanne").is_err());
+        assert!(parse_exchange_journey_row("8501120 1929 11 24256 11 9!X Lausanne").is_err());
+    }
+
+    #[cfg(feature = "serde")]
     #[test]
     fn multiple_row_parsing() {
         let lines = vec![
@@ -34,16 +34,27 @@
 /// UMSTEIGL
 use std::{path::Path, str::FromStr};
 
-use nom::{IResult, Parser, character::char, combinator::map, sequence::preceded};
+use nom::{
+    IResult, Parser,
+    branch::alt,
+    character::{
+        char,
+        complete::{i16, space0, space1},
+    },
+    combinator::{map, opt},
+    sequence::{preceded, terminated},
+};
 use rustc_hash::FxHashMap;
 
 use crate::{
     error::{HResult, HrdfError},
     models::{DirectionType, ExchangeTimeLine, LineInfo},
     parsing::{
-        error::PResult,
+        ParsingOptions,
+        error::{LineErrors, PResult},
         helpers::{
-            i16_from_n_digits_parser, optional_i32_from_n_digits_parser, read_lines,
+            end_of_field_parser, field_parser, i16_from_n_digits_parser,
+            optional_i32_from_n_digits_parser, optional_stop_id_field_parser, read_lines,
             string_from_n_chars_parser,
         },
     },
@@ -66,6 +77,10 @@ type ExchangeTimeLineRow = (
 );
 
 fn parse_exchange_line_row(input: &str) -> IResult<&str, ExchangeTimeLineRow> {
+    alt((parse_fixed_width_row, parse_whitespace_separated_row)).parse(input)
+}
+
+fn parse_fixed_width_row(input: &str) -> IResult<&str, ExchangeTimeLineRow> {
     // TODO: I haven't seen an is_guaranteed field in the doc. Check if this makes sense.
     // It is present in UMSTEIGL. Maybe a copy/paste leftover
     //
@@ -117,6 +132,68 @@ fn parse_exchange_line_row(input: &str) -> IResult<&str, ExchangeTimeLineRow> {
     ))
 }
 
+fn parse_whitespace_separated_row(input: &str) -> IResult<&str, ExchangeTimeLineRow> {
+    let columns = || {
+        (
+            terminated(field_parser, space1),
+            terminated(field_parser, space1),
+            terminated(field_parser, space1),
+            terminated(field_parser, space1),
+            terminated(field_parser, space1),
+            terminated(field_parser, space1),
+            terminated(field_parser, space1),
+            terminated(field_parser, space1),
+            i16,
+            terminated(
+                map(opt(char('!')), |guaranteed| guaranteed.is_some()),
+                end_of_field_parser,
+            ),
+        )
+    };
+    // Without the stop number, the row applies to all the stops.
+    let (
+        res,
+        (
+            stop_id,
+            (
+                administration_1,
+                transport_type_1,
+                line_id_1,
+                direction_1,
+                administration_2,
+                transport_type_2,
+                line_id_2,
+                direction_2,
+                duration,
+                is_guaranteed,
+            ),
+        ),
+    ) = preceded(
+        space0,
+        alt((
+            (terminated(optional_stop_id_field_parser, space1), columns()),
+            map(columns(), |columns| (None, columns)),
+        )),
+    )
+    .parse(input)?;
+    Ok((
+        res,
+        (
+            stop_id,
+            administration_1,
+            transport_type_1,
+            line_id_1,
+            direction_1,
+            administration_2,
+            transport_type_2,
+            line_id_2,
+            direction_2,
+            duration,
+            is_guaranteed,
+        ),
+    ))
+}
+
 fn parse_line(
     line: &str,
     auto_increment: &AutoIncrement,
@@ -139,9 +216,21 @@ fn parse_line(
         ),
     ) = parse_exchange_line_row(line)?;
 
-    let transport_type_id_1 = *transport_types_pk_type_converter
-        .get(&transport_type_id_1)
-        .ok_or("Unknown legacy ID for transport_type_1 {transport_type_id_1}")?;
+    let administration_1 = if administration_1 == "*" {
+        None
+    } else {
+        Some(administration_1)
+    };
+
+    let transport_type_id_1 = if transport_type_id_1 == "*" {
+        None
+    } else {
+        Some(
+            *transport_types_pk_type_converter
+                .get(&transport_type_id_1)
+                .ok_or("Unknown legacy ID for transport_type_1 {transport_type_id_1}")?,
+        )
+    };
 
     let line_id_1 = if line_id_1 == "*" {
         None
@@ -155,9 +244,21 @@ fn parse_line(
         Some(DirectionType::from_str(&direction_1)?)
     };
 
-    let transport_type_id_2 = *transport_types_pk_type_converter
-        .get(&transport_type_id_2)
-        .ok_or("Unknown legacy ID for transport_type_id_2 {transport_type_id_2}")?;
+    let administration_2 = if administration_2 == "*" {
+        None
+    } else {
+        Some(administration_2)
+    };
+
+    let transport_type_id_2 = if transport_type_id_2 == "*" {
+        None
+    } else {
+        Some(
+            *transport_types_pk_type_converter
+                .get(&transport_type_id_2)
+                .ok_or("Unknown legacy ID for transport_type_id_2 {transport_type_id_2}")?,
+        )
+    };
 
     let line_id_2 = if line_id_2 == "*" {
         None
@@ -195,24 +296,33 @@ fn parse_line(
 pub fn parse(
     path: &Path,
     transport_types_pk_type_converter: &FxHashMap<String, i32>,
+    options: &ParsingOptions,
+    errors: &mut LineErrors,
 ) -> HResult<ResourceStorage<ExchangeTimeLine>> {
     log::info!("Parsing UMSTEIGL...");
     let file = path.join("UMSTEIGL");
-    let lines = read_lines(&file, 0)?;
+    let lines = read_lines(&file, options)?;
     let auto_increment = AutoIncrement::new();
     let exchanges = lines
         .into_iter()
         .enumerate()
         .filter(|(_, line)| !line.trim().is_empty())
-        .map(|(line_number, line)| {
-            parse_line(&line, &auto_increment, transport_types_pk_type_converter).map_err(|e| {
-                HrdfError::Parsing {
+        .filter_map(|(line_number, line)| {
+            let result = parse_line(&line, &auto_increment, transport_types_pk_type_converter)
+                .map_err(|e| HrdfError::Parsing {
                     error: e,
                     file: String::from(file.to_string_lossy()),
                     line,
                     line_number,
-                }
-            })
+                });
+            errors.handle(result).transpose()
+        })
+        .filter(|result| match result {
+            Ok((_, exchange)) => [exchange.line_1(), exchange.line_2()]
+                .iter()
+                .filter_map(|line_info| line_info.administration())
+                .all(|administration| options.includes_administration(administration)),
+            Err(_) => true,
         })
         .collect::<HResult<FxHashMap<_, _>>>()?;
 
@@ -223,6 +333,7 @@ pub fn parse(
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
+    #[cfg(feature = "serde")]
     use crate::parsing::tests::get_json_values;
     use pretty_assertions::assert_eq;
 
@@ -354,6 +465,72 @@ mod tests {
         assert!(!is_guaranteed);
     }
 
+    #[test]
+    fn row_parser_with_variable_whitespace() {
+        let line = "8301113\t000011\tS\t*\t*\t007000\tB\t*\t*\t003\tLuino (I)";
+        let (_, row) = parse_exchange_line_row(line).unwrap();
+        assert_eq!(Some(8301113), row.0);
+        assert_eq!(
+            ["000011", "S", "*", "*", "007000", "B", "*", "*"],
+            [
+                row.1.as_str(),
+                row.2.as_str(),
+                row.3.as_str(),
+                row.4.as_str(),
+                row.5.as_str(),
+                row.6.as_str(),
+                row.7.as_str(),
+                row.8.as_str()
+            ]
+        );
+        assert_eq!(3, row.9);
+        assert!(!row.10);
+
+        let line = "sbg034 B 7339 H sbg034 TX 7341 H 000! Waldshut, Busbahnhof";
+        let (_, row) = parse_exchange_line_row(line).unwrap();
+        assert_eq!(None, row.0);
+        assert_eq!(
+            ["sbg034", "B", "7339", "H", "sbg034", "TX", "7341", "H"],
+            [
+                row.1.as_str(),
+                row.2.as_str(),
+                row.3.as_str(),
+                row.4.as_str(),
+                row.5.as_str(),
+                row.6.as_str(),
+                row.7.as_str(),
+                row.8.as_str()
+            ]
+        );
+        assert_eq!(0, row.9);
+        assert!(row.10);
+
+        // The leftover of the duration is not taken for the name of the stop.
+        let line = "8301113\t000011\tS\t*\t*\t007000\tB\t*\t*\t3X\tLuino (I)";
+        assert!(parse_exchange_line_row(line).is_err());
+    }
+
+    #[test]
+    fn wildcards() {
+        let mut transport_types_pk_type_converter = FxHashMap::default();
+        transport_types_pk_type_converter.insert("TER".to_string(), 1);
+        let line = "8500010 *      *   *        * 000011 TER *        * 010  Basel SBB";
+        let (_, exchange) = parse_line(
+            line,
+            &AutoIncrement::new(),
+            &transport_types_pk_type_converter,
+        )
+        .unwrap();
+
+        assert_eq!(exchange.line_1().administration(), None);
+        assert_eq!(exchange.line_1().transport_type_id(), None);
+        assert_eq!(exchange.line_1().line_id(), None);
+        assert_eq!(exchange.line_2().administration(), Some("000011"));
+        assert_eq!(exchange.line_2().transport_type_id(), Some(1));
+        assert_eq!(exchange.duration(), 10);
+    }
+
+    #[cfg(feature = "serde")]
     #[test]
     fn multiline_parser() {
         let rows = vec![
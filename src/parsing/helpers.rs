@@ -1,6 +1,6 @@
 use std::{
     fs::File,
-    io::{self, Read, Seek},
+    io::{self, BufRead, Read},
     path::Path,
 };
 
@@ -9,12 +9,24 @@ use std::{
 use nom::{
     IResult, Parser,
     branch::alt,
-    bytes::{complete::take_till, tag},
-    character::{anychar, one_of},
-    combinator::{map, map_res, opt},
+    bytes::{
+        complete::{take_till, take_till1, take_while1},
+        tag,
+    },
+    character::{
+        anychar,
+        complete::{i32, space1},
+        one_of,
+    },
+    combinator::{eof, map, map_res, opt, peek, value},
     multi::count,
 };
 
+use crate::{
+    error::{HResult, HrdfError},
+    parsing::{ParsingOptions, error::PResult},
+};
+
 pub(crate) fn is_newline(c: char) -> bool {
     c == '\n' || c == '\r'
 }
@@ -84,20 +96,98 @@ pub(crate) fn optional_i32_from_n_digits_parser(
     }
 }
 
+/// Same as optional_i32_from_n_digits_parser, but fails if the n characters are neither blank
+/// nor a number.
+pub(crate) fn blank_or_i32_from_n_digits_parser(
+    n_digits: usize,
+) -> impl FnMut(&str) -> IResult<&str, Option<i32>> {
+    move |input: &str| {
+        alt((
+            exactly_n_spaces_or_at_parser(n_digits),
+            map(i32_from_n_digits_parser(n_digits), Some),
+        ))
+        .parse(input)
+    }
+}
+
+/// A field of a row whose columns are separated by any number of spaces or tabs instead of being
+/// aligned. Some producers write the UMSTEIGV, UMSTEIGL and UMSTEIGZ files this way, their rows
+/// are read field by field when the fixed-width columns do not match.
+pub(crate) fn field_parser(input: &str) -> IResult<&str, String> {
+    map(take_till1(char::is_whitespace), String::from).parse(input)
+}
+
+/// The end of a numeric field of a row whose columns are not aligned: a separator or the end of
+/// the row must follow, so that e.g. "0X" is not read as 0.
+pub(crate) fn end_of_field_parser(input: &str) -> IResult<&str, ()> {
+    value((), peek(alt((space1, eof)))).parse(input)
+}
+
+/// The stop number field of a row whose columns are not aligned, None for all stops (@).
+pub(crate) fn optional_stop_id_field_parser(input: &str) -> IResult<&str, Option<i32>> {
+    alt((map(i32, Some), value(None, take_while1(|c: char| c == '@')))).parse(input)
+}
+
 pub(crate) fn direction_parser(input: &str) -> IResult<&str, (String, i32)> {
     (map(tag("R"), String::from), i32_from_n_digits_parser(6)).parse(input)
 }
 
-pub(crate) fn read_lines(path: &Path, bytes_offset: u64) -> io::Result<Vec<String>> {
-    let mut file = File::open(path)?;
-    file.seek(io::SeekFrom::Start(bytes_offset))?;
+/// Normalizes a direction code of an *R row to the keys of RICHTUNG (e.g. R63). The numeric
+/// codes are accepted padded or without the R prefix (R000063, R 63, 0000063), the other codes
+/// are only trimmed.
+pub(crate) fn normalize_direction_code(code: &str) -> String {
+    let code = code.trim();
+    let number = code.strip_prefix('R').unwrap_or(code).trim_start();
+    match number.parse::<i32>() {
+        Ok(id) if number.bytes().all(|b| b.is_ascii_digit()) => format!("R{id}"),
+        _ => code.to_string(),
+    }
+}
+
+/// Reads the lines of the file. The lines skipped by the rules of the file (see
+/// ParsingOptions::set_file_rules) are returned empty, to keep the line numbers.
+pub(crate) fn read_lines(path: &Path, options: &ParsingOptions) -> io::Result<Vec<String>> {
+    let file = File::open(path)?;
     let mut reader = io::BufReader::new(file);
     let mut contents = String::new();
     reader.read_to_string(&mut contents)?;
-    let lines = contents.lines().map(String::from).collect();
+
+    let rules = path
+        .file_name()
+        .and_then(|file_name| options.file_rules(&file_name.to_string_lossy()));
+    let lines = contents
+        .lines()
+        .enumerate()
+        .map(|(line_number, line)| match rules {
+            Some(rules) if rules.skips(line_number, line) => String::new(),
+            _ => String::from(line),
+        })
+        .collect();
     Ok(lines)
 }
 
+/// Parses the non-empty lines of the file one at a time, without reading the whole file.
+/// Stops at the first line which cannot be parsed.
+pub(crate) fn for_each_line(
+    path: &Path,
+    mut parse_line: impl FnMut(&str) -> PResult<()>,
+) -> HResult<()> {
+    let reader = io::BufReader::new(File::open(path)?);
+    for (line_number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        parse_line(&line).map_err(|e| HrdfError::Parsing {
+            error: e,
+            file: String::from(path.to_string_lossy()),
+            line,
+            line_number,
+        })?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -303,6 +393,40 @@ mod tests {
         assert_eq!(remaining, "rest");
     }
 
+    #[test]
+    fn test_blank_or_i32_from_n_digits_parser() {
+        assert_eq!(
+            blank_or_i32_from_n_digits_parser(6)("-00800rest").unwrap(),
+            ("rest", Some(-800))
+        );
+        assert_eq!(
+            blank_or_i32_from_n_digits_parser(6)("  @   rest").unwrap(),
+            ("rest", None)
+        );
+        assert!(blank_or_i32_from_n_digits_parser(6)("Nord  rest").is_err());
+    }
+
+    #[test]
+    fn test_field_parser() {
+        assert_eq!(
+            field_parser("000011\t 000037").unwrap(),
+            ("\t 000037", "000011".to_string())
+        );
+        assert!(field_parser(" 000011").is_err());
+    }
+
+    #[test]
+    fn test_optional_stop_id_field_parser() {
+        assert_eq!(
+            optional_stop_id_field_parser("8500010 rest").unwrap(),
+            (" rest", Some(8500010))
+        );
+        assert_eq!(
+            optional_stop_id_field_parser("@@@@@@@ rest").unwrap(),
+            (" rest", None)
+        );
+    }
+
     #[test]
     fn test_direction_parser_basic() {
         let input = "R123456more";
@@ -338,4 +462,15 @@ mod tests {
         let result = direction_parser(input);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_normalize_direction_code() {
+        assert_eq!(normalize_direction_code("R000063"), "R63");
+        assert_eq!(normalize_direction_code("R 63   "), "R63");
+        assert_eq!(normalize_direction_code("0000063"), "R63");
+        assert_eq!(normalize_direction_code("  63   "), "R63");
+        assert_eq!(normalize_direction_code("RABC01 "), "RABC01");
+        assert_eq!(normalize_direction_code("R-12"), "R-12");
+        assert_eq!(normalize_direction_code("       "), "");
+    }
 }
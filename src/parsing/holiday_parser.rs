@@ -22,7 +22,8 @@ use crate::{
     error::{HResult, HrdfError},
     models::{Holiday, Language},
     parsing::{
-        error::{PResult, ParsingError},
+        ParsingOptions,
+        error::{LineErrors, PResult, ParsingError},
         helpers::{read_lines, string_from_n_chars_parser, string_till_eol_parser},
     },
     storage::ResourceStorage,
@@ -48,22 +49,27 @@ fn parse_line(line: &str, auto_increment: &AutoIncrement) -> PResult<(i32, Holid
     Ok((id, Holiday::new(id, date, name)))
 }
 
-pub fn parse(path: &Path) -> HResult<ResourceStorage<Holiday>> {
+pub fn parse(
+    path: &Path,
+    options: &ParsingOptions,
+    errors: &mut LineErrors,
+) -> HResult<ResourceStorage<Holiday>> {
     log::info!("Parsing FEIERTAG...");
     let file = path.join("FEIERTAG");
-    let lines = read_lines(&file, 0)?;
+    let lines = read_lines(&file, options)?;
     let auto_increment = AutoIncrement::new();
     let holidays = lines
         .into_iter()
         .enumerate()
         .filter(|(_, line)| !line.trim().is_empty())
-        .map(|(line_number, line)| {
-            parse_line(&line, &auto_increment).map_err(|e| HrdfError::Parsing {
+        .filter_map(|(line_number, line)| {
+            let result = parse_line(&line, &auto_increment).map_err(|e| HrdfError::Parsing {
                 error: e,
                 file: String::from(file.to_string_lossy()),
                 line,
                 line_number,
-            })
+            });
+            errors.handle(result).transpose()
         })
         .collect::<HResult<FxHashMap<_, _>>>()?;
     Ok(ResourceStorage::new(holidays))
@@ -103,6 +109,7 @@ fn parse_name_translations(name_translations: String) -> PResult<FxHashMap<Langu
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
+    #[cfg(feature = "serde")]
     use crate::parsing::tests::get_json_values;
     use pretty_assertions::assert_eq;
 
@@ -117,6 +124,7 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn row_converter_v207() {
         let auto_increment = AutoIncrement::new();
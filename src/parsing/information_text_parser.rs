@@ -26,7 +26,8 @@ use crate::{
     error::{HResult, HrdfError},
     models::{InformationText, Language},
     parsing::{
-        error::PResult,
+        ParsingOptions,
+        error::{LineErrors, PResult},
         helpers::{i32_from_n_digits_parser, read_lines, string_till_eol_parser},
     },
     storage::ResourceStorage,
@@ -59,25 +60,31 @@ fn parse_line(
     Ok(())
 }
 
-pub fn parse(path: &Path) -> HResult<ResourceStorage<InformationText>> {
+pub fn parse(
+    path: &Path,
+    options: &ParsingOptions,
+    errors: &mut LineErrors,
+) -> HResult<ResourceStorage<InformationText>> {
     let mut infotextmap: FxHashMap<i32, InformationText> = FxHashMap::default();
     let languages = ["DE", "EN", "FR", "IT"];
     for language in languages {
         log::info!("Parsing INFOTEXT_{language}...");
 
         let file = path.join(format!("INFOTEXT_{language}"));
-        let lines = read_lines(&file, 0)?;
+        let lines = read_lines(&file, options)?;
         lines
             .into_iter()
             .enumerate()
             .filter(|(_, line)| !line.trim().is_empty())
             .try_for_each(|(line_number, line)| {
-                parse_line(&line, &mut infotextmap, language).map_err(|e| HrdfError::Parsing {
-                    error: e,
-                    file: String::from(file.to_string_lossy()),
-                    line,
-                    line_number,
-                })
+                let result =
+                    parse_line(&line, &mut infotextmap, language).map_err(|e| HrdfError::Parsing {
+                        error: e,
+                        file: String::from(file.to_string_lossy()),
+                        line,
+                        line_number,
+                    });
+                errors.handle(result).map(|_| ())
             })?;
     }
     Ok(ResourceStorage::new(infotextmap))
@@ -89,6 +96,7 @@ pub fn parse(path: &Path) -> HResult<ResourceStorage<InformationText>> {
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "serde")]
     use crate::parsing::tests::get_json_values;
 
     // Note this useful idiom: importing names from outer (for mod tests) scope.
@@ -111,6 +119,7 @@ mod tests {
         assert_eq!("2518", &infotext);
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn parse_and_transform_infotext() {
         let input = "000001921 ch:1:sjyid:100001:3995-001";
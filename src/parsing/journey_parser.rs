@@ -9,13 +9,12 @@ use std::path::Path;
 /// 1 file(s).
 /// File(s) read by the parser:
 /// FPLAN
-use chrono::NaiveTime;
 use nom::{
     IResult, Parser,
     branch::alt,
-    bytes::tag,
+    bytes::{complete::take_till, tag},
     character::{char, complete::space1},
-    combinator::map,
+    combinator::{map, rest, verify},
     sequence::preceded,
 };
 use rustc_hash::{FxHashMap, FxHashSet};
@@ -23,19 +22,47 @@ use rustc_hash::{FxHashMap, FxHashSet};
 use crate::{
     JourneyId,
     error::{HResult, HrdfError},
-    models::{Journey, JourneyMetadataEntry, JourneyMetadataType, JourneyRouteEntry},
+    models::{
+        Journey, JourneyMetadataEntry, JourneyMetadataType, JourneyNumberChange, JourneyRouteEntry,
+        Model, ServiceTime,
+    },
     parsing::{
-        error::{PResult, ParsingError},
+        ParseIssue, ParseReport, ParsingOptions, RouteTimeCheck, Visitor,
+        error::{LineErrors, PResult, ParsingError},
         helpers::{
-            direction_parser, i32_from_n_digits_parser, optional_i32_from_n_digits_parser,
-            read_lines, string_from_n_chars_parser,
+            blank_or_i32_from_n_digits_parser, for_each_line, i32_from_n_digits_parser,
+            normalize_direction_code, optional_i32_from_n_digits_parser, read_lines,
+            string_from_n_chars_parser,
         },
     },
     storage::ResourceStorage,
-    utils::{AutoIncrement, create_time_from_value},
+    utils::{AutoIncrement, create_service_time_from_value},
 };
 
-type JourneyAndTypeConverter = (ResourceStorage<Journey>, FxHashSet<JourneyId>);
+// (journeys, legacy ids of the journeys, stop names as written in the route rows)
+type JourneyAndTypeConverter = (
+    ResourceStorage<Journey>,
+    FxHashSet<JourneyId>,
+    FxHashMap<i32, String>,
+);
+
+/// The resources built while reading the rows of FPLAN.
+struct JourneyAccumulator {
+    data: FxHashMap<i32, Journey>,
+    pk_type_converter: FxHashSet<JourneyId>,
+    // Only collected for ParsingOptions::placeholder_stops.
+    stop_names: Option<FxHashMap<i32, String>>,
+}
+
+impl JourneyAccumulator {
+    fn new(collect_stop_names: bool) -> Self {
+        Self {
+            data: FxHashMap::default(),
+            pk_type_converter: FxHashSet::default(),
+            stop_names: collect_stop_names.then(FxHashMap::default),
+        }
+    }
+}
 
 #[derive(Debug)]
 enum JourneyLines {
@@ -44,9 +71,7 @@ enum JourneyLines {
         transport_company_id: String,
         #[allow(unused)]
         transport_variant: i32,
-        #[allow(unused)]
         num_cycles: Option<i32>,
-        #[allow(unused)]
         cycle_dura_min: Option<i32>,
     },
     Gline {
@@ -63,7 +88,6 @@ enum JourneyLines {
         offer: String,
         stop_from_id: Option<i32>,
         stop_to_id: Option<i32>,
-        #[allow(unused)]
         reference: Option<i32>,
     },
     Iline {
@@ -104,15 +128,30 @@ enum JourneyLines {
         departure_time: Option<i32>,
         arrival_time: Option<i32>,
     },
+    UnknownLine {
+        line_type: String,
+        content: String,
+    },
+    ShLine {
+        stop_id: i32,
+        bit_field_id: Option<i32>,
+    },
+    KwLine {
+        is_train: bool,
+        journey_id: i32,
+        administration: String,
+        stop_from_id: Option<i32>,
+        stop_to_id: Option<i32>,
+        departure_time: Option<i32>,
+        arrival_time: Option<i32>,
+    },
     JourneyLine {
         stop_id: i32,
         #[allow(unused)]
         stop_name: String,
         arrival_time: Option<i32>,
         departure_time: Option<i32>,
-        #[allow(unused)]
         journey_id: Option<i32>,
-        #[allow(unused)]
         administration: String,
     },
 }
@@ -123,6 +162,7 @@ enum JourneyLines {
 ///     - The journey number (primary key with the TU code)
 ///     - Transport company (TU) code (see File BETRIEB_*)
 ///         - For the TU code = 801, the region information must also be taken into account. This information is contained in line *I with the INFOTEXTCODE RN.
+///           The region is resolved when the data storage is built (see Journey::region).
 ///     - Option
 ///         - NOT PART OF HRDF. 3-digit means of transport variant code without technical meaning
 ///     - (optional) Number of cycles
@@ -235,7 +275,7 @@ fn row_a_ve_combinator(input: &str) -> IResult<&str, JourneyLines> {
 ///         - The term “Angebot” (offer) may be imprecise here. The HRDF doc. uses the word “Attribut” (attribute), which is also somewhat imprecise. Basically, it is a collective term for extensions (e.g. dining car) or restrictions (e.g. no bicycles) that apply.
 ///     - Stop from which the offer category applies
 ///     - Stop up to which the offer category applies
-///     - Reference to the validity information
+///     - Reference to the validity information (bit field). If not available = every day of the journey.
 ///
 /// ### Example (excerpt):
 ///
@@ -281,7 +321,8 @@ fn row_a_combinator(input: &str) -> IResult<&str, JourneyLines> {
 ///     - Departure time
 ///     - Time of arrival
 ///     - Comments:
-///         - The Swiss Journey ID (SJYID) is identified via the *I line with the code JY
+///         - The Swiss Journey ID (SJYID) is identified via the *I line with the code JY (see Journey::swiss_journey_id)
+///         - The region of the journeys of the TU 801 is identified via the *I line with the code RN (see Journey::region)
 ///
 /// ### Example (excerpt):
 ///
@@ -381,7 +422,8 @@ fn row_l_combinator(input: &str) -> IResult<&str, JourneyLines> {
 ///
 /// - *R lines: Reference to the direction text (see file RICHTUNG / DIRECTION). It includes:
 ///     - Direction (H=forward,R=backward)
-///     - Reference to direction code
+///     - Reference to direction code (usually R and 6 digits, padded and alphanumeric codes are
+///       also found)
 ///     - Stop from which the direction applies
 ///     - Stop to which the direction applies
 ///     - Departure time
@@ -410,10 +452,9 @@ fn row_r_combinator(input: &str) -> IResult<&str, JourneyLines> {
                 string_from_n_chars_parser(1),
                 preceded(
                     char(' '),
-                    alt((
-                        map(direction_parser, |(prefix, id)| format!("{prefix}{id}")),
-                        string_from_n_chars_parser(7),
-                    )),
+                    map(string_from_n_chars_parser(7), |code| {
+                        normalize_direction_code(&code)
+                    }),
                 ),
                 preceded(char(' '), optional_i32_from_n_digits_parser(7)),
                 preceded(char(' '), optional_i32_from_n_digits_parser(7)),
@@ -499,6 +540,122 @@ fn row_ci_co_combinator(input: &str) -> IResult<&str, JourneyLines> {
     .parse(input)
 }
 
+/// ## SH lines
+///
+/// - *SH lines: seasonal stop, the journey serves the stop only on some days. It includes:
+///     - The stop
+///     - Reference to the validity information (see file BITFELD). If not available = always.
+///
+/// ### Example (excerpt):
+///
+/// `
+/// *Z ...
+/// *G ...
+/// *A VE ...
+/// *SH 8509002 004711                                          % HS-Nr. 8509002 wird an den Gültigkeitstagen 004711 bedient
+/// ...
+/// `
+fn row_sh_combinator(input: &str) -> IResult<&str, JourneyLines> {
+    map(
+        preceded(
+            tag("*SH "),
+            (
+                i32_from_n_digits_parser(7),
+                preceded(char(' '), optional_i32_from_n_digits_parser(6)),
+            ),
+        ),
+        |(stop_id, bit_field_id)| JourneyLines::ShLine {
+            stop_id,
+            bit_field_id,
+        },
+    )
+    .parse(input)
+}
+
+/// ## KW/KWZ lines
+///
+/// - *KW/KWZ lines: coupled runs (wing trains, through coaches). It includes:
+///     - Journey number of the coupled journey
+///     - Administration of the coupled journey
+///     - Stop from which the coupling applies
+///     - Stop to which the coupling applies
+///     - Departure time
+///     - Time of arrival
+///
+/// *KW: the coupled journey runs as a through coach of this journey between the stops. *KWZ: this
+/// journey runs as a through coach of the coupled journey (the train) between the stops.
+///
+/// ### Example (excerpt):
+///
+/// `
+/// *Z ...
+/// *G ...
+/// *A VE ...
+/// *KW 002345 000011 8500010 8500090                           % Kurswagen 2345 (SBB) ab HS-Nr. 8500010 bis HS-Nr. 8500090
+/// ...
+/// *KWZ 000567 000011 8500010 8500090                          % Im Zug 567 (SBB) ab HS-Nr. 8500010 bis HS-Nr. 8500090
+/// ...
+/// `
+fn row_kw_combinator(input: &str) -> IResult<&str, JourneyLines> {
+    map(
+        (
+            alt((tag("*KWZ"), tag("*KW"))),
+            preceded(char(' '), i32_from_n_digits_parser(6)),
+            preceded(char(' '), string_from_n_chars_parser(6)),
+            preceded(char(' '), optional_i32_from_n_digits_parser(7)),
+            preceded(char(' '), optional_i32_from_n_digits_parser(7)),
+            preceded(char(' '), optional_i32_from_n_digits_parser(6)),
+            preceded(char(' '), optional_i32_from_n_digits_parser(6)),
+        ),
+        |(
+            kw,
+            journey_id,
+            administration,
+            stop_from_id,
+            stop_to_id,
+            departure_time,
+            arrival_time,
+        )| JourneyLines::KwLine {
+            is_train: kw == "*KWZ",
+            journey_id,
+            administration,
+            stop_from_id,
+            stop_to_id,
+            departure_time,
+            arrival_time,
+        },
+    )
+    .parse(input)
+}
+
+/// The star-rows this parser knows, a malformed one of them is an error.
+const KNOWN_LINE_TYPES: [&str; 11] = ["Z", "G", "A", "I", "L", "R", "CI", "CO", "KW", "KWZ", "SH"];
+
+/// ## Unknown star-lines
+///
+/// - The star-rows of a type which is not parsed (e.g. introduced by a later HRDF revision) are
+///   kept as they are, so that the rest of the file can be parsed. It includes:
+///     - The type of the row (e.g. XY for *XY)
+///     - The rest of the row, trimmed
+fn row_unknown_combinator(input: &str) -> IResult<&str, JourneyLines> {
+    map(
+        preceded(
+            char('*'),
+            (
+                verify(take_till(char::is_whitespace), |line_type: &str| {
+                    !line_type.is_empty() && !KNOWN_LINE_TYPES.contains(&line_type)
+                }),
+                rest,
+            ),
+        ),
+        |(line_type, content): (&str, &str)| JourneyLines::UnknownLine {
+            line_type: line_type.to_string(),
+            content: content.trim().to_string(),
+        },
+    )
+    .parse(input)
+}
+
 /// ## Journey description
 ///
 /// - Once all the lines described have been defined, the run is described with the journey times:
@@ -525,7 +682,17 @@ fn row_ci_co_combinator(input: &str) -> IResult<&str, JourneyLines> {
 /// 0053202 Am Kl. Wannsee/Am Gr  02016  02016               %
 /// `
 ///
+/// The stop names are written on 20 characters, but some exports contain longer names which
+/// shift the following columns (see row_journey_description_long_name_combinator).
 fn row_journey_description_combinator(input: &str) -> IResult<&str, JourneyLines> {
+    alt((
+        row_journey_description_fixed_width_combinator,
+        row_journey_description_long_name_combinator,
+    ))
+    .parse(input)
+}
+
+fn row_journey_description_fixed_width_combinator(input: &str) -> IResult<&str, JourneyLines> {
     map(
         (
             i32_from_n_digits_parser(7),
@@ -549,10 +716,48 @@ fn row_journey_description_combinator(input: &str) -> IResult<&str, JourneyLines
     .parse(input)
 }
 
+/// The columns are anchored on the end of the stop name: the name is extended character by
+/// character until it ends with a non-blank character followed by readable time columns.
+fn row_journey_description_long_name_combinator(input: &str) -> IResult<&str, JourneyLines> {
+    let chars: Vec<char> = input.chars().collect();
+    // 8 characters before the name, 4 columns of 7 characters after it.
+    let max_name_len = chars.len().saturating_sub(8 + 4 * 7);
+
+    (21..=max_name_len)
+        .filter(|name_len| chars[7 + name_len] != ' ')
+        .find_map(|name_len| {
+            map(
+                (
+                    i32_from_n_digits_parser(7),
+                    preceded(char(' '), string_from_n_chars_parser(name_len)),
+                    preceded(char(' '), blank_or_i32_from_n_digits_parser(6)),
+                    preceded(char(' '), blank_or_i32_from_n_digits_parser(6)),
+                    preceded(char(' '), blank_or_i32_from_n_digits_parser(6)),
+                    preceded(char(' '), string_from_n_chars_parser(6)),
+                ),
+                |(stop_id, stop_name, arrival_time, departure_time, journey_id, administration)| {
+                    JourneyLines::JourneyLine {
+                        stop_id,
+                        stop_name,
+                        arrival_time,
+                        departure_time,
+                        journey_id,
+                        administration,
+                    }
+                },
+            )
+            .parse(input)
+            .ok()
+        })
+        .ok_or(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Verify,
+        )))
+}
+
 fn parse_line(
     line: &str,
-    data: &mut FxHashMap<i32, Journey>,
-    pk_type_converter: &mut FxHashSet<JourneyId>,
+    accumulator: &mut JourneyAccumulator,
     auto_increment: &AutoIncrement,
     transport_types_pk_type_converter: &FxHashMap<String, i32>,
     attributes_pk_type_converter: &FxHashMap<String, i32>,
@@ -567,21 +772,31 @@ fn parse_line(
         row_l_combinator,
         row_r_combinator,
         row_ci_co_combinator,
+        row_sh_combinator,
+        row_kw_combinator,
+        row_unknown_combinator,
         row_journey_description_combinator,
     ))
     .parse(line)?;
 
+    let JourneyAccumulator {
+        data,
+        pk_type_converter,
+        stop_names,
+    } = accumulator;
     match journey_lines {
         JourneyLines::Zline {
             journey_id,
             transport_company_id,
             transport_variant: _,
-            num_cycles: _,
-            cycle_dura_min: _,
+            num_cycles,
+            cycle_dura_min,
         } => {
             let id = auto_increment.next();
             pk_type_converter.insert((journey_id, transport_company_id.to_owned()));
-            data.insert(id, Journey::new(id, journey_id, transport_company_id));
+            let mut journey = Journey::new(id, journey_id, transport_company_id);
+            journey.set_cycle(num_cycles, cycle_dura_min);
+            data.insert(id, journey);
         }
         JourneyLines::Gline {
             offer,
@@ -644,7 +859,7 @@ fn parse_line(
             offer,
             stop_from_id,
             stop_to_id,
-            reference: _,
+            reference,
         } => {
             let journey = data.get_mut(&auto_increment.get()).ok_or_else(|| {
                 ParsingError::UnknownId(format!(
@@ -662,7 +877,7 @@ fn parse_line(
                     stop_from_id,
                     stop_to_id,
                     Some(attribute_id),
-                    None,
+                    reference,
                     None,
                     None,
                     None,
@@ -685,8 +900,8 @@ fn parse_line(
                     auto_increment.get()
                 ))
             })?;
-            let arrival_time = create_time(arrival_time)?;
-            let departure_time = create_time(departure_time)?;
+            let arrival_time = create_service_time(arrival_time)?;
+            let departure_time = create_service_time(departure_time)?;
 
             journey.add_metadata_entry(
                 JourneyMetadataType::InformationText,
@@ -716,38 +931,31 @@ fn parse_line(
                     auto_increment.get()
                 ))
             })?;
-            let arrival_time = create_time(arrival_time)?;
-            let departure_time = create_time(departure_time)?;
+            let arrival_time = create_service_time(arrival_time)?;
+            let departure_time = create_service_time(departure_time)?;
 
-            let direction_id = if ref_direction_code.is_empty() {
-                None
-            } else {
-                let id = *directions_pk_type_converter
-                    .get(&ref_direction_code)
-                    .ok_or_else(|| {
-                        ParsingError::UnknownId(format!(
-                            "Type A row missing for id {ref_direction_code}."
-                        ))
-                    })?;
-                Some(id)
-            };
+            // A code without RICHTUNG entry is kept as is, DataStorage::validate reports it.
+            let direction_id = directions_pk_type_converter
+                .get(&ref_direction_code)
+                .copied();
 
-            journey.add_metadata_entry(
-                JourneyMetadataType::Direction,
-                JourneyMetadataEntry::new(
-                    stop_from_id,
-                    stop_to_id,
-                    direction_id,
-                    None,
-                    departure_time,
-                    arrival_time,
-                    Some(direction),
-                    None,
-                ),
+            let mut entry = JourneyMetadataEntry::new(
+                stop_from_id,
+                stop_to_id,
+                direction_id,
+                None,
+                departure_time,
+                arrival_time,
+                Some(direction),
+                None,
             );
+            if direction_id.is_none() && !ref_direction_code.is_empty() {
+                entry.set_raw_code(ref_direction_code);
+            }
+            journey.add_metadata_entry(JourneyMetadataType::Direction, entry);
         }
         JourneyLines::Lline {
-            mut line_info,
+            line_info,
             stop_from_id,
             stop_to_id,
             departure_time,
@@ -759,34 +967,36 @@ fn parse_line(
                     auto_increment.get()
                 ))
             })?;
-            let arrival_time = create_time(arrival_time)?;
-            let departure_time = create_time(departure_time)?;
+            let arrival_time = create_service_time(arrival_time)?;
+            let departure_time = create_service_time(departure_time)?;
 
-            let line_info_first_char = line_info
-                .chars()
-                .next()
-                .ok_or("Missing line info (the string is empty).")?;
+            if line_info.is_empty() {
+                return Err("Missing line info (the string is empty).".into());
+            }
 
-            let (resource_id, extra_field_1) = if line_info_first_char == '#' {
-                line_info.drain(..line_info_first_char.len_utf8());
-                (Some(line_info.parse::<i32>()?), None)
-            } else {
-                (None, Some(line_info))
+            // A reference (#...) which is not a LINIE ID is kept as is.
+            let (resource_id, extra_field_1, raw_code) = match line_info.strip_prefix('#') {
+                Some(reference) => match parse_line_reference(reference) {
+                    Some(id) => (Some(id), None, None),
+                    None => (None, None, Some(line_info)),
+                },
+                None => (None, Some(line_info), None),
             };
 
-            journey.add_metadata_entry(
-                JourneyMetadataType::Line,
-                JourneyMetadataEntry::new(
-                    stop_from_id,
-                    stop_to_id,
-                    resource_id,
-                    None,
-                    departure_time,
-                    arrival_time,
-                    extra_field_1,
-                    None,
-                ),
+            let mut entry = JourneyMetadataEntry::new(
+                stop_from_id,
+                stop_to_id,
+                resource_id,
+                None,
+                departure_time,
+                arrival_time,
+                extra_field_1,
+                None,
             );
+            if let Some(raw_code) = raw_code {
+                entry.set_raw_code(raw_code);
+            }
+            journey.add_metadata_entry(JourneyMetadataType::Line, entry);
         }
         JourneyLines::CiLine {
             num_minutes,
@@ -801,8 +1011,8 @@ fn parse_line(
                     auto_increment.get()
                 ))
             })?;
-            let arrival_time = create_time(arrival_time)?;
-            let departure_time = create_time(departure_time)?;
+            let arrival_time = create_service_time(arrival_time)?;
+            let departure_time = create_service_time(departure_time)?;
 
             journey.add_metadata_entry(
                 JourneyMetadataType::ExchangeTimeBoarding,
@@ -831,8 +1041,8 @@ fn parse_line(
                     auto_increment.get()
                 ))
             })?;
-            let arrival_time = create_time(arrival_time)?;
-            let departure_time = create_time(departure_time)?;
+            let arrival_time = create_service_time(arrival_time)?;
+            let departure_time = create_service_time(departure_time)?;
 
             journey.add_metadata_entry(
                 JourneyMetadataType::ExchangeTimeDisembarking,
@@ -848,13 +1058,80 @@ fn parse_line(
                 ),
             );
         }
+        JourneyLines::UnknownLine { line_type, content } => {
+            let journey = data.get_mut(&auto_increment.get()).ok_or_else(|| {
+                ParsingError::UnknownId(format!(
+                    "Type A row missing for id {}.",
+                    auto_increment.get()
+                ))
+            })?;
+            let mut entry =
+                JourneyMetadataEntry::new(None, None, None, None, None, None, Some(content), None);
+            entry.set_raw_code(line_type);
+            journey.add_metadata_entry(JourneyMetadataType::Unknown, entry);
+        }
+        JourneyLines::ShLine {
+            stop_id,
+            bit_field_id,
+        } => {
+            let journey = data.get_mut(&auto_increment.get()).ok_or_else(|| {
+                ParsingError::UnknownId(format!(
+                    "Type A row missing for id {}.",
+                    auto_increment.get()
+                ))
+            })?;
+            journey.add_metadata_entry(
+                JourneyMetadataType::SeasonalStop,
+                JourneyMetadataEntry::new(
+                    Some(stop_id),
+                    Some(stop_id),
+                    None,
+                    bit_field_id,
+                    None,
+                    None,
+                    None,
+                    None,
+                ),
+            );
+        }
+        JourneyLines::KwLine {
+            is_train,
+            journey_id,
+            administration,
+            stop_from_id,
+            stop_to_id,
+            departure_time,
+            arrival_time,
+        } => {
+            let journey = data.get_mut(&auto_increment.get()).ok_or_else(|| {
+                ParsingError::UnknownId(format!(
+                    "Type A row missing for id {}.",
+                    auto_increment.get()
+                ))
+            })?;
+            let arrival_time = create_service_time(arrival_time)?;
+            let departure_time = create_service_time(departure_time)?;
+
+            let mut entry = JourneyMetadataEntry::new(
+                stop_from_id,
+                stop_to_id,
+                None,
+                None,
+                departure_time,
+                arrival_time,
+                Some(administration),
+                Some(journey_id),
+            );
+            entry.set_raw_code(if is_train { "KWZ" } else { "KW" }.to_string());
+            journey.add_metadata_entry(JourneyMetadataType::Coupling, entry);
+        }
         JourneyLines::JourneyLine {
             stop_id,
-            stop_name: _,
+            stop_name,
             arrival_time,
             departure_time,
-            journey_id: _,
-            administration: _,
+            journey_id,
+            administration,
         } => {
             let journey = data.get_mut(&auto_increment.get()).ok_or_else(|| {
                 ParsingError::UnknownId(format!(
@@ -862,14 +1139,32 @@ fn parse_line(
                     auto_increment.get()
                 ))
             })?;
-            let arrival_time = create_time(arrival_time)?;
-            let departure_time = create_time(departure_time)?;
 
-            journey.add_route_entry(JourneyRouteEntry::new(
-                stop_id,
-                arrival_time,
-                departure_time,
-            ));
+            // The number is usually repeated on every row, only a different one is a change.
+            if let Some(journey_id) = journey_id {
+                let (current_id, current_administration) = journey
+                    .number_changes()
+                    .last()
+                    .map_or((journey.legacy_id(), journey.administration()), |change| {
+                        (change.legacy_id(), change.administration())
+                    });
+                let administration = if administration.is_empty() {
+                    current_administration.to_string()
+                } else {
+                    administration
+                };
+                if journey_id != current_id || administration != current_administration {
+                    journey.add_number_change(JourneyNumberChange::new(
+                        stop_id,
+                        journey_id,
+                        administration,
+                    ));
+                }
+            }
+            if let Some(stop_names) = stop_names {
+                stop_names.entry(stop_id).or_insert(stop_name);
+            }
+            journey.add_route_entry(create_route_entry(stop_id, arrival_time, departure_time)?);
         }
     }
     Ok(())
@@ -880,24 +1175,44 @@ pub fn parse(
     transport_types_pk_type_converter: &FxHashMap<String, i32>,
     attributes_pk_type_converter: &FxHashMap<String, i32>,
     directions_pk_type_converter: &FxHashMap<String, i32>,
+    options: &ParsingOptions,
+    report: &mut ParseReport,
+    errors: &mut LineErrors,
 ) -> HResult<JourneyAndTypeConverter> {
     log::info!("Parsing FPLAN...");
     let file = path.join("FPLAN");
-    let lines = read_lines(&file, 0)?;
+    let lines = read_lines(&file, options)?;
 
     let auto_increment = AutoIncrement::new();
-    let mut data = FxHashMap::default();
-    let mut pk_type_converter = FxHashSet::default();
+    let mut accumulator = JourneyAccumulator::new(options.placeholder_stops());
+    // The rows of a journey whose administration is not parsed are skipped up to the next *Z row.
+    let mut skip_journey = false;
 
     lines
         .into_iter()
         .enumerate()
         .filter(|(_, line)| !line.trim().is_empty())
         .try_for_each(|(line_number, line)| {
-            parse_line(
+            if options.administrations().is_some() && line.starts_with("*Z ") {
+                // An invalid *Z row is reported by parse_line.
+                skip_journey = match row_z_combinator(&line) {
+                    Ok((
+                        _,
+                        JourneyLines::Zline {
+                            transport_company_id,
+                            ..
+                        },
+                    )) => !options.includes_administration(&transport_company_id),
+                    _ => false,
+                };
+            }
+            if skip_journey {
+                return Ok(());
+            }
+
+            let result = parse_line(
                 &line,
-                &mut data,
-                &mut pk_type_converter,
+                &mut accumulator,
                 &auto_increment,
                 transport_types_pk_type_converter,
                 attributes_pk_type_converter,
@@ -908,35 +1223,232 @@ pub fn parse(
                 file: String::from(file.to_string_lossy()),
                 line,
                 line_number,
-            })
+            });
+            errors.handle(result).map(|_| ())
         })?;
 
-    Ok((ResourceStorage::new(data), pk_type_converter))
+    let JourneyAccumulator {
+        mut data,
+        pk_type_converter,
+        stop_names,
+    } = accumulator;
+    report_unknown_lines(&data, report);
+    check_route_times(&mut data, options.route_time_check(), report);
+    if options.expand_cycles() {
+        expand_cycles(&mut data, &auto_increment);
+    }
+
+    Ok((
+        ResourceStorage::new(data),
+        pk_type_converter,
+        stop_names.unwrap_or_default(),
+    ))
+}
+
+/// Serves parsing::visit. The rows other than *Z, *G, *A VE and the route rows are skipped.
+pub(crate) fn visit(path: &Path, visitor: &mut impl Visitor) -> HResult<()> {
+    log::info!("Visiting FPLAN...");
+    let mut journey_started = false;
+
+    for_each_line(&path.join("FPLAN"), |line| {
+        let journey_lines = if line.starts_with("*Z ") {
+            row_z_combinator(line)?.1
+        } else if line.starts_with("*G ") {
+            row_g_combinator(line)?.1
+        } else if line.starts_with("*A VE ") {
+            row_a_ve_combinator(line)?.1
+        } else if line.starts_with('*') {
+            return Ok(());
+        } else {
+            row_journey_description_combinator(line)?.1
+        };
+
+        match journey_lines {
+            JourneyLines::Zline {
+                journey_id,
+                transport_company_id,
+                ..
+            } => {
+                if journey_started {
+                    visitor.journey_finished();
+                }
+                journey_started = true;
+                visitor.journey_started(journey_id, &transport_company_id);
+            }
+            JourneyLines::Gline {
+                offer,
+                stop_from_id,
+                stop_to_id,
+            } => visitor.journey_transport_type(&offer, stop_from_id, stop_to_id),
+            JourneyLines::AVEline {
+                stop_from_id,
+                stop_to_id,
+                bit_field_id,
+            } => visitor.journey_bit_field(bit_field_id, stop_from_id, stop_to_id),
+            JourneyLines::JourneyLine {
+                stop_id,
+                arrival_time,
+                departure_time,
+                ..
+            } => visitor.route_entry(create_route_entry(stop_id, arrival_time, departure_time)?),
+            _ => {}
+        }
+        Ok(())
+    })?;
+
+    if journey_started {
+        visitor.journey_finished();
+    }
+    Ok(())
+}
+
+/// Reports the number of unknown star-rows of each type.
+fn report_unknown_lines(data: &FxHashMap<i32, Journey>, report: &mut ParseReport) {
+    let mut counts: FxHashMap<&str, usize> = FxHashMap::default();
+    for (line_type, _) in data.values().flat_map(|journey| journey.unknown_rows()) {
+        *counts.entry(line_type).or_default() += 1;
+    }
+
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_unstable();
+    for (line_type, count) in counts {
+        report.add(ParseIssue::UnknownJourneyRows {
+            line_type: line_type.to_string(),
+            count,
+        });
+    }
+}
+
+/// Adds a journey for each repetition of the cyclical journeys, with the same journey number and
+/// administration. The IDs of the repetitions follow the IDs of the parsed journeys.
+fn expand_cycles(data: &mut FxHashMap<i32, Journey>, auto_increment: &AutoIncrement) {
+    let mut ids: Vec<i32> = data.keys().copied().collect();
+    ids.sort_unstable();
+
+    for id in ids {
+        // unwrap: The IDs were taken from the map.
+        let journey = data.get_mut(&id).unwrap();
+        let offsets = journey.cycle_offsets();
+        journey.set_cycle(None, None);
+
+        let repetitions: Vec<_> = offsets
+            .into_iter()
+            .map(|offset| journey.shifted(auto_increment.next(), offset))
+            .collect();
+        for repetition in repetitions {
+            data.insert(repetition.id(), repetition);
+        }
+    }
+}
+
+/// Reports the journeys whose stop times go backwards and drops or repairs them depending on the check mode.
+fn check_route_times(
+    data: &mut FxHashMap<i32, Journey>,
+    route_time_check: RouteTimeCheck,
+    report: &mut ParseReport,
+) {
+    if route_time_check == RouteTimeCheck::Disabled {
+        return;
+    }
+
+    let mut ids: Vec<i32> = data.keys().copied().collect();
+    ids.sort_unstable();
+
+    for id in ids {
+        let Some(journey) = data.get_mut(&id) else {
+            continue;
+        };
+        let Some(index) = journey.find_non_monotonic_route_entry() else {
+            continue;
+        };
+
+        log::warn!(
+            "Journey {} ({}, {}) has stop times going backwards.",
+            journey.id(),
+            journey.legacy_id(),
+            journey.administration()
+        );
+        report.add(ParseIssue::NonMonotonicRouteTimes {
+            journey_id: journey.id(),
+            legacy_id: journey.legacy_id(),
+            administration: journey.administration().to_string(),
+            stop_id: journey.route()[index].stop_id(),
+            action: route_time_check,
+        });
+
+        match route_time_check {
+            RouteTimeCheck::Drop => {
+                data.remove(&id);
+            }
+            RouteTimeCheck::Repair => {
+                journey.repair_route_times();
+            }
+            RouteTimeCheck::Disabled | RouteTimeCheck::Report => {}
+        }
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
 // --- Helper Functions
 // ------------------------------------------------------------------------------------------------
 
-fn create_time(time: Option<i32>) -> PResult<Option<NaiveTime>> {
-    time.map(|value| {
-        create_time_from_value(match value.abs() {
-            val if val >= 2400 => val % 2400,
-            val => val,
-        } as u32)
+/// The LINIE ID of a *L reference (without #), padded or not. None if the reference contains
+/// other characters than digits.
+fn parse_line_reference(reference: &str) -> Option<i32> {
+    let reference = reference.trim();
+    if reference.is_empty() || !reference.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    reference.parse().ok()
+}
+
+/// A negative time means that the passengers cannot alight (arrival) or board (departure) at the
+/// stop.
+fn create_route_entry(
+    stop_id: i32,
+    arrival_time: Option<i32>,
+    departure_time: Option<i32>,
+) -> PResult<JourneyRouteEntry> {
+    let mut route_entry = JourneyRouteEntry::new(
+        stop_id,
+        create_route_time(stop_id, arrival_time)?,
+        create_route_time(stop_id, departure_time)?,
+    );
+    route_entry.set_alighting_allowed(arrival_time.is_none_or(|time| time >= 0));
+    route_entry.set_boarding_allowed(departure_time.is_none_or(|time| time >= 0));
+    Ok(route_entry)
+}
+
+/// An invalid time of a route row is reported with the stop of the row.
+fn create_route_time(stop_id: i32, time: Option<i32>) -> PResult<Option<ServiceTime>> {
+    create_service_time(time).map_err(|error| ParsingError::InvalidRouteTime {
+        stop_id,
+        // unwrap: There is an error only if there is a time.
+        value: time.unwrap(),
+        source: Box::new(error),
     })
-    .transpose()
+}
+
+/// Times after midnight are written as 2400 + time (e.g. 02530 for 01:30 on the next day).
+/// A journey crossing two midnights has times above 4800, and so on.
+/// A negative time means that boarding or alighting is not possible, its sign is ignored here.
+fn create_service_time(time: Option<i32>) -> PResult<Option<ServiceTime>> {
+    time.map(|value| create_service_time_from_value(value.abs()))
+        .transpose()
 }
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "serde")]
     use crate::parsing::tests::get_json_values;
 
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
     //use crate::parsing::tests::get_json_values;
+    use chrono::NaiveTime;
     use pretty_assertions::assert_eq;
 
+    #[cfg(feature = "serde")]
     #[test]
     fn parsing_rows() {
         let rows = vec![
@@ -955,8 +1467,7 @@ mod tests {
             "8509000 Chur                  00948                        %".to_string(),
         ];
         let auto_increment = AutoIncrement::new();
-        let mut data = FxHashMap::default();
-        let mut pk_type_converter = FxHashSet::default();
+        let mut accumulator = JourneyAccumulator::new(true);
         let mut transport_types_pk_type_converter = FxHashMap::<String, i32>::default();
         transport_types_pk_type_converter.insert("IR".to_string(), 100);
         let mut attributes_pk_type_converter = FxHashMap::<String, i32>::default();
@@ -966,8 +1477,7 @@ mod tests {
         for line in rows {
             parse_line(
                 &line,
-                &mut data,
-                &mut pk_type_converter,
+                &mut accumulator,
                 &auto_increment,
                 &transport_types_pk_type_converter,
                 &attributes_pk_type_converter,
@@ -992,7 +1502,8 @@ mod tests {
                 "departure_time": null,
                 "arrival_time": null,
                 "extra_field_1": null,
-                "extra_field_2": null
+                "extra_field_2": null,
+                "raw_code": null
               }
             ],
             "TransportType": [
@@ -1004,7 +1515,8 @@ mod tests {
                 "departure_time": null,
                 "arrival_time": null,
                 "extra_field_1": null,
-                "extra_field_2": null
+                "extra_field_2": null,
+                "raw_code": null
               }
             ],
             "InformationText": [
@@ -1016,7 +1528,8 @@ mod tests {
                 "departure_time": null,
                 "arrival_time": null,
                 "extra_field_1": "JY",
-                "extra_field_2": null
+                "extra_field_2": null,
+                "raw_code": null
               }
             ],
             "BitField": [
@@ -1028,7 +1541,8 @@ mod tests {
                 "departure_time": null,
                 "arrival_time": null,
                 "extra_field_1": null,
-                "extra_field_2": null
+                "extra_field_2": null,
+                "raw_code": null
               }
             ],
             "ExchangeTimeBoarding": [
@@ -1040,7 +1554,8 @@ mod tests {
                 "departure_time": null,
                 "arrival_time": null,
                 "extra_field_1": null,
-                "extra_field_2": 2
+                "extra_field_2": 2,
+                "raw_code": null
               }
             ],
             "Line": [
@@ -1052,7 +1567,8 @@ mod tests {
                 "departure_time": null,
                 "arrival_time": null,
                 "extra_field_1": "35",
-                "extra_field_2": null
+                "extra_field_2": null,
+                "raw_code": null
               }
             ],
             "Direction": [
@@ -1064,36 +1580,326 @@ mod tests {
                 "departure_time": null,
                 "arrival_time": null,
                 "extra_field_1": "H",
-                "extra_field_2": null
+                "extra_field_2": null,
+                "raw_code": null
               }
             ]
           },
           "route": [
             {
               "stop_id": 8507000,
-              "arrival_time": null,
-              "departure_time": "06:38:00"
+              "arrival": null,
+              "departure": 398,
+              "boarding_allowed": true,
+              "alighting_allowed": true
             },
             {
               "stop_id": 8508005,
-              "arrival_time": "06:52:00",
-              "departure_time": "06:53:00"
+              "arrival": 412,
+              "departure": 413,
+              "boarding_allowed": true,
+              "alighting_allowed": true
             },
             {
               "stop_id": 8508008,
-              "arrival_time": "07:04:00",
-              "departure_time": "07:05:00"
+              "arrival": 424,
+              "departure": 425,
+              "boarding_allowed": true,
+              "alighting_allowed": true
             },
             {
               "stop_id": 8509000,
-              "arrival_time": "09:48:00",
-              "departure_time": null
+              "arrival": 588,
+              "departure": null,
+              "boarding_allowed": true,
+              "alighting_allowed": true
             }
-          ]
+          ],
+          "number_changes": []
         }"#;
 
-        let (attribute, reference) = get_json_values(data.get(&1).unwrap(), reference).unwrap();
+        let (attribute, reference) =
+            get_json_values(accumulator.data.get(&1).unwrap(), reference).unwrap();
         assert_eq!(attribute, reference);
+        assert_eq!(accumulator.stop_names.as_ref().unwrap().len(), 4);
+        assert_eq!(
+            accumulator
+                .stop_names
+                .as_ref()
+                .unwrap()
+                .get(&8509000)
+                .map(String::as_str),
+            Some("Chur")
+        );
+    }
+
+    #[test]
+    fn parsing_rows_after_midnight() {
+        let rows = vec![
+            "*Z 000470 000011   101                                     %".to_string(),
+            "*I hi 8503000 8509000        000018037  02432  04805       %".to_string(),
+            "8500010 Basel SBB                    02330                 %".to_string(),
+            "8503000 Zürich HB             02425  02432                 %".to_string(),
+            "8509000 Chur                  04805                        %".to_string(),
+        ];
+        let auto_increment = AutoIncrement::new();
+        let mut accumulator = JourneyAccumulator::new(true);
+        let empty_pk_type_converter = FxHashMap::<String, i32>::default();
+
+        for line in rows {
+            parse_line(
+                &line,
+                &mut accumulator,
+                &auto_increment,
+                &empty_pk_type_converter,
+                &empty_pk_type_converter,
+                &empty_pk_type_converter,
+            )
+            .unwrap();
+        }
+
+        let route = accumulator.data.get(&1).unwrap().route();
+        assert_eq!(route[0].departure_day_offset(), 0);
+        assert_eq!(route[1].arrival_day_offset(), 1);
+        assert_eq!(route[1].departure_day_offset(), 1);
+        assert_eq!(
+            route[1].arrival_time(),
+            Some(NaiveTime::from_hms_opt(0, 25, 0).unwrap())
+        );
+        assert_eq!(route[2].arrival_day_offset(), 2);
+        assert_eq!(
+            route[2].arrival_time(),
+            Some(NaiveTime::from_hms_opt(0, 5, 0).unwrap())
+        );
+
+        let entry =
+            &accumulator.data.get(&1).unwrap().metadata()[&JourneyMetadataType::InformationText][0];
+        assert_eq!(entry.departure_time(), Some(ServiceTime::new(24 * 60 + 32)));
+        assert_eq!(entry.arrival_time().map(|time| time.day_offset()), Some(2));
+    }
+
+    #[test]
+    fn parsing_rows_with_negative_times() {
+        let rows = vec![
+            "*Z 000470 000011   101                                     %".to_string(),
+            "8500010 Basel SBB                    00800                 %".to_string(),
+            "0000175 Hauenstein-Basistunn -00833 -00833                 %".to_string(),
+            "8503000 Zürich HB             00900 -00905                 %".to_string(),
+            "8509000 Chur                  01005                        %".to_string(),
+        ];
+        let auto_increment = AutoIncrement::new();
+        let mut accumulator = JourneyAccumulator::new(true);
+        let empty_pk_type_converter = FxHashMap::<String, i32>::default();
+
+        for line in rows {
+            parse_line(
+                &line,
+                &mut accumulator,
+                &auto_increment,
+                &empty_pk_type_converter,
+                &empty_pk_type_converter,
+                &empty_pk_type_converter,
+            )
+            .unwrap();
+        }
+
+        let route = accumulator.data.get(&1).unwrap().route();
+        let flags: Vec<_> = route
+            .iter()
+            .map(|entry| (entry.alighting_allowed(), entry.boarding_allowed()))
+            .collect();
+        assert_eq!(
+            flags,
+            vec![(true, true), (false, false), (true, false), (true, true)]
+        );
+        assert_eq!(
+            route[1].departure_time(),
+            Some(NaiveTime::from_hms_opt(8, 33, 0).unwrap())
+        );
+        assert_eq!(
+            route[2].departure_time(),
+            Some(NaiveTime::from_hms_opt(9, 5, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn parsing_rows_with_number_change() {
+        let rows = vec![
+            "*Z 000315 000011   101                                     %".to_string(),
+            "8500010 Basel SBB                    00800 000315 000011 %".to_string(),
+            "8500090 Basel Bad Bf          00805  00810 000315 000011 %".to_string(),
+            "8000191 Karlsruhe Hbf         00935  00940 009315 80____ %".to_string(),
+            "8000105 Frankfurt(Main)Hbf    01100        009315        %".to_string(),
+        ];
+        let auto_increment = AutoIncrement::new();
+        let mut accumulator = JourneyAccumulator::new(true);
+        let empty_pk_type_converter = FxHashMap::<String, i32>::default();
+
+        for line in rows {
+            parse_line(
+                &line,
+                &mut accumulator,
+                &auto_increment,
+                &empty_pk_type_converter,
+                &empty_pk_type_converter,
+                &empty_pk_type_converter,
+            )
+            .unwrap();
+        }
+
+        let journey = accumulator.data.get(&1).unwrap();
+        assert_eq!(
+            journey.number_changes(),
+            &[JourneyNumberChange::new(
+                8000191,
+                9315,
+                "80____".to_string()
+            )]
+        );
+        assert_eq!(journey.number_at(8500090), (315, "000011"));
+        assert_eq!(journey.number_at(8000191), (9315, "80____"));
+        assert_eq!(journey.number_at(8000105), (9315, "80____"));
+    }
+
+    #[test]
+    fn stop_names_are_only_collected_for_placeholder_stops() {
+        for collect_stop_names in [false, true] {
+            let auto_increment = AutoIncrement::new();
+            let mut accumulator = JourneyAccumulator::new(collect_stop_names);
+            for line in [
+                "*Z 000470 000011   101                                     %",
+                "8500010 Basel SBB                    00800                 %",
+            ] {
+                parse_line(
+                    line,
+                    &mut accumulator,
+                    &auto_increment,
+                    &FxHashMap::default(),
+                    &FxHashMap::default(),
+                    &FxHashMap::default(),
+                )
+                .unwrap();
+            }
+
+            assert_eq!(
+                accumulator.stop_names.map(|stop_names| stop_names.len()),
+                collect_stop_names.then_some(1)
+            );
+        }
+    }
+
+    #[test]
+    fn parsing_rows_with_malformed_time() {
+        let rows = [
+            "*Z 000470 000011   101                                     %".to_string(),
+            "8500010 Basel SBB                    00875                 %".to_string(),
+        ];
+        let auto_increment = AutoIncrement::new();
+        let mut accumulator = JourneyAccumulator::new(true);
+        let empty_pk_type_converter = FxHashMap::<String, i32>::default();
+
+        let results = rows
+            .iter()
+            .map(|line| {
+                parse_line(
+                    line,
+                    &mut accumulator,
+                    &auto_increment,
+                    &empty_pk_type_converter,
+                    &empty_pk_type_converter,
+                    &empty_pk_type_converter,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert!(results[0].is_ok());
+        match &results[1] {
+            Err(ParsingError::InvalidRouteTime {
+                stop_id,
+                value,
+                source,
+            }) => {
+                assert_eq!((*stop_id, *value), (8500010, 875));
+                assert!(matches!(
+                    **source,
+                    ParsingError::UnableToBuildTime(8, 75, 0)
+                ));
+            }
+            result => panic!("Unexpected result: {result:?}"),
+        }
+    }
+
+    fn build_journey_with_times(id: i32, times: &[(Option<&str>, Option<&str>)]) -> Journey {
+        let mut journey = Journey::new(id, id * 100, "000011".to_string());
+        for (index, (arrival, departure)) in times.iter().enumerate() {
+            journey.add_route_entry(JourneyRouteEntry::new(
+                index as i32 + 1,
+                arrival.map(|t| NaiveTime::parse_from_str(t, "%H:%M").unwrap().into()),
+                departure.map(|t| NaiveTime::parse_from_str(t, "%H:%M").unwrap().into()),
+            ));
+        }
+        journey
+    }
+
+    fn build_journeys_with_one_backwards() -> FxHashMap<i32, Journey> {
+        let mut data = FxHashMap::default();
+        data.insert(
+            1,
+            build_journey_with_times(1, &[(None, Some("08:00")), (Some("08:10"), None)]),
+        );
+        data.insert(
+            2,
+            build_journey_with_times(
+                2,
+                &[
+                    (None, Some("09:00")),
+                    (Some("08:50"), Some("09:05")),
+                    (Some("09:10"), None),
+                ],
+            ),
+        );
+        data
+    }
+
+    #[test]
+    fn check_route_times_reports() {
+        let mut data = build_journeys_with_one_backwards();
+        let mut report = ParseReport::new();
+        check_route_times(&mut data, RouteTimeCheck::Report, &mut report);
+
+        assert_eq!(data.len(), 2);
+        assert_eq!(
+            report.issues(),
+            &vec![ParseIssue::NonMonotonicRouteTimes {
+                journey_id: 2,
+                legacy_id: 200,
+                administration: "000011".to_string(),
+                stop_id: 2,
+                action: RouteTimeCheck::Report,
+            }]
+        );
+    }
+
+    #[test]
+    fn check_route_times_drops_and_repairs() {
+        let mut data = build_journeys_with_one_backwards();
+        let mut report = ParseReport::new();
+        check_route_times(&mut data, RouteTimeCheck::Drop, &mut report);
+        assert_eq!(data.len(), 1);
+        assert!(data.contains_key(&1));
+        assert_eq!(report.issues().len(), 1);
+
+        let mut data = build_journeys_with_one_backwards();
+        let mut report = ParseReport::new();
+        check_route_times(&mut data, RouteTimeCheck::Repair, &mut report);
+        assert_eq!(data.len(), 2);
+        assert_eq!(data.get(&2).unwrap().find_non_monotonic_route_entry(), None);
+        assert_eq!(report.issues().len(), 1);
+
+        let mut data = build_journeys_with_one_backwards();
+        let mut report = ParseReport::new();
+        check_route_times(&mut data, RouteTimeCheck::Disabled, &mut report);
+        assert!(report.is_empty());
     }
 
     mod row_z {
@@ -1161,6 +1967,48 @@ mod tests {
                 "% Fahrtnummer 123456, für TU 11 (SBB), mit Variante 101 (ignore), 12 mal, alle 60 Minuten"
             );
         }
+
+        #[test]
+        fn cycles_are_expanded() {
+            let auto_increment = AutoIncrement::new();
+            let mut accumulator = JourneyAccumulator::new(false);
+
+            for line in [
+                "*Z 000007 000011   101 002 030 %",
+                "8500101 Aarstadt                     02320 000007 000011 %",
+                "8500104 Seeburg               02340        000007 000011 %",
+            ] {
+                parse_line(
+                    line,
+                    &mut accumulator,
+                    &auto_increment,
+                    &FxHashMap::default(),
+                    &FxHashMap::default(),
+                    &FxHashMap::default(),
+                )
+                .unwrap();
+            }
+            assert_eq!(accumulator.data[&1].num_cycles(), Some(2));
+            assert_eq!(accumulator.data[&1].cycle_duration(), Some(30));
+            assert_eq!(accumulator.data[&1].cycle_offsets(), vec![30, 60]);
+
+            expand_cycles(&mut accumulator.data, &auto_increment);
+
+            assert_eq!(accumulator.data.len(), 3);
+            let departures: Vec<_> = (1..=3)
+                .map(|id| {
+                    let journey = &accumulator.data[&id];
+                    assert_eq!(journey.legacy_id(), 7);
+                    assert_eq!(journey.num_cycles(), None);
+                    journey.route()[0].departure().unwrap().minutes()
+                })
+                .collect();
+            assert_eq!(departures, vec![23 * 60 + 20, 23 * 60 + 50, 24 * 60 + 20]);
+            assert_eq!(
+                accumulator.data[&3].route()[1].arrival().unwrap().minutes(),
+                24 * 60 + 40
+            );
+        }
     }
 
     mod row_g {
@@ -1497,6 +2345,38 @@ mod tests {
             assert_eq!(None, arrival_time);
             assert_eq!("%", res.trim());
         }
+
+        #[test]
+        fn lenient_references() {
+            let auto_increment = AutoIncrement::new();
+            let mut accumulator = JourneyAccumulator::new(false);
+
+            for line in [
+                "*Z 002359 000011   101                                     %",
+                "*L #0000022 8500010 8507492                                %",
+                "*L # 22     8500010 8507492                                %",
+                "*L #22A     8500010 8507492                                %",
+                "*L S22      8500010 8507492                                %",
+            ] {
+                parse_line(
+                    line,
+                    &mut accumulator,
+                    &auto_increment,
+                    &FxHashMap::default(),
+                    &FxHashMap::default(),
+                    &FxHashMap::default(),
+                )
+                .unwrap();
+            }
+
+            let entries = &accumulator.data[&1].metadata()[&JourneyMetadataType::Line];
+            assert_eq!(entries[0].resource_id(), Some(22));
+            assert_eq!(entries[1].resource_id(), Some(22));
+            assert_eq!(entries[2].resource_id(), None);
+            assert_eq!(entries[2].raw_code(), Some("#22A"));
+            assert_eq!(entries[3].resource_id(), None);
+            assert_eq!(entries[3].raw_code(), None);
+        }
     }
 
     mod row_r {
@@ -1590,6 +2470,52 @@ mod tests {
             assert_eq!(None, arrival_time);
             assert_eq!("% gilt für die gesamte Hin-Richtung", res);
         }
+
+        #[test]
+        fn padded_and_alphanumeric_codes() {
+            let (_, (_, ref_direction_code, stop_from_id, ..)) =
+                row_r_parser("*R R R63     1300146 8574808             %").unwrap();
+            assert_eq!("R63", ref_direction_code);
+            assert_eq!(Some(1300146), stop_from_id);
+
+            let (_, (_, ref_direction_code, ..)) =
+                row_r_parser("*R R 0000063 1300146 8574808             %").unwrap();
+            assert_eq!("R63", ref_direction_code);
+
+            let (_, (_, ref_direction_code, ..)) =
+                row_r_parser("*R H RAB12X  1300146 8574808             %").unwrap();
+            assert_eq!("RAB12X", ref_direction_code);
+        }
+
+        #[test]
+        fn unknown_code_is_kept_raw() {
+            let auto_increment = AutoIncrement::new();
+            let mut accumulator = JourneyAccumulator::new(false);
+            let mut directions_pk_type_converter = FxHashMap::<String, i32>::default();
+            directions_pk_type_converter.insert("R63".to_string(), 63);
+
+            for line in [
+                "*Z 002359 000011   101                                     %",
+                "*R R R000063 1300146 8574808                               %",
+                "*R H RAB12X  8574808 1300146                               %",
+            ] {
+                parse_line(
+                    line,
+                    &mut accumulator,
+                    &auto_increment,
+                    &FxHashMap::default(),
+                    &FxHashMap::default(),
+                    &directions_pk_type_converter,
+                )
+                .unwrap();
+            }
+
+            let entries = &accumulator.data[&1].metadata()[&JourneyMetadataType::Direction];
+            assert_eq!(entries[0].resource_id(), Some(63));
+            assert_eq!(entries[0].raw_code(), None);
+            assert_eq!(entries[1].resource_id(), None);
+            assert_eq!(entries[1].raw_code(), Some("RAB12X"));
+        }
     }
 
     mod row_ci_co {
@@ -1684,6 +2610,208 @@ mod tests {
         }
     }
 
+    mod row_unknown {
+        // Note this useful idiom: importing names from outer (for mod tests) scope.
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn unknown_rows_are_kept() {
+            let auto_increment = AutoIncrement::new();
+            let mut accumulator = JourneyAccumulator::new(false);
+
+            for line in [
+                "*Z 002359 000011   101                                     %",
+                "*XY 1234 ABC % Neue Zeile",
+                "*XY 5678 %",
+                "*QZ                                                        %",
+            ] {
+                parse_line(
+                    line,
+                    &mut accumulator,
+                    &auto_increment,
+                    &FxHashMap::default(),
+                    &FxHashMap::default(),
+                    &FxHashMap::default(),
+                )
+                .unwrap();
+            }
+
+            assert_eq!(
+                accumulator.data[&1].unknown_rows(),
+                vec![
+                    ("XY", "1234 ABC % Neue Zeile"),
+                    ("XY", "5678 %"),
+                    ("QZ", "%"),
+                ]
+            );
+
+            let mut report = ParseReport::new();
+            report_unknown_lines(&accumulator.data, &mut report);
+            assert_eq!(
+                report.issues(),
+                &vec![
+                    ParseIssue::UnknownJourneyRows {
+                        line_type: "QZ".to_string(),
+                        count: 1,
+                    },
+                    ParseIssue::UnknownJourneyRows {
+                        line_type: "XY".to_string(),
+                        count: 2,
+                    },
+                ]
+            );
+        }
+
+        #[test]
+        fn malformed_known_row_is_an_error() {
+            assert!(row_unknown_combinator("*Z 00235").is_err());
+            assert!(row_unknown_combinator("*KW abc").is_err());
+        }
+    }
+
+    mod row_sh {
+        // Note this useful idiom: importing names from outer (for mod tests) scope.
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn success_with_bit_field() {
+            let input = "*SH 8509002 004711                                          % HS-Nr. 8509002 wird an den Gültigkeitstagen 004711 bedient";
+            let (res, row_sh) = row_sh_combinator(input).unwrap();
+            match row_sh {
+                JourneyLines::ShLine {
+                    stop_id,
+                    bit_field_id,
+                } => {
+                    assert_eq!(8509002, stop_id);
+                    assert_eq!(Some(4711), bit_field_id);
+                }
+                l => panic!("ShLine expected but got {l:?}"),
+            }
+            assert_eq!(
+                "% HS-Nr. 8509002 wird an den Gültigkeitstagen 004711 bedient",
+                res.trim()
+            );
+        }
+
+        #[test]
+        fn stored_as_seasonal_stop() {
+            let auto_increment = AutoIncrement::new();
+            let mut accumulator = JourneyAccumulator::new(false);
+
+            for line in [
+                "*Z 002359 000011   101                                     %",
+                "*SH 8509002 004711                                          %",
+            ] {
+                parse_line(
+                    line,
+                    &mut accumulator,
+                    &auto_increment,
+                    &FxHashMap::default(),
+                    &FxHashMap::default(),
+                    &FxHashMap::default(),
+                )
+                .unwrap();
+            }
+
+            let entries = &accumulator.data[&1].metadata()[&JourneyMetadataType::SeasonalStop];
+            assert_eq!(entries.len(), 1);
+            assert_eq!(entries[0].from_stop_id(), Some(8509002));
+            assert_eq!(entries[0].bit_field_id(), Some(4711));
+        }
+    }
+
+    mod row_kw {
+        // Note this useful idiom: importing names from outer (for mod tests) scope.
+        use super::*;
+        use crate::models::CouplingType;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn success_kw_and_kwz() {
+            let (res, row_kw) = row_kw_combinator(
+                "*KW 002345 000011 8500010 8500090                           % Kurswagen 2345",
+            )
+            .unwrap();
+            match row_kw {
+                JourneyLines::KwLine {
+                    is_train,
+                    journey_id,
+                    administration,
+                    stop_from_id,
+                    stop_to_id,
+                    departure_time,
+                    arrival_time,
+                } => {
+                    assert!(!is_train);
+                    assert_eq!(2345, journey_id);
+                    assert_eq!("000011", administration);
+                    assert_eq!(Some(8500010), stop_from_id);
+                    assert_eq!(Some(8500090), stop_to_id);
+                    assert_eq!(None, departure_time);
+                    assert_eq!(None, arrival_time);
+                }
+                l => panic!("KwLine expected but got {l:?}"),
+            }
+            assert_eq!("% Kurswagen 2345", res.trim());
+
+            let (_, row_kwz) =
+                row_kw_combinator("*KWZ 000567 000011                 001230 001310           %")
+                    .unwrap();
+            match row_kwz {
+                JourneyLines::KwLine {
+                    is_train,
+                    journey_id,
+                    stop_from_id,
+                    departure_time,
+                    arrival_time,
+                    ..
+                } => {
+                    assert!(is_train);
+                    assert_eq!(567, journey_id);
+                    assert_eq!(None, stop_from_id);
+                    assert_eq!(Some(1230), departure_time);
+                    assert_eq!(Some(1310), arrival_time);
+                }
+                l => panic!("KwLine expected but got {l:?}"),
+            }
+        }
+
+        #[test]
+        fn couplings_of_journey() {
+            let auto_increment = AutoIncrement::new();
+            let mut accumulator = JourneyAccumulator::new(false);
+
+            for line in [
+                "*Z 002359 000011   101                                     %",
+                "*KW 002345 000011 8500010 8500090                          %",
+                "*KWZ 000567 000011 8500090 8500218                         %",
+            ] {
+                parse_line(
+                    line,
+                    &mut accumulator,
+                    &auto_increment,
+                    &FxHashMap::default(),
+                    &FxHashMap::default(),
+                    &FxHashMap::default(),
+                )
+                .unwrap();
+            }
+
+            let couplings = accumulator.data[&1].couplings();
+            assert_eq!(couplings.len(), 2);
+            assert_eq!(couplings[0].coupling_type(), CouplingType::ThroughCoach);
+            assert_eq!(couplings[0].journey_legacy_id(), 2345);
+            assert_eq!(couplings[0].administration(), "000011");
+            assert_eq!(couplings[0].from_stop_id(), Some(8500010));
+            assert_eq!(couplings[0].until_stop_id(), Some(8500090));
+            assert_eq!(couplings[1].coupling_type(), CouplingType::Train);
+            assert_eq!(couplings[1].journey_legacy_id(), 567);
+            assert_eq!(couplings[1].until_stop_id(), Some(8500218));
+        }
+    }
+
     mod row_journey_description {
         type JourneyDescriptorRow = (i32, String, Option<i32>, Option<i32>, Option<i32>, String);
 
@@ -1750,6 +2878,45 @@ mod tests {
             assert_eq!("%", res.trim());
         }
 
+        #[test]
+        fn success_with_umlauts() {
+            let input = "8503001 Bärschwil Dörfli      00803  00804 000007 000001 %";
+            let (_, (stop_id, stop_name, arrival_time, departure_time, journey_id, administration)) =
+                row_journey_description_parser(input).unwrap();
+
+            assert_eq!(8503001, stop_id);
+            assert_eq!("Bärschwil Dörfli", stop_name);
+            assert_eq!(Some(803), arrival_time);
+            assert_eq!(Some(804), departure_time);
+            assert_eq!(Some(7), journey_id);
+            assert_eq!("000001", administration);
+        }
+
+        #[test]
+        fn success_with_long_name() {
+            let input = "8503000 Zürich Hürlimann-Areal, Südstraße  00803  00804 000007 000001 %";
+            let (
+                res,
+                (stop_id, stop_name, arrival_time, departure_time, journey_id, administration),
+            ) = row_journey_description_parser(input).unwrap();
+
+            assert_eq!(8503000, stop_id);
+            assert_eq!("Zürich Hürlimann-Areal, Südstraße", stop_name);
+            assert_eq!(Some(803), arrival_time);
+            assert_eq!(Some(804), departure_time);
+            assert_eq!(Some(7), journey_id);
+            assert_eq!("000001", administration);
+            assert_eq!("%", res.trim());
+
+            let input = "8503000 Zürich Hürlimann-Areal, Südstraße         00804 000007 000001 %";
+            let (_, (_, stop_name, arrival_time, departure_time, ..)) =
+                row_journey_description_parser(input).unwrap();
+
+            assert_eq!("Zürich Hürlimann-Areal, Südstraße", stop_name);
+            assert_eq!(None, arrival_time);
+            assert_eq!(Some(804), departure_time);
+        }
+
         #[test]
         fn success_journey_all_options() {
             let input = "0053291 Wannseebrücke         02015  02015 052344 80____ % HS-Nr. 0053291 Ankunft 20:15, Abfahrt 20:15, Fahrtnummer 052344, Verwaltung 80____ (DB)";
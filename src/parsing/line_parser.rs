@@ -54,9 +54,11 @@ use crate::{
     error::{HResult, HrdfError},
     models::{Color, Line, Model},
     parsing::{
-        error::{PResult, ParsingError},
+        ParsingOptions,
+        error::{LineErrors, PResult, ParsingError},
         helpers::{
-            i16_from_n_digits_parser, i32_from_n_digits_parser, read_lines, string_till_eol_parser,
+            i16_from_n_digits_parser, i32_from_n_digits_parser, read_lines,
+            string_from_n_chars_parser, string_till_eol_parser,
         },
     },
     storage::ResourceStorage,
@@ -85,8 +87,10 @@ enum LineType {
         long_name: String,
     },
     // * Line type R T: Line region name (reserved for BAV ID)
-    #[allow(unused)]
-    RTline,
+    RTline {
+        id: i32,
+        region_name: String,
+    },
     // * Line type D T: Line description
     DTline {
         id: i32,
@@ -106,12 +110,17 @@ enum LineType {
         g: i16,
         b: i16,
     },
-    // * Line type H: Main line (not present)
-    #[allow(unused)]
-    Hline,
-    // * Line type I: Line info texts (not present)
-    #[allow(unused)]
-    Iline,
+    // * Line type H: Main line
+    Hline {
+        id: i32,
+        main_line_id: i32,
+    },
+    // * Line type I: Line info texts
+    Iline {
+        id: i32,
+        code: String,
+        info_text_id: i32,
+    },
 }
 
 fn row_k_nt_lt_dt_w_combinator(input: &str) -> IResult<&str, Option<LineType>> {
@@ -120,7 +129,14 @@ fn row_k_nt_lt_dt_w_combinator(input: &str) -> IResult<&str, Option<LineType>> {
             i32_from_n_digits_parser(7),
             preceded(
                 char(' '),
-                alt((tag("K "), tag("N T "), tag("L T "), tag("W "), tag("D T "))),
+                alt((
+                    tag("K "),
+                    tag("N T "),
+                    tag("L T "),
+                    tag("R T "),
+                    tag("W "),
+                    tag("D T "),
+                )),
             ),
             string_till_eol_parser,
         ),
@@ -134,6 +150,10 @@ fn row_k_nt_lt_dt_w_combinator(input: &str) -> IResult<&str, Option<LineType>> {
                 id,
                 long_name: name,
             }),
+            "R T " => Some(LineType::RTline {
+                id,
+                region_name: name,
+            }),
             "W " => Some(LineType::Wline {
                 id,
                 internal_designation: name,
@@ -168,8 +188,43 @@ fn row_f_b_combinator(input: &str) -> IResult<&str, Option<LineType>> {
     .parse(input)
 }
 
+fn row_h_combinator(input: &str) -> IResult<&str, Option<LineType>> {
+    map(
+        (
+            i32_from_n_digits_parser(7),
+            preceded(tag(" H "), i32_from_n_digits_parser(7)),
+        ),
+        |(id, main_line_id)| Some(LineType::Hline { id, main_line_id }),
+    )
+    .parse(input)
+}
+
+fn row_i_combinator(input: &str) -> IResult<&str, Option<LineType>> {
+    map(
+        (
+            i32_from_n_digits_parser(7),
+            preceded(tag(" I "), string_from_n_chars_parser(2)),
+            preceded(char(' '), i32_from_n_digits_parser(9)),
+        ),
+        |(id, code, info_text_id)| {
+            Some(LineType::Iline {
+                id,
+                code,
+                info_text_id,
+            })
+        },
+    )
+    .parse(input)
+}
+
 fn parse_line(line: &str, data: &mut FxHashMap<i32, Line>) -> PResult<()> {
-    let (_, line_row) = alt((row_k_nt_lt_dt_w_combinator, row_f_b_combinator)).parse(line)?;
+    let (_, line_row) = alt((
+        row_k_nt_lt_dt_w_combinator,
+        row_f_b_combinator,
+        row_h_combinator,
+        row_i_combinator,
+    ))
+    .parse(line)?;
 
     match line_row.ok_or(ParsingError::MissingLineType)? {
         LineType::Kline { id, name } => {
@@ -199,6 +254,18 @@ fn parse_line(line: &str, data: &mut FxHashMap<i32, Line>) -> PResult<()> {
             }
             line.set_long_name(long_name);
         }
+        LineType::RTline { id, region_name } => {
+            let line = data.get_mut(&id).ok_or_else(|| {
+                ParsingError::UnknownId(format!("For id: {id}, type K row missing."))
+            })?;
+            if id != line.id() {
+                return Err(ParsingError::UnknownId(format!(
+                    "Line id not corresponding, {id}, {}",
+                    line.id()
+                )));
+            }
+            line.set_region_name(region_name);
+        }
         LineType::Wline {
             id,
             internal_designation,
@@ -251,19 +318,48 @@ fn parse_line(line: &str, data: &mut FxHashMap<i32, Line>) -> PResult<()> {
             }
             line.set_background_color(Color::new(r, g, b));
         }
-        l => {
-            return Err(ParsingError::Unknown(format!("Line not parsed {l:?}")));
+        LineType::Hline { id, main_line_id } => {
+            let line = data.get_mut(&id).ok_or_else(|| {
+                ParsingError::UnknownId(format!("For id: {id}, type K row missing."))
+            })?;
+            if id != line.id() {
+                return Err(ParsingError::UnknownId(format!(
+                    "Line id not corresponding, {id}, {}",
+                    line.id()
+                )));
+            }
+            line.set_main_line_id(main_line_id);
+        }
+        LineType::Iline {
+            id,
+            code,
+            info_text_id,
+        } => {
+            let line = data.get_mut(&id).ok_or_else(|| {
+                ParsingError::UnknownId(format!("For id: {id}, type K row missing."))
+            })?;
+            if id != line.id() {
+                return Err(ParsingError::UnknownId(format!(
+                    "Line id not corresponding, {id}, {}",
+                    line.id()
+                )));
+            }
+            line.add_info_text_ref(code, info_text_id);
         }
     }
 
     Ok(())
 }
 
-pub fn parse(path: &Path) -> HResult<ResourceStorage<Line>> {
+pub fn parse(
+    path: &Path,
+    options: &ParsingOptions,
+    errors: &mut LineErrors,
+) -> HResult<ResourceStorage<Line>> {
     log::info!("Parsing LINIE...");
 
     let file = path.join("LINIE");
-    let lines = read_lines(&file, 0)?;
+    let lines = read_lines(&file, options)?;
 
     let mut data = FxHashMap::default();
 
@@ -272,12 +368,13 @@ pub fn parse(path: &Path) -> HResult<ResourceStorage<Line>> {
         .enumerate()
         .filter(|(_, line)| !line.trim().is_empty())
         .try_for_each(|(line_number, line)| {
-            parse_line(&line, &mut data).map_err(|e| HrdfError::Parsing {
+            let result = parse_line(&line, &mut data).map_err(|e| HrdfError::Parsing {
                 error: e,
                 file: String::from(file.to_string_lossy()),
                 line,
                 line_number,
-            })
+            });
+            errors.handle(result).map(|_| ())
         })?;
 
     Ok(ResourceStorage::new(data))
@@ -285,6 +382,7 @@ pub fn parse(path: &Path) -> HResult<ResourceStorage<Line>> {
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "serde")]
     use crate::parsing::tests::get_json_values;
 
     use super::*;
@@ -451,6 +549,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_parse_line_k_creates_new_line() {
         let mut data = FxHashMap::default();
@@ -464,14 +563,34 @@ mod tests {
                 "short_name": "",
                 "long_name": "",
                 "internal_designation": "",
+                "region_name": "",
                 "description": "",
                 "text_color": {"r":0,"g":0,"b":0},
-                "background_color": {"r":0,"g":0,"b":0}
+                "background_color": {"r":0,"g":0,"b":0},
+                "info_text_refs": []
             }"#;
         let (line, reference) = get_json_values(line, reference).unwrap();
         assert_eq!(line, reference);
     }
 
+    #[test]
+    fn test_row_rt_h_i_combinators_valid() {
+        let mut data = FxHashMap::default();
+        parse_line("0000001 K ch:1:SLNID:33:1", &mut data).unwrap();
+        parse_line("0000001 R T 033", &mut data).unwrap();
+        parse_line("0000001 H 0000002", &mut data).unwrap();
+        parse_line("0000001 I TU 000000001", &mut data).unwrap();
+        parse_line("0000001 I XI 000000042", &mut data).unwrap();
+
+        let line = &data[&1];
+        assert_eq!(line.region_name(), "033");
+        assert_eq!(line.main_line_id(), Some(2));
+        assert_eq!(
+            line.info_text_refs(),
+            &[("TU".to_string(), 1), ("XI".to_string(), 42)]
+        );
+    }
+
     #[test]
     #[should_panic]
     fn test_parse_line_nt_requires_existing_k() {
@@ -479,6 +598,7 @@ mod tests {
         parse_line("0000001 N T ShortName", &mut data).unwrap();
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_parse_line_complete_sequence() {
         let mut data = FxHashMap::default();
@@ -490,6 +610,8 @@ mod tests {
         parse_line("0000001 D T Wow what a description", &mut data).unwrap();
         parse_line("0000001 F 255 128 064", &mut data).unwrap();
         parse_line("0000001 B 010 020 030", &mut data).unwrap();
+        parse_line("0000001 H 0000002", &mut data).unwrap();
+        parse_line("0000001 I TU 000000001", &mut data).unwrap();
 
         assert_eq!(data.len(), 1);
         let line = data.get(&1).unwrap();
@@ -500,14 +622,18 @@ mod tests {
                 "short_name": "Short",
                 "long_name": "Long Name",
                 "internal_designation": "internal",
+                "region_name": "",
                 "description": "Wow what a description",
                 "text_color": {"r":255,"g":128,"b":64},
-                "background_color": {"r":10,"g":20,"b":30}
+                "background_color": {"r":10,"g":20,"b":30},
+                "main_line_id": 2,
+                "info_text_refs": [["TU", 1]]
             }"#;
         let (line, reference) = get_json_values(line, reference).unwrap();
         assert_eq!(line, reference);
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_parse_line_multiple_lines() {
         let mut data = FxHashMap::default();
@@ -526,9 +652,11 @@ mod tests {
                 "short_name": "L1",
                 "long_name": "",
                 "internal_designation": "",
+                "region_name": "",
                 "description": "",
                 "text_color": {"r":0,"g":0,"b":0},
-                "background_color": {"r":0,"g":0,"b":0}
+                "background_color": {"r":0,"g":0,"b":0},
+                "info_text_refs": []
             }"#;
         let (line, reference) = get_json_values(line, reference).unwrap();
         assert_eq!(line, reference);
@@ -540,9 +668,11 @@ mod tests {
                 "short_name": "L2",
                 "long_name": "",
                 "internal_designation": "",
+                "region_name": "",
                 "description": "",
                 "text_color": {"r":0,"g":0,"b":0},
-                "background_color": {"r":0,"g":0,"b":0}
+                "background_color": {"r":0,"g":0,"b":0},
+                "info_text_refs": []
             }"#;
         let (line, reference) = get_json_values(line, reference).unwrap();
         assert_eq!(line, reference);
@@ -566,6 +696,7 @@ mod tests {
         parse_line("", &mut data).unwrap();
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_color_parsing() {
         let mut data = FxHashMap::default();
@@ -581,9 +712,11 @@ mod tests {
                 "short_name": "",
                 "long_name": "",
                 "internal_designation": "",
+                "region_name": "",
                 "description": "",
                 "text_color": {"r":255,"g":0,"b":128},
-                "background_color": {"r":64,"g":128,"b":255}
+                "background_color": {"r":64,"g":128,"b":255},
+                "info_text_refs": []
             }"#;
         let (line, reference) = get_json_values(line, reference).unwrap();
         assert_eq!(line, reference);
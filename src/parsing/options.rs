@@ -0,0 +1,295 @@
+#[cfg(all(feature = "fetch", feature = "serde"))]
+use std::collections::{BTreeMap, BTreeSet};
+
+use rustc_hash::{FxHashMap, FxHashSet};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+// ------------------------------------------------------------------------------------------------
+// --- ParsingOptions
+// ------------------------------------------------------------------------------------------------
+
+/// Options controlling how the HRDF files are parsed.
+/// The default options parse the data as is, without any additional check.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ParsingOptions {
+    mode: ParsingMode,
+    route_time_check: RouteTimeCheck,
+    duplicate_platforms: DuplicatePlatformPolicy,
+    // Creates a stop (named as in FPLAN) for each stop of a route which is not defined in BAHNHOF.
+    placeholder_stops: bool,
+    // Continues parsing after a line which cannot be parsed, all the errors are returned together.
+    collect_errors: bool,
+    // Creates a journey for each repetition of a cyclical journey (*Z row with a number of cycles).
+    expand_cycles: bool,
+    // Only the journeys of these administrations (e.g. "000011") are parsed, with their platforms
+    // and exchange times. None parses all the administrations.
+    administrations: Option<FxHashSet<String>>,
+    // By file name (e.g. "ECKDATEN"), for the dialects which add a header or comments to the files.
+    file_rules: FxHashMap<String, FileRules>,
+}
+
+impl ParsingOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Getters/Setters
+
+    pub fn mode(&self) -> ParsingMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, value: ParsingMode) {
+        self.mode = value;
+    }
+
+    pub fn route_time_check(&self) -> RouteTimeCheck {
+        self.route_time_check
+    }
+
+    pub fn set_route_time_check(&mut self, value: RouteTimeCheck) {
+        self.route_time_check = value;
+    }
+
+    pub fn duplicate_platforms(&self) -> DuplicatePlatformPolicy {
+        self.duplicate_platforms
+    }
+
+    pub fn set_duplicate_platforms(&mut self, value: DuplicatePlatformPolicy) {
+        self.duplicate_platforms = value;
+    }
+
+    pub fn placeholder_stops(&self) -> bool {
+        self.placeholder_stops
+    }
+
+    pub fn set_placeholder_stops(&mut self, value: bool) {
+        self.placeholder_stops = value;
+    }
+
+    pub fn collect_errors(&self) -> bool {
+        self.collect_errors
+    }
+
+    pub fn set_collect_errors(&mut self, value: bool) {
+        self.collect_errors = value;
+    }
+
+    pub fn expand_cycles(&self) -> bool {
+        self.expand_cycles
+    }
+
+    pub fn set_expand_cycles(&mut self, value: bool) {
+        self.expand_cycles = value;
+    }
+
+    pub fn administrations(&self) -> Option<&FxHashSet<String>> {
+        self.administrations.as_ref()
+    }
+
+    pub fn set_administrations(&mut self, value: Option<FxHashSet<String>>) {
+        self.administrations = value;
+    }
+
+    pub fn file_rules(&self, file_name: &str) -> Option<&FileRules> {
+        self.file_rules.get(file_name)
+    }
+
+    /// Sets how the lines of the file are read, e.g. "ATTRIBUT" or "BETRIEB_DE".
+    pub fn set_file_rules(&mut self, file_name: &str, value: FileRules) {
+        self.file_rules.insert(file_name.to_string(), value);
+    }
+
+    // Functions
+
+    /// Returns true if the data of the administration is parsed.
+    pub fn includes_administration(&self, administration: &str) -> bool {
+        self.administrations
+            .as_ref()
+            .is_none_or(|administrations| administrations.contains(administration))
+    }
+
+    /// The options serialized with their collections sorted, so that equal options always give
+    /// the same key (see the cache of Hrdf::new).
+    #[cfg(all(feature = "fetch", feature = "serde"))]
+    pub(crate) fn cache_key(&self) -> String {
+        #[derive(Serialize)]
+        struct CanonicalOptions<'a> {
+            mode: ParsingMode,
+            route_time_check: RouteTimeCheck,
+            duplicate_platforms: DuplicatePlatformPolicy,
+            placeholder_stops: bool,
+            collect_errors: bool,
+            expand_cycles: bool,
+            administrations: Option<BTreeSet<&'a String>>,
+            file_rules: BTreeMap<&'a String, &'a FileRules>,
+        }
+
+        // Destructured so that a new option cannot be left out of the key.
+        let Self {
+            mode,
+            route_time_check,
+            duplicate_platforms,
+            placeholder_stops,
+            collect_errors,
+            expand_cycles,
+            administrations,
+            file_rules,
+        } = self;
+        let canonical_options = CanonicalOptions {
+            mode: *mode,
+            route_time_check: *route_time_check,
+            duplicate_platforms: *duplicate_platforms,
+            placeholder_stops: *placeholder_stops,
+            collect_errors: *collect_errors,
+            expand_cycles: *expand_cycles,
+            administrations: administrations
+                .as_ref()
+                .map(|administrations| administrations.iter().collect()),
+            file_rules: file_rules.iter().collect(),
+        };
+        // unwrap: The options only contain types which can be serialized to JSON.
+        serde_json::to_string(&canonical_options).unwrap()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- FileRules
+// ------------------------------------------------------------------------------------------------
+
+/// Lines of a file which are not part of the HRDF data and are skipped. The skipped lines are
+/// still counted in the line numbers of the parsing errors.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FileRules {
+    // Number of lines of the header at the start of the file.
+    skip_lines: usize,
+    // The lines starting with this prefix (e.g. "#"), leading whitespace excluded, are comments.
+    comment_prefix: Option<String>,
+}
+
+impl FileRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Getters/Setters
+
+    pub fn skip_lines(&self) -> usize {
+        self.skip_lines
+    }
+
+    pub fn set_skip_lines(&mut self, value: usize) {
+        self.skip_lines = value;
+    }
+
+    pub fn comment_prefix(&self) -> Option<&str> {
+        self.comment_prefix.as_deref()
+    }
+
+    pub fn set_comment_prefix(&mut self, value: Option<String>) {
+        self.comment_prefix = value;
+    }
+
+    // Functions
+
+    /// Returns true if the line (0-based line number) is skipped.
+    pub fn skips(&self, line_number: usize, line: &str) -> bool {
+        line_number < self.skip_lines
+            || self
+                .comment_prefix
+                .as_deref()
+                .is_some_and(|prefix| line.trim_start().starts_with(prefix))
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- ParsingMode
+// ------------------------------------------------------------------------------------------------
+
+/// How inconsistencies in the data that can be resolved are handled.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ParsingMode {
+    /// The inconsistencies are resolved and reported in the parse report.
+    #[default]
+    Lenient,
+    /// The inconsistencies are parsing errors.
+    Strict,
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- RouteTimeCheck
+// ------------------------------------------------------------------------------------------------
+
+/// What to do with journeys whose stop times go backwards.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
+pub enum RouteTimeCheck {
+    /// The route times are not checked.
+    #[default]
+    Disabled,
+    /// The journeys are kept as is and reported in the parse report.
+    Report,
+    /// The journeys are reported and removed.
+    Drop,
+    /// The journeys are reported and the times going backwards are replaced by the previous time of the route.
+    Repair,
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- DuplicatePlatformPolicy
+// ------------------------------------------------------------------------------------------------
+
+/// What to do with a platform defined several times at the same stop with the same link index
+/// (#...) in a GLEIS file. The SLOID and coordinates rows always apply to the platform kept.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
+pub enum DuplicatePlatformPolicy {
+    /// The first definition is kept and the duplicates are reported.
+    #[default]
+    KeepFirst,
+    /// The last definition is kept and the duplicates are reported.
+    KeepLast,
+    /// The duplicates are parsing errors.
+    Error,
+    /// The name and the sectors missing in the first definition are taken from the duplicates,
+    /// which are reported.
+    Merge,
+}
+
+#[cfg(all(test, feature = "fetch", feature = "serde"))]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn cache_key_ignores_the_order_of_the_collections() {
+        let codes: Vec<_> = (0..64).map(|i| format!("{i:06}")).collect();
+        let mut options_1 = ParsingOptions::new();
+        options_1.set_administrations(Some(codes.iter().cloned().collect()));
+        options_1.set_file_rules("ECKDATEN", FileRules::new());
+        options_1.set_file_rules("ATTRIBUT", FileRules::new());
+        let mut options_2 = ParsingOptions::new();
+        options_2.set_administrations(Some(codes.iter().rev().cloned().collect()));
+        options_2.set_file_rules("ATTRIBUT", FileRules::new());
+        options_2.set_file_rules("ECKDATEN", FileRules::new());
+        assert_eq!(options_1.cache_key(), options_2.cache_key());
+
+        options_2.set_expand_cycles(true);
+        assert_ne!(options_1.cache_key(), options_2.cache_key());
+        assert_ne!(ParsingOptions::new().cache_key(), options_1.cache_key());
+    }
+}
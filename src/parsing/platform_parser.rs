@@ -1,4 +1,7 @@
-use std::path::Path;
+use std::{
+    collections::{BTreeMap, hash_map::Entry},
+    path::Path,
+};
 
 /// # List of track and bus platform information.
 ///
@@ -98,11 +101,12 @@ use rustc_hash::{FxHashMap, FxHashSet};
 use crate::{
     JourneyId, Version,
     error::{HResult, HrdfError},
-    models::{CoordinateSystem, Coordinates, JourneyPlatform, Model, Platform},
+    models::{CoordinateSystem, Coordinates, JourneyPlatform, Model, Platform, PlatformSector},
     parsing::{
-        error::{PResult, ParsingError},
+        DuplicatePlatformPolicy, ParseIssue, ParseReport, ParsingOptions, Visitor,
+        error::{LineErrors, PResult, ParsingError},
         helpers::{
-            i32_from_n_digits_parser, optional_i32_from_n_digits_parser, read_lines,
+            for_each_line, i32_from_n_digits_parser, optional_i32_from_n_digits_parser, read_lines,
             string_from_n_chars_parser, string_till_eol_parser,
         },
     },
@@ -125,13 +129,9 @@ enum PlatformLine {
         platform_name: String,
         code: Option<String>,
     },
-    // Currently unused. Maybe we will want to use it at some point
     Section {
-        #[allow(unused)]
         stop_id: i32,
-        #[allow(unused)]
         index: i32,
-        #[allow(unused)]
         section_data: String,
     },
     Sloid {
@@ -144,7 +144,6 @@ enum PlatformLine {
         index: i32,
         x: f64,
         y: f64,
-        #[allow(unused)]
         altitude: Option<f64>,
     },
 }
@@ -254,14 +253,21 @@ fn sloid_combinator(input: &str) -> IResult<&str, PlatformLine> {
     .parse(input)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn parse_line(
     line: &str,
     platforms: &mut FxHashMap<i32, Platform>,
-    journey_platform: &mut FxHashMap<(i32, i32), JourneyPlatform>,
+    journey_platform: &mut FxHashMap<(i32, String, i32), JourneyPlatform>,
     platforms_pk_type_converter: &mut FxHashMap<(i32, i32), i32>,
     journeys_pk_type_converter: &FxHashSet<JourneyId>,
+    options: &ParsingOptions,
     auto_increment: &AutoIncrement,
     coordinate_system: CoordinateSystem,
+    // Platforms defined in the file being parsed, with their SLOID.
+    file_platforms: &mut FxHashMap<(i32, i32), Option<String>>,
+    // The section defined by the previous row (platform ID, position in the sectors of the platform).
+    current_sector: &mut Option<(i32, usize)>,
+    report: &mut ParseReport,
 ) -> PResult<()> {
     let (_, platform_row) = alt((
         journey_platform_combinator,
@@ -281,6 +287,12 @@ fn parse_line(
             time,
             bit_field_id,
         } => {
+            *current_sector = None;
+
+            if !options.includes_administration(&administration) {
+                return Ok(());
+            }
+
             let key = (journey_id, administration.clone());
             let _journey_id = journeys_pk_type_converter.get(&key).ok_or_else(|| {
                 ParsingError::UnknownId(format!(
@@ -297,7 +309,7 @@ fn parse_line(
                         ))
                     })?;
 
-                let time = time.map(|x| create_time_from_value(x as u32)).transpose()?;
+                let time = time.map(create_time_from_value).transpose()?;
 
                 let jp_instance = JourneyPlatform::new(
                     journey_id,
@@ -310,11 +322,33 @@ fn parse_line(
             }
         }
         PlatformLine::Section {
-            stop_id: _,
-            index: _,
-            section_data: _,
+            stop_id,
+            index,
+            section_data,
         } => {
-            // TODO: We should maybe use this data at some point
+            let id = *platforms_pk_type_converter
+                .get(&(stop_id, index))
+                .ok_or_else(|| {
+                    ParsingError::UnknownId(format!(
+                        "Legacy Platform Id (stop_id, index): ({stop_id}, {index})"
+                    ))
+                })?;
+
+            let sectors = platforms
+                .get_mut(&id)
+                .ok_or_else(|| ParsingError::UnknownId(format!("Unknown platforms Id: {id}")))?
+                .sectors_mut();
+
+            // The sections of the LV95 file are defined again in the WGS84 file.
+            let name = section_data.trim_matches('\'');
+            let position = match sectors.iter().position(|sector| sector.name() == name) {
+                Some(position) => position,
+                None => {
+                    sectors.push(PlatformSector::new(name.to_string()));
+                    sectors.len() - 1
+                }
+            };
+            *current_sector = Some((id, position));
         }
         PlatformLine::Platform {
             stop_id,
@@ -322,20 +356,60 @@ fn parse_line(
             platform_name,
             code,
         } => {
+            *current_sector = None;
+            let sectors: Vec<_> = code.into_iter().map(PlatformSector::new).collect();
             let id = auto_increment.next();
 
-            let id = platforms_pk_type_converter
+            // The platforms of the LV95 file are defined again in the WGS84 file.
+            let id = *platforms_pk_type_converter
                 .entry((stop_id, index))
                 .or_insert(id);
 
-            // if let Some(previous) = platforms_pk_type_converter.insert((stop_id, index), id) {
-            //     log::warn!(
-            //         "Warning: previous id {previous} for ({stop_id}, {index}). The pair (stop_id, index), ({stop_id}, {index}), is not unique."
-            //     );
-            // };
-            platforms
-                .entry(*id)
-                .or_insert(Platform::new(*id, platform_name, code, stop_id));
+            match file_platforms.entry((stop_id, index)) {
+                Entry::Occupied(_) => {
+                    let policy = options.duplicate_platforms();
+                    if policy == DuplicatePlatformPolicy::Error {
+                        return Err(ParsingError::DuplicatePlatform(stop_id, index));
+                    }
+
+                    log::warn!(
+                        "The platform ({stop_id}, {index}) is defined several times, the duplicate is handled with {policy:?}."
+                    );
+                    report.add(ParseIssue::DuplicatePlatform {
+                        stop_id,
+                        index,
+                        action: policy,
+                    });
+
+                    let platform = platforms.get_mut(&id).ok_or_else(|| {
+                        ParsingError::UnknownId(format!("Unknown platforms Id: {id}"))
+                    })?;
+                    match policy {
+                        DuplicatePlatformPolicy::KeepLast => {
+                            platform.set_name(platform_name);
+                            platform.set_sectors(sectors);
+                        }
+                        DuplicatePlatformPolicy::Merge => {
+                            if platform.name().is_empty() {
+                                platform.set_name(platform_name);
+                            }
+                            if platform.sectors().is_empty() {
+                                platform.set_sectors(sectors);
+                            }
+                        }
+                        DuplicatePlatformPolicy::KeepFirst | DuplicatePlatformPolicy::Error => {}
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    platforms.entry(id).or_insert(Platform::new(
+                        id,
+                        platform_name,
+                        sectors,
+                        stop_id,
+                    ));
+                    entry.insert(None);
+                }
+            }
         }
         PlatformLine::Sloid {
             stop_id,
@@ -350,18 +424,28 @@ fn parse_line(
                     ))
                 })?;
 
-            platforms
+            let platform = platforms
                 .get_mut(id)
-                .ok_or_else(|| ParsingError::UnknownId(format!("Unknown platforms Id: {id}")))?
-                .set_sloid(sloid);
-            // TODO: We should maybe check for consistency between LV95 and GWS sloids
+                .ok_or_else(|| ParsingError::UnknownId(format!("Unknown platforms Id: {id}")))?;
+
+            let sector = section_of(*current_sector, *id);
+            if let Some(sector) =
+                sector.and_then(|position| platform.sectors_mut().get_mut(position))
+            {
+                sector.set_sloid(sloid.clone());
+            }
+            // The rows following a section also describe the platform if it isn't described yet.
+            if sector.is_none() || platform.sloid().is_empty() {
+                platform.set_sloid(sloid.clone());
+                file_platforms.insert((stop_id, index), Some(sloid));
+            }
         }
         PlatformLine::Coord {
             stop_id,
             index,
             x,
             y,
-            altitude: _,
+            altitude,
         } => {
             let id = platforms_pk_type_converter
                 .get(&(stop_id, index))
@@ -375,102 +459,273 @@ fn parse_line(
                 .get_mut(id)
                 .ok_or_else(|| ParsingError::UnknownId(format!("Unknown platforms Id: {id}")))?;
 
+            let sector = section_of(*current_sector, *id);
             match coordinate_system {
                 c @ CoordinateSystem::LV95 => {
                     let value = Coordinates::new(c, x, y);
-                    platform.set_lv95_coordinates(value);
+                    if let Some(sector) =
+                        sector.and_then(|position| platform.sectors_mut().get_mut(position))
+                    {
+                        sector.set_lv95_coordinates(value);
+                    }
+                    if sector.is_none() || platform.lv95_coordinates().is_none() {
+                        platform.set_lv95_coordinates(value);
+                    }
                 }
                 c @ CoordinateSystem::WGS84 => {
                     // WGS84 coordinates are stored in reverse order for some unknown reason.
                     let value = Coordinates::new(c, y, x);
-                    platform.set_wgs84_coordinates(value);
+                    if let Some(sector) =
+                        sector.and_then(|position| platform.sectors_mut().get_mut(position))
+                    {
+                        sector.set_wgs84_coordinates(value);
+                    }
+                    if sector.is_none() || platform.wgs84_coordinates().is_none() {
+                        platform.set_wgs84_coordinates(value);
+                    }
                 }
             }
+            if let Some(altitude) =
+                altitude.filter(|_| sector.is_none() || platform.altitude().is_none())
+            {
+                platform.set_altitude(altitude);
+            }
         }
     }
     Ok(())
 }
 
-pub fn parse(
-    version: Version,
-    path: &Path,
-    journeys_pk_type_converter: &FxHashSet<JourneyId>,
-) -> HResult<(ResourceStorage<JourneyPlatform>, ResourceStorage<Platform>)> {
-    let prefix = match version {
+/// Position of the section defined by the previous row, if it belongs to the given platform.
+fn section_of(current_sector: Option<(i32, usize)>, platform_id: i32) -> Option<usize> {
+    current_sector
+        .filter(|&(id, _)| id == platform_id)
+        .map(|(_, position)| position)
+}
+
+fn file_prefix(version: Version) -> HResult<&'static str> {
+    match version {
         Version::V_5_40_41_2_0_7 => Ok("GLEISE"),
         Version::V_5_40_41_2_0_4 | Version::V_5_40_41_2_0_5 | Version::V_5_40_41_2_0_6 => {
             Ok("GLEIS")
         }
         _ => Err(HrdfError::SupportedVersion(version)),
-    }?;
+    }
+}
+
+/// Serves parsing::visit, only the LV95 file is read (the WGS84 file defines the same platforms).
+pub(crate) fn visit(version: Version, path: &Path, visitor: &mut impl Visitor) -> HResult<()> {
+    let prefix = file_prefix(version)?;
+    log::info!("Visiting {prefix}_LV95...");
+    for_each_line(&path.join(format!("{prefix}_LV95")), |line| {
+        let (_, platform_row) = alt((
+            journey_platform_combinator,
+            platform_combinator,
+            section_combinator,
+            sloid_combinator,
+            coord_combinator,
+        ))
+        .parse(line)?;
+
+        match platform_row {
+            PlatformLine::JourneyPlatform {
+                stop_id,
+                journey_id,
+                administration,
+                index,
+                bit_field_id,
+                ..
+            } => {
+                visitor.journey_platform(journey_id, &administration, stop_id, index, bit_field_id)
+            }
+            PlatformLine::Platform {
+                stop_id,
+                index,
+                platform_name,
+                code,
+            } => visitor.platform(stop_id, index, &platform_name, code.as_deref()),
+            PlatformLine::Section { .. }
+            | PlatformLine::Sloid { .. }
+            | PlatformLine::Coord { .. } => {}
+        }
+        Ok(())
+    })
+}
+
+pub fn parse(
+    version: Version,
+    path: &Path,
+    journeys_pk_type_converter: &FxHashSet<JourneyId>,
+    options: &ParsingOptions,
+    report: &mut ParseReport,
+    errors: &mut LineErrors,
+) -> HResult<(ResourceStorage<JourneyPlatform>, ResourceStorage<Platform>)> {
+    let prefix = file_prefix(version)?;
     let auto_increment = AutoIncrement::new();
     let mut platforms = FxHashMap::default();
     let mut platforms_pk_type_converter = FxHashMap::default();
 
     let mut journey_platform = FxHashMap::default();
+    let mut lv95_platforms = FxHashMap::default();
+    let mut wgs84_platforms = FxHashMap::default();
 
     log::info!("Parsing {prefix}_LV95...");
     let file = path.join(format!("{prefix}_LV95"));
-    let platforms_lv95 = read_lines(&file, 0)?;
+    let mut current_sector = None;
+    let platforms_lv95 = read_lines(&file, options)?;
     platforms_lv95
         .into_iter()
         .enumerate()
         .filter(|(_, line)| !line.trim().is_empty())
         .try_for_each(|(line_number, line)| {
-            parse_line(
+            let result = parse_line(
                 &line,
                 &mut platforms,
                 &mut journey_platform,
                 &mut platforms_pk_type_converter,
                 journeys_pk_type_converter,
+                options,
                 &auto_increment,
                 CoordinateSystem::LV95,
+                &mut lv95_platforms,
+                &mut current_sector,
+                report,
             )
             .map_err(|e| HrdfError::Parsing {
                 error: e,
                 file: String::from(file.to_string_lossy()),
                 line,
                 line_number,
-            })
+            });
+            errors.handle(result).map(|_| ())
         })?;
 
     log::info!("Parsing {prefix}_WGS...");
     let file = path.join(format!("{prefix}_WGS"));
-    let platforms_wgs84 = read_lines(&file, 0)?;
+    let mut current_sector = None;
+    let platforms_wgs84 = read_lines(&file, options)?;
     platforms_wgs84
         .into_iter()
         .enumerate()
         .filter(|(_, line)| !line.trim().is_empty())
         .try_for_each(|(line_number, line)| {
-            parse_line(
+            let result = parse_line(
                 &line,
                 &mut platforms,
                 &mut journey_platform,
                 &mut platforms_pk_type_converter,
                 journeys_pk_type_converter,
+                options,
                 &auto_increment,
                 CoordinateSystem::WGS84,
+                &mut wgs84_platforms,
+                &mut current_sector,
+                report,
             )
             .map_err(|e| HrdfError::Parsing {
                 error: e,
                 file: String::from(file.to_string_lossy()),
                 line,
                 line_number,
-            })
+            });
+            errors.handle(result).map(|_| ())
         })?;
 
+    check_consistency(&lv95_platforms, &wgs84_platforms, report);
+
+    if options.administrations().is_some() {
+        // Only the platforms of the parsed journeys are kept.
+        let platform_ids: FxHashSet<i32> = journey_platform
+            .values()
+            .map(JourneyPlatform::platform_id)
+            .collect();
+        platforms.retain(|id, _| platform_ids.contains(id));
+    }
+
     Ok((
         ResourceStorage::new(journey_platform),
         ResourceStorage::new(platforms),
     ))
 }
+
+/// The indices of the platforms of a stop only in LV95, only in WGS84 and with different SLOIDs.
+type PlatformDiscrepancies = (Vec<i32>, Vec<i32>, Vec<i32>);
+
+/// Reports, per stop, the platforms defined in only one of the LV95 and WGS84 files and the platforms whose SLOIDs differ.
+fn check_consistency(
+    lv95_platforms: &FxHashMap<(i32, i32), Option<String>>,
+    wgs84_platforms: &FxHashMap<(i32, i32), Option<String>>,
+    report: &mut ParseReport,
+) {
+    // stop_id => (only_in_lv95, only_in_wgs84, sloid_mismatches)
+    let mut discrepancies: BTreeMap<i32, PlatformDiscrepancies> = BTreeMap::new();
+
+    for (&(stop_id, index), lv95_sloid) in lv95_platforms {
+        match wgs84_platforms.get(&(stop_id, index)) {
+            None => discrepancies.entry(stop_id).or_default().0.push(index),
+            Some(wgs84_sloid) if wgs84_sloid != lv95_sloid => {
+                discrepancies.entry(stop_id).or_default().2.push(index)
+            }
+            Some(_) => {}
+        }
+    }
+
+    for &(stop_id, index) in wgs84_platforms.keys() {
+        if !lv95_platforms.contains_key(&(stop_id, index)) {
+            discrepancies.entry(stop_id).or_default().1.push(index);
+        }
+    }
+
+    for (stop_id, (mut only_in_lv95, mut only_in_wgs84, mut sloid_mismatches)) in discrepancies {
+        only_in_lv95.sort_unstable();
+        only_in_wgs84.sort_unstable();
+        sloid_mismatches.sort_unstable();
+
+        log::warn!("The platforms of stop {stop_id} differ between the LV95 and WGS84 files.");
+        report.add(ParseIssue::InconsistentPlatforms {
+            stop_id,
+            only_in_lv95,
+            only_in_wgs84,
+            sloid_mismatches,
+        });
+    }
+}
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "serde")]
     use crate::parsing::tests::get_json_values;
 
     use super::*;
     use pretty_assertions::assert_eq;
 
+    #[test]
+    fn check_consistency_reports_discrepancies_per_stop() {
+        let lv95_platforms = FxHashMap::from_iter([
+            ((8500010, 1), Some("ch:1:sloid:10:1:1".to_string())),
+            ((8500010, 2), Some("ch:1:sloid:10:2:2".to_string())),
+            ((8500010, 3), None),
+            ((8503000, 1), None),
+        ]);
+        let wgs84_platforms = FxHashMap::from_iter([
+            ((8500010, 1), Some("ch:1:sloid:10:1:1".to_string())),
+            ((8500010, 2), Some("ch:1:sloid:10:2:3".to_string())),
+            ((8500010, 4), None),
+            ((8503000, 1), None),
+        ]);
+        let mut report = ParseReport::new();
+
+        check_consistency(&lv95_platforms, &wgs84_platforms, &mut report);
+
+        assert_eq!(
+            report.issues(),
+            &vec![ParseIssue::InconsistentPlatforms {
+                stop_id: 8500010,
+                only_in_lv95: vec![3],
+                only_in_wgs84: vec![4],
+                sloid_mismatches: vec![2],
+            }]
+        );
+    }
+
     #[test]
     fn test_journey_platform_combinator_basic() {
         let input = "8500010 000003 000011 #0000001      053751";
@@ -770,6 +1025,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_parse_line_platform_creation() {
         let mut platforms = FxHashMap::default();
@@ -784,8 +1040,12 @@ mod tests {
             &mut journey_platform,
             &mut platforms_pk_type_converter,
             &journeys_pk_type_converter,
+            &ParsingOptions::default(),
             &auto_increment,
             CoordinateSystem::LV95,
+            &mut FxHashMap::default(),
+            &mut None,
+            &mut ParseReport::new(),
         )
         .unwrap();
         assert_eq!(platforms.len(), 1);
@@ -796,11 +1056,11 @@ mod tests {
             {
                 "id":1,
                 "name":"11",
-                "sectors":null,
+                "sectors":[],
                 "stop_id":8500010,
                 "sloid":"",
-                "lv95_coordinates":{"coordinate_system":"LV95","x":0.0,"y":0.0},
-                "wgs84_coordinates":{"coordinate_system":"LV95","x":0.0,"y":0.0}
+                "lv95_coordinates":null,
+                "wgs84_coordinates":null
             }"#;
         let (platform, reference) = get_json_values(platform, reference).unwrap();
         assert_eq!(platform, reference);
@@ -821,8 +1081,12 @@ mod tests {
             &mut journey_platform,
             &mut platforms_pk_type_converter,
             &journeys_pk_type_converter,
+            &ParsingOptions::default(),
             &auto_increment,
             CoordinateSystem::LV95,
+            &mut FxHashMap::default(),
+            &mut None,
+            &mut ParseReport::new(),
         )
         .unwrap();
     }
@@ -842,12 +1106,17 @@ mod tests {
             &mut journey_platform,
             &mut platforms_pk_type_converter,
             &journeys_pk_type_converter,
+            &ParsingOptions::default(),
             &auto_increment,
             CoordinateSystem::LV95,
+            &mut FxHashMap::default(),
+            &mut None,
+            &mut ParseReport::new(),
         )
         .unwrap();
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_parse_line_complete_platform_sequence() {
         let mut platforms = FxHashMap::default();
@@ -863,8 +1132,12 @@ mod tests {
             &mut journey_platform,
             &mut platforms_pk_type_converter,
             &journeys_pk_type_converter,
+            &ParsingOptions::default(),
             &auto_increment,
             CoordinateSystem::LV95,
+            &mut FxHashMap::default(),
+            &mut None,
+            &mut ParseReport::new(),
         )
         .unwrap();
 
@@ -875,8 +1148,12 @@ mod tests {
             &mut journey_platform,
             &mut platforms_pk_type_converter,
             &journeys_pk_type_converter,
+            &ParsingOptions::default(),
             &auto_increment,
             CoordinateSystem::LV95,
+            &mut FxHashMap::default(),
+            &mut None,
+            &mut ParseReport::new(),
         )
         .unwrap();
 
@@ -887,8 +1164,12 @@ mod tests {
             &mut journey_platform,
             &mut platforms_pk_type_converter,
             &journeys_pk_type_converter,
+            &ParsingOptions::default(),
             &auto_increment,
             CoordinateSystem::LV95,
+            &mut FxHashMap::default(),
+            &mut None,
+            &mut ParseReport::new(),
         )
         .unwrap();
 
@@ -903,11 +1184,12 @@ mod tests {
             {
                 "id":1,
                 "name":"5",
-                "sectors":null,
+                "sectors":[],
                 "stop_id":8574200,
                 "sloid":"ch:1:sloid:74200:1:3",
                 "lv95_coordinates":{"coordinate_system":"LV95","x":2692827.0,"y":1247287.0},
-                "wgs84_coordinates":{"coordinate_system":"LV95","x":0.0,"y":0.0}
+                "wgs84_coordinates":null,
+                "altitude":680.0
             }"#;
         let (platform, reference) = get_json_values(platform, reference).unwrap();
         assert_eq!(platform, reference);
@@ -928,8 +1210,12 @@ mod tests {
             &mut journey_platform,
             &mut platforms_pk_type_converter,
             &journeys_pk_type_converter,
+            &ParsingOptions::default(),
             &auto_increment,
             CoordinateSystem::WGS84,
+            &mut FxHashMap::default(),
+            &mut None,
+            &mut ParseReport::new(),
         )
         .unwrap();
 
@@ -940,8 +1226,12 @@ mod tests {
             &mut journey_platform,
             &mut platforms_pk_type_converter,
             &journeys_pk_type_converter,
+            &ParsingOptions::default(),
             &AutoIncrement::new(),
             CoordinateSystem::WGS84,
+            &mut FxHashMap::default(),
+            &mut None,
+            &mut ParseReport::new(),
         )
         .unwrap();
 
@@ -950,6 +1240,168 @@ mod tests {
         // at line 368 in platform_parser.rs
     }
 
+    #[test]
+    fn test_parse_line_sections() {
+        let mut platforms = FxHashMap::default();
+        let mut platforms_pk_type_converter = FxHashMap::default();
+        let auto_increment = AutoIncrement::new();
+        let mut current_sector = None;
+
+        let mut parse = |line, coordinate_system| {
+            parse_line(
+                line,
+                &mut platforms,
+                &mut FxHashMap::default(),
+                &mut platforms_pk_type_converter,
+                &FxHashSet::default(),
+                &ParsingOptions::default(),
+                &auto_increment,
+                coordinate_system,
+                &mut FxHashMap::default(),
+                &mut current_sector,
+                &mut ParseReport::new(),
+            )
+            .unwrap()
+        };
+        for line in [
+            "8500207 #0000001 G '1'",
+            "8500207 #0000001 g A ch:1:sloid:207:1:1",
+            "8500207 #0000001 A 'AB'",
+            "8500207 #0000001 g A ch:1:sloid:207:1:2",
+            "8500207 #0000001 k 2600028 1199657 540",
+            "8500207 #0000001 A 'C'",
+        ] {
+            parse(line, CoordinateSystem::LV95);
+        }
+        // The sections are defined again in the WGS84 file.
+        for line in ["8500207 #0000001 A 'C'", "8500207 #0000001 k 7.439 46.948"] {
+            parse(line, CoordinateSystem::WGS84);
+        }
+
+        let platform = &platforms[&1];
+        assert_eq!(platform.sloid(), "ch:1:sloid:207:1:1");
+        assert_eq!(
+            platform.lv95_coordinates().unwrap().easting(),
+            Some(2600028.0)
+        );
+        assert_eq!(platform.altitude(), Some(540.0));
+        assert_eq!(
+            platform.wgs84_coordinates().unwrap().latitude(),
+            Some(46.948)
+        );
+
+        let sectors = platform.sectors();
+        assert_eq!(sectors.len(), 2);
+        assert_eq!(sectors[0].name(), "AB");
+        assert_eq!(sectors[0].sloid(), Some("ch:1:sloid:207:1:2"));
+        assert_eq!(
+            sectors[0].lv95_coordinates().unwrap().northing(),
+            Some(1199657.0)
+        );
+        assert!(sectors[0].wgs84_coordinates().is_none());
+        assert_eq!(sectors[1].name(), "C");
+        assert_eq!(sectors[1].sloid(), None);
+        assert!(sectors[1].wgs84_coordinates().is_some());
+    }
+
+    fn parse_duplicate_platforms(
+        policy: DuplicatePlatformPolicy,
+    ) -> (PResult<()>, FxHashMap<i32, Platform>, ParseReport) {
+        let mut platforms = FxHashMap::default();
+        let mut platforms_pk_type_converter = FxHashMap::default();
+        let mut options = ParsingOptions::new();
+        options.set_duplicate_platforms(policy);
+        let auto_increment = AutoIncrement::new();
+        let mut file_platforms = FxHashMap::default();
+        let mut report = ParseReport::new();
+
+        let result = ["8500010 #0000001 G '1'", "8500010 #0000001 G '2' A 'AB'"]
+            .into_iter()
+            .try_for_each(|line| {
+                parse_line(
+                    line,
+                    &mut platforms,
+                    &mut FxHashMap::default(),
+                    &mut platforms_pk_type_converter,
+                    &FxHashSet::default(),
+                    &options,
+                    &auto_increment,
+                    CoordinateSystem::LV95,
+                    &mut file_platforms,
+                    &mut None,
+                    &mut report,
+                )
+            });
+        (result, platforms, report)
+    }
+
+    #[test]
+    fn test_duplicate_platforms_are_handled_by_policy() {
+        let (result, platforms, report) =
+            parse_duplicate_platforms(DuplicatePlatformPolicy::KeepFirst);
+        assert!(result.is_ok());
+        assert_eq!(platforms.len(), 1);
+        assert_eq!(platforms[&1].name(), "1");
+        assert!(platforms[&1].sectors().is_empty());
+        assert_eq!(
+            report.issues(),
+            &vec![ParseIssue::DuplicatePlatform {
+                stop_id: 8500010,
+                index: 1,
+                action: DuplicatePlatformPolicy::KeepFirst,
+            }]
+        );
+
+        let (_, platforms, _) = parse_duplicate_platforms(DuplicatePlatformPolicy::KeepLast);
+        assert_eq!(platforms[&1].name(), "2");
+        assert_eq!(platforms[&1].sectors()[0].name(), "AB");
+
+        let (_, platforms, _) = parse_duplicate_platforms(DuplicatePlatformPolicy::Merge);
+        assert_eq!(platforms[&1].name(), "1");
+        assert_eq!(platforms[&1].sectors()[0].name(), "AB");
+
+        let (result, _, report) = parse_duplicate_platforms(DuplicatePlatformPolicy::Error);
+        assert!(matches!(
+            result,
+            Err(ParsingError::DuplicatePlatform(8500010, 1))
+        ));
+        assert!(report.is_empty());
+    }
+
+    #[test]
+    fn test_journey_platform_of_same_journey_number_in_two_administrations() {
+        let mut platforms = FxHashMap::default();
+        let mut journey_platform = FxHashMap::default();
+        let mut platforms_pk_type_converter = FxHashMap::from_iter([((8500010, 1), 1)]);
+        let journeys_pk_type_converter =
+            FxHashSet::from_iter([(3, "000011".to_string()), (3, "000801".to_string())]);
+        let auto_increment = AutoIncrement::new();
+
+        for line in [
+            "8500010 000003 000011 #0000001      053751",
+            "8500010 000003 000801 #0000001      053752",
+        ] {
+            parse_line(
+                line,
+                &mut platforms,
+                &mut journey_platform,
+                &mut platforms_pk_type_converter,
+                &journeys_pk_type_converter,
+                &ParsingOptions::default(),
+                &auto_increment,
+                CoordinateSystem::LV95,
+                &mut FxHashMap::default(),
+                &mut None,
+                &mut ParseReport::new(),
+            )
+            .unwrap();
+        }
+
+        assert_eq!(journey_platform.len(), 2);
+        let journey_platform = journey_platform.get(&(3, "000801".to_string(), 1)).unwrap();
+        assert_eq!(journey_platform.bit_field_id(), Some(53752));
+    }
+
     #[test]
     #[should_panic]
     fn test_journey_platform_requires_valid_journey() {
@@ -965,8 +1417,12 @@ mod tests {
             &mut journey_platform,
             &mut platforms_pk_type_converter,
             &journeys_pk_type_converter,
+            &ParsingOptions::default(),
             &auto_increment,
             CoordinateSystem::LV95,
+            &mut FxHashMap::default(),
+            &mut None,
+            &mut ParseReport::new(),
         )
         .unwrap();
     }
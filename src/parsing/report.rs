@@ -0,0 +1,148 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "serde")]
+use crate::{error::HResult, validation};
+use crate::{
+    parsing::options::{DuplicatePlatformPolicy, RouteTimeCheck},
+    validation::{Issue, Severity},
+};
+
+// ------------------------------------------------------------------------------------------------
+// --- ParseReport
+// ------------------------------------------------------------------------------------------------
+
+/// Issues found in the data while parsing the HRDF files.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
+pub struct ParseReport {
+    issues: Vec<ParseIssue>,
+}
+
+impl ParseReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Getters/Setters
+
+    pub fn issues(&self) -> &Vec<ParseIssue> {
+        &self.issues
+    }
+
+    // Functions
+
+    pub(crate) fn add(&mut self, issue: ParseIssue) {
+        self.issues.push(issue);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Serializes the issues as a JSON array of {"code", "severity", "payload"} objects.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> HResult<String> {
+        validation::issues_to_json(&self.issues)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- ParseIssue
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
+pub enum ParseIssue {
+    /// The arrival or departure time at stop_id is earlier than a previous time of the route.
+    NonMonotonicRouteTimes {
+        journey_id: i32,
+        legacy_id: i32,
+        administration: String,
+        stop_id: i32,
+        action: RouteTimeCheck,
+    },
+    /// The stop is used in a route but is not defined in BAHNHOF (e.g. a foreign stop).
+    /// A placeholder stop with the name written in FPLAN has been created.
+    PlaceholderStop { stop_id: i32, name: String },
+    /// The designation is defined several times in ZUGART, the first definition is used for the references.
+    DuplicateTransportType {
+        designation: String,
+        kept_id: i32,
+        ignored_id: i32,
+    },
+    /// The platforms of the stop differ between the LV95 and WGS84 GLEIS files.
+    /// The platforms are identified by their link index (#...) at the stop.
+    InconsistentPlatforms {
+        stop_id: i32,
+        only_in_lv95: Vec<i32>,
+        only_in_wgs84: Vec<i32>,
+        sloid_mismatches: Vec<i32>,
+    },
+    /// The platform is defined several times at the stop in the GLEIS file. The platform is
+    /// identified by its link index (#...) at the stop.
+    DuplicatePlatform {
+        stop_id: i32,
+        index: i32,
+        action: DuplicatePlatformPolicy,
+    },
+    /// The bit field of the journey (*A VE) is not defined in BITFELD, the journey operates every
+    /// day.
+    MissingBitField {
+        journey_id: i32,
+        legacy_id: i32,
+        administration: String,
+        bit_field_id: i32,
+    },
+    /// The language tag is not known in ZUGART, the texts of its block have been skipped.
+    UnknownLanguage { name: String },
+    /// UMSTEIGB has no default row (9999999), the fallback exchange times are used.
+    MissingDefaultExchangeTime { fallback: (i16, i16) },
+    /// FPLAN contains star-rows of a type which is not parsed (e.g. *XY), they are kept as is
+    /// (see Journey::unknown_rows).
+    UnknownJourneyRows { line_type: String, count: usize },
+}
+
+impl Issue for ParseIssue {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::NonMonotonicRouteTimes { .. } => "non_monotonic_route_times",
+            Self::PlaceholderStop { .. } => "placeholder_stop",
+            Self::DuplicateTransportType { .. } => "duplicate_transport_type",
+            Self::InconsistentPlatforms { .. } => "inconsistent_platforms",
+            Self::DuplicatePlatform { .. } => "duplicate_platform",
+            Self::MissingDefaultExchangeTime { .. } => "missing_default_exchange_time",
+            Self::UnknownLanguage { .. } => "unknown_language",
+            Self::MissingBitField { .. } => "missing_bit_field",
+            Self::UnknownJourneyRows { .. } => "unknown_journey_rows",
+        }
+    }
+
+    fn severity(&self) -> Severity {
+        match self {
+            // The journey was dropped or repaired.
+            Self::NonMonotonicRouteTimes { action, .. } if *action != RouteTimeCheck::Report => {
+                Severity::Warning
+            }
+            Self::NonMonotonicRouteTimes { .. } => Severity::Error,
+            Self::PlaceholderStop { .. }
+            | Self::InconsistentPlatforms { .. }
+            | Self::UnknownJourneyRows { .. } => Severity::Info,
+            Self::DuplicateTransportType { .. }
+            | Self::DuplicatePlatform { .. }
+            | Self::MissingDefaultExchangeTime { .. }
+            | Self::UnknownLanguage { .. }
+            | Self::MissingBitField { .. } => Severity::Warning,
+        }
+    }
+}
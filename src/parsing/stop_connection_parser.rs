@@ -56,7 +56,8 @@ use crate::{
     error::{HResult, HrdfError},
     models::{Model, StopConnection},
     parsing::{
-        error::{PResult, ParsingError},
+        ParsingOptions,
+        error::{LineErrors, PResult, ParsingError},
         helpers::{
             i16_from_n_digits_parser, i32_from_n_digits_parser, read_lines, string_till_eol_parser,
         },
@@ -163,7 +164,9 @@ fn parse_line(
 
 pub fn parse(
     path: &Path,
+    options: &ParsingOptions,
     attributes_pk_type_converter: &FxHashMap<String, i32>,
+    errors: &mut LineErrors,
 ) -> HResult<ResourceStorage<StopConnection>> {
     log::info!("Parsing METABHF...");
 
@@ -171,13 +174,13 @@ pub fn parse(
     let mut stations = FxHashMap::default();
 
     let file = path.join("METABHF");
-    let station_lines = read_lines(&file, 0)?;
+    let station_lines = read_lines(&file, options)?;
     station_lines
         .into_iter()
         .enumerate()
         .filter(|(_, line)| !line.trim().is_empty())
         .try_for_each(|(line_number, line)| {
-            parse_line(
+            let result = parse_line(
                 &line,
                 &mut stations,
                 attributes_pk_type_converter,
@@ -188,7 +191,8 @@ pub fn parse(
                 file: String::from(file.to_string_lossy()),
                 line,
                 line_number,
-            })
+            });
+            errors.handle(result).map(|_| ())
         })?;
 
     Ok(ResourceStorage::new(stations))
@@ -196,6 +200,7 @@ pub fn parse(
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "serde")]
     use crate::parsing::tests::get_json_values;
 
     use super::*;
@@ -527,6 +532,7 @@ mod tests {
         assert_eq!(data.len(), 0);
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_parse_line_realistic_scenario() {
         let mut data = FxHashMap::default();
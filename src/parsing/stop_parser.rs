@@ -160,6 +160,9 @@ use std::path::Path;
 ///     - G = Global ID (in Switzerland: SLOID)
 ///         - Type designator (“a”/”A”, “A” only for *_60)
 ///         - SLOID
+/// - the country and the canton of the stop:
+///     - L = Country code (e.g. CH)
+///     - I KT = Canton number (e.g. 1 for Zurich)
 ///
 /// The format is included:
 ///
@@ -242,14 +245,18 @@ use crate::{
     error::{HResult, HrdfError},
     models::{CoordinateSystem, Coordinates, Stop, Version},
     parsing::{
-        error::{PResult, ParsingError},
-        helpers::{read_lines, string_from_n_chars_parser, string_till_eol_parser},
+        ParseIssue, ParseReport, ParsingMode, ParsingOptions, Visitor,
+        error::{LineErrors, PResult, ParsingError},
+        helpers::{for_each_line, read_lines, string_from_n_chars_parser, string_till_eol_parser},
     },
     storage::ResourceStorage,
 };
 
 type StopStorageAndExchangeTimes = (ResourceStorage<Stop>, (i16, i16));
 
+/// Default exchange times (InterCity, all other journey types) used in lenient mode when UMSTEIGB has no 9999999 row.
+const FALLBACK_DEFAULT_EXCHANGE_TIME: (i16, i16) = (2, 2);
+
 struct StopLine {
     stop_id: i32,
     designation: String,
@@ -262,7 +269,6 @@ struct CoordLine {
     stop_id: i32,
     x: f64,
     y: f64,
-    #[allow(unused)]
     altitude: f64,
 }
 
@@ -286,30 +292,11 @@ struct TimesLines {
 
 enum DescriptionLine {
     Comment,
-    Restriction {
-        stop_id: i32,
-        restrictions: i16,
-    },
-    Sloid {
-        stop_id: i32,
-        sloid: String,
-    },
-    Boarding {
-        stop_id: i32,
-        sloid: String,
-    },
-    Country {
-        #[allow(unused)]
-        stop_id: i32,
-        #[allow(unused)]
-        country_code: String,
-    },
-    Canton {
-        #[allow(unused)]
-        stop_id: i32,
-        #[allow(unused)]
-        canton_id: i32,
-    },
+    Restriction { stop_id: i32, restrictions: i16 },
+    Sloid { stop_id: i32, sloid: String },
+    Boarding { stop_id: i32, sloid: String },
+    Country { stop_id: i32, country_code: String },
+    Canton { stop_id: i32, canton_id: i32 },
 }
 
 fn comment_combinator(input: &str) -> IResult<&str, DescriptionLine> {
@@ -426,16 +413,21 @@ fn parse_description_line(line: &str, stops: &mut FxHashMap<i32, Stop>) -> PResu
             }
         }
         DescriptionLine::Country {
-            stop_id: _,
-            country_code: _,
+            stop_id,
+            country_code,
         } => {
-            // TODO: For the moment this line is not used
+            if let Some(stop) = stops.get_mut(&stop_id) {
+                stop.set_country_code(country_code);
+            } else {
+                log::info!("Unknown stop ID: {stop_id} for country");
+            }
         }
-        DescriptionLine::Canton {
-            stop_id: _,
-            canton_id: _,
-        } => {
-            // TODO: For the moment this line is not used
+        DescriptionLine::Canton { stop_id, canton_id } => {
+            if let Some(stop) = stops.get_mut(&stop_id) {
+                stop.set_canton(canton_id);
+            } else {
+                log::info!("Unknown stop ID: {stop_id} for canton");
+            }
         }
     }
     Ok(())
@@ -573,6 +565,31 @@ fn parse_stop_line(line: &str, stops: &mut FxHashMap<i32, Stop>) -> PResult<()>
     Ok(())
 }
 
+/// Serves parsing::visit, only BAHNHOF is read.
+pub(crate) fn visit(path: &Path, visitor: &mut impl Visitor) -> HResult<()> {
+    log::info!("Visiting BAHNHOF...");
+    for_each_line(&path.join("BAHNHOF"), |line| {
+        let (
+            _,
+            StopLine {
+                stop_id,
+                designation,
+                long_name,
+                abbreviation,
+                synonyms,
+            },
+        ) = station_combinator.parse(line)?;
+        visitor.stop(Stop::new(
+            stop_id,
+            designation,
+            long_name,
+            abbreviation,
+            synonyms,
+        ));
+        Ok(())
+    })
+}
+
 fn parse_coord_line(
     line: &str,
     stops: &mut FxHashMap<i32, Stop>,
@@ -584,7 +601,7 @@ fn parse_coord_line(
             stop_id,
             x,
             y,
-            altitude: _, // altitude is not stored at the moment
+            altitude,
         },
     ) = coordinates_combinator.parse(line)?;
 
@@ -602,6 +619,7 @@ fn parse_coord_line(
             // are stored in reverse order
         }
     }
+    stop.set_altitude(altitude);
 
     Ok(())
 }
@@ -666,113 +684,155 @@ fn parse_times_line(line: &str, stops: &mut FxHashMap<i32, Stop>) -> PResult<Opt
     }
 }
 
-pub fn parse(version: Version, path: &Path) -> HResult<StopStorageAndExchangeTimes> {
+/// Returns an error in strict mode if UMSTEIGB has no default row, otherwise falls back to FALLBACK_DEFAULT_EXCHANGE_TIME.
+fn resolve_default_exchange_time(
+    default_exchange_time: Option<(i16, i16)>,
+    mode: ParsingMode,
+    report: &mut ParseReport,
+) -> PResult<(i16, i16)> {
+    match (default_exchange_time, mode) {
+        (Some(exchange_time), _) => Ok(exchange_time),
+        (None, ParsingMode::Strict) => Err(ParsingError::MissingDefaultExchangeTime),
+        (None, ParsingMode::Lenient) => {
+            log::warn!(
+                "UMSTEIGB has no default exchange time, {FALLBACK_DEFAULT_EXCHANGE_TIME:?} is used."
+            );
+            report.add(ParseIssue::MissingDefaultExchangeTime {
+                fallback: FALLBACK_DEFAULT_EXCHANGE_TIME,
+            });
+            Ok(FALLBACK_DEFAULT_EXCHANGE_TIME)
+        }
+    }
+}
+
+pub fn parse(
+    version: Version,
+    path: &Path,
+    options: &ParsingOptions,
+    report: &mut ParseReport,
+    errors: &mut LineErrors,
+) -> HResult<StopStorageAndExchangeTimes> {
     log::info!("Parsing BAHNHOF...");
 
     let mut stops = FxHashMap::default();
     let file = path.join("BAHNHOF");
-    read_lines(&file, 0)?
+    read_lines(&file, options)?
         .into_iter()
         .enumerate()
         .filter(|(_, line)| !line.trim().is_empty())
         .try_for_each(|(line_number, line)| {
-            parse_stop_line(&line, &mut stops).map_err(|e| HrdfError::Parsing {
+            let result = parse_stop_line(&line, &mut stops).map_err(|e| HrdfError::Parsing {
                 error: e,
                 file: String::from(file.to_string_lossy()),
                 line,
                 line_number,
-            })
+            });
+            errors.handle(result).map(|_| ())
         })?;
 
     log::info!("Parsing BFKOORD_LV95...");
     let file = path.join("BFKOORD_LV95");
-    read_lines(&file, 0)?
+    read_lines(&file, options)?
         .into_iter()
         .enumerate()
         .filter(|(_, line)| !line.trim().is_empty())
         .try_for_each(|(line_number, line)| {
-            parse_coord_line(&line, &mut stops, CoordinateSystem::LV95).map_err(|e| {
+            let result = parse_coord_line(&line, &mut stops, CoordinateSystem::LV95).map_err(|e| {
                 HrdfError::Parsing {
                     error: e,
                     file: String::from(file.to_string_lossy()),
                     line,
                     line_number,
                 }
-            })
+            });
+            errors.handle(result).map(|_| ())
         })?;
 
     let file = path.join("BFKOORD_WGS");
     log::info!("Parsing BFKOORD_WGS...");
-    read_lines(&file, 0)?
+    read_lines(&file, options)?
         .into_iter()
         .enumerate()
         .filter(|(_, line)| !line.trim().is_empty())
         .try_for_each(|(line_number, line)| {
-            parse_coord_line(&line, &mut stops, CoordinateSystem::WGS84).map_err(|e| {
-                HrdfError::Parsing {
-                    error: e,
-                    file: String::from(file.to_string_lossy()),
-                    line,
-                    line_number,
-                }
-            })
+            let result =
+                parse_coord_line(&line, &mut stops, CoordinateSystem::WGS84).map_err(|e| {
+                    HrdfError::Parsing {
+                        error: e,
+                        file: String::from(file.to_string_lossy()),
+                        line,
+                        line_number,
+                    }
+                });
+            errors.handle(result).map(|_| ())
         })?;
 
     log::info!("Parsing BFPRIOS...");
     let file = path.join("BFPRIOS");
-    read_lines(&file, 0)?
+    read_lines(&file, options)?
         .into_iter()
         .enumerate()
         .filter(|(_, line)| !line.trim().is_empty())
         .try_for_each(|(line_number, line)| {
-            parse_prios_line(&line, &mut stops).map_err(|e| HrdfError::Parsing {
+            let result = parse_prios_line(&line, &mut stops).map_err(|e| HrdfError::Parsing {
                 error: e,
                 file: String::from(file.to_string_lossy()),
                 line,
                 line_number,
-            })
+            });
+            errors.handle(result).map(|_| ())
         })?;
 
     log::info!("Parsing KMINFO...");
     let file = path.join("KMINFO");
-    read_lines(&file, 0)?
+    read_lines(&file, options)?
         .into_iter()
         .enumerate()
         .filter(|(_, line)| !line.trim().is_empty())
         .try_for_each(|(line_number, line)| {
-            parse_flags_line(&line, &mut stops).map_err(|e| HrdfError::Parsing {
+            let result = parse_flags_line(&line, &mut stops).map_err(|e| HrdfError::Parsing {
                 error: e,
                 file: String::from(file.to_string_lossy()),
                 line,
                 line_number,
-            })
+            });
+            errors.handle(result).map(|_| ())
         })?;
 
     log::info!("Parsing UMSTEIGB...");
     let file = path.join("UMSTEIGB");
-    let default_exchange_time = read_lines(&file, 0)?
+    let mut default_exchange_time = None;
+    read_lines(&file, options)?
         .into_iter()
-        .filter(|line| !line.trim().is_empty())
-        .map(|line| parse_times_line(&line, &mut stops))
-        .try_fold(None, |acc, curr| match (curr, acc) {
-            (Err(e), _) => Err(e),
-            (Ok(None), None) => Ok(None),
-            (_, Some(w)) => Ok(Some(w)),
-            (Ok(Some(v)), None) => Ok(Some(v)),
-        })
-        .map_err(|e| HrdfError::Parsing {
-            error: e,
-            file: String::from(file.to_string_lossy()),
-            line: String::default(),
-            line_number: 0,
-        })?
-        .ok_or(ParsingError::MissingDefaultExchangeTime)
-        .map_err(|e| HrdfError::Parsing {
-            error: e,
-            file: String::from(file.to_string_lossy()),
-            line: String::default(),
-            line_number: 0,
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .try_for_each(|(line_number, line)| {
+            match parse_times_line(&line, &mut stops) {
+                // The default row can be anywhere in the file, the first one is used.
+                Ok(Some(exchange_time)) => {
+                    default_exchange_time.get_or_insert(exchange_time);
+                    Ok(())
+                }
+                Ok(None) => Ok(()),
+                Err(e) => errors
+                    .handle::<()>(Err(HrdfError::Parsing {
+                        error: e,
+                        file: String::from(file.to_string_lossy()),
+                        line,
+                        line_number,
+                    }))
+                    .map(|_| ()),
+            }
         })?;
+    let default_exchange_time =
+        resolve_default_exchange_time(default_exchange_time, options.mode(), report).map_err(
+            |e| HrdfError::Parsing {
+                error: e,
+                file: String::from(file.to_string_lossy()),
+                line: String::default(),
+                line_number: 0,
+            },
+        )?;
 
     let bhfart = match version {
         Version::V_5_40_41_2_0_4 | Version::V_5_40_41_2_0_5 | Version::V_5_40_41_2_0_6 => {
@@ -783,17 +843,19 @@ pub fn parse(version: Version, path: &Path) -> HResult<StopStorageAndExchangeTim
     }?;
     log::info!("Parsing {bhfart}...");
     let file = path.join(bhfart);
-    read_lines(&file, 0)?
+    read_lines(&file, options)?
         .into_iter()
         .enumerate()
         .filter(|(_, line)| !line.trim().is_empty())
         .try_for_each(|(line_number, line)| {
-            parse_description_line(&line, &mut stops).map_err(|e| HrdfError::Parsing {
-                error: e,
-                file: String::from(file.to_string_lossy()),
-                line,
-                line_number,
-            })
+            let result =
+                parse_description_line(&line, &mut stops).map_err(|e| HrdfError::Parsing {
+                    error: e,
+                    file: String::from(file.to_string_lossy()),
+                    line,
+                    line_number,
+                });
+            errors.handle(result).map(|_| ())
         })?;
 
     Ok((ResourceStorage::new(stops), default_exchange_time))
@@ -926,6 +988,21 @@ mod tests {
         assert_eq!(times_line.exchange_time_other, 2);
     }
 
+    #[test]
+    fn test_combinators_with_variable_whitespace() {
+        let (_, prios_line) = prios_combinator("8500010\t4\tBasel SBB").unwrap();
+        assert_eq!(prios_line.stop_id, 8500010);
+        assert_eq!(prios_line.exchange_priority, 4);
+        assert_eq!(prios_line.name, "Basel SBB");
+
+        let (_, flags_line) = flags_combinator("8500010 \t 5000 Basel SBB").unwrap();
+        assert_eq!(flags_line.exchange_flag, 5000);
+
+        let (_, times_line) = times_combinator("8500010\t05 5\tBasel SBB").unwrap();
+        assert_eq!(times_line.exchange_time_inter_city, 5);
+        assert_eq!(times_line.exchange_time_other, 5);
+    }
+
     #[test]
     fn test_times_combinator_specific_stop() {
         let input = "8500010 05 05 Basel SBB";
@@ -994,6 +1071,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_description_line_sets_country_and_canton() {
+        let mut stops = FxHashMap::default();
+        stops.insert(
+            8500010,
+            Stop::new(8500010, "Basel SBB".to_string(), None, None, None),
+        );
+
+        parse_description_line("8500010 L CH", &mut stops).unwrap();
+        parse_description_line("8500010 I KT 000000012", &mut stops).unwrap();
+        // Rows of unknown stops are ignored.
+        parse_description_line("8500020 L CH", &mut stops).unwrap();
+
+        let stop = stops.get(&8500010).unwrap();
+        assert_eq!(stop.country_code(), Some("CH"));
+        assert_eq!(stop.canton(), Some(12));
+    }
+
     #[test]
     fn test_parse_stop_line_creates_stop() {
         let mut stops = FxHashMap::default();
@@ -1021,6 +1116,7 @@ mod tests {
 
         let stop = stops.get(&8500010).unwrap();
         assert!(stop.wgs84_coordinates().is_some());
+        assert_eq!(stop.altitude(), Some(0.0));
     }
 
     #[test]
@@ -1062,6 +1158,30 @@ mod tests {
         assert_eq!(stop.exchange_time(), Some((5, 5)));
     }
 
+    #[test]
+    fn resolve_default_exchange_time_depends_on_mode() {
+        let mut report = ParseReport::new();
+
+        assert_eq!(
+            resolve_default_exchange_time(Some((3, 4)), ParsingMode::Strict, &mut report).unwrap(),
+            (3, 4)
+        );
+        assert!(matches!(
+            resolve_default_exchange_time(None, ParsingMode::Strict, &mut report),
+            Err(ParsingError::MissingDefaultExchangeTime)
+        ));
+        assert!(report.is_empty());
+
+        assert_eq!(
+            resolve_default_exchange_time(None, ParsingMode::Lenient, &mut report).unwrap(),
+            (2, 2)
+        );
+        assert_eq!(
+            report.issues(),
+            &vec![ParseIssue::MissingDefaultExchangeTime { fallback: (2, 2) }]
+        );
+    }
+
     #[test]
     fn test_parse_times_line_default_sets_none() {
         let mut stops = FxHashMap::default();
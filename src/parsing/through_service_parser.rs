@@ -40,7 +40,8 @@ use crate::{
     error::{HResult, HrdfError},
     models::{Model, ThroughService},
     parsing::{
-        error::PResult,
+        ParsingOptions,
+        error::{LineErrors, PResult},
         helpers::{i32_from_n_digits_parser, read_lines, string_from_n_chars_parser},
     },
     storage::ResourceStorage,
@@ -96,6 +97,7 @@ fn parse_line(
     line: &str,
     data: &mut FxHashMap<i32, ThroughService>,
     journeys_pk_type_converter: &FxHashSet<JourneyId>,
+    options: &ParsingOptions,
     auto_increment: &AutoIncrement,
 ) -> PResult<()> {
     let (_, through_service_line) = through_service_combinator(line)?;
@@ -110,6 +112,12 @@ fn parse_line(
             bit_field_id,
             journey_2_stop_id,
         } => {
+            if !options.includes_administration(&journey_1_administration)
+                || !options.includes_administration(&journey_2_administration)
+            {
+                return Ok(());
+            }
+
             let journey_1 =
                 journeys_pk_type_converter.get(&(journey_1_id, journey_1_administration.clone()));
             if journey_1.is_none() {
@@ -149,22 +157,25 @@ fn parse_line(
 pub fn parse(
     path: &Path,
     journeys_pk_type_converter: &FxHashSet<JourneyId>,
+    options: &ParsingOptions,
+    errors: &mut LineErrors,
 ) -> HResult<ResourceStorage<ThroughService>> {
     log::info!("Parsing DURCHBI...");
     let auto_increment = AutoIncrement::new();
     let mut through_services = FxHashMap::default();
 
     let file = path.join("DURCHBI");
-    let through_service_lines = read_lines(&file, 0)?;
+    let through_service_lines = read_lines(&file, options)?;
     through_service_lines
         .into_iter()
         .enumerate()
         .filter(|(_, line)| !line.trim().is_empty())
         .try_for_each(|(line_number, line)| {
-            parse_line(
+            let result = parse_line(
                 &line,
                 &mut through_services,
                 journeys_pk_type_converter,
+                options,
                 &auto_increment,
             )
             .map_err(|e| HrdfError::Parsing {
@@ -172,13 +183,15 @@ pub fn parse(
                 file: String::from(file.to_string_lossy()),
                 line,
                 line_number,
-            })
+            });
+            errors.handle(result).map(|_| ())
         })?;
     Ok(ResourceStorage::new(through_services))
 }
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "serde")]
     use crate::parsing::tests::get_json_values;
 
     use super::*;
@@ -278,6 +291,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_parse_line_creates_through_service() {
         let mut data = FxHashMap::default();
@@ -290,6 +304,7 @@ mod tests {
             "000001 000871 8576671 024064 000871 000010 8576671",
             &mut data,
             &journeys,
+            &ParsingOptions::default(),
             &auto_increment,
         )
         .unwrap();
@@ -309,6 +324,7 @@ mod tests {
         assert_eq!(ts, reference);
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_parse_line_missing_journey_logs_warning() {
         let mut data = FxHashMap::default();
@@ -320,6 +336,7 @@ mod tests {
             "000001 000871 8576671 024064 000871 000010 8576671",
             &mut data,
             &journeys,
+            &ParsingOptions::default(),
             &auto_increment,
         )
         .unwrap();
@@ -339,6 +356,7 @@ mod tests {
         assert_eq!(ts, reference);
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_parse_line_multiple_through_services() {
         let mut data = FxHashMap::default();
@@ -353,6 +371,7 @@ mod tests {
             "000001 000871 8576671 024064 000871 000010 8576671",
             &mut data,
             &journeys,
+            &ParsingOptions::default(),
             &auto_increment,
         )
         .unwrap();
@@ -361,6 +380,7 @@ mod tests {
             "000002 000181 8530625 000003 000181 000000 8530625",
             &mut data,
             &journeys,
+            &ParsingOptions::default(),
             &auto_increment,
         )
         .unwrap();
@@ -390,6 +410,7 @@ mod tests {
         assert_eq!(ts, reference);
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_parse_line_matching_stops() {
         let mut data = FxHashMap::default();
@@ -401,6 +422,7 @@ mod tests {
             "000002 000181 8530625 000003 000181 000000 8530625",
             &mut data,
             &journeys,
+            &ParsingOptions::default(),
             &auto_increment,
         )
         .unwrap();
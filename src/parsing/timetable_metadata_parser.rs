@@ -27,7 +27,11 @@ use rustc_hash::FxHashMap;
 use crate::{
     error::{HResult, HrdfError},
     models::{Model, TimetableMetadataEntry},
-    parsing::{error::PResult, helpers::read_lines},
+    parsing::{
+        ParsingOptions,
+        error::{PResult, ParsingError},
+        helpers::read_lines,
+    },
     storage::ResourceStorage,
     utils::AutoIncrement,
 };
@@ -67,29 +71,25 @@ fn parse_line(
     auto_increment: &AutoIncrement,
 ) -> PResult<()> {
     let (_, res) = alt((date_combinator, info_combinator)).parse(line)?;
-    match res {
-        InfoLines::Date(d) => {
-            let tt = TimetableMetadataEntry::new(
-                auto_increment.next(),
-                keys[*index].to_owned(),
-                d.to_string(),
-            );
-            data.insert(tt.id(), tt);
-            *index += 1;
-        }
-        InfoLines::MetaData(mt) => {
-            for t in mt {
-                let tt =
-                    TimetableMetadataEntry::new(auto_increment.next(), keys[*index].to_owned(), t);
-                data.insert(tt.id(), tt);
-                *index += 1;
-            }
-        }
+    let values = match res {
+        InfoLines::Date(d) => vec![d.to_string()],
+        InfoLines::MetaData(mt) => mt,
+    };
+    for value in values {
+        let key = keys
+            .get(*index)
+            .ok_or_else(|| ParsingError::Unknown(format!("Unexpected value: {value}")))?;
+        let tt = TimetableMetadataEntry::new(auto_increment.next(), key.to_string(), value);
+        data.insert(tt.id(), tt);
+        *index += 1;
     }
     Ok(())
 }
 
-pub fn parse(path: &Path) -> HResult<ResourceStorage<TimetableMetadataEntry>> {
+pub fn parse(
+    path: &Path,
+    options: &ParsingOptions,
+) -> HResult<ResourceStorage<TimetableMetadataEntry>> {
     log::info!("Parsing ECKDATEN...");
     let auto_increment = AutoIncrement::new();
     let keys = [
@@ -103,7 +103,7 @@ pub fn parse(path: &Path) -> HResult<ResourceStorage<TimetableMetadataEntry>> {
     let mut index = 0;
     let mut data = FxHashMap::default();
     let file = path.join("ECKDATEN");
-    let time_table = read_lines(&file, 0)?;
+    let time_table = read_lines(&file, options)?;
     time_table
         .into_iter()
         .enumerate()
@@ -293,4 +293,17 @@ mod tests {
             _ => panic!("Expected MetaData variant"),
         }
     }
+
+    #[test]
+    fn parse_line_rejects_values_beyond_the_keys() {
+        let mut data = FxHashMap::default();
+        let mut index = 0;
+        let auto_increment = AutoIncrement::new();
+        let keys = ["start_date", "end_date"];
+
+        parse_line("14.12.2025", &mut data, &keys, &mut index, &auto_increment).unwrap();
+        let result = parse_line("a$b", &mut data, &keys, &mut index, &auto_increment);
+        assert!(matches!(result, Err(ParsingError::Unknown(_))));
+        assert_eq!(index, 2);
+    }
 }
@@ -52,7 +52,8 @@ use crate::error::{HResult, HrdfError};
 use crate::{
     models::{Language, TransportCompany},
     parsing::{
-        error::PResult,
+        ParsingOptions,
+        error::{LineErrors, PResult},
         helpers::{read_lines, string_till_eol_parser},
     },
     storage::ResourceStorage,
@@ -192,8 +193,14 @@ fn parse_transport_company_line(
                 transport_company.insert(id, tc);
             }
         }
-        TransportCompanyLine::Nline { id: _, sboid: _ } => {
-            // TODO: Use sboid some day
+        TransportCompanyLine::Nline { id, sboid } => {
+            if let Some(tc) = transport_company.get_mut(&id) {
+                tc.set_sboid(sboid);
+            } else {
+                let mut tc = TransportCompany::new(id);
+                tc.set_sboid(sboid);
+                transport_company.insert(id, tc);
+            }
         }
         TransportCompanyLine::ColonLine {
             id,
@@ -212,7 +219,11 @@ fn parse_transport_company_line(
     Ok(())
 }
 
-pub fn parse(path: &Path) -> HResult<ResourceStorage<TransportCompany>> {
+pub fn parse(
+    path: &Path,
+    options: &ParsingOptions,
+    errors: &mut LineErrors,
+) -> HResult<ResourceStorage<TransportCompany>> {
     let languages = [
         Language::German,
         Language::English,
@@ -230,19 +241,19 @@ pub fn parse(path: &Path) -> HResult<ResourceStorage<TransportCompany>> {
         };
         log::info!("Parsing BETRIEB_{postfix}...");
         let file = path.join(format!("BETRIEB_{postfix}"));
-        read_lines(&file, 0)?
+        read_lines(&file, options)?
             .into_iter()
             .enumerate()
             .filter(|(_, line)| !line.trim().is_empty())
             .try_for_each(|(line_number, line)| {
-                parse_transport_company_line(&line, &mut transport_company, language).map_err(|e| {
-                    HrdfError::Parsing {
+                let result = parse_transport_company_line(&line, &mut transport_company, language)
+                    .map_err(|e| HrdfError::Parsing {
                         error: e,
                         file: String::from(file.to_string_lossy()),
                         line,
                         line_number,
-                    }
-                })
+                    });
+                errors.handle(result).map(|_| ())
             })?;
     }
 
@@ -252,6 +263,7 @@ pub fn parse(path: &Path) -> HResult<ResourceStorage<TransportCompany>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "serde")]
     use crate::parsing::tests::get_json_values;
     use pretty_assertions::assert_eq;
 
@@ -375,6 +387,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_parse_transport_company_line_creates_new_company() {
         let mut companies = FxHashMap::default();
@@ -399,6 +412,7 @@ mod tests {
         assert_eq!(company, reference);
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_parse_transport_company_line_updates_existing() {
         let mut companies = FxHashMap::default();
@@ -429,6 +443,7 @@ mod tests {
         assert_eq!(company, reference);
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_parse_transport_company_line_multiple_languages() {
         let mut companies = FxHashMap::default();
@@ -462,6 +477,7 @@ mod tests {
         assert_eq!(company, reference);
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_colon_line_creates_company_if_not_exists() {
         let mut companies = FxHashMap::default();
@@ -483,8 +499,9 @@ mod tests {
         assert_eq!(company, reference);
     }
 
+    #[cfg(feature = "serde")]
     #[test]
-    fn test_nline_parsing_ignores_sboid() {
+    fn test_nline_parsing_sets_sboid() {
         let mut companies = FxHashMap::default();
         companies.insert(379, TransportCompany::new(379));
 
@@ -495,7 +512,6 @@ mod tests {
         );
 
         assert!(result.is_ok());
-        // SBOID is currently not used (TODO in code)
         let company = companies.get(&379).unwrap();
         let reference = r#"
             {
@@ -503,7 +519,8 @@ mod tests {
                 "short_name":{},
                 "long_name":{},
                 "full_name":{},
-                "administrations":[]
+                "administrations":[],
+                "sboid":"ch:1:sboid:379"
             }"#;
 
         let (company, reference) = get_json_values(company, reference).unwrap();
@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::{collections::hash_map::Entry, path::Path};
 
 /// # ZUGART file
 ///
@@ -97,7 +97,8 @@ use crate::{
     error::{HResult, HrdfError},
     models::{Language, Model, TransportType},
     parsing::{
-        error::{PResult, ParsingError},
+        ParseIssue, ParseReport, ParsingMode, ParsingOptions,
+        error::{LineErrors, PResult, ParsingError},
         helpers::{
             optional_i32_from_n_digits_parser, read_lines, string_from_n_chars_parser,
             string_till_eol_parser,
@@ -243,7 +244,10 @@ fn parse_line(
     data: &mut FxHashMap<i32, TransportType>,
     pk_type_converter: &mut FxHashMap<String, i32>,
     auto_increment: &AutoIncrement,
-    current_language: &mut Language,
+    // None while in the block of an unknown language, whose lines are skipped.
+    current_language: &mut Option<Language>,
+    mode: ParsingMode,
+    report: &mut ParseReport,
 ) -> PResult<()> {
     let (_, transport_row) = alt((
         offer_definition_combinator,
@@ -267,11 +271,27 @@ fn parse_line(
         } => {
             let id = auto_increment.next();
 
-            if let Some(previous) = pk_type_converter.insert(designation.to_owned(), id) {
-                log::error!(
-                    "Warning: previous id {previous} for {designation}. The designation, {designation}, is not unique."
-                );
-            };
+            // The first definition of a designation wins.
+            match pk_type_converter.entry(designation.to_owned()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(id);
+                }
+                Entry::Occupied(entry) => {
+                    if mode == ParsingMode::Strict {
+                        return Err(ParsingError::DuplicateTransportType(designation));
+                    }
+
+                    let kept_id = *entry.get();
+                    log::warn!(
+                        "The designation {designation} is not unique, the first definition (id {kept_id}) is kept."
+                    );
+                    report.add(ParseIssue::DuplicateTransportType {
+                        designation: designation.to_owned(),
+                        kept_id,
+                        ignored_id: id,
+                    });
+                }
+            }
             let tt = TransportType::new(
                 id,
                 designation.to_owned(),
@@ -287,30 +307,41 @@ fn parse_line(
         TransportTypeAndTypeLine::LanguageDefinition(language) => {
             match language.as_str() {
                 "Deutsch" => {
-                    *current_language = Language::German;
+                    *current_language = Some(Language::German);
                 }
                 "Franzoesisch" => {
-                    *current_language = Language::French;
+                    *current_language = Some(Language::French);
                 }
                 "Englisch" => {
-                    *current_language = Language::English;
+                    *current_language = Some(Language::English);
                 }
                 "Italienisch" => {
-                    *current_language = Language::Italian;
+                    *current_language = Some(Language::Italian);
                 }
                 "text" => {
                     // Do nothing
                 }
-                _ => unreachable!(),
+                _ => {
+                    if mode == ParsingMode::Strict {
+                        return Err(ParsingError::UnknownLanguage(language));
+                    }
+
+                    log::warn!("Unknown language <{language}>, its texts are skipped.");
+                    report.add(ParseIssue::UnknownLanguage { name: language });
+                    *current_language = None;
+                }
             };
         }
         TransportTypeAndTypeLine::Class {
             product_class_id,
             product_class_name,
         } => {
+            let Some(current_language) = *current_language else {
+                return Ok(());
+            };
             for transport_type in data.values_mut() {
                 if transport_type.product_class_id() == product_class_id {
-                    transport_type.set_product_class_name(*current_language, &product_class_name)
+                    transport_type.set_product_class_name(current_language, &product_class_name)
                 }
             }
         }
@@ -318,9 +349,12 @@ fn parse_line(
             category_id: _,
             category_name,
         } => {
+            let Some(current_language) = *current_language else {
+                return Ok(());
+            };
             let id = auto_increment.get();
             if let Some(transport_type) = data.get_mut(&id) {
-                transport_type.set_category_name(*current_language, &category_name);
+                transport_type.set_category_name(current_language, &category_name);
             } else {
                 return Err(ParsingError::UnknownId(format!(
                     "Error: TransportType: {id}"
@@ -340,35 +374,43 @@ fn parse_line(
     Ok(())
 }
 
-pub fn parse(path: &Path) -> HResult<TransportTypeAndTypeConverter> {
+pub fn parse(
+    path: &Path,
+    options: &ParsingOptions,
+    report: &mut ParseReport,
+    errors: &mut LineErrors,
+) -> HResult<TransportTypeAndTypeConverter> {
     log::info!("Parsing ZUGART...");
 
     let file = path.join("ZUGART");
-    let transport_types = read_lines(&file, 0)?;
+    let transport_types = read_lines(&file, options)?;
 
     let auto_increment = AutoIncrement::new();
     let mut data = FxHashMap::default();
     let mut pk_type_converter = FxHashMap::default();
-    let mut current_language = Language::default();
+    let mut current_language = Some(Language::default());
 
     transport_types
         .into_iter()
         .enumerate()
         .filter(|(_, line)| !line.trim().is_empty())
         .try_for_each(|(line_number, line)| {
-            parse_line(
+            let result = parse_line(
                 &line,
                 &mut data,
                 &mut pk_type_converter,
                 &auto_increment,
                 &mut current_language,
+                options.mode(),
+                report,
             )
             .map_err(|e| HrdfError::Parsing {
                 error: e,
                 file: String::from(file.to_string_lossy()),
                 line,
                 line_number,
-            })
+            });
+            errors.handle(result).map(|_| ())
         })?;
 
     Ok((ResourceStorage::new(data), pk_type_converter))
@@ -379,6 +421,95 @@ mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
 
+    fn parse_lines(
+        lines: &[&str],
+        mode: ParsingMode,
+    ) -> (PResult<()>, FxHashMap<String, i32>, ParseReport) {
+        let mut data = FxHashMap::default();
+        let mut pk_type_converter = FxHashMap::default();
+        let auto_increment = AutoIncrement::new();
+        let mut current_language = Some(Language::default());
+        let mut report = ParseReport::new();
+
+        let result = lines.iter().try_for_each(|line| {
+            parse_line(
+                line,
+                &mut data,
+                &mut pk_type_converter,
+                &auto_increment,
+                &mut current_language,
+                mode,
+                &mut report,
+            )
+        });
+        (result, pk_type_converter, report)
+    }
+
+    #[test]
+    fn duplicate_designation_keeps_first_definition() {
+        let (result, pk_type_converter, report) = parse_lines(
+            &["RUB 6 A 0 RUB      0 B", "RUB 7 A 0 RUB      0 B"],
+            ParsingMode::Lenient,
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(pk_type_converter.get("RUB"), Some(&1));
+        assert_eq!(
+            report.issues(),
+            &vec![ParseIssue::DuplicateTransportType {
+                designation: "RUB".to_string(),
+                kept_id: 1,
+                ignored_id: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn duplicate_designation_is_an_error_in_strict_mode() {
+        let (result, pk_type_converter, _) = parse_lines(
+            &["RUB 6 A 0 RUB      0 B", "RUB 7 A 0 RUB      0 B"],
+            ParsingMode::Strict,
+        );
+
+        assert!(matches!(
+            result,
+            Err(ParsingError::DuplicateTransportType(designation)) if designation == "RUB"
+        ));
+        assert_eq!(pk_type_converter.get("RUB"), Some(&1));
+    }
+
+    #[test]
+    fn unknown_language_block_is_skipped() {
+        let (result, _, report) = parse_lines(
+            &[
+                "RUB 6 A 0 RUB      0 B",
+                "<Spanisch>",
+                "class06 Autobus",
+                "<Deutsch>",
+                "class06 Bus",
+            ],
+            ParsingMode::Lenient,
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(
+            report.issues(),
+            &vec![ParseIssue::UnknownLanguage {
+                name: "Spanisch".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn unknown_language_is_an_error_in_strict_mode() {
+        let (result, _, _) = parse_lines(&["<Deutch>"], ParsingMode::Strict);
+
+        assert!(matches!(
+            result,
+            Err(ParsingError::UnknownLanguage(name)) if name == "Deutch"
+        ));
+    }
+
     #[test]
     fn test_offer_definition_combinator_with_flag() {
         let input = "RUB 6 A 0 RUB      0 B";
@@ -0,0 +1,158 @@
+use std::path::Path;
+
+use crate::{
+    error::HResult,
+    models::{JourneyRouteEntry, Stop, Version},
+    parsing::{journey_parser, platform_parser, stop_parser},
+};
+
+/// Receives the records of the HRDF files read by visit. Every function does nothing by default.
+///
+/// The records reference each other with the codes of the files (journey numbers and
+/// administrations, designations of the transport types, indexes of the platforms), as visit keeps
+/// nothing in memory to resolve them.
+pub trait Visitor {
+    /// A stop of BAHNHOF, with its names.
+    fn stop(&mut self, _stop: Stop) {}
+
+    /// A *Z row of FPLAN. The rows up to journey_finished belong to this journey.
+    fn journey_started(&mut self, _legacy_id: i32, _administration: &str) {}
+
+    /// A *G row of the current journey, the designation of the transport type (e.g. "IC").
+    fn journey_transport_type(
+        &mut self,
+        _designation: &str,
+        _from_stop_id: Option<i32>,
+        _until_stop_id: Option<i32>,
+    ) {
+    }
+
+    /// A *A VE row of the current journey, the bit field of its operating days.
+    fn journey_bit_field(
+        &mut self,
+        _bit_field_id: Option<i32>,
+        _from_stop_id: Option<i32>,
+        _until_stop_id: Option<i32>,
+    ) {
+    }
+
+    /// A stop of the route of the current journey.
+    fn route_entry(&mut self, _route_entry: JourneyRouteEntry) {}
+
+    fn journey_finished(&mut self) {}
+
+    /// A platform of GLEISE, identified by the stop and its index (#0000001 is 1).
+    fn platform(&mut self, _stop_id: i32, _index: i32, _name: &str, _sectors: Option<&str>) {}
+
+    /// A row of GLEISE assigning a platform to a journey.
+    fn journey_platform(
+        &mut self,
+        _legacy_id: i32,
+        _administration: &str,
+        _stop_id: i32,
+        _index: i32,
+        _bit_field_id: Option<i32>,
+    ) {
+    }
+}
+
+/// Reads BAHNHOF, FPLAN and GLEISE (LV95) in this order and passes their records to the visitor
+/// as they are read, one line at a time, without building a DataStorage. The memory used doesn't
+/// depend on the size of the files, which suits loading a full export into another store.
+///
+/// The other rows of the files are skipped and the parsing stops at the first line in error.
+pub fn visit(path: &Path, version: Version, visitor: &mut impl Visitor) -> HResult<()> {
+    stop_parser::visit(path, visitor)?;
+    journey_parser::visit(path, visitor)?;
+    platform_parser::visit(version, path, visitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Model;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Default)]
+    struct Counter {
+        stops: Vec<i32>,
+        journeys: Vec<(i32, String, Vec<i32>)>,
+        transport_types: Vec<String>,
+        platforms: usize,
+        journey_platforms: usize,
+        open: bool,
+    }
+
+    impl Visitor for Counter {
+        fn stop(&mut self, stop: Stop) {
+            self.stops.push(stop.id());
+        }
+
+        fn journey_started(&mut self, legacy_id: i32, administration: &str) {
+            assert!(!self.open);
+            self.open = true;
+            self.journeys
+                .push((legacy_id, administration.to_string(), Vec::new()));
+        }
+
+        fn journey_transport_type(
+            &mut self,
+            designation: &str,
+            _from_stop_id: Option<i32>,
+            _until_stop_id: Option<i32>,
+        ) {
+            self.transport_types.push(designation.to_string());
+        }
+
+        fn route_entry(&mut self, route_entry: JourneyRouteEntry) {
+            // unwrap: A route row always follows a *Z row.
+            self.journeys
+                .last_mut()
+                .unwrap()
+                .2
+                .push(route_entry.stop_id());
+        }
+
+        fn journey_finished(&mut self) {
+            assert!(self.open);
+            self.open = false;
+        }
+
+        fn platform(&mut self, _stop_id: i32, _index: i32, _name: &str, _sectors: Option<&str>) {
+            self.platforms += 1;
+        }
+
+        fn journey_platform(
+            &mut self,
+            _legacy_id: i32,
+            _administration: &str,
+            _stop_id: i32,
+            _index: i32,
+            _bit_field_id: Option<i32>,
+        ) {
+            self.journey_platforms += 1;
+        }
+    }
+
+    #[test]
+    fn visit_mini_dataset() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/mini");
+        let mut counter = Counter::default();
+        visit(&path, Version::V_5_40_41_2_0_7, &mut counter).unwrap();
+
+        assert_eq!(counter.stops.len(), 5);
+        assert!(!counter.open);
+        assert_eq!(counter.journeys.len(), 6);
+        assert_eq!(
+            counter.journeys[0],
+            (
+                1,
+                "000001".to_string(),
+                vec![8500101, 8500102, 8500103, 8500104]
+            )
+        );
+        assert_eq!(counter.transport_types.len(), 6);
+        assert_eq!(counter.platforms, 2);
+        assert_eq!(counter.journey_platforms, 3);
+    }
+}
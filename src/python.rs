@@ -0,0 +1,242 @@
+use std::path::PathBuf;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+};
+
+use crate::{
+    error::HrdfError,
+    hrdf::Hrdf,
+    models::{Model, Stop, Version},
+};
+
+fn to_py_err(error: HrdfError) -> PyErr {
+    PyRuntimeError::new_err(error.to_string())
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- PyHrdf
+// ------------------------------------------------------------------------------------------------
+
+#[pyclass(name = "Hrdf", module = "hrdf_parser", frozen)]
+pub struct PyHrdf {
+    hrdf: Hrdf,
+}
+
+#[pymethods]
+impl PyHrdf {
+    /// Parses an HRDF archive already decompressed into a directory. The version is the name of
+    /// a Version variant (e.g. "V_5_40_41_2_0_7").
+    #[staticmethod]
+    fn from_directory(version: &str, path: PathBuf) -> PyResult<Self> {
+        let version = version
+            .parse::<Version>()
+            .map_err(|_| PyValueError::new_err(format!("Unknown version: {version}")))?;
+        let hrdf = Hrdf::from_directory(version, &path).map_err(to_py_err)?;
+        Ok(Self { hrdf })
+    }
+
+    /// Decodes data encoded by Hrdf::to_bytes.
+    #[cfg(feature = "serde")]
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        let hrdf = Hrdf::from_bytes(data).map_err(to_py_err)?;
+        Ok(Self { hrdf })
+    }
+
+    fn timetable_period(&self) -> (NaiveDate, NaiveDate) {
+        let timetable_period = self.hrdf.timetable_period();
+        (*timetable_period.start(), *timetable_period.end())
+    }
+
+    #[pyo3(signature = (query, expand_meta_stops = false))]
+    fn search_stops(&self, query: &str, expand_meta_stops: bool) -> Vec<PyStop> {
+        self.hrdf
+            .data_storage()
+            .search_stops(query, expand_meta_stops)
+            .into_iter()
+            .map(PyStop::new)
+            .collect()
+    }
+
+    /// The departures of the station board, flattened and sorted by departure time.
+    /// The GIL is released while the board is built.
+    #[pyo3(signature = (stop_id, from, until, expand_meta_stops = false))]
+    fn station_board(
+        &self,
+        py: Python<'_>,
+        stop_id: i32,
+        from: NaiveDateTime,
+        until: NaiveDateTime,
+        expand_meta_stops: bool,
+    ) -> PyResult<Vec<PyDeparture>> {
+        let station_board = py
+            .allow_threads(|| {
+                self.hrdf
+                    .data_storage()
+                    .station_board(stop_id, from, until, expand_meta_stops)
+            })
+            .map_err(to_py_err)?;
+
+        let mut departures: Vec<_> = station_board
+            .platforms()
+            .iter()
+            .flat_map(|platform| {
+                platform.entries().iter().map(|entry| PyDeparture {
+                    journey_id: entry.journey_id(),
+                    stop_id: platform.stop_id(),
+                    platform: platform.name().map(str::to_string),
+                    departure_at: entry.departure_at(),
+                    destination_stop_id: entry.destination_stop_id(),
+                    line_name: entry.line_name().map(str::to_string),
+                    attributes: entry.attributes().to_vec(),
+                })
+            })
+            .collect();
+        departures.sort_by_key(|departure| (departure.departure_at, departure.journey_id));
+        Ok(departures)
+    }
+
+    /// Earliest arrival times, one row per origin and one column per destination.
+    /// The GIL is released during the routing, so that other Python threads can run.
+    #[pyo3(signature = (origin_stop_ids, destination_stop_ids, departure_at, max_transfers = 2))]
+    fn earliest_arrival_matrix(
+        &self,
+        py: Python<'_>,
+        origin_stop_ids: Vec<i32>,
+        destination_stop_ids: Vec<i32>,
+        departure_at: NaiveDateTime,
+        max_transfers: usize,
+    ) -> PyResult<Vec<Vec<Option<NaiveDateTime>>>> {
+        let matrix = py
+            .allow_threads(|| {
+                self.hrdf.data_storage().earliest_arrival_matrix(
+                    &origin_stop_ids,
+                    &destination_stop_ids,
+                    departure_at,
+                    max_transfers,
+                )
+            })
+            .map_err(to_py_err)?;
+        Ok(matrix.arrival_times().to_vec())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- PyStop
+// ------------------------------------------------------------------------------------------------
+
+#[pyclass(name = "Stop", module = "hrdf_parser", frozen, get_all)]
+pub struct PyStop {
+    id: i32,
+    name: String,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+}
+
+impl PyStop {
+    fn new(stop: &Stop) -> Self {
+        let coordinates = stop.wgs84_position();
+        Self {
+            id: stop.id(),
+            name: stop.name().to_string(),
+            latitude: coordinates.and_then(|coordinates| coordinates.latitude()),
+            longitude: coordinates.and_then(|coordinates| coordinates.longitude()),
+        }
+    }
+}
+
+#[pymethods]
+impl PyStop {
+    fn __repr__(&self) -> String {
+        format!("Stop({}, {:?})", self.id, self.name)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- PyDeparture
+// ------------------------------------------------------------------------------------------------
+
+#[pyclass(name = "Departure", module = "hrdf_parser", frozen, get_all)]
+pub struct PyDeparture {
+    journey_id: i32,
+    stop_id: i32,
+    platform: Option<String>,
+    departure_at: NaiveDateTime,
+    destination_stop_id: Option<i32>,
+    line_name: Option<String>,
+    attributes: Vec<String>,
+}
+
+#[pymethods]
+impl PyDeparture {
+    fn __repr__(&self) -> String {
+        format!(
+            "Departure({}, {:?}, {})",
+            self.journey_id, self.line_name, self.departure_at
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Module
+// ------------------------------------------------------------------------------------------------
+
+#[pymodule]
+fn hrdf_parser(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyHrdf>()?;
+    module.add_class::<PyStop>()?;
+    module.add_class::<PyDeparture>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::py_run;
+
+    #[test]
+    fn queries_from_python() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let hrdf_parser = pyo3::wrap_pymodule!(hrdf_parser)(py);
+            let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/mini");
+            py_run!(
+                py,
+                hrdf_parser path,
+                r#"
+                from datetime import datetime
+
+                hrdf = hrdf_parser.Hrdf.from_directory("V_5_40_41_2_0_7", path)
+                assert hrdf.timetable_period()[0].isoformat() == "2025-12-14"
+
+                [stop] = hrdf.search_stops("aarstadt nord")
+                assert (stop.id, stop.name) == (8500102, "Aarstadt Nord")
+                assert stop.latitude is not None
+
+                departures = hrdf.station_board(
+                    8500101, datetime(2025, 12, 15, 6), datetime(2025, 12, 15, 7, 30)
+                )
+                assert [departure.departure_at for departure in departures] == [
+                    datetime(2025, 12, 15, 6),
+                    datetime(2025, 12, 15, 7),
+                    datetime(2025, 12, 15, 7, 15),
+                ]
+
+                matrix = hrdf.earliest_arrival_matrix(
+                    [8500101], [8500104], datetime(2025, 12, 15, 6)
+                )
+                assert matrix == [[datetime(2025, 12, 15, 6, 20)]]
+
+                try:
+                    hrdf.earliest_arrival_matrix([8500101], [8500104], datetime(2030, 1, 1))
+                    raise AssertionError("The date is outside of the timetable period")
+                except RuntimeError:
+                    pass
+                "#
+            );
+        });
+    }
+}
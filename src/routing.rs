@@ -0,0 +1,574 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use rustc_hash::{FxHashMap, FxHashSet};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    models::{Model, ServiceTime},
+    storage::DataStorage,
+    transfers::UNKNOWN_PRODUCT_CLASS,
+};
+
+// ------------------------------------------------------------------------------------------------
+// --- ArrivalMatrix
+// ------------------------------------------------------------------------------------------------
+
+/// Earliest arrival times from a set of origin stops to a set of destination stops
+/// (see DataStorage::earliest_arrival_matrix).
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ArrivalMatrix {
+    origin_stop_ids: Vec<i32>,
+    destination_stop_ids: Vec<i32>,
+    departure_at: NaiveDateTime,
+    arrival_times: Vec<Vec<Option<NaiveDateTime>>>,
+}
+
+impl ArrivalMatrix {
+    pub fn new(
+        origin_stop_ids: Vec<i32>,
+        destination_stop_ids: Vec<i32>,
+        departure_at: NaiveDateTime,
+        arrival_times: Vec<Vec<Option<NaiveDateTime>>>,
+    ) -> Self {
+        Self {
+            origin_stop_ids,
+            destination_stop_ids,
+            departure_at,
+            arrival_times,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn origin_stop_ids(&self) -> &[i32] {
+        &self.origin_stop_ids
+    }
+
+    pub fn destination_stop_ids(&self) -> &[i32] {
+        &self.destination_stop_ids
+    }
+
+    pub fn departure_at(&self) -> NaiveDateTime {
+        self.departure_at
+    }
+
+    /// One row per origin, one column per destination, in the order of the stop IDs.
+    /// None if the destination cannot be reached.
+    pub fn arrival_times(&self) -> &[Vec<Option<NaiveDateTime>>] {
+        &self.arrival_times
+    }
+
+    // Functions
+
+    pub fn arrival_at(
+        &self,
+        origin_stop_id: i32,
+        destination_stop_id: i32,
+    ) -> Option<NaiveDateTime> {
+        let row = self
+            .origin_stop_ids
+            .iter()
+            .position(|&stop_id| stop_id == origin_stop_id)?;
+        let column = self
+            .destination_stop_ids
+            .iter()
+            .position(|&stop_id| stop_id == destination_stop_id)?;
+        self.arrival_times[row][column]
+    }
+
+    /// Time between the departure and the earliest arrival, in minutes.
+    pub fn travel_time(&self, origin_stop_id: i32, destination_stop_id: i32) -> Option<i64> {
+        self.arrival_at(origin_stop_id, destination_stop_id)
+            .map(|arrival_at| (arrival_at - self.departure_at).num_minutes())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- ItineraryLeg
+// ------------------------------------------------------------------------------------------------
+
+/// A ride on a journey from the stop where it is boarded to the stop where it is left
+/// (see DataStorage::earliest_itinerary).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ItineraryLeg {
+    journey_id: i32,
+    service_day: NaiveDate,
+    from_stop_id: i32,
+    to_stop_id: i32,
+    departure_at: NaiveDateTime,
+    arrival_at: NaiveDateTime,
+}
+
+impl ItineraryLeg {
+    pub fn new(
+        journey_id: i32,
+        service_day: NaiveDate,
+        from_stop_id: i32,
+        to_stop_id: i32,
+        departure_at: NaiveDateTime,
+        arrival_at: NaiveDateTime,
+    ) -> Self {
+        Self {
+            journey_id,
+            service_day,
+            from_stop_id,
+            to_stop_id,
+            departure_at,
+            arrival_at,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn journey_id(&self) -> i32 {
+        self.journey_id
+    }
+
+    /// The day on which the journey leaves its first stop.
+    pub fn service_day(&self) -> NaiveDate {
+        self.service_day
+    }
+
+    pub fn from_stop_id(&self) -> i32 {
+        self.from_stop_id
+    }
+
+    pub fn to_stop_id(&self) -> i32 {
+        self.to_stop_id
+    }
+
+    pub fn departure_at(&self) -> NaiveDateTime {
+        self.departure_at
+    }
+
+    pub fn arrival_at(&self) -> NaiveDateTime {
+        self.arrival_at
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Timetable
+// ------------------------------------------------------------------------------------------------
+
+/// A journey on a service day, with its (arrival, departure) at each stop of its pattern.
+struct Trip {
+    journey_id: i32,
+    service_day: NaiveDate,
+    times: Vec<(Option<NaiveDateTime>, Option<NaiveDateTime>)>,
+}
+
+/// How a stop is reached, from the step which reached the previous stop (None at the origin).
+enum Step {
+    Ride {
+        pattern: usize,
+        trip: usize,
+        boarding_position: usize,
+        alighting_position: usize,
+        previous: Option<usize>,
+    },
+    Walk {
+        previous: Option<usize>,
+    },
+}
+
+/// The labels of the stops after a RAPTOR run from an origin.
+struct Arrivals {
+    /// Earliest arrival at the stops over all the rounds.
+    best_arrivals: Vec<Option<NaiveDateTime>>,
+    /// Earliest time a vehicle can be boarded at the stops.
+    ready_times: Vec<Option<NaiveDateTime>>,
+    /// Step reaching the stops at their best arrival.
+    arrival_steps: Vec<Option<usize>>,
+    /// Step reaching the stops at their ready time.
+    ready_steps: Vec<Option<usize>>,
+    steps: Vec<Step>,
+}
+
+/// Trips serving the same sequence of stops, none of them overtaking another, sorted by time.
+struct Pattern {
+    stops: Vec<usize>,
+    trips: Vec<Trip>,
+}
+
+/// The journeys operating around the departure time, indexed for RAPTOR. The stops are
+/// identified by their index in stop_ids.
+struct Timetable {
+    stop_ids: Vec<i32>,
+    stop_indexes: FxHashMap<i32, usize>,
+    patterns: Vec<Pattern>,
+    /// (pattern, position in the pattern) of the stops.
+    patterns_by_stop: Vec<Vec<(usize, usize)>>,
+    /// (stop, duration) of the transfers on foot (METABHF).
+    footpaths: Vec<Vec<(usize, TimeDelta)>>,
+    exchange_times: Vec<TimeDelta>,
+}
+
+impl Timetable {
+    fn new(data_storage: &DataStorage, departure_at: NaiveDateTime) -> Self {
+        let mut stop_indexes = FxHashMap::default();
+        let mut stop_ids = Vec::new();
+        let mut index_of = |stop_id: i32| {
+            *stop_indexes.entry(stop_id).or_insert_with(|| {
+                stop_ids.push(stop_id);
+                stop_ids.len() - 1
+            })
+        };
+
+        // The journeys of the previous service day can still be running after midnight.
+        let date = departure_at.date();
+        let service_days = [date.pred_opt(), Some(date), date.succ_opt()];
+        let mut trips_by_stops: FxHashMap<Vec<usize>, Vec<Trip>> = FxHashMap::default();
+        for journey in data_storage.journeys().entries() {
+            let stops: Vec<_> = journey
+                .route()
+                .iter()
+                .map(|route_entry| index_of(route_entry.stop_id()))
+                .collect();
+            for service_day in service_days.into_iter().flatten() {
+                if !journey
+                    .operates_on(service_day, data_storage)
+                    .unwrap_or(false)
+                {
+                    continue;
+                }
+
+                let start_of_day = service_day.and_time(NaiveTime::MIN);
+                let to_datetime = |service_time: ServiceTime| {
+                    start_of_day + TimeDelta::minutes(i64::from(service_time.minutes()))
+                };
+                let trip = Trip {
+                    journey_id: journey.id(),
+                    service_day,
+                    times: journey
+                        .route()
+                        .iter()
+                        .map(|route_entry| {
+                            (
+                                route_entry.arrival().map(to_datetime),
+                                route_entry.departure().map(to_datetime),
+                            )
+                        })
+                        .collect(),
+                };
+                let is_over = trip
+                    .times
+                    .iter()
+                    .all(|&(arrival, departure)| arrival.or(departure) < Some(departure_at));
+                if !is_over {
+                    trips_by_stops.entry(stops.clone()).or_default().push(trip);
+                }
+            }
+        }
+
+        let mut patterns = Vec::new();
+        for (stops, mut trips) in trips_by_stops {
+            trips.sort_by_key(|trip| {
+                trip.times
+                    .iter()
+                    .map(|&(arrival, departure)| departure.or(arrival))
+                    .collect::<Vec<_>>()
+            });
+            let mut stop_patterns: Vec<Pattern> = Vec::new();
+            for trip in trips {
+                let pattern = stop_patterns.iter_mut().find(|pattern| {
+                    // unwrap: A pattern has at least one trip.
+                    is_never_before(&trip, pattern.trips.last().unwrap())
+                });
+                match pattern {
+                    Some(pattern) => pattern.trips.push(trip),
+                    None => stop_patterns.push(Pattern {
+                        stops: stops.clone(),
+                        trips: vec![trip],
+                    }),
+                }
+            }
+            patterns.extend(stop_patterns);
+        }
+        // Makes the order of the patterns, hence of the scans, deterministic.
+        patterns.sort_by(|pattern_1, pattern_2| {
+            pattern_1
+                .stops
+                .cmp(&pattern_2.stops)
+                .then_with(|| pattern_1.trips[0].times.cmp(&pattern_2.trips[0].times))
+                .then_with(|| {
+                    pattern_1.trips[0]
+                        .journey_id
+                        .cmp(&pattern_2.trips[0].journey_id)
+                })
+        });
+
+        let mut connections: Vec<(usize, usize, TimeDelta)> = Vec::new();
+        for stop_connection in data_storage.stop_connections().entries() {
+            connections.push((
+                index_of(stop_connection.stop_id_1()),
+                index_of(stop_connection.stop_id_2()),
+                TimeDelta::minutes(i64::from(stop_connection.duration())),
+            ));
+        }
+
+        let mut patterns_by_stop = vec![Vec::new(); stop_ids.len()];
+        for (pattern_index, pattern) in patterns.iter().enumerate() {
+            for (position, &stop) in pattern.stops.iter().enumerate() {
+                patterns_by_stop[stop].push((pattern_index, position));
+            }
+        }
+
+        let mut footpaths = vec![Vec::new(); stop_ids.len()];
+        for (from, to, duration) in connections {
+            footpaths[from].push((to, duration));
+        }
+
+        let exchange_times = stop_ids
+            .iter()
+            .map(|&stop_id| {
+                let exchange_time = data_storage
+                    .stops()
+                    .find(stop_id)
+                    .map_or(data_storage.default_exchange_time().1, |stop| {
+                        stop.effective_exchange_time(UNKNOWN_PRODUCT_CLASS, data_storage)
+                    });
+                TimeDelta::minutes(i64::from(exchange_time))
+            })
+            .collect();
+
+        Self {
+            stop_ids,
+            stop_indexes,
+            patterns,
+            patterns_by_stop,
+            footpaths,
+            exchange_times,
+        }
+    }
+
+    /// RAPTOR: round k finds the earliest arrivals with k - 1 transfers.
+    fn earliest_arrivals(
+        &self,
+        origin: usize,
+        departure_at: NaiveDateTime,
+        max_transfers: usize,
+    ) -> Arrivals {
+        let stop_count = self.stop_ids.len();
+        let mut arrivals = Arrivals {
+            best_arrivals: vec![None; stop_count],
+            ready_times: vec![None; stop_count],
+            arrival_steps: vec![None; stop_count],
+            ready_steps: vec![None; stop_count],
+            steps: Vec::new(),
+        };
+        let mut marked = FxHashSet::default();
+
+        arrivals.best_arrivals[origin] = Some(departure_at);
+        arrivals.ready_times[origin] = Some(departure_at);
+        marked.insert(origin);
+        self.relax_footpaths(&[origin], &mut arrivals, &mut marked);
+
+        for _ in 0..=max_transfers {
+            let mut start_positions: FxHashMap<usize, usize> = FxHashMap::default();
+            for &stop in &marked {
+                for &(pattern, position) in &self.patterns_by_stop[stop] {
+                    let start = start_positions.entry(pattern).or_insert(position);
+                    *start = (*start).min(position);
+                }
+            }
+            marked.clear();
+
+            // The vehicles of this round are boarded after the arrivals of the previous rounds.
+            let previous_ready_times = arrivals.ready_times.clone();
+            let previous_ready_steps = arrivals.ready_steps.clone();
+            let mut improved = Vec::new();
+            for (pattern_index, start) in start_positions {
+                let pattern = &self.patterns[pattern_index];
+                // (trip, boarding position, step reaching the boarding stop)
+                let mut current_trip: Option<(usize, usize, Option<usize>)> = None;
+                for (position, &stop) in pattern.stops.iter().enumerate().skip(start) {
+                    let arrival =
+                        current_trip.and_then(|(trip, ..)| pattern.trips[trip].times[position].0);
+                    if let Some(arrival) = arrival.filter(|&arrival| {
+                        arrivals.best_arrivals[stop].is_none_or(|best| arrival < best)
+                    }) {
+                        // unwrap: There is an arrival, hence a current trip.
+                        let (trip, boarding_position, previous) = current_trip.unwrap();
+                        arrivals.steps.push(Step::Ride {
+                            pattern: pattern_index,
+                            trip,
+                            boarding_position,
+                            alighting_position: position,
+                            previous,
+                        });
+                        let step = Some(arrivals.steps.len() - 1);
+                        arrivals.best_arrivals[stop] = Some(arrival);
+                        arrivals.arrival_steps[stop] = step;
+                        let ready_time = arrival + self.exchange_times[stop];
+                        if arrivals.ready_times[stop].is_none_or(|time| ready_time < time) {
+                            arrivals.ready_times[stop] = Some(ready_time);
+                            arrivals.ready_steps[stop] = step;
+                        }
+                        improved.push(stop);
+                    }
+
+                    let Some(ready_time) = previous_ready_times[stop] else {
+                        continue;
+                    };
+                    let boardable_trip = pattern.trips.iter().position(|trip| {
+                        trip.times[position]
+                            .1
+                            .is_some_and(|departure| departure >= ready_time)
+                    });
+                    if let Some(boardable_trip) = boardable_trip
+                        && current_trip.is_none_or(|(trip, ..)| boardable_trip < trip)
+                    {
+                        current_trip = Some((boardable_trip, position, previous_ready_steps[stop]));
+                    }
+                }
+            }
+
+            marked.extend(improved.iter().copied());
+            self.relax_footpaths(&improved, &mut arrivals, &mut marked);
+            if marked.is_empty() {
+                break;
+            }
+        }
+
+        arrivals
+    }
+
+    fn relax_footpaths(
+        &self,
+        stops: &[usize],
+        arrivals: &mut Arrivals,
+        marked: &mut FxHashSet<usize>,
+    ) {
+        for &stop in stops {
+            // unwrap: The stops have been reached.
+            let arrival = arrivals.best_arrivals[stop].unwrap();
+            let previous = arrivals.arrival_steps[stop];
+            for &(to, duration) in &self.footpaths[stop] {
+                let arrival = arrival + duration;
+                if arrivals.best_arrivals[to].is_none_or(|best| arrival < best) {
+                    arrivals.steps.push(Step::Walk { previous });
+                    let step = Some(arrivals.steps.len() - 1);
+                    arrivals.best_arrivals[to] = Some(arrival);
+                    arrivals.arrival_steps[to] = step;
+                    if arrivals.ready_times[to].is_none_or(|time| arrival < time) {
+                        arrivals.ready_times[to] = Some(arrival);
+                        arrivals.ready_steps[to] = step;
+                    }
+                    marked.insert(to);
+                }
+            }
+        }
+    }
+
+    /// The rides reaching the destination at its best arrival, following the steps back to the
+    /// origin.
+    fn legs(&self, arrivals: &Arrivals, destination: usize) -> Vec<ItineraryLeg> {
+        let mut legs = Vec::new();
+        let mut step = arrivals.arrival_steps[destination];
+        while let Some(index) = step {
+            step = match arrivals.steps[index] {
+                Step::Ride {
+                    pattern,
+                    trip,
+                    boarding_position,
+                    alighting_position,
+                    previous,
+                } => {
+                    let pattern = &self.patterns[pattern];
+                    let trip = &pattern.trips[trip];
+                    // unwrap: The trip was boarded at a departure and left at an arrival.
+                    legs.push(ItineraryLeg::new(
+                        trip.journey_id,
+                        trip.service_day,
+                        self.stop_ids[pattern.stops[boarding_position]],
+                        self.stop_ids[pattern.stops[alighting_position]],
+                        trip.times[boarding_position].1.unwrap(),
+                        trip.times[alighting_position].0.unwrap(),
+                    ));
+                    previous
+                }
+                Step::Walk { previous } => previous,
+            };
+        }
+        legs.reverse();
+        legs
+    }
+}
+
+/// A trip can follow another one in a pattern if it never leaves or arrives earlier at a stop.
+fn is_never_before(trip: &Trip, previous_trip: &Trip) -> bool {
+    trip.times.iter().zip(&previous_trip.times).all(
+        |(&(arrival, departure), &(previous_arrival, previous_departure))| {
+            arrival >= previous_arrival && departure >= previous_departure
+        },
+    )
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Serves DataStorage::earliest_arrival_matrix.
+pub(crate) fn earliest_arrival_matrix(
+    data_storage: &DataStorage,
+    origin_stop_ids: &[i32],
+    destination_stop_ids: &[i32],
+    departure_at: NaiveDateTime,
+    max_transfers: usize,
+) -> ArrivalMatrix {
+    let timetable = Timetable::new(data_storage, departure_at);
+
+    let mut arrival_times = Vec::with_capacity(origin_stop_ids.len());
+    for &origin_stop_id in origin_stop_ids {
+        let best_arrivals = match timetable.stop_indexes.get(&origin_stop_id) {
+            Some(&origin) => {
+                timetable
+                    .earliest_arrivals(origin, departure_at, max_transfers)
+                    .best_arrivals
+            }
+            None => Vec::new(),
+        };
+        let row = destination_stop_ids
+            .iter()
+            .map(|&destination_stop_id| {
+                if destination_stop_id == origin_stop_id {
+                    return Some(departure_at);
+                }
+                timetable
+                    .stop_indexes
+                    .get(&destination_stop_id)
+                    .and_then(|&destination| best_arrivals.get(destination).copied().flatten())
+            })
+            .collect();
+        arrival_times.push(row);
+    }
+
+    ArrivalMatrix::new(
+        origin_stop_ids.to_vec(),
+        destination_stop_ids.to_vec(),
+        departure_at,
+        arrival_times,
+    )
+}
+
+/// Serves DataStorage::earliest_itinerary.
+pub(crate) fn earliest_itinerary(
+    data_storage: &DataStorage,
+    origin_stop_id: i32,
+    destination_stop_id: i32,
+    departure_at: NaiveDateTime,
+    max_transfers: usize,
+) -> Option<Vec<ItineraryLeg>> {
+    if origin_stop_id == destination_stop_id {
+        return Some(Vec::new());
+    }
+
+    let timetable = Timetable::new(data_storage, departure_at);
+    let &origin = timetable.stop_indexes.get(&origin_stop_id)?;
+    let &destination = timetable.stop_indexes.get(&destination_stop_id)?;
+    let arrivals = timetable.earliest_arrivals(origin, departure_at, max_transfers);
+    arrivals.best_arrivals[destination]?;
+    Some(timetable.legs(&arrivals, destination))
+}
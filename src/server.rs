@@ -0,0 +1,303 @@
+//! HTTP+JSON API over a loaded timetable, built with axum: stop search, station boards and
+//! earliest arrival times. The responses are the data transfer objects of the dto module.
+use std::net::SocketAddr;
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+};
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+
+pub use crate::dto::{MAX_DEPARTURES_WINDOW, MAX_MATRIX_STOPS};
+use crate::{
+    dto::{DepartureDto, StopDto},
+    error::{HResult, HrdfError},
+    routing::ArrivalMatrix,
+    shared_storage::SharedStorage,
+};
+
+type ApiResult<T> = Result<Json<T>, (StatusCode, String)>;
+
+/// The routes of the API, to be nested into the router of an application:
+///
+/// - `GET /stops?query=Basel&expandMetaStops=true`, the stops matching the query.
+/// - `GET /stops/{id}`
+/// - `GET /stops/{id}/departures?from=2026-03-02T08:00:00&until=2026-03-02T09:00:00`, the
+///   departures of the station board, sorted by departure time.
+/// - `POST /earliest-arrivals` with `{"originStopIds": [...], "destinationStopIds": [...],
+///   "departureAt": "2026-03-02T08:00:00", "maxTransfers": 2}`.
+///
+/// The dates and times are local (Swiss) ISO 8601 strings without offset. The departures and the
+/// earliest arrivals are computed on the blocking threads of tokio, and their requests are
+/// limited by MAX_DEPARTURES_WINDOW and MAX_MATRIX_STOPS.
+pub fn router(storage: SharedStorage) -> Router {
+    Router::new()
+        .route("/stops", get(search_stops))
+        .route("/stops/{id}", get(stop))
+        .route("/stops/{id}/departures", get(departures))
+        .route("/earliest-arrivals", post(earliest_arrivals))
+        .with_state(storage)
+}
+
+/// Serves the API on the address until the process is stopped.
+pub async fn serve(storage: SharedStorage, address: SocketAddr) -> HResult<()> {
+    let listener = tokio::net::TcpListener::bind(address).await?;
+    log::info!("Serving the HRDF API on {address}...");
+    axum::serve(listener, router(storage)).await?;
+    Ok(())
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Handlers
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchStopsParams {
+    query: String,
+    #[serde(default)]
+    expand_meta_stops: bool,
+}
+
+async fn search_stops(
+    State(storage): State<SharedStorage>,
+    Query(params): Query<SearchStopsParams>,
+) -> Json<Vec<StopDto>> {
+    Json(
+        storage
+            .search_stops(&params.query, params.expand_meta_stops)
+            .into_iter()
+            .map(StopDto::from)
+            .collect(),
+    )
+}
+
+fn bad_request(error: HrdfError) -> (StatusCode, String) {
+    (StatusCode::BAD_REQUEST, error.to_string())
+}
+
+/// Runs a CPU-bound query outside of the async executor, so that it doesn't stall the other
+/// requests.
+async fn spawn_query<T: Send + 'static>(
+    query: impl FnOnce() -> ApiResult<T> + Send + 'static,
+) -> ApiResult<T> {
+    tokio::task::spawn_blocking(query)
+        .await
+        .map_err(|error| (StatusCode::INTERNAL_SERVER_ERROR, error.to_string()))?
+}
+
+fn stop_not_found(id: i32) -> (StatusCode, String) {
+    (StatusCode::NOT_FOUND, format!("Stop {id} not found"))
+}
+
+async fn stop(State(storage): State<SharedStorage>, Path(id): Path<i32>) -> ApiResult<StopDto> {
+    storage
+        .stop(id)
+        .map(|stop| Json(StopDto::from(stop)))
+        .ok_or_else(|| stop_not_found(id))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeparturesParams {
+    from: NaiveDateTime,
+    until: NaiveDateTime,
+    #[serde(default)]
+    expand_meta_stops: bool,
+}
+
+async fn departures(
+    State(storage): State<SharedStorage>,
+    Path(id): Path<i32>,
+    Query(params): Query<DeparturesParams>,
+) -> ApiResult<Vec<DepartureDto>> {
+    if storage.stop(id).is_none() {
+        return Err(stop_not_found(id));
+    }
+    if params.until - params.from > MAX_DEPARTURES_WINDOW {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "The window cannot exceed {} hours",
+                MAX_DEPARTURES_WINDOW.num_hours()
+            ),
+        ));
+    }
+
+    spawn_query(move || {
+        let station_board = storage
+            .station_board(id, params.from, params.until, params.expand_meta_stops)
+            .map_err(bad_request)?;
+        Ok(Json(DepartureDto::from_station_board(
+            &station_board,
+            &storage,
+        )))
+    })
+    .await
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EarliestArrivalsRequest {
+    origin_stop_ids: Vec<i32>,
+    destination_stop_ids: Vec<i32>,
+    departure_at: NaiveDateTime,
+    #[serde(default = "default_max_transfers")]
+    max_transfers: usize,
+}
+
+fn default_max_transfers() -> usize {
+    2
+}
+
+async fn earliest_arrivals(
+    State(storage): State<SharedStorage>,
+    Json(request): Json<EarliestArrivalsRequest>,
+) -> ApiResult<ArrivalMatrix> {
+    if request.origin_stop_ids.len() > MAX_MATRIX_STOPS
+        || request.destination_stop_ids.len() > MAX_MATRIX_STOPS
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("At most {MAX_MATRIX_STOPS} origins and {MAX_MATRIX_STOPS} destinations"),
+        ));
+    }
+
+    spawn_query(move || {
+        storage
+            .earliest_arrival_matrix(
+                &request.origin_stop_ids,
+                &request.destination_stop_ids,
+                request.departure_at,
+                request.max_transfers,
+            )
+            .map(Json)
+            .map_err(bad_request)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path as FilePath;
+
+    use chrono::TimeDelta;
+
+    use super::*;
+    use crate::{hrdf::Hrdf, models::Version};
+    use pretty_assertions::assert_eq;
+
+    fn storage() -> SharedStorage {
+        let path = FilePath::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/mini");
+        let hrdf = Hrdf::from_directory(Version::V_5_40_41_2_0_7, &path).unwrap();
+        SharedStorage::new(hrdf.into_data_storage())
+    }
+
+    #[tokio::test]
+    async fn handlers() {
+        let storage = storage();
+
+        let Json(stops) = search_stops(
+            State(storage.clone()),
+            Query(SearchStopsParams {
+                query: "Aarstadt".to_string(),
+                expand_meta_stops: false,
+            }),
+        )
+        .await;
+        assert_eq!(stops[0].id, 8500101);
+
+        let Json(stop_dto) = stop(State(storage.clone()), Path(8500101)).await.unwrap();
+        assert_eq!(stop_dto.name, "Aarstadt");
+        let error = stop(State(storage.clone()), Path(1)).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::NOT_FOUND);
+
+        let at = |year, hour| {
+            chrono::NaiveDate::from_ymd_opt(year, 12, 15)
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap()
+        };
+        let Json(departures_dto) = departures(
+            State(storage.clone()),
+            Path(8500101),
+            Query(DeparturesParams {
+                from: at(2025, 5),
+                until: at(2025, 10),
+                expand_meta_stops: false,
+            }),
+        )
+        .await
+        .unwrap();
+        assert!(!departures_dto.is_empty());
+        assert!(
+            departures_dto
+                .windows(2)
+                .all(|pair| pair[0].departure_at <= pair[1].departure_at)
+        );
+
+        let error = departures(
+            State(storage.clone()),
+            Path(8500101),
+            Query(DeparturesParams {
+                from: at(2030, 5),
+                until: at(2030, 10),
+                expand_meta_stops: false,
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error.0, StatusCode::BAD_REQUEST);
+
+        let Json(matrix) = earliest_arrivals(
+            State(storage),
+            Json(EarliestArrivalsRequest {
+                origin_stop_ids: vec![8500101],
+                destination_stop_ids: vec![8500104],
+                departure_at: at(2025, 6),
+                max_transfers: default_max_transfers(),
+            }),
+        )
+        .await
+        .unwrap();
+        assert!(matrix.arrival_times()[0][0].is_some());
+    }
+
+    #[tokio::test]
+    async fn requests_are_limited() {
+        let storage = storage();
+        let from = chrono::NaiveDate::from_ymd_opt(2025, 12, 15)
+            .unwrap()
+            .and_hms_opt(5, 0, 0)
+            .unwrap();
+
+        let error = departures(
+            State(storage.clone()),
+            Path(8500101),
+            Query(DeparturesParams {
+                from,
+                until: from + MAX_DEPARTURES_WINDOW + TimeDelta::minutes(1),
+                expand_meta_stops: false,
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error.0, StatusCode::BAD_REQUEST);
+
+        let error = earliest_arrivals(
+            State(storage),
+            Json(EarliestArrivalsRequest {
+                origin_stop_ids: vec![8500101; MAX_MATRIX_STOPS + 1],
+                destination_stop_ids: vec![8500104],
+                departure_at: from,
+                max_transfers: default_max_transfers(),
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error.0, StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,114 @@
+use std::{ops::Deref, sync::Arc};
+
+use crate::{
+    hrdf::Hrdf,
+    models::{Journey, Line, Platform, Stop},
+    storage::DataStorage,
+};
+
+// The queries only borrow the data, so the multi-threaded servers can share it without locks.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Hrdf>();
+    assert_send_sync::<DataStorage>();
+};
+
+// ------------------------------------------------------------------------------------------------
+// --- SharedStorage
+// ------------------------------------------------------------------------------------------------
+
+/// A DataStorage shared between threads, cloned without copying the data. All the functions of
+/// DataStorage are available through Deref.
+#[derive(Clone, Debug)]
+pub struct SharedStorage {
+    data_storage: Arc<DataStorage>,
+}
+
+impl SharedStorage {
+    pub fn new(data_storage: DataStorage) -> Self {
+        Self {
+            data_storage: Arc::new(data_storage),
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn data_storage(&self) -> &Arc<DataStorage> {
+        &self.data_storage
+    }
+
+    // Functions
+
+    pub fn stop(&self, id: i32) -> Option<&Stop> {
+        self.data_storage.stops().find(id)
+    }
+
+    pub fn journey(&self, id: i32) -> Option<&Journey> {
+        self.data_storage.journeys().find(id)
+    }
+
+    pub fn line(&self, id: i32) -> Option<&Line> {
+        self.data_storage.lines().find(id)
+    }
+
+    pub fn platform(&self, id: i32) -> Option<&Platform> {
+        self.data_storage.platforms().find(id)
+    }
+
+    /// Iterates over the stops without collecting them (unlike ResourceStorage::entries).
+    pub fn stops(&self) -> impl Iterator<Item = &Stop> {
+        self.data_storage.stops().data().values()
+    }
+
+    /// Iterates over the journeys without collecting them (unlike ResourceStorage::entries).
+    pub fn journeys(&self) -> impl Iterator<Item = &Journey> {
+        self.data_storage.journeys().data().values()
+    }
+}
+
+impl Deref for SharedStorage {
+    type Target = DataStorage;
+
+    fn deref(&self) -> &Self::Target {
+        &self.data_storage
+    }
+}
+
+impl From<DataStorage> for SharedStorage {
+    fn from(data_storage: DataStorage) -> Self {
+        Self::new(data_storage)
+    }
+}
+
+impl From<Hrdf> for SharedStorage {
+    fn from(hrdf: Hrdf) -> Self {
+        Self::new(hrdf.into_data_storage())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, thread};
+
+    use super::*;
+    use crate::models::Version;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn queries_from_several_threads() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/mini");
+        let shared_storage =
+            SharedStorage::from(Hrdf::from_directory(Version::V_5_40_41_2_0_7, &path).unwrap());
+
+        let names: Vec<_> = (0..4)
+            .map(|_| {
+                let shared_storage = shared_storage.clone();
+                thread::spawn(move || shared_storage.stop(8500101).unwrap().name().to_string())
+            })
+            .map(|thread| thread.join().unwrap())
+            .collect();
+        assert_eq!(names, vec!["Aarstadt"; 4]);
+        assert_eq!(shared_storage.journeys().count(), 6);
+        assert_eq!(Arc::strong_count(shared_storage.data_storage()), 1);
+    }
+}
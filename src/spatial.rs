@@ -0,0 +1,200 @@
+use rstar::{AABB, PointDistance, RTree, RTreeObject, primitives::GeomWithData};
+
+use crate::{
+    models::{Coordinates, Model, Platform, Stop},
+    storage::{DataStorage, ResourceStorage},
+};
+
+/// Position in LV95 (easting, northing), in meters. The own points of a tree mixing HRDF stops
+/// or platforms with other objects must use the same system (see Coordinates::to_lv95).
+fn lv95_position(coordinates: Coordinates) -> Option<[f64; 2]> {
+    let coordinates = coordinates.to_lv95();
+    Some([coordinates.easting()?, coordinates.northing()?])
+}
+
+fn distance_2(position: &[f64; 2], point: &[f64; 2]) -> f64 {
+    (position[0] - point[0]).powi(2) + (position[1] - point[1]).powi(2)
+}
+
+/// Position on the unit sphere. The straight-line distances between these positions are in the
+/// same order as the great-circle distances (see Coordinates::distance_m).
+fn unit_sphere_position(coordinates: Coordinates) -> [f64; 3] {
+    let coordinates = coordinates.to_wgs84();
+    // unwrap: The coordinates are in WGS84.
+    let (latitude, longitude) = (
+        coordinates.latitude().unwrap().to_radians(),
+        coordinates.longitude().unwrap().to_radians(),
+    );
+    [
+        latitude.cos() * longitude.cos(),
+        latitude.cos() * longitude.sin(),
+        latitude.sin(),
+    ]
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- StopPoint
+// ------------------------------------------------------------------------------------------------
+
+/// A stop which can be inserted into an rstar::RTree, at its LV95 position.
+#[derive(Clone, Copy, Debug)]
+pub struct StopPoint<'a> {
+    stop: &'a Stop,
+    position: [f64; 2],
+}
+
+impl<'a> StopPoint<'a> {
+    /// None if the stop has no coordinates.
+    pub fn new(stop: &'a Stop) -> Option<Self> {
+        let coordinates = stop.lv95_coordinates().or(stop.wgs84_coordinates())?;
+        Some(Self {
+            stop,
+            position: lv95_position(coordinates)?,
+        })
+    }
+
+    // Getters/Setters
+
+    pub fn stop(&self) -> &'a Stop {
+        self.stop
+    }
+
+    /// LV95 (easting, northing), in meters.
+    pub fn position(&self) -> [f64; 2] {
+        self.position
+    }
+}
+
+impl RTreeObject for StopPoint<'_> {
+    type Envelope = AABB<[f64; 2]>;
+
+    fn envelope(&self) -> Self::Envelope {
+        AABB::from_point(self.position)
+    }
+}
+
+impl PointDistance for StopPoint<'_> {
+    fn distance_2(&self, point: &[f64; 2]) -> f64 {
+        distance_2(&self.position, point)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- PlatformPoint
+// ------------------------------------------------------------------------------------------------
+
+/// A platform which can be inserted into an rstar::RTree, at its LV95 position.
+#[derive(Clone, Copy, Debug)]
+pub struct PlatformPoint<'a> {
+    platform: &'a Platform,
+    position: [f64; 2],
+}
+
+impl<'a> PlatformPoint<'a> {
+    /// The position is given by Platform::best_coordinates. None if neither the platform nor its
+    /// stop have coordinates.
+    pub fn new(platform: &'a Platform, data_storage: &DataStorage) -> Option<Self> {
+        let coordinates = platform.best_coordinates(data_storage)?;
+        Some(Self {
+            platform,
+            position: lv95_position(coordinates)?,
+        })
+    }
+
+    // Getters/Setters
+
+    pub fn platform(&self) -> &'a Platform {
+        self.platform
+    }
+
+    /// LV95 (easting, northing), in meters.
+    pub fn position(&self) -> [f64; 2] {
+        self.position
+    }
+}
+
+impl RTreeObject for PlatformPoint<'_> {
+    type Envelope = AABB<[f64; 2]>;
+
+    fn envelope(&self) -> Self::Envelope {
+        AABB::from_point(self.position)
+    }
+}
+
+impl PointDistance for PlatformPoint<'_> {
+    fn distance_2(&self, point: &[f64; 2]) -> f64 {
+        distance_2(&self.position, point)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- StopIndex
+// ------------------------------------------------------------------------------------------------
+
+/// The IDs of the stops with coordinates, in an rstar::RTree (see DataStorage::nearest_stops).
+#[derive(Debug, Default)]
+pub(crate) struct StopIndex {
+    tree: RTree<GeomWithData<[f64; 3], i32>>,
+}
+
+impl StopIndex {
+    pub(crate) fn new(stops: &ResourceStorage<Stop>) -> Self {
+        let points = stops
+            .entries()
+            .into_iter()
+            .filter_map(|stop| {
+                let coordinates = stop.wgs84_position()?;
+                Some(GeomWithData::new(
+                    unit_sphere_position(coordinates),
+                    stop.id(),
+                ))
+            })
+            .collect();
+        Self {
+            tree: RTree::bulk_load(points),
+        }
+    }
+
+    // Functions
+
+    /// The IDs of the stops, the closest to the coordinates first.
+    pub(crate) fn nearest(&self, coordinates: Coordinates) -> impl Iterator<Item = i32> {
+        self.tree
+            .nearest_neighbor_iter(&unit_sphere_position(coordinates))
+            .map(|point| point.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CoordinateSystem, Model};
+    use pretty_assertions::assert_eq;
+    use rstar::RTree;
+
+    fn build_stop(id: i32, easting: f64, northing: f64) -> Stop {
+        let mut stop = Stop::new(id, id.to_string(), None, None, None);
+        stop.set_lv95_coordinates(Coordinates::new(CoordinateSystem::LV95, easting, northing));
+        stop
+    }
+
+    #[test]
+    fn nearest_stop_in_a_tree() {
+        let stops = [
+            build_stop(1, 2_600_000.0, 1_200_000.0),
+            build_stop(2, 2_601_000.0, 1_200_000.0),
+            Stop::new(3, "3".to_string(), None, None, None),
+        ];
+        let points: Vec<_> = stops.iter().filter_map(StopPoint::new).collect();
+        assert_eq!(points.len(), 2);
+
+        let tree = RTree::bulk_load(points);
+        let nearest = tree.nearest_neighbor(&[2_600_900.0, 1_200_100.0]).unwrap();
+        assert_eq!(nearest.stop().id(), 2);
+        assert_eq!(
+            tree.locate_within_distance([2_600_000.0, 1_200_000.0], 1_000.0_f64.powi(2))
+                .count(),
+            2
+        );
+    }
+}
@@ -0,0 +1,310 @@
+use chrono::NaiveDateTime;
+use rustc_hash::FxHashMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    JourneyId,
+    error::HResult,
+    models::{Color, Departure, Journey, Model, Platform, ThroughService},
+    storage::DataStorage,
+};
+
+// ------------------------------------------------------------------------------------------------
+// --- StationBoard
+// ------------------------------------------------------------------------------------------------
+
+/// Departures from a stop, and possibly from the stops grouped under it in METABHF, grouped by
+/// platform (see DataStorage::station_board).
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StationBoard {
+    stop_ids: Vec<i32>,
+    platforms: Vec<StationBoardPlatform>,
+}
+
+impl StationBoard {
+    pub fn new(stop_ids: Vec<i32>, platforms: Vec<StationBoardPlatform>) -> Self {
+        Self {
+            stop_ids,
+            platforms,
+        }
+    }
+
+    // Getters/Setters
+
+    /// The requested stop and, if the meta stops are expanded, the members of its meta stop,
+    /// sorted by ID.
+    pub fn stop_ids(&self) -> &[i32] {
+        &self.stop_ids
+    }
+
+    /// Sorted by stop ID, then by platform name, the departures without known platform of a stop
+    /// coming after its platforms.
+    pub fn platforms(&self) -> &[StationBoardPlatform] {
+        &self.platforms
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- StationBoardPlatform
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StationBoardPlatform {
+    stop_id: i32,
+    platform_id: Option<i32>,
+    name: Option<String>,
+    sectors: Vec<String>,
+    entries: Vec<StationBoardEntry>,
+}
+
+impl StationBoardPlatform {
+    pub fn new(stop_id: i32, platform: Option<&Platform>, entries: Vec<StationBoardEntry>) -> Self {
+        Self {
+            stop_id,
+            platform_id: platform.map(|platform| platform.id()),
+            name: platform.map(|platform| platform.name().to_string()),
+            sectors: platform
+                .map(|platform| {
+                    platform
+                        .sectors()
+                        .iter()
+                        .map(|sector| sector.name().to_string())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            entries,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn stop_id(&self) -> i32 {
+        self.stop_id
+    }
+
+    /// None for the departures without known platform.
+    pub fn platform_id(&self) -> Option<i32> {
+        self.platform_id
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Designations of the sectors of the platform, empty if none are known.
+    pub fn sectors(&self) -> &[String] {
+        &self.sectors
+    }
+
+    /// Sorted by departure time.
+    pub fn entries(&self) -> &[StationBoardEntry] {
+        &self.entries
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- StationBoardEntry
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StationBoardEntry {
+    journey_id: i32,
+    departure_at: NaiveDateTime,
+    destination_stop_id: Option<i32>,
+    line_name: Option<String>,
+    text_color: Option<Color>,
+    background_color: Option<Color>,
+    attributes: Vec<String>,
+    continues_as: Option<JourneyId>,
+}
+
+impl StationBoardEntry {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        journey_id: i32,
+        departure_at: NaiveDateTime,
+        destination_stop_id: Option<i32>,
+        line_name: Option<String>,
+        text_color: Option<Color>,
+        background_color: Option<Color>,
+        attributes: Vec<String>,
+        continues_as: Option<(i32, String)>,
+    ) -> Self {
+        Self {
+            journey_id,
+            departure_at,
+            destination_stop_id,
+            line_name,
+            text_color,
+            background_color,
+            attributes,
+            continues_as,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn journey_id(&self) -> i32 {
+        self.journey_id
+    }
+
+    pub fn departure_at(&self) -> NaiveDateTime {
+        self.departure_at
+    }
+
+    /// Last stop of the journey.
+    pub fn destination_stop_id(&self) -> Option<i32> {
+        self.destination_stop_id
+    }
+
+    /// Short name of the line (e.g. "S1"), or its name if it has no short name.
+    pub fn line_name(&self) -> Option<&str> {
+        self.line_name.as_deref()
+    }
+
+    pub fn text_color(&self) -> Option<Color> {
+        self.text_color
+    }
+
+    pub fn background_color(&self) -> Option<Color> {
+        self.background_color
+    }
+
+    /// Designations of the attributes of the journey (e.g. "VR" for the reservation), in the
+    /// order of the journey.
+    pub fn attributes(&self) -> &[String] {
+        &self.attributes
+    }
+
+    /// (legacy_id, administration) of the journey the passengers can stay seated in (DURCHBI),
+    /// if there is one on this day.
+    pub fn continues_as(&self) -> Option<&(i32, String)> {
+        self.continues_as.as_ref()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Serves DataStorage::station_board.
+pub(crate) fn station_board(
+    data_storage: &DataStorage,
+    stop_id: i32,
+    from: NaiveDateTime,
+    until: NaiveDateTime,
+    expand_meta_stops: bool,
+) -> HResult<StationBoard> {
+    let stop_ids = if expand_meta_stops {
+        data_storage.meta_stop_members(stop_id)
+    } else {
+        vec![stop_id]
+    };
+
+    let mut through_services: FxHashMap<&JourneyId, Vec<&ThroughService>> = FxHashMap::default();
+    for through_service in data_storage.through_service().data().values() {
+        through_services
+            .entry(through_service.journey_1_id())
+            .or_default()
+            .push(through_service);
+    }
+
+    let mut platforms = Vec::new();
+    for &stop_id in &stop_ids {
+        let mut stop_platforms: Vec<_> = data_storage
+            .platforms()
+            .data()
+            .values()
+            .filter(|platform| platform.stop_id() == stop_id)
+            .collect();
+        stop_platforms.sort_by(|platform_1, platform_2| {
+            platform_1
+                .name()
+                .cmp(platform_2.name())
+                .then(platform_1.id().cmp(&platform_2.id()))
+        });
+
+        let mut entries_by_platform: FxHashMap<Option<i32>, Vec<StationBoardEntry>> =
+            FxHashMap::default();
+        for departure in data_storage.departures(stop_id, from, until)? {
+            let Some(journey) = departure.journey(data_storage) else {
+                continue;
+            };
+            let platform_id = stop_platforms
+                .iter()
+                .find(|platform| {
+                    journey.serves_platform(platform, departure.service_day(), data_storage)
+                })
+                .map(|platform| platform.id());
+            entries_by_platform
+                .entry(platform_id)
+                .or_default()
+                .push(create_entry(
+                    data_storage,
+                    journey,
+                    &departure,
+                    &through_services,
+                ));
+        }
+
+        for platform in &stop_platforms {
+            if let Some(entries) = entries_by_platform.remove(&Some(platform.id())) {
+                platforms.push(StationBoardPlatform::new(stop_id, Some(platform), entries));
+            }
+        }
+        if let Some(entries) = entries_by_platform.remove(&None) {
+            platforms.push(StationBoardPlatform::new(stop_id, None, entries));
+        }
+    }
+
+    Ok(StationBoard::new(stop_ids, platforms))
+}
+
+fn create_entry(
+    data_storage: &DataStorage,
+    journey: &Journey,
+    departure: &Departure,
+    through_services: &FxHashMap<&JourneyId, Vec<&ThroughService>>,
+) -> StationBoardEntry {
+    let line = journey
+        .line_id()
+        .and_then(|line_id| data_storage.lines().find(line_id));
+
+    let mut attributes: Vec<String> = Vec::new();
+    for attribute in journey.attributes_on(departure.service_day(), data_storage) {
+        if !attributes
+            .iter()
+            .any(|designation| designation == attribute.designation())
+        {
+            attributes.push(attribute.designation().to_string());
+        }
+    }
+
+    let journey_id = (journey.legacy_id(), journey.administration().to_string());
+    let continues_as = through_services
+        .get(&journey_id)
+        .into_iter()
+        .flatten()
+        .find(|through_service| {
+            data_storage.is_bit_field_active(
+                Some(through_service.bit_field_id()),
+                departure.service_day(),
+            )
+        })
+        .map(|through_service| through_service.journey_2_id().clone());
+
+    StationBoardEntry::new(
+        journey.id(),
+        departure.departure_at(),
+        journey.last_stop_id().ok(),
+        line.map(|line| line.display_name().to_string()),
+        line.map(|line| line.text_color()),
+        line.map(|line| line.background_color()),
+        attributes,
+        continues_as,
+    )
+}
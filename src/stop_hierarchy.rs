@@ -0,0 +1,236 @@
+use rustc_hash::FxHashMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    models::{Model, Platform, Stop},
+    storage::DataStorage,
+};
+
+// ------------------------------------------------------------------------------------------------
+// --- LocationType
+// ------------------------------------------------------------------------------------------------
+
+/// Level of a location in the stop hierarchy, as the location_type of GTFS.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LocationType {
+    /// A stop of BAHNHOF.
+    Station,
+    /// A boarding area of BHFART, part of a station.
+    BoardingArea,
+    /// A platform of GLEISE, part of a boarding area or directly of a station.
+    Platform,
+}
+
+impl LocationType {
+    /// The location_type value of GTFS.
+    pub fn gtfs_location_type(&self) -> i32 {
+        match self {
+            Self::Station => 1,
+            Self::BoardingArea => 4,
+            Self::Platform => 0,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- BoardingArea
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BoardingArea {
+    sloid: String,
+    stop_id: i32,
+    platform_ids: Vec<i32>,
+}
+
+impl BoardingArea {
+    pub fn new(sloid: String, stop_id: i32, platform_ids: Vec<i32>) -> Self {
+        Self {
+            sloid,
+            stop_id,
+            platform_ids,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn sloid(&self) -> &str {
+        &self.sloid
+    }
+
+    /// ID of the parent station.
+    pub fn stop_id(&self) -> i32 {
+        self.stop_id
+    }
+
+    /// IDs of the child platforms, sorted.
+    pub fn platform_ids(&self) -> &[i32] {
+        &self.platform_ids
+    }
+
+    // Functions
+
+    pub fn stop<'a>(&self, data_storage: &'a DataStorage) -> Option<&'a Stop> {
+        data_storage.stops().find(self.stop_id)
+    }
+
+    pub fn platforms<'a>(&self, data_storage: &'a DataStorage) -> Vec<&'a Platform> {
+        self.platform_ids
+            .iter()
+            .filter_map(|&platform_id| data_storage.platforms().find(platform_id))
+            .collect()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- StopHierarchy
+// ------------------------------------------------------------------------------------------------
+
+/// Stations (stops) → boarding areas → platforms, built from the SLOIDs of BHFART and GLEISE
+/// (see DataStorage::stop_hierarchy). A platform belongs to the boarding area of its stop whose
+/// SLOID is the SLOID of the platform or a prefix of it (e.g. ch:1:sloid:7000:4 for
+/// ch:1:sloid:7000:4:7), otherwise directly to its stop.
+#[derive(Debug)]
+pub struct StopHierarchy {
+    boarding_areas: Vec<BoardingArea>,
+    boarding_area_indices_by_stop_id: FxHashMap<i32, Vec<usize>>,
+    boarding_area_index_by_platform_id: FxHashMap<i32, usize>,
+    platform_ids_by_stop_id: FxHashMap<i32, Vec<i32>>,
+    location_types_by_sloid: FxHashMap<String, LocationType>,
+}
+
+impl StopHierarchy {
+    // Getters/Setters
+
+    /// Sorted by stop, then by SLOID.
+    pub fn boarding_areas(&self) -> &[BoardingArea] {
+        &self.boarding_areas
+    }
+
+    // Functions
+
+    pub fn boarding_area(&self, sloid: &str) -> Option<&BoardingArea> {
+        self.boarding_areas
+            .iter()
+            .find(|boarding_area| boarding_area.sloid() == sloid)
+    }
+
+    /// The boarding areas of the station.
+    pub fn boarding_areas_of_stop(&self, stop_id: i32) -> Vec<&BoardingArea> {
+        self.boarding_area_indices_by_stop_id
+            .get(&stop_id)
+            .into_iter()
+            .flatten()
+            .map(|&index| &self.boarding_areas[index])
+            .collect()
+    }
+
+    /// The parent boarding area of the platform, None if the platform belongs directly to its stop.
+    pub fn boarding_area_of_platform(&self, platform_id: i32) -> Option<&BoardingArea> {
+        self.boarding_area_index_by_platform_id
+            .get(&platform_id)
+            .map(|&index| &self.boarding_areas[index])
+    }
+
+    /// IDs of all the platforms of the station, sorted, including those of its boarding areas.
+    pub fn platform_ids_of_stop(&self, stop_id: i32) -> &[i32] {
+        self.platform_ids_by_stop_id
+            .get(&stop_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Level of the location identified by the SLOID, None for an unknown SLOID.
+    pub fn location_type(&self, sloid: &str) -> Option<LocationType> {
+        self.location_types_by_sloid.get(sloid).copied()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Functions
+// ------------------------------------------------------------------------------------------------
+
+fn contains_sloid(boarding_area_sloid: &str, platform_sloid: &str) -> bool {
+    platform_sloid
+        .strip_prefix(boarding_area_sloid)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(':'))
+}
+
+/// Serves DataStorage::stop_hierarchy.
+pub(crate) fn stop_hierarchy(data_storage: &DataStorage) -> StopHierarchy {
+    let mut platform_ids_by_stop_id: FxHashMap<i32, Vec<i32>> = FxHashMap::default();
+    for platform in data_storage.platforms().data().values() {
+        platform_ids_by_stop_id
+            .entry(platform.stop_id())
+            .or_default()
+            .push(platform.id());
+    }
+    platform_ids_by_stop_id
+        .values_mut()
+        .for_each(|platform_ids| platform_ids.sort_unstable());
+
+    let mut stops = data_storage.stops().entries();
+    stops.sort_by_key(|stop| stop.id());
+
+    let mut location_types_by_sloid = FxHashMap::default();
+    let mut boarding_areas = Vec::new();
+    let mut boarding_area_indices_by_stop_id: FxHashMap<i32, Vec<usize>> = FxHashMap::default();
+    let mut boarding_area_index_by_platform_id = FxHashMap::default();
+
+    for stop in stops {
+        if !stop.sloid().is_empty() {
+            location_types_by_sloid.insert(stop.sloid().to_string(), LocationType::Station);
+        }
+
+        let platforms: Vec<_> = platform_ids_by_stop_id
+            .get(&stop.id())
+            .into_iter()
+            .flatten()
+            .filter_map(|&platform_id| data_storage.platforms().find(platform_id))
+            .collect();
+        for platform in &platforms {
+            if !platform.sloid().is_empty() {
+                location_types_by_sloid
+                    .insert(platform.sloid().to_string(), LocationType::Platform);
+            }
+        }
+
+        let mut sloids: Vec<_> = stop.boarding_areas().iter().collect();
+        sloids.sort();
+        sloids.dedup();
+        for sloid in sloids {
+            let index = boarding_areas.len();
+            let platform_ids: Vec<_> = platforms
+                .iter()
+                .filter(|platform| {
+                    !boarding_area_index_by_platform_id.contains_key(&platform.id())
+                        && contains_sloid(sloid, platform.sloid())
+                })
+                .map(|platform| platform.id())
+                .collect();
+            for &platform_id in &platform_ids {
+                boarding_area_index_by_platform_id.insert(platform_id, index);
+            }
+            // A platform with the SLOID of the boarding area stays a platform.
+            location_types_by_sloid
+                .entry(sloid.clone())
+                .or_insert(LocationType::BoardingArea);
+            boarding_area_indices_by_stop_id
+                .entry(stop.id())
+                .or_default()
+                .push(index);
+            boarding_areas.push(BoardingArea::new(sloid.clone(), stop.id(), platform_ids));
+        }
+    }
+
+    StopHierarchy {
+        boarding_areas,
+        boarding_area_indices_by_stop_id,
+        boarding_area_index_by_platform_id,
+        platform_ids_by_stop_id,
+        location_types_by_sloid,
+    }
+}
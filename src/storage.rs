@@ -1,20 +1,43 @@
-use std::{path::Path, time::Instant};
+#[cfg(feature = "rkyv")]
+use std::hash::Hash;
+#[cfg(feature = "spatial")]
+use std::sync::OnceLock;
+use std::{
+    cmp::Reverse, collections::BTreeSet, mem, ops::RangeInclusive, path::Path, time::Instant,
+};
 
-use chrono::{Days, NaiveDate};
+use chrono::{Days, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
 use rustc_hash::{FxHashMap, FxHashSet};
-use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
+#[cfg(feature = "serde")]
+use crate::cache::CacheSegment;
+#[cfg(feature = "spatial")]
+use crate::spatial::StopIndex;
 use crate::{
     JourneyError, JourneyId,
+    administrations::{self, Administration},
+    delta::{self, JourneyDelta},
     error::{HResult, HrdfError},
+    export, instrumentation,
     models::{
-        Attribute, BitField, Direction, ExchangeTimeAdministration, ExchangeTimeJourney,
-        ExchangeTimeLine, Holiday, InformationText, Journey, JourneyPlatform, Line, Model,
-        Platform, Stop, StopConnection, ThroughService, TimetableMetadataEntry, TransportCompany,
+        Arrival, Attribute, BitField, BoundingBox, CompositeJourney, CoordinateSystem, Coordinates,
+        Departure, Direction, ExchangeTimeAdministration, ExchangeTimeJourney, ExchangeTimeLine,
+        Holiday, InformationText, Journey, JourneyPlatform, JourneyRouteEntry, Language, Line,
+        LineGeometry, LineOverview, Model, Platform, SegmentStatistics, ServiceTime, Stop,
+        StopCluster, StopConnection, ThroughService, TimetableMetadataEntry, TransportCompany,
         TransportType, Version,
     },
-    parsing,
-    utils::{count_days_between_two_dates, timetable_end_date, timetable_start_date},
+    parsing::{self, ParseIssue, ParseReport, ParsingMode, ParsingOptions, error::LineErrors},
+    routing::{self, ArrivalMatrix, ItineraryLeg},
+    station_board::{self, StationBoard},
+    stop_hierarchy::{self, StopHierarchy},
+    transfers::{self, Transfer, TransferStatistics},
+    utils::{
+        count_days_between_two_dates, date_of_bit_index, timetable_end_date, timetable_start_date,
+    },
+    validation::{self, ValidationReport},
 };
 
 // ------------------------------------------------------------------------------------------------
@@ -22,7 +45,13 @@ use crate::{
 // ------------------------------------------------------------------------------------------------
 //
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct DataStorage {
     // Time-relevant data.
     bit_fields: ResourceStorage<BitField>,
@@ -61,114 +90,161 @@ pub struct DataStorage {
         FxHashMap<(JourneyId, JourneyId, i32), i32>,
     exchange_times_administration_map: FxHashMap<(Option<i32>, String, String), i32>,
     exchange_times_journey_map: FxHashMap<(i32, JourneyId, JourneyId), FxHashSet<i32>>,
+    journeys_by_swiss_journey_id: FxHashMap<String, i32>,
+    transport_companies_by_sboid: FxHashMap<String, i32>,
+
+    // Legacy codes (HRDF keys) to IDs
+    attributes_pk_type_converter: FxHashMap<String, i32>,
+    directions_pk_type_converter: FxHashMap<String, i32>,
+    transport_types_pk_type_converter: FxHashMap<String, i32>,
+    journeys_pk_type_converter: FxHashSet<JourneyId>,
 
     // Additional global data
     default_exchange_time: (i16, i16), // (InterCity exchange time, Exchange time for all other journey types)
+    timetable_period: (NaiveDate, NaiveDate), // (First day, Last day) as defined in ECKDATEN
+    parse_report: ParseReport,
+
+    // Built by the first query which needs it.
+    #[cfg(feature = "spatial")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "rkyv", with(rkyv::with::Skip))]
+    stop_index: OnceLock<StopIndex>,
 }
 
 impl DataStorage {
     pub fn new(version: Version, path: &Path) -> HResult<Self> {
+        Self::new_with_options(version, path, &ParsingOptions::default())
+    }
+
+    pub fn new_with_options(
+        version: Version,
+        path: &Path,
+        options: &ParsingOptions,
+    ) -> HResult<Self> {
+        let mut parse_report = ParseReport::new();
+        let mut line_errors = LineErrors::new(options.collect_errors());
+
         // Time-relevant data
         let complete = Instant::now();
         let now = Instant::now();
-        let bit_fields = parsing::load_bit_fields(path)?;
-        log::info!("Time elapsed for bitfields parsing: {:?}", now.elapsed());
+        let bit_fields = parsing::load_bit_fields(path, options, &mut line_errors)?;
+        instrumentation::record_parse("bitfields", now);
         let now = Instant::now();
-        let holidays = parsing::load_holidays(path)?;
-        log::info!("Time elapsed for holidays parsing: {:?}", now.elapsed());
+        let holidays = parsing::load_holidays(path, options, &mut line_errors)?;
+        instrumentation::record_parse("holidays", now);
 
         let now = Instant::now();
-        let timetable_metadata = parsing::load_timetable_metadata(path)?;
-        log::info!(
-            "Time elapsed for timetable_metadata parsing: {:?}",
-            now.elapsed()
+        let timetable_metadata = parsing::load_timetable_metadata(path, options)?;
+        instrumentation::record_parse("timetable_metadata", now);
+        let timetable_period = (
+            timetable_start_date(&timetable_metadata)?,
+            timetable_end_date(&timetable_metadata)?,
         );
 
         // Basic data
         let now = Instant::now();
-        let (attributes, attributes_pk_type_converter) = parsing::load_attributes(path)?;
-        log::info!("Time elapsed for attributes parsing: {:?}", now.elapsed());
+        let (attributes, attributes_pk_type_converter) =
+            parsing::load_attributes(path, options, &mut line_errors)?;
+        instrumentation::record_parse("attributes", now);
         let now = Instant::now();
-        let (directions, directions_pk_type_converter) = parsing::load_directions(path)?;
-        log::info!("Time elapsed for directions parsing: {:?}", now.elapsed());
+        let (directions, directions_pk_type_converter) =
+            parsing::load_directions(path, options, &mut line_errors)?;
+        instrumentation::record_parse("directions", now);
         let now = Instant::now();
-        let information_texts = parsing::load_information_texts(path)?;
-        log::info!(
-            "Time elapsed for information_texts parsing: {:?}",
-            now.elapsed()
-        );
+        let information_texts = parsing::load_information_texts(path, options, &mut line_errors)?;
+        instrumentation::record_parse("information_texts", now);
         let now = Instant::now();
-        let lines = parsing::load_lines(path)?;
-        log::info!("Time elapsed for line parsing: {:?}", now.elapsed());
+        let lines = parsing::load_lines(path, options, &mut line_errors)?;
+        instrumentation::record_parse("line", now);
         let now = Instant::now();
-        let transport_companies = parsing::load_transport_companies(path)?;
-        log::info!(
-            "Time elapsed for transport_companies parsing: {:?}",
-            now.elapsed()
-        );
+        let transport_companies =
+            parsing::load_transport_companies(path, options, &mut line_errors)?;
+        instrumentation::record_parse("transport_companies", now);
         let now = Instant::now();
         let (transport_types, transport_types_pk_type_converter) =
-            parsing::load_transport_types(path)?;
-        log::info!(
-            "Time elapsed for transport_types parsing: {:?}",
-            now.elapsed()
-        );
+            parsing::load_transport_types(path, options, &mut parse_report, &mut line_errors)?;
+        instrumentation::record_parse("transport_types", now);
 
         // Stop data
         let now = Instant::now();
-        let stop_connections = parsing::load_stop_connections(path, &attributes_pk_type_converter)?;
-        log::info!(
-            "Time elapsed for stop_connections parsing: {:?}",
-            now.elapsed()
-        );
+        let stop_connections = parsing::load_stop_connections(
+            path,
+            options,
+            &attributes_pk_type_converter,
+            &mut line_errors,
+        )?;
+        instrumentation::record_parse("stop_connections", now);
         let now = Instant::now();
-        let (stops, default_exchange_time) = parsing::load_stops(version, path)?;
-        log::info!("Time elapsed for stops parsing: {:?}", now.elapsed());
+        let (mut stops, default_exchange_time) =
+            parsing::load_stops(version, path, options, &mut parse_report, &mut line_errors)?;
+        instrumentation::record_parse("stops", now);
 
         // Timetable data
         let now = Instant::now();
-        let (journeys, journeys_pk_type_converter) = parsing::load_journeys(
+        let (mut journeys, journeys_pk_type_converter, stop_names) = parsing::load_journeys(
             path,
             &transport_types_pk_type_converter,
             &attributes_pk_type_converter,
             &directions_pk_type_converter,
+            options,
+            &mut parse_report,
+            &mut line_errors,
+        )?;
+        instrumentation::record_parse("journeys", now);
+        if options.placeholder_stops() {
+            add_placeholder_stops(&mut stops, stop_names, &mut parse_report);
+        }
+        resolve_missing_bit_fields(
+            &mut journeys,
+            &bit_fields,
+            options.mode(),
+            &mut parse_report,
         )?;
-        log::info!("Time elapsed for journeys parsing: {:?}", now.elapsed());
+        resolve_journey_information_texts(&mut journeys, &information_texts);
 
         let now = Instant::now();
-        let (journey_platform, platforms) =
-            parsing::load_platforms(version, path, &journeys_pk_type_converter)?;
-        log::info!("Time elapsed for platforms parsing: {:?}", now.elapsed());
+        let (journey_platform, platforms) = parsing::load_platforms(
+            version,
+            path,
+            &journeys_pk_type_converter,
+            options,
+            &mut parse_report,
+            &mut line_errors,
+        )?;
+        instrumentation::record_parse("platforms", now);
         let now = Instant::now();
-        let through_service = parsing::load_through_service(path, &journeys_pk_type_converter)?;
-        log::info!(
-            "Time elapsed for through_service parsing: {:?}",
-            now.elapsed()
-        );
+        let through_service = parsing::load_through_service(
+            path,
+            &journeys_pk_type_converter,
+            options,
+            &mut line_errors,
+        )?;
+        instrumentation::record_parse("through_service", now);
 
         // Exchange times
         let now = Instant::now();
-        let exchange_times_administration = parsing::load_exchange_times_administration(path)?;
-        log::info!(
-            "Time elapsed for exchange_times_administration parsing: {:?}",
-            now.elapsed()
-        );
+        let exchange_times_administration =
+            parsing::load_exchange_times_administration(path, options, &mut line_errors)?;
+        instrumentation::record_parse("exchange_times_administration", now);
         let now = Instant::now();
-        let exchange_times_journey =
-            parsing::load_exchange_times_journey(path, &journeys_pk_type_converter)?;
-        log::info!(
-            "Time elapsed for exchange_times_journey parsing: {:?}",
-            now.elapsed()
-        );
+        let exchange_times_journey = parsing::load_exchange_times_journey(
+            path,
+            &journeys_pk_type_converter,
+            options,
+            &mut line_errors,
+        )?;
+        instrumentation::record_parse("exchange_times_journey", now);
         let now = Instant::now();
-        let exchange_times_line =
-            parsing::load_exchange_times_line(path, &transport_types_pk_type_converter)?;
-        log::info!(
-            "Time elapsed for exchange_times_line parsing: {:?}",
-            now.elapsed()
-        );
+        let exchange_times_line = parsing::load_exchange_times_line(
+            path,
+            &transport_types_pk_type_converter,
+            options,
+            &mut line_errors,
+        )?;
+        instrumentation::record_parse("exchange_times_line", now);
 
         log::info!("Parsing of all HRDF files in {:?}", complete.elapsed());
+        line_errors.into_result()?;
 
         log::info!("Building bit_fields_by_day...");
         let bit_fields_by_day = create_bit_fields_by_day(&bit_fields, &timetable_metadata)?;
@@ -187,6 +263,11 @@ impl DataStorage {
             create_exchange_times_administration_map(&exchange_times_administration);
         log::info!("Building exchange times journey_map...");
         let exchange_times_journey_map = create_exchange_times_journey_map(&exchange_times_journey);
+        log::info!("Building journeys by Swiss Journey ID...");
+        let journeys_by_swiss_journey_id = create_journeys_by_swiss_journey_id(&journeys);
+        log::info!("Building transport companies by SBOID...");
+        let transport_companies_by_sboid =
+            create_transport_companies_by_sboid(&transport_companies);
         log::info!("Building through service map...");
 
         let data_storage = Self {
@@ -221,8 +302,19 @@ impl DataStorage {
             bit_field_id_for_through_service_by_journey_id_stop_id,
             exchange_times_administration_map,
             exchange_times_journey_map,
+            journeys_by_swiss_journey_id,
+            transport_companies_by_sboid,
+            // Legacy codes
+            attributes_pk_type_converter,
+            directions_pk_type_converter,
+            transport_types_pk_type_converter,
+            journeys_pk_type_converter,
             // Additional global data
             default_exchange_time,
+            timetable_period,
+            parse_report,
+            #[cfg(feature = "spatial")]
+            stop_index: OnceLock::new(),
         };
 
         Ok(data_storage)
@@ -234,6 +326,22 @@ impl DataStorage {
         &self.bit_fields
     }
 
+    pub fn holidays(&self) -> &ResourceStorage<Holiday> {
+        &self.holidays
+    }
+
+    pub fn attributes(&self) -> &ResourceStorage<Attribute> {
+        &self.attributes
+    }
+
+    pub fn directions(&self) -> &ResourceStorage<Direction> {
+        &self.directions
+    }
+
+    pub fn information_texts(&self) -> &ResourceStorage<InformationText> {
+        &self.information_texts
+    }
+
     pub fn journeys(&self) -> &ResourceStorage<Journey> {
         &self.journeys
     }
@@ -242,6 +350,10 @@ impl DataStorage {
         &self.lines
     }
 
+    pub fn journey_platform(&self) -> &ResourceStorage<JourneyPlatform> {
+        &self.journey_platform
+    }
+
     pub fn platforms(&self) -> &ResourceStorage<Platform> {
         &self.platforms
     }
@@ -258,6 +370,10 @@ impl DataStorage {
         &self.stops
     }
 
+    pub fn transport_companies(&self) -> &ResourceStorage<TransportCompany> {
+        &self.transport_companies
+    }
+
     pub fn transport_types(&self) -> &ResourceStorage<TransportType> {
         &self.transport_types
     }
@@ -312,40 +428,1450 @@ impl DataStorage {
         &self.exchange_times_journey_map
     }
 
+    /// Returns the ID of the attribute with the code used in the HRDF files (e.g. "WR").
+    pub fn attribute_id_by_code(&self, code: &str) -> Option<i32> {
+        self.attributes_pk_type_converter.get(code).copied()
+    }
+
+    /// Returns the ID of the direction with the code used in the HRDF files (e.g. "R000001"),
+    /// normalized like the *R rows of FPLAN.
+    pub fn direction_id_by_code(&self, code: &str) -> Option<i32> {
+        self.directions_pk_type_converter
+            .get(&parsing::normalize_direction_code(code))
+            .copied()
+    }
+
+    /// Returns the ID of the transport type with the designation (e.g. "ICE").
+    pub fn transport_type_id_by_designation(&self, designation: &str) -> Option<i32> {
+        self.transport_types_pk_type_converter
+            .get(designation)
+            .copied()
+    }
+
+    /// Returns true if FPLAN contains a journey with the number and the administration.
+    /// Kept up to date by apply_delta.
+    pub fn contains_journey(&self, legacy_id: i32, administration: &str) -> bool {
+        self.journeys_pk_type_converter
+            .contains(&(legacy_id, administration.to_string()))
+    }
+
+    /// Returns the journey with the Swiss Journey ID (e.g. ch:1:sjyid:100001:3995-001), see
+    /// Journey::swiss_journey_id.
+    pub fn journey_by_sjyid(&self, sjyid: &str) -> Option<&Journey> {
+        self.journeys_by_swiss_journey_id
+            .get(sjyid)
+            .and_then(|&journey_id| self.journeys.find(journey_id))
+    }
+
+    /// Returns the transport company with the SBOID (e.g. ch:1:sboid:100001), see TransportCompany::sboid.
+    pub fn transport_company_by_sboid(&self, sboid: &str) -> Option<&TransportCompany> {
+        self.transport_companies_by_sboid
+            .get(sboid)
+            .and_then(|&transport_company_id| self.transport_companies.find(transport_company_id))
+    }
+
+    /// Exchange times (InterCity, all other journey types) used when a stop has no specific exchange time.
+    /// In lenient mode, this is (2, 2) if UMSTEIGB defines no default.
     pub fn default_exchange_time(&self) -> (i16, i16) {
         self.default_exchange_time
     }
+
+    /// Issues found in the data while parsing.
+    pub fn parse_report(&self) -> &ParseReport {
+        &self.parse_report
+    }
+
+    /// First and last day (inclusive) covered by the timetable.
+    pub fn timetable_period(&self) -> RangeInclusive<NaiveDate> {
+        self.timetable_period.0..=self.timetable_period.1
+    }
+
+    // Functions
+
+    /// Returns an error if the date is not covered by the timetable.
+    pub fn check_date(&self, date: NaiveDate) -> HResult<()> {
+        if self.timetable_period().contains(&date) {
+            Ok(())
+        } else {
+            Err(HrdfError::OutOfTimetablePeriod {
+                date,
+                start: self.timetable_period.0,
+                end: self.timetable_period.1,
+            })
+        }
+    }
+
+    /// Returns the journeys running at the given date and time, including those which started
+    /// on a previous service day and are still running (e.g. night buses). Fails with
+    /// OutOfTimetablePeriod if the date is not covered by the timetable, the previous service days
+    /// which are not covered are skipped.
+    pub fn journeys_active_at(&self, datetime: NaiveDateTime) -> HResult<Vec<&Journey>> {
+        self.check_date(datetime.date())?;
+        Ok(self
+            .journeys
+            .entries()
+            .into_iter()
+            .filter(|journey| journey.is_active_at(datetime, self))
+            .collect())
+    }
+
+    /// Returns the journeys leaving the stop between from and until (both included), the earliest
+    /// first. Fails with OutOfTimetablePeriod if the date of from is not covered by the timetable,
+    /// the other service days which are not covered are ignored.
+    pub fn departures(
+        &self,
+        stop_id: i32,
+        from: NaiveDateTime,
+        until: NaiveDateTime,
+    ) -> HResult<Vec<Departure>> {
+        self.check_date(from.date())?;
+        Ok(instrumentation::timed_query("departures", || {
+            let mut departures: Vec<_> = self
+                .stop_events(stop_id, from, until, |route_entry| route_entry.departure())
+                .into_iter()
+                .map(|(journey_id, service_day, departure_at)| {
+                    Departure::new(journey_id, stop_id, service_day, departure_at)
+                })
+                .collect();
+            departures.sort_by_key(|departure| (departure.departure_at(), departure.journey_id()));
+            departures
+        }))
+    }
+
+    /// Returns the journeys reaching the stop between from and until (both included), the earliest
+    /// first. Fails with OutOfTimetablePeriod if the date of from is not covered by the timetable,
+    /// the other service days which are not covered are ignored.
+    pub fn arrivals(
+        &self,
+        stop_id: i32,
+        from: NaiveDateTime,
+        until: NaiveDateTime,
+    ) -> HResult<Vec<Arrival>> {
+        self.check_date(from.date())?;
+        Ok(instrumentation::timed_query("arrivals", || {
+            let mut arrivals: Vec<_> = self
+                .stop_events(stop_id, from, until, |route_entry| route_entry.arrival())
+                .into_iter()
+                .map(|(journey_id, service_day, arrival_at)| {
+                    Arrival::new(journey_id, stop_id, service_day, arrival_at)
+                })
+                .collect();
+            arrivals.sort_by_key(|arrival| (arrival.arrival_at(), arrival.journey_id()));
+            arrivals
+        }))
+    }
+
+    /// (journey_id, service_day, date and time) of the journeys serving the stop between from and
+    /// until, the time of a route entry being given by service_time.
+    fn stop_events(
+        &self,
+        stop_id: i32,
+        from: NaiveDateTime,
+        until: NaiveDateTime,
+        service_time: impl Fn(&JourneyRouteEntry) -> Option<ServiceTime>,
+    ) -> Vec<(i32, NaiveDate, NaiveDateTime)> {
+        let mut journey_ids = FxHashSet::default();
+        for &bit_field_id in self
+            .bit_fields_by_stop_id
+            .get(&stop_id)
+            .into_iter()
+            .flatten()
+        {
+            if let Some(ids) = self
+                .journeys_by_stop_id_and_bit_field_id
+                .get(&(stop_id, bit_field_id))
+            {
+                journey_ids.extend(ids.iter().copied());
+            }
+        }
+
+        let mut stop_events = Vec::new();
+        for journey in journey_ids
+            .into_iter()
+            .filter_map(|id| self.journeys.find(id))
+        {
+            let service_times = journey
+                .route()
+                .iter()
+                .filter(|route_entry| route_entry.stop_id() == stop_id)
+                .filter_map(&service_time);
+            for service_time in service_times {
+                let offset = TimeDelta::minutes(i64::from(service_time.minutes()));
+                let last_service_day = (until - offset).date();
+                for service_day in (from - offset).date().iter_days() {
+                    if service_day > last_service_day {
+                        break;
+                    }
+
+                    let datetime = service_day.and_time(NaiveTime::MIN) + offset;
+                    if datetime >= from
+                        && datetime <= until
+                        && journey.operates_on(service_day, self).unwrap_or(false)
+                        && journey.serves_stop_on(stop_id, service_day, self)
+                    {
+                        stop_events.push((journey.id(), service_day, datetime));
+                    }
+                }
+            }
+        }
+        stop_events
+    }
+
+    /// Minimum exchange time in minutes from journey 1 to journey 2 at the stop, journey 1 operating
+    /// on the service day. The most specific rule applies: UMSTEIGZ (journeys), UMSTEIGL (lines),
+    /// UMSTEIGV (administrations, at the stop then everywhere), UMSTEIGB (stop) and finally
+    /// the default exchange time.
+    pub fn exchange_time(
+        &self,
+        stop_id: i32,
+        journey_1: &Journey,
+        journey_2: &Journey,
+        service_day: NaiveDate,
+    ) -> i16 {
+        transfers::exchange_time(self, stop_id, journey_1, journey_2, service_day)
+    }
+
+    /// Line exchange times (UMSTEIGL) applying at the stop, those specific to the stop first, then
+    /// those applying at all the stops, each sorted by ID. See ExchangeTimeLine::applies_to.
+    pub fn line_exchange_times_at(&self, stop_id: i32) -> Vec<&ExchangeTimeLine> {
+        let mut exchange_times: Vec<_> = self
+            .exchange_times_line
+            .data()
+            .values()
+            .filter(|exchange_time| exchange_time.stop_id().is_none_or(|id| id == stop_id))
+            .collect();
+        exchange_times
+            .sort_by_key(|exchange_time| (exchange_time.stop_id().is_none(), exchange_time.id()));
+        exchange_times
+    }
+
+    /// Transfer at the stop from journey 1, operating on the service day, to the next departure of
+    /// journey 2 (or the last one less than an hour before the arrival), with the exchange time
+    /// required (see exchange_time) and whether the connection is guaranteed. None if journey 1
+    /// doesn't operate on the day or doesn't arrive at the stop, or if journey 2 doesn't depart
+    /// from the stop around the arrival.
+    pub fn can_transfer(
+        &self,
+        journey_1: &Journey,
+        journey_2: &Journey,
+        stop_id: i32,
+        service_day: NaiveDate,
+    ) -> HResult<Option<Transfer>> {
+        transfers::can_transfer(self, journey_1, journey_2, stop_id, service_day)
+    }
+
+    /// Stations, boarding areas and platforms with their parent/child relations (see StopHierarchy).
+    pub fn stop_hierarchy(&self) -> StopHierarchy {
+        stop_hierarchy::stop_hierarchy(self)
+    }
+
+    /// Administrations used by the journeys, sorted by code, with the transport company of BETRIEB
+    /// listing each one and their number of journeys.
+    pub fn administrations(&self) -> Vec<Administration> {
+        administrations::administrations(self)
+    }
+
+    /// Transfers realized at the stop on the date: for every arriving journey, the first departure
+    /// of every other line and destination reachable within the exchange time (see exchange_time)
+    /// and an hour. Fails with OutOfTimetablePeriod if the date is not covered by the timetable.
+    pub fn transfer_statistics(
+        &self,
+        stop_id: i32,
+        date: NaiveDate,
+    ) -> HResult<TransferStatistics> {
+        transfers::transfer_statistics(self, stop_id, date)
+    }
+
+    /// Earliest arrival times from every origin stop to every destination stop when leaving at
+    /// departure_at, with at most max_transfers transfers (RAPTOR). A transfer requires the
+    /// exchange time of the stop (UMSTEIGB) and the transfers on foot follow METABHF.
+    /// Fails with OutOfTimetablePeriod if the date of departure_at is not covered by the timetable.
+    pub fn earliest_arrival_matrix(
+        &self,
+        origin_stop_ids: &[i32],
+        destination_stop_ids: &[i32],
+        departure_at: NaiveDateTime,
+        max_transfers: usize,
+    ) -> HResult<ArrivalMatrix> {
+        self.check_date(departure_at.date())?;
+        Ok(instrumentation::timed_query(
+            "earliest_arrival_matrix",
+            || {
+                routing::earliest_arrival_matrix(
+                    self,
+                    origin_stop_ids,
+                    destination_stop_ids,
+                    departure_at,
+                    max_transfers,
+                )
+            },
+        ))
+    }
+
+    /// The rides of the earliest itinerary from the origin stop to the destination stop when
+    /// leaving at departure_at, with at most max_transfers transfers, found by the same RAPTOR run
+    /// as earliest_arrival_matrix. None if the destination cannot be reached, no rides if it is the
+    /// origin or is reached on foot.
+    /// Fails with OutOfTimetablePeriod if the date of departure_at is not covered by the timetable.
+    pub fn earliest_itinerary(
+        &self,
+        origin_stop_id: i32,
+        destination_stop_id: i32,
+        departure_at: NaiveDateTime,
+        max_transfers: usize,
+    ) -> HResult<Option<Vec<ItineraryLeg>>> {
+        self.check_date(departure_at.date())?;
+        Ok(instrumentation::timed_query("earliest_itinerary", || {
+            routing::earliest_itinerary(
+                self,
+                origin_stop_id,
+                destination_stop_id,
+                departure_at,
+                max_transfers,
+            )
+        }))
+    }
+
+    /// Departures from the stop between from and until (both included), grouped by platform and
+    /// annotated for display (line, colors, attributes and the journey continuing as a through
+    /// service). If expand_meta_stops is true, the departures from the stops grouped under it in
+    /// METABHF are included (e.g. the bus stops in front of a railway station).
+    /// Fails with OutOfTimetablePeriod if the date of from is not covered by the timetable.
+    pub fn station_board(
+        &self,
+        stop_id: i32,
+        from: NaiveDateTime,
+        until: NaiveDateTime,
+        expand_meta_stops: bool,
+    ) -> HResult<StationBoard> {
+        self.check_date(from.date())?;
+        instrumentation::timed_query("station_board", || {
+            station_board::station_board(self, stop_id, from, until, expand_meta_stops)
+        })
+    }
+
+    /// Returns the stops whose name, long name, abbreviation or synonyms contain the query,
+    /// ignoring the case. The exact matches come first, then the names starting with the query,
+    /// then the others, each sorted by name. If expand_meta_stops is true, the stops grouped under
+    /// a matching stop in METABHF are added after it, unless they match too.
+    pub fn search_stops(&self, query: &str, expand_meta_stops: bool) -> Vec<&Stop> {
+        instrumentation::timed_query("search_stops", || {
+            let query = query.trim().to_lowercase();
+            if query.is_empty() {
+                return Vec::new();
+            }
+
+            let mut matches: Vec<_> = self
+                .stops
+                .data()
+                .values()
+                .filter_map(|stop| {
+                    let names = [Some(stop.name()), stop.long_name(), stop.abbreviation()]
+                        .into_iter()
+                        .flatten()
+                        .chain(stop.synonyms().iter().map(String::as_str))
+                        .map(str::to_lowercase);
+                    // 0 for an exact match, 1 for a prefix, 2 otherwise.
+                    names
+                        .filter(|name| name.contains(&query))
+                        .map(|name| {
+                            if name == query {
+                                0
+                            } else if name.starts_with(&query) {
+                                1
+                            } else {
+                                2
+                            }
+                        })
+                        .min()
+                        .map(|rank| (rank, stop))
+                })
+                .collect();
+            matches.sort_by(|(rank_1, stop_1), (rank_2, stop_2)| {
+                rank_1
+                    .cmp(rank_2)
+                    .then(stop_1.name().cmp(stop_2.name()))
+                    .then(stop_1.id().cmp(&stop_2.id()))
+            });
+
+            let mut stops: Vec<&Stop> = matches.into_iter().map(|(_, stop)| stop).collect();
+            if expand_meta_stops {
+                let mut stop_ids: FxHashSet<_> = stops.iter().map(|stop| stop.id()).collect();
+                let mut expanded_stops = Vec::with_capacity(stops.len());
+                for stop in stops {
+                    expanded_stops.push(stop);
+                    for stop_id in self.meta_stop_members(stop.id()) {
+                        if stop_ids.insert(stop_id) {
+                            expanded_stops.extend(self.stops.find(stop_id));
+                        }
+                    }
+                }
+                stops = expanded_stops;
+            }
+            stops
+        })
+    }
+
+    /// Returns the journeys operating on the date with the train number as written for the
+    /// passengers, i.e. the designation of the transport type followed by the journey number
+    /// (e.g. "IC 571", "IC571" or "ic 571"), or the number alone. The designation is compared
+    /// ignoring the case. Several operators (administrations) can use the same number, their
+    /// journeys are all returned, sorted by administration, then by first departure.
+    pub fn find_journeys_by_train_number(
+        &self,
+        train_number: &str,
+        date: NaiveDate,
+    ) -> HResult<Vec<&Journey>> {
+        self.check_date(date)?;
+
+        let train_number = train_number.trim();
+        let split_at = train_number
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(train_number.len());
+        let (designation, number) = train_number.split_at(split_at);
+        let designation = designation.trim();
+        let Ok(legacy_id) = number.trim().parse::<i32>() else {
+            return Ok(Vec::new());
+        };
+
+        let mut journeys: Vec<_> = self
+            .journeys
+            .data()
+            .values()
+            .filter(|journey| journey.legacy_id() == legacy_id)
+            .filter(|journey| {
+                designation.is_empty()
+                    || journey.transport_type(self).is_ok_and(|transport_type| {
+                        transport_type
+                            .designation()
+                            .eq_ignore_ascii_case(designation)
+                    })
+            })
+            .filter(|journey| journey.operates_on(date, self).unwrap_or(false))
+            .collect();
+        journeys.sort_by_key(|journey| {
+            (
+                journey.administration().to_string(),
+                journey
+                    .route()
+                    .first()
+                    .and_then(|route_entry| route_entry.departure()),
+                journey.id(),
+            )
+        });
+        Ok(journeys)
+    }
+
+    /// Returns the stop and the stops grouped under it in METABHF, sorted by ID.
+    pub fn meta_stop_members(&self, stop_id: i32) -> Vec<i32> {
+        let mut stop_ids: Vec<_> = self
+            .stop_connections_by_stop_id
+            .get(&stop_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|&stop_connection_id| self.stop_connections.find(stop_connection_id))
+            .map(|stop_connection| stop_connection.stop_id_2())
+            .chain([stop_id])
+            .collect();
+        stop_ids.sort_unstable();
+        stop_ids.dedup();
+        stop_ids
+    }
+
+    /// Chains the journeys linked by through services (DURCHBI) on the date into the runs seen by the
+    /// passengers. A journey continuing as several journeys (e.g. a train being split) starts
+    /// several runs. Only the runs with at least two legs are returned, sorted by their legs.
+    pub fn composite_journeys(&self, date: NaiveDate) -> Vec<CompositeJourney> {
+        let mut journey_ids_by_legacy_id: FxHashMap<JourneyId, i32> = FxHashMap::default();
+        let linked_journey_ids: FxHashSet<_> = self
+            .through_service
+            .data()
+            .values()
+            .flat_map(|through_service| {
+                [
+                    through_service.journey_1_id(),
+                    through_service.journey_2_id(),
+                ]
+            })
+            .collect();
+        for journey in self.journeys.data().values() {
+            let journey_id = (journey.legacy_id(), journey.administration().to_string());
+            if linked_journey_ids.contains(&journey_id) {
+                journey_ids_by_legacy_id.insert(journey_id, journey.id());
+            }
+        }
+
+        // journey_id => [(stop_id, next_journey_id)]
+        let mut links: FxHashMap<i32, Vec<(i32, i32)>> = FxHashMap::default();
+        let mut continued_journey_ids = FxHashSet::default();
+        for through_service in self.through_service.data().values() {
+            let (Some(&journey_id_1), Some(&journey_id_2)) = (
+                journey_ids_by_legacy_id.get(through_service.journey_1_id()),
+                journey_ids_by_legacy_id.get(through_service.journey_2_id()),
+            ) else {
+                continue;
+            };
+            let operates = self
+                .journeys
+                .find(journey_id_1)
+                .is_some_and(|journey| journey.operates_on(date, self).unwrap_or(false));
+            if operates && self.is_bit_field_active(Some(through_service.bit_field_id()), date) {
+                links
+                    .entry(journey_id_1)
+                    .or_default()
+                    .push((through_service.journey_1_stop_id(), journey_id_2));
+                continued_journey_ids.insert(journey_id_2);
+            }
+        }
+
+        let mut composite_journeys = Vec::new();
+        let mut first_journey_ids: Vec<_> = links
+            .keys()
+            .filter(|journey_id| !continued_journey_ids.contains(journey_id))
+            .copied()
+            .collect();
+        first_journey_ids.sort_unstable();
+        for journey_id in first_journey_ids {
+            chain_journeys(
+                &links,
+                vec![journey_id],
+                Vec::new(),
+                &mut composite_journeys,
+            );
+        }
+        composite_journeys.sort_by(|composite_journey_1, composite_journey_2| {
+            composite_journey_1
+                .journey_ids()
+                .cmp(composite_journey_2.journey_ids())
+        });
+        composite_journeys
+    }
+
+    /// Returns true if the bit field is set on the date.
+    /// No bit field or a bit field of 0 means every day.
+    pub(crate) fn is_bit_field_active(&self, bit_field_id: Option<i32>, date: NaiveDate) -> bool {
+        match bit_field_id {
+            None | Some(0) => true,
+            Some(bit_field_id) => self
+                .bit_fields
+                .find(bit_field_id)
+                .is_some_and(|bit_field| bit_field.is_active_on(date, self.timetable_period.0)),
+        }
+    }
+
+    /// Returns the k stops closest to the coordinates, with their distance in meters, the closest first.
+    /// Stops without coordinates are ignored. The spatial index of the stops is built by the
+    /// first call.
+    #[cfg(feature = "spatial")]
+    pub fn nearest_stops(&self, coordinates: Coordinates, k: usize) -> Vec<(&Stop, f64)> {
+        let stop_index = self.stop_index.get_or_init(|| StopIndex::new(&self.stops));
+
+        let mut stops: Vec<(&Stop, f64)> = Vec::new();
+        for stop_id in stop_index.nearest(coordinates) {
+            // unwrap: The index is built from the stops.
+            let stop = self.stops.find(stop_id).unwrap();
+            // unwrap: Only the stops with coordinates are indexed.
+            let stop_coordinates = stop.wgs84_position().unwrap();
+            let distance = coordinates.distance_m(&stop_coordinates);
+            // The stops as far as the k-th one are kept, so that the ties are sorted by ID.
+            if stops.len() >= k && stops.last().is_none_or(|&(_, last)| distance > last) {
+                break;
+            }
+            stops.push((stop, distance));
+        }
+        stops.sort_by(|(stop_1, distance_1), (stop_2, distance_2)| {
+            distance_1
+                .total_cmp(distance_2)
+                .then(stop_1.id().cmp(&stop_2.id()))
+        });
+        stops.truncate(k);
+        stops
+    }
+
+    /// Smallest WGS84 rectangle containing all the stops with coordinates.
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        self.stop_coordinates()
+            .fold(None, |bounding_box, coordinates| {
+                // unwrap: The coordinates are in WGS84.
+                let (latitude, longitude) = (
+                    coordinates.latitude().unwrap(),
+                    coordinates.longitude().unwrap(),
+                );
+                let (south, west, north, east) = match bounding_box {
+                    None => (latitude, longitude, latitude, longitude),
+                    Some((south, west, north, east)) => (
+                        f64::min(south, latitude),
+                        f64::min(west, longitude),
+                        f64::max(north, latitude),
+                        f64::max(east, longitude),
+                    ),
+                };
+                Some((south, west, north, east))
+            })
+            .map(|(south, west, north, east)| {
+                BoundingBox::new(
+                    Coordinates::new(CoordinateSystem::WGS84, south, west),
+                    Coordinates::new(CoordinateSystem::WGS84, north, east),
+                )
+            })
+    }
+
+    /// Returns the stops inside the bounding box (e.g. a geo::Rect with the geo feature), sorted
+    /// by ID. Stops without coordinates are ignored.
+    pub fn stops_in_bounding_box(&self, bounding_box: impl Into<BoundingBox>) -> Vec<&Stop> {
+        let bounding_box = bounding_box.into();
+        let mut stops: Vec<_> = self
+            .stops
+            .entries()
+            .into_iter()
+            .filter(|stop| {
+                stop.wgs84_position()
+                    .is_some_and(|coordinates| bounding_box.contains(&coordinates))
+            })
+            .collect();
+        stops.sort_by_key(|stop| stop.id());
+        stops
+    }
+
+    /// WGS84 centroid of all the stops with coordinates.
+    pub fn centroid(&self) -> Option<Coordinates> {
+        centroid(self.stop_coordinates())
+    }
+
+    /// WGS84 coordinates of the stops, converted from LV95 if needed.
+    fn stop_coordinates(&self) -> impl Iterator<Item = Coordinates> {
+        self.stops
+            .data()
+            .values()
+            .filter_map(|stop| stop.wgs84_position())
+    }
+
+    /// Groups the stops which are physically co-located: the stops linked in METABHF and the stops
+    /// sharing the same SLOID (ch:1:sloid:<number>) end up in the same cluster.
+    /// Every stop belongs to exactly one cluster, the clusters are sorted by their smallest stop ID.
+    pub fn stop_clusters(&self) -> Vec<StopCluster> {
+        let mut parents: FxHashMap<i32, i32> =
+            self.stops.data().keys().map(|&id| (id, id)).collect();
+
+        for stop_connection in self.stop_connections.entries() {
+            let (stop_id_1, stop_id_2) = (stop_connection.stop_id_1(), stop_connection.stop_id_2());
+            if parents.contains_key(&stop_id_1) && parents.contains_key(&stop_id_2) {
+                union_stops(&mut parents, stop_id_1, stop_id_2);
+            }
+        }
+
+        let mut stop_id_by_sloid = FxHashMap::default();
+        for stop in self.stops.entries() {
+            let sloid: Vec<_> = stop.sloid().split(':').take(4).collect();
+            if sloid.len() < 4 {
+                continue;
+            }
+            let other_stop_id = *stop_id_by_sloid.entry(sloid.join(":")).or_insert(stop.id());
+            union_stops(&mut parents, stop.id(), other_stop_id);
+        }
+
+        let mut clusters: FxHashMap<i32, Vec<i32>> = FxHashMap::default();
+        let mut stop_ids: Vec<_> = parents.keys().copied().collect();
+        stop_ids.sort_unstable();
+        for stop_id in stop_ids {
+            let root = find_stop_root(&mut parents, stop_id);
+            clusters.entry(root).or_default().push(stop_id);
+        }
+
+        let mut clusters: Vec<_> = clusters
+            .into_values()
+            .map(|stop_ids| {
+                let centroid = centroid(
+                    stop_ids
+                        .iter()
+                        .filter_map(|&stop_id| self.stops.find(stop_id))
+                        .filter_map(|stop| stop.wgs84_position()),
+                );
+                StopCluster::new(stop_ids, centroid)
+            })
+            .collect();
+        clusters.sort_by_key(|cluster| cluster.stop_ids()[0]);
+        clusters
+    }
+
+    /// Summarizes every line from its journeys: operators, transport types, terminal stops and
+    /// active period. Sorted by line ID, the lines without journeys are included.
+    pub fn lines_overview(&self) -> Vec<LineOverview> {
+        #[derive(Default)]
+        struct Summary<'a> {
+            administrations: BTreeSet<&'a str>,
+            transport_type_ids: BTreeSet<i32>,
+            terminal_stop_ids: BTreeSet<i32>,
+            active_period: Option<(NaiveDate, NaiveDate)>,
+        }
+
+        let mut summaries: FxHashMap<i32, Summary> = self
+            .lines
+            .data()
+            .keys()
+            .map(|&line_id| (line_id, Summary::default()))
+            .collect();
+
+        for journey in self.journeys.data().values() {
+            let Some(summary) = journey
+                .line_id()
+                .and_then(|line_id| summaries.get_mut(&line_id))
+            else {
+                continue;
+            };
+
+            summary.administrations.insert(journey.administration());
+            if let Ok(transport_type_id) = journey.transport_type_id() {
+                summary.transport_type_ids.insert(transport_type_id);
+            }
+            summary
+                .terminal_stop_ids
+                .extend(journey.first_stop_id().ok());
+            summary
+                .terminal_stop_ids
+                .extend(journey.last_stop_id().ok());
+            if let Some((start, end)) = journey
+                .bit_field_id()
+                .ok()
+                .and_then(|bit_field_id| self.active_period_of_bit_field(bit_field_id))
+            {
+                summary.active_period = Some(match summary.active_period {
+                    None => (start, end),
+                    Some((current_start, current_end)) => {
+                        (current_start.min(start), current_end.max(end))
+                    }
+                });
+            }
+        }
+
+        let mut lines_overview: Vec<_> = summaries
+            .into_iter()
+            .map(|(line_id, summary)| {
+                LineOverview::new(
+                    line_id,
+                    summary
+                        .administrations
+                        .into_iter()
+                        .map(str::to_string)
+                        .collect(),
+                    summary.transport_type_ids.into_iter().collect(),
+                    summary.terminal_stop_ids.into_iter().collect(),
+                    summary.active_period,
+                )
+            })
+            .collect();
+        lines_overview.sort_by_key(|line_overview| line_overview.line_id());
+        lines_overview
+    }
+
+    /// First and last days of the timetable period on which the bit field is set.
+    /// No bit field or a bit field of 0 means every day.
+    fn active_period_of_bit_field(
+        &self,
+        bit_field_id: Option<i32>,
+    ) -> Option<(NaiveDate, NaiveDate)> {
+        let bit_field = match bit_field_id {
+            None | Some(0) => return Some(self.timetable_period),
+            Some(bit_field_id) => self.bit_fields.find(bit_field_id)?,
+        };
+
+        let mut active_days = bit_field
+            .bits()
+            .iter()
+            .enumerate()
+            .filter(|&(_, &bit)| bit == 1)
+            .filter_map(|(index, _)| date_of_bit_index(index, self.timetable_period.0))
+            .filter(|date| self.timetable_period().contains(date));
+        let first = active_days.next()?;
+        Some((first, active_days.next_back().unwrap_or(first)))
+    }
+
+    /// Computes the representative geometry of every line served by at least one journey,
+    /// sorted by line ID. The journeys without line or with less than two stops are ignored.
+    pub fn line_geometries(&self) -> Vec<LineGeometry> {
+        let mut stop_sequences: FxHashMap<i32, BTreeSet<Vec<i32>>> = FxHashMap::default();
+        for journey in self.journeys.data().values() {
+            let Some(line_id) = journey.line_id() else {
+                continue;
+            };
+            let stop_ids: Vec<_> = journey
+                .route()
+                .iter()
+                .map(|route_entry| route_entry.stop_id())
+                .collect();
+            if stop_ids.len() >= 2 {
+                stop_sequences.entry(line_id).or_default().insert(stop_ids);
+            }
+        }
+
+        let mut line_geometries: Vec<_> = stop_sequences
+            .into_iter()
+            .map(|(line_id, stop_sequences)| line_geometry(line_id, stop_sequences))
+            .collect();
+        line_geometries.sort_by_key(|line_geometry| line_geometry.line_id());
+        line_geometries
+    }
+
+    /// Computes the travel time statistics of every pair of consecutive stops served by at least one
+    /// journey, sorted by (from_stop_id, to_stop_id). The travel time of a journey goes from its
+    /// departure from the first stop to its arrival at the second one. The unknown stops are ignored.
+    pub fn segment_statistics(&self) -> Vec<SegmentStatistics> {
+        let mut travel_times: FxHashMap<(i32, i32), Vec<u32>> = FxHashMap::default();
+
+        for journey in self.journeys.data().values() {
+            for route_entries in journey.route().windows(2) {
+                let (from, to) = (&route_entries[0], &route_entries[1]);
+                if self.stops.find(from.stop_id()).is_none()
+                    || self.stops.find(to.stop_id()).is_none()
+                {
+                    continue;
+                }
+
+                let departure = from.departure().or(from.arrival());
+                let arrival = to.arrival().or(to.departure());
+                let (Some(departure), Some(arrival)) = (departure, arrival) else {
+                    continue;
+                };
+
+                travel_times
+                    .entry((from.stop_id(), to.stop_id()))
+                    .or_default()
+                    .push(arrival.minutes().saturating_sub(departure.minutes()));
+            }
+        }
+
+        let mut segment_statistics: Vec<_> = travel_times
+            .into_iter()
+            .map(|((from_stop_id, to_stop_id), travel_times)| {
+                SegmentStatistics::new(from_stop_id, to_stop_id, travel_times)
+            })
+            .collect();
+        segment_statistics.sort_by_key(|segment_statistics| {
+            (
+                segment_statistics.from_stop_id(),
+                segment_statistics.to_stop_id(),
+            )
+        });
+        segment_statistics
+    }
+
+    /// Exports the line geometries as a GeoJSON FeatureCollection: one MultiLineString feature
+    /// per line (main variant first, then the branches), with the line ID and name as properties.
+    pub fn line_geometries_to_geojson(&self) -> HResult<String> {
+        export::line_geometries_geojson(self)
+    }
+
+    /// Exports the service network as GraphML: the stops are the nodes (name, latitude, longitude)
+    /// and every pair of consecutive stops served by at least one journey is a directed edge
+    /// (number of journeys, minimum and mean travel time in minutes).
+    pub fn to_graphml(&self) -> String {
+        export::graphml(self)
+    }
+
+    /// Exports the timetable as a GTFS feed: the (file name, content) of agency.txt (one agency
+    /// per administration), stops.txt, routes.txt (one route per administration and line, or
+    /// transport type without a line), trips.txt (one trip per journey and repetition of a
+    /// cyclical journey), stop_times.txt and calendar_dates.txt (one service per bit field).
+    pub fn to_gtfs(&self) -> Vec<(&'static str, String)> {
+        export::gtfs(self)
+    }
+
+    /// Exports the stops, the journeys, their stop times (minutes since the start of the operating
+    /// day) and the dates of their bit fields to the tables stops, journeys, stop_times and
+    /// bit_field_dates of the SQLite database at the path, replacing these tables if they exist.
+    /// A cyclical journey is a single row with its num_cycles and cycle_duration, as in FPLAN.
+    #[cfg(feature = "sqlite")]
+    pub fn to_sqlite(&self, path: &std::path::Path) -> HResult<()> {
+        export::sqlite(self, path)
+    }
+
+    /// Exports the stops as a DataFrame with the columns id, name, latitude and longitude (WGS84),
+    /// sorted by ID.
+    #[cfg(feature = "polars")]
+    pub fn stops_to_dataframe(&self) -> HResult<polars::frame::DataFrame> {
+        export::stops_dataframe(self)
+    }
+
+    /// Exports the journeys as a DataFrame with the columns id, legacy_id, administration,
+    /// line_id, transport_type (designation), first_stop_id and last_stop_id, sorted by ID.
+    #[cfg(feature = "polars")]
+    pub fn journeys_to_dataframe(&self) -> HResult<polars::frame::DataFrame> {
+        export::journeys_dataframe(self)
+    }
+
+    /// Exports the stop times of the journeys operating on the date as a DataFrame with the
+    /// columns journey_id, sequence (position in the route), stop_id, arrival_at and departure_at,
+    /// sorted by journey, then by sequence.
+    #[cfg(feature = "polars")]
+    pub fn stop_times_to_dataframe(&self, date: NaiveDate) -> HResult<polars::frame::DataFrame> {
+        export::stop_times_dataframe(self, date)
+    }
+
+    /// Cross-checks the references between the resources and reports the dangling ones.
+    pub fn validate(&self) -> ValidationReport {
+        validation::validate(self)
+    }
+
+    /// Journeys added, removed and changed in new, a later export of the same timetable period.
+    pub fn journey_delta(&self, new: &DataStorage) -> JourneyDelta {
+        delta::journey_delta(self, new)
+    }
+
+    /// Updates the journeys with a delta computed by journey_delta from this export, so that
+    /// the unchanged journeys of the new export need not be parsed. The journeys added or changed
+    /// get new IDs and their bit fields are reused when an identical one exists. The platforms,
+    /// through services and exchange times of the journeys no longer in FPLAN are dropped, and
+    /// the maps derived from the journeys are rebuilt.
+    pub fn apply_delta(&mut self, delta: &JourneyDelta) -> HResult<()> {
+        if delta.timetable_period() != self.timetable_period {
+            return Err(HrdfError::IncompatibleDelta {
+                expected: self.timetable_period,
+                found: delta.timetable_period(),
+            });
+        }
+
+        // The IDs of the removed journeys are not reused.
+        let mut next_journey_id = self.journeys.data().keys().max().map_or(1, |id| id + 1);
+        let mut next_bit_field_id = self.bit_fields.data().keys().max().map_or(1, |id| id + 1);
+        let mut bit_field_ids_by_bits: FxHashMap<Vec<u8>, i32> = self
+            .bit_fields
+            .entries()
+            .into_iter()
+            .map(|bit_field| (bit_field.bits().clone(), bit_field.id()))
+            .collect();
+
+        let removed_ids = delta
+            .removed()
+            .iter()
+            .chain(delta.changed().iter().map(|(id, _, _)| id));
+        for id in removed_ids {
+            self.journeys.data_mut().remove(id);
+        }
+
+        let new_journeys = delta
+            .added()
+            .iter()
+            .map(|(journey, bit_field)| (journey, bit_field))
+            .chain(
+                delta
+                    .changed()
+                    .iter()
+                    .map(|(_, journey, bit_field)| (journey, bit_field)),
+            );
+        for (journey, bit_field) in new_journeys {
+            let mut journey = journey.clone();
+            journey.set_id(next_journey_id);
+            next_journey_id += 1;
+
+            if let Some(bit_field) = bit_field {
+                let bit_field_id = *bit_field_ids_by_bits
+                    .entry(bit_field.bits().clone())
+                    .or_insert_with(|| {
+                        let id = next_bit_field_id;
+                        next_bit_field_id += 1;
+                        self.bit_fields
+                            .data_mut()
+                            .insert(id, BitField::new(id, bit_field.bits().clone()));
+                        id
+                    });
+                journey.set_bit_field_id(Some(bit_field_id));
+            }
+            self.journeys.data_mut().insert(journey.id(), journey);
+        }
+
+        self.journeys_pk_type_converter = create_journeys_pk_type_converter(&self.journeys);
+        let journeys_pk_type_converter = &self.journeys_pk_type_converter;
+        let contains = |legacy_id: i32, administration: &str| {
+            journeys_pk_type_converter.contains(&(legacy_id, administration.to_string()))
+        };
+        self.journey_platform
+            .data_mut()
+            .retain(|_, journey_platform| {
+                contains(
+                    journey_platform.journey_legacy_id(),
+                    journey_platform.administration(),
+                )
+            });
+        self.through_service
+            .data_mut()
+            .retain(|_, through_service| {
+                journeys_pk_type_converter.contains(through_service.journey_1_id())
+                    && journeys_pk_type_converter.contains(through_service.journey_2_id())
+            });
+        self.exchange_times_journey
+            .data_mut()
+            .retain(|_, exchange_time| {
+                contains(
+                    exchange_time.journey_legacy_id_1(),
+                    exchange_time.administration_1(),
+                ) && contains(
+                    exchange_time.journey_legacy_id_2(),
+                    exchange_time.administration_2(),
+                )
+            });
+
+        self.bit_fields_by_day =
+            create_bit_fields_by_day(&self.bit_fields, &self.timetable_metadata)?;
+        self.bit_fields_by_stop_id = create_bit_fields_by_stop_id(&self.journeys)?;
+        self.journeys_by_stop_id_and_bit_field_id =
+            create_journeys_by_stop_id_and_bit_field_id(&self.journeys)?;
+        self.bit_field_id_for_through_service_by_journey_id_stop_id =
+            create_bit_field_id_through_service_by_journey_id_stop_id(&self.through_service);
+        self.exchange_times_journey_map =
+            create_exchange_times_journey_map(&self.exchange_times_journey);
+        self.journeys_by_swiss_journey_id = create_journeys_by_swiss_journey_id(&self.journeys);
+        Ok(())
+    }
+
+    /// Serves Hrdf::build_cache.
+    #[cfg(feature = "serde")]
+    pub(crate) fn encode_segment(&self, segment: CacheSegment) -> HResult<Vec<u8>> {
+        fn encode(value: impl Serialize) -> HResult<Vec<u8>> {
+            Ok(bincode::serde::encode_to_vec(
+                value,
+                bincode::config::standard(),
+            )?)
+        }
+
+        match segment {
+            CacheSegment::Core => encode((
+                &self.holidays,
+                &self.timetable_metadata,
+                self.default_exchange_time,
+                self.timetable_period,
+                &self.parse_report,
+            )),
+            CacheSegment::BitFields => encode((
+                &self.bit_fields,
+                &self.bit_fields_by_day,
+                &self.bit_fields_by_stop_id,
+            )),
+            CacheSegment::Attributes => {
+                encode((&self.attributes, &self.attributes_pk_type_converter))
+            }
+            CacheSegment::InformationTexts => encode(&self.information_texts),
+            CacheSegment::Directions => {
+                encode((&self.directions, &self.directions_pk_type_converter))
+            }
+            CacheSegment::Lines => encode(&self.lines),
+            CacheSegment::TransportCompanies => encode((
+                &self.transport_companies,
+                &self.transport_companies_by_sboid,
+            )),
+            CacheSegment::TransportTypes => encode((
+                &self.transport_types,
+                &self.transport_types_pk_type_converter,
+            )),
+            CacheSegment::Stops => encode(&self.stops),
+            CacheSegment::StopConnections => {
+                encode((&self.stop_connections, &self.stop_connections_by_stop_id))
+            }
+            CacheSegment::Journeys => encode((
+                &self.journeys,
+                &self.journeys_by_stop_id_and_bit_field_id,
+                &self.journeys_pk_type_converter,
+                &self.journeys_by_swiss_journey_id,
+            )),
+            CacheSegment::JourneyPlatform => encode(&self.journey_platform),
+            CacheSegment::Platforms => encode(&self.platforms),
+            CacheSegment::ThroughService => encode((
+                &self.through_service,
+                &self.bit_field_id_for_through_service_by_journey_id_stop_id,
+            )),
+            CacheSegment::ExchangeTimes => encode((
+                &self.exchange_times_administration,
+                &self.exchange_times_journey,
+                &self.exchange_times_line,
+                &self.exchange_times_administration_map,
+                &self.exchange_times_journey_map,
+            )),
+        }
+    }
+
+    /// Serves Hrdf::load_from_cache_segments.
+    #[cfg(feature = "serde")]
+    pub(crate) fn decode_segment(&mut self, segment: CacheSegment, data: &[u8]) -> HResult<()> {
+        fn decode<T: DeserializeOwned>(data: &[u8]) -> HResult<T> {
+            let (value, _) = bincode::serde::decode_from_slice(data, bincode::config::standard())?;
+            Ok(value)
+        }
+
+        match segment {
+            CacheSegment::Core => {
+                (
+                    self.holidays,
+                    self.timetable_metadata,
+                    self.default_exchange_time,
+                    self.timetable_period,
+                    self.parse_report,
+                ) = decode(data)?;
+            }
+            CacheSegment::BitFields => {
+                (
+                    self.bit_fields,
+                    self.bit_fields_by_day,
+                    self.bit_fields_by_stop_id,
+                ) = decode(data)?;
+            }
+            CacheSegment::Attributes => {
+                (self.attributes, self.attributes_pk_type_converter) = decode(data)?;
+            }
+            CacheSegment::InformationTexts => self.information_texts = decode(data)?,
+            CacheSegment::Directions => {
+                (self.directions, self.directions_pk_type_converter) = decode(data)?;
+            }
+            CacheSegment::Lines => self.lines = decode(data)?,
+            CacheSegment::TransportCompanies => {
+                (self.transport_companies, self.transport_companies_by_sboid) = decode(data)?;
+            }
+            CacheSegment::TransportTypes => {
+                (self.transport_types, self.transport_types_pk_type_converter) = decode(data)?;
+            }
+            CacheSegment::Stops => self.stops = decode(data)?,
+            CacheSegment::StopConnections => {
+                (self.stop_connections, self.stop_connections_by_stop_id) = decode(data)?;
+            }
+            CacheSegment::Journeys => {
+                (
+                    self.journeys,
+                    self.journeys_by_stop_id_and_bit_field_id,
+                    self.journeys_pk_type_converter,
+                    self.journeys_by_swiss_journey_id,
+                ) = decode(data)?;
+            }
+            CacheSegment::JourneyPlatform => self.journey_platform = decode(data)?,
+            CacheSegment::Platforms => self.platforms = decode(data)?,
+            CacheSegment::ThroughService => {
+                (
+                    self.through_service,
+                    self.bit_field_id_for_through_service_by_journey_id_stop_id,
+                ) = decode(data)?;
+            }
+            CacheSegment::ExchangeTimes => {
+                (
+                    self.exchange_times_administration,
+                    self.exchange_times_journey,
+                    self.exchange_times_line,
+                    self.exchange_times_administration_map,
+                    self.exchange_times_journey_map,
+                ) = decode(data)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The archived data, queried in place (see MappedHrdf).
+#[cfg(feature = "rkyv")]
+impl ArchivedDataStorage {
+    // Getters/Setters
+    pub fn journeys(&self) -> &ArchivedResourceStorage<Journey> {
+        &self.journeys
+    }
+
+    pub fn lines(&self) -> &ArchivedResourceStorage<Line> {
+        &self.lines
+    }
+
+    pub fn platforms(&self) -> &ArchivedResourceStorage<Platform> {
+        &self.platforms
+    }
+
+    pub fn stops(&self) -> &ArchivedResourceStorage<Stop> {
+        &self.stops
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
 // --- ResourceStorage
 // ------------------------------------------------------------------------------------------------
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ResourceStorage<M: Model<M>> {
-    data: FxHashMap<M::K, M>,
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
+pub struct ResourceStorage<M: Model<M>> {
+    data: FxHashMap<M::K, M>,
+}
+
+impl<M: Model<M>> Default for ResourceStorage<M> {
+    fn default() -> Self {
+        Self::new(FxHashMap::default())
+    }
+}
+
+impl<M: Model<M>> ResourceStorage<M> {
+    pub fn new(data: FxHashMap<M::K, M>) -> Self {
+        Self { data }
+    }
+
+    pub fn data(&self) -> &FxHashMap<M::K, M> {
+        &self.data
+    }
+
+    pub(crate) fn data_mut(&mut self) -> &mut FxHashMap<M::K, M> {
+        &mut self.data
+    }
+
+    pub fn find(&self, k: M::K) -> Option<&M> {
+        // TODO: there might be a problem when k is not in data so we can't unwrap here
+        self.data().get(&k)
+    }
+
+    pub fn entries(&self) -> Vec<&M> {
+        self.data.values().collect()
+    }
+
+    pub fn resolve_ids(&self, ids: &FxHashSet<M::K>) -> Option<Vec<&M>> {
+        ids.iter().map(|id| self.find(id.clone())).collect()
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<M> ArchivedResourceStorage<M>
+where
+    M: Model<M> + rkyv::Archive,
+    M::K: rkyv::Archive,
+    rkyv::Archived<M::K>: Eq + Hash,
+{
+    pub fn find(&self, k: &rkyv::Archived<M::K>) -> Option<&rkyv::Archived<M>> {
+        self.data.get(k)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &rkyv::Archived<M>> {
+        self.data.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Geometry
+// ------------------------------------------------------------------------------------------------
+
+/// Mean of the coordinates, in WGS84. None is returned if there are no coordinates.
+fn centroid(coordinates: impl Iterator<Item = Coordinates>) -> Option<Coordinates> {
+    let (count, latitude, longitude) = coordinates.fold(
+        (0_u32, 0.0, 0.0),
+        |(count, latitude, longitude), coordinates| {
+            let coordinates = coordinates.to_wgs84();
+            (
+                count + 1,
+                latitude + coordinates.latitude().unwrap_or_default(),
+                longitude + coordinates.longitude().unwrap_or_default(),
+            )
+        },
+    );
+
+    (count > 0).then(|| {
+        Coordinates::new(
+            CoordinateSystem::WGS84,
+            latitude / f64::from(count),
+            longitude / f64::from(count),
+        )
+    })
+}
+
+fn find_stop_root(parents: &mut FxHashMap<i32, i32>, stop_id: i32) -> i32 {
+    let mut root = stop_id;
+    while parents[&root] != root {
+        root = parents[&root];
+    }
+    // Path compression.
+    let mut current = stop_id;
+    while current != root {
+        let next = parents[&current];
+        parents.insert(current, root);
+        current = next;
+    }
+    root
+}
+
+fn union_stops(parents: &mut FxHashMap<i32, i32>, stop_id_1: i32, stop_id_2: i32) {
+    let root_1 = find_stop_root(parents, stop_id_1);
+    let root_2 = find_stop_root(parents, stop_id_2);
+    // The smallest stop ID is the root, so that the result is deterministic.
+    if root_1 < root_2 {
+        parents.insert(root_2, root_1);
+    } else {
+        parents.insert(root_1, root_2);
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Through services
+// ------------------------------------------------------------------------------------------------
+
+/// Follows the links from the last journey of the chain until a journey which does not continue.
+/// A journey already in the chain is not followed twice, in case the links form a cycle.
+fn chain_journeys(
+    links: &FxHashMap<i32, Vec<(i32, i32)>>,
+    journey_ids: Vec<i32>,
+    through_stop_ids: Vec<i32>,
+    composite_journeys: &mut Vec<CompositeJourney>,
+) {
+    // unwrap: The chain always contains at least one journey.
+    let last_journey_id = *journey_ids.last().unwrap();
+    let next_links: Vec<_> = links
+        .get(&last_journey_id)
+        .into_iter()
+        .flatten()
+        .filter(|(_, next_journey_id)| !journey_ids.contains(next_journey_id))
+        .collect();
+
+    if next_links.is_empty() {
+        if journey_ids.len() >= 2 {
+            composite_journeys.push(CompositeJourney::new(journey_ids, through_stop_ids));
+        }
+        return;
+    }
+
+    for &(stop_id, next_journey_id) in next_links {
+        let mut journey_ids = journey_ids.clone();
+        journey_ids.push(next_journey_id);
+        let mut through_stop_ids = through_stop_ids.clone();
+        through_stop_ids.push(stop_id);
+        chain_journeys(links, journey_ids, through_stop_ids, composite_journeys);
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Line geometries
+// ------------------------------------------------------------------------------------------------
+
+/// The longest stop sequence is the main variant (the first in stop ID order in case of a tie).
+/// Then, from the longest to the shortest, the runs of segments of the other stop sequences which
+/// are not covered yet become branches. A segment is covered in both directions.
+fn line_geometry(line_id: i32, stop_sequences: BTreeSet<Vec<i32>>) -> LineGeometry {
+    let mut stop_sequences: Vec<_> = stop_sequences.into_iter().collect();
+    // The sort is stable, so the ties keep the order of the BTreeSet.
+    stop_sequences.sort_by_key(|stop_ids| Reverse(stop_ids.len()));
+    let main_variant = stop_sequences.remove(0);
+
+    let segment = |stop_id_1: i32, stop_id_2: i32| {
+        (
+            i32::min(stop_id_1, stop_id_2),
+            i32::max(stop_id_1, stop_id_2),
+        )
+    };
+    let mut covered: FxHashSet<_> = main_variant
+        .windows(2)
+        .map(|stop_ids| segment(stop_ids[0], stop_ids[1]))
+        .collect();
+
+    let mut branches = Vec::new();
+    for stop_sequence in stop_sequences {
+        let mut branch = Vec::new();
+        for stop_ids in stop_sequence.windows(2) {
+            if covered.insert(segment(stop_ids[0], stop_ids[1])) {
+                if branch.is_empty() {
+                    branch.push(stop_ids[0]);
+                }
+                branch.push(stop_ids[1]);
+            } else if !branch.is_empty() {
+                branches.push(mem::take(&mut branch));
+            }
+        }
+        if !branch.is_empty() {
+            branches.push(branch);
+        }
+    }
+
+    LineGeometry::new(line_id, main_variant, branches)
 }
 
-impl<M: Model<M>> ResourceStorage<M> {
-    pub fn new(data: FxHashMap<M::K, M>) -> Self {
-        Self { data }
-    }
+// ------------------------------------------------------------------------------------------------
+// --- Placeholders
+// ------------------------------------------------------------------------------------------------
 
-    pub fn data(&self) -> &FxHashMap<M::K, M> {
-        &self.data
+/// Creates a stop for each stop of a route which is not defined in BAHNHOF, named as in FPLAN.
+fn add_placeholder_stops(
+    stops: &mut ResourceStorage<Stop>,
+    stop_names: FxHashMap<i32, String>,
+    parse_report: &mut ParseReport,
+) {
+    let mut stop_names: Vec<_> = stop_names
+        .into_iter()
+        .filter(|(stop_id, _)| !stops.data.contains_key(stop_id))
+        .collect();
+    stop_names.sort_unstable();
+
+    for (stop_id, name) in stop_names {
+        log::warn!("Stop {stop_id} ({name}) is not defined in BAHNHOF, a placeholder is created.");
+        parse_report.add(ParseIssue::PlaceholderStop {
+            stop_id,
+            name: name.clone(),
+        });
+        stops
+            .data
+            .insert(stop_id, Stop::new(stop_id, name, None, None, None));
     }
+}
 
-    pub fn find(&self, k: M::K) -> Option<&M> {
-        // TODO: there might be a problem when k is not in data so we can't unwrap here
-        self.data().get(&k)
-    }
+/// The journeys whose bit field is not defined in BITFELD operate every day, as with the bit
+/// field 000000.
+fn resolve_missing_bit_fields(
+    journeys: &mut ResourceStorage<Journey>,
+    bit_fields: &ResourceStorage<BitField>,
+    mode: ParsingMode,
+    parse_report: &mut ParseReport,
+) -> HResult<()> {
+    let mut journeys: Vec<_> = journeys.data.values_mut().collect();
+    journeys.sort_unstable_by_key(|journey| journey.id());
+
+    for journey in journeys {
+        let Ok(Some(bit_field_id)) = journey.bit_field_id() else {
+            continue;
+        };
+        if bit_field_id == 0 || bit_fields.data.contains_key(&bit_field_id) {
+            continue;
+        }
+        if mode == ParsingMode::Strict {
+            return Err(HrdfError::BitFieldIdNotFound(bit_field_id));
+        }
 
-    pub fn entries(&self) -> Vec<&M> {
-        self.data.values().collect()
+        log::warn!(
+            "The bit field {bit_field_id} of the journey {} ({}) is not defined in BITFELD, the journey operates every day.",
+            journey.legacy_id(),
+            journey.administration()
+        );
+        parse_report.add(ParseIssue::MissingBitField {
+            journey_id: journey.id(),
+            legacy_id: journey.legacy_id(),
+            administration: journey.administration().to_string(),
+            bit_field_id,
+        });
+        journey.set_bit_field_id(None);
     }
+    Ok(())
+}
 
-    pub fn resolve_ids(&self, ids: &FxHashSet<M::K>) -> Option<Vec<&M>> {
-        ids.iter().map(|&id| self.find(id)).collect()
+/// The Swiss Journey ID and the region of a journey are the texts of its *I JY and *I RN rows.
+fn resolve_journey_information_texts(
+    journeys: &mut ResourceStorage<Journey>,
+    information_texts: &ResourceStorage<InformationText>,
+) {
+    for journey in journeys.data.values_mut() {
+        let text = |code: &str| {
+            journey
+                .information_text_ids(code)
+                .into_iter()
+                .find_map(|id| information_texts.find(id))
+                .and_then(|information_text| information_text.content(Language::German))
+                .map(str::to_string)
+        };
+        let (swiss_journey_id, region) = (text("JY"), text("RN"));
+        journey.set_swiss_journey_id(swiss_journey_id);
+        journey.set_region(region);
     }
 }
 
@@ -358,8 +1884,8 @@ fn create_bit_fields_by_day(
     timetable_metadata: &ResourceStorage<TimetableMetadataEntry>,
 ) -> HResult<FxHashMap<NaiveDate, FxHashSet<i32>>> {
     let start_date = timetable_start_date(timetable_metadata)?;
-    let num_days =
-        count_days_between_two_dates(start_date, timetable_end_date(timetable_metadata)?);
+    let end_date = timetable_end_date(timetable_metadata)?;
+    let num_days = count_days_between_two_dates(start_date, end_date);
 
     let dates = (0..num_days)
         .map(|i| {
@@ -376,29 +1902,22 @@ fn create_bit_fields_by_day(
         map.entry(*date).or_insert(FxHashSet::default()).insert(0);
     });
 
-    bit_fields
-        .data()
-        .keys()
-        .try_fold(map, |mut acc, bit_field_id| {
-            let bit_field = bit_fields
-                .find(*bit_field_id)
-                .ok_or(HrdfError::BitFieldIdNotFound(*bit_field_id))?;
-            let indexes: Vec<usize> = bit_field
+    Ok(bit_fields
+        .entries()
+        .into_iter()
+        .fold(map, |mut acc, bit_field| {
+            bit_field
                 .bits()
                 .iter()
-                // The first two bits must be ignored.
-                .skip(2)
                 .enumerate()
-                .filter(|&(ref i, &x)| *i < num_days && x == 1)
-                .map(|(i, _)| i)
-                .collect();
-
-            indexes.iter().for_each(|&i| {
-                acc.entry(dates[i]).or_default().insert(bit_field.id());
-            });
-
-            Ok(acc)
-        })
+                .filter(|&(_, &x)| x == 1)
+                .filter_map(|(i, _)| date_of_bit_index(i, start_date))
+                .filter(|date| *date <= end_date)
+                .for_each(|date| {
+                    acc.entry(date).or_default().insert(bit_field.id());
+                });
+            acc
+        }))
 }
 
 fn create_bit_fields_by_stop_id(
@@ -437,6 +1956,46 @@ fn create_journeys_by_stop_id_and_bit_field_id(
     )
 }
 
+fn create_transport_companies_by_sboid(
+    transport_companies: &ResourceStorage<TransportCompany>,
+) -> FxHashMap<String, i32> {
+    transport_companies
+        .entries()
+        .into_iter()
+        .filter_map(|transport_company| {
+            transport_company
+                .sboid()
+                .map(|sboid| (sboid.to_string(), transport_company.id()))
+        })
+        .collect()
+}
+
+/// The (legacy ID, administration) of the journeys, as collected by parsing::load_journeys.
+fn create_journeys_pk_type_converter(journeys: &ResourceStorage<Journey>) -> FxHashSet<JourneyId> {
+    journeys
+        .entries()
+        .into_iter()
+        .map(|journey| (journey.legacy_id(), journey.administration().to_string()))
+        .collect()
+}
+
+/// The repetitions of a cyclical journey share its Swiss Journey ID, the first journey is kept.
+fn create_journeys_by_swiss_journey_id(
+    journeys: &ResourceStorage<Journey>,
+) -> FxHashMap<String, i32> {
+    let mut journeys_by_swiss_journey_id = FxHashMap::default();
+    let mut journeys = journeys.entries();
+    journeys.sort_unstable_by_key(|journey| journey.id());
+    for journey in journeys {
+        if let Some(swiss_journey_id) = journey.swiss_journey_id() {
+            journeys_by_swiss_journey_id
+                .entry(swiss_journey_id.to_string())
+                .or_insert(journey.id());
+        }
+    }
+    journeys_by_swiss_journey_id
+}
+
 /// Given journey_stop_id, and journey_id_1, journey_id_2, we obtain the bit_field_id of the ThroughService
 fn create_bit_field_id_through_service_by_journey_id_stop_id(
     through_services: &ResourceStorage<ThroughService>,
@@ -516,7 +2075,11 @@ fn create_exchange_times_administration_map(
 
 #[cfg(test)]
 mod tests {
-    use crate::{JourneyMetadataEntry, JourneyMetadataType, JourneyRouteEntry};
+    use crate::{
+        JourneyMetadataEntry, JourneyMetadataType, JourneyRouteEntry, ServiceTime,
+        utils::{BIT_FIELD_PADDING, bit_index_of_date},
+        validation::{ReferenceKind, ResourceKind, ValidationIssue},
+    };
 
     use super::*;
     use chrono::{NaiveDate, NaiveTime};
@@ -555,7 +2118,7 @@ mod tests {
                 None,
                 None,
                 bit_field_id,
-                Some(NaiveTime::from_hms_opt(8, 0, 0).unwrap()),
+                Some(NaiveTime::from_hms_opt(8, 0, 0).unwrap().into()),
                 None,
                 None,
                 None,
@@ -566,12 +2129,20 @@ mod tests {
             let departure = if index + 1 == route_stops.len() {
                 None
             } else {
-                Some(NaiveTime::from_hms_opt(8, (index as u32) * 10, 0).unwrap())
+                Some(
+                    (NaiveTime::from_hms_opt(8, 0, 0).unwrap()
+                        + TimeDelta::minutes(index as i64 * 10))
+                    .into(),
+                )
             };
             let arrival = if index == 0 {
                 None
             } else {
-                Some(NaiveTime::from_hms_opt(8, (index as u32) * 10 - 5, 0).unwrap())
+                Some(
+                    (NaiveTime::from_hms_opt(8, 0, 0).unwrap()
+                        + TimeDelta::minutes(index as i64 * 10 - 5))
+                    .into(),
+                )
             };
             journey.add_route_entry(JourneyRouteEntry::new(*stop_id, arrival, departure));
         }
@@ -619,6 +2190,110 @@ mod tests {
         assert_eq!(by_stop_and_bit.get(&(20, 7)).unwrap(), &vec![1]);
     }
 
+    #[test]
+    fn composite_journeys_follow_through_services() {
+        let mut journeys = FxHashMap::default();
+        for (id, route_stops) in [
+            (1, [1, 2]),
+            (2, [2, 3]),
+            (3, [3, 4]),
+            (4, [3, 5]),
+            (5, [6, 7]),
+            (6, [7, 8]),
+        ] {
+            journeys.insert(
+                id,
+                build_journey_with_bitfield(id, id * 100, None, &route_stops),
+            );
+        }
+        let mut through_services = FxHashMap::default();
+        for (id, legacy_id_1, stop_id, legacy_id_2, bit_field_id) in [
+            (1, 100, 2, 200, 0),
+            (2, 200, 3, 300, 0),
+            // The train is split.
+            (3, 200, 3, 400, 0),
+            // Unknown bit field, the through service never applies.
+            (4, 500, 7, 600, 9),
+        ] {
+            through_services.insert(
+                id,
+                ThroughService::new(
+                    id,
+                    (legacy_id_1, "CH".to_string()),
+                    stop_id,
+                    (legacy_id_2, "CH".to_string()),
+                    stop_id,
+                    bit_field_id,
+                ),
+            );
+        }
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let data_storage = DataStorage {
+            journeys: ResourceStorage::new(journeys),
+            through_service: ResourceStorage::new(through_services),
+            timetable_period: (date, date),
+            ..Default::default()
+        };
+
+        let composite_journeys = data_storage.composite_journeys(date);
+        assert_eq!(composite_journeys.len(), 2);
+        assert_eq!(composite_journeys[0].journey_ids(), &[1, 2, 3]);
+        assert_eq!(composite_journeys[0].through_stop_ids(), &[2, 3]);
+        assert_eq!(
+            composite_journeys[0].destination_stop_id(&data_storage),
+            Some(4)
+        );
+        assert_eq!(composite_journeys[1].journey_ids(), &[1, 2, 4]);
+    }
+
+    #[test]
+    fn stop_effective_exchange_time_falls_back_to_the_default() {
+        let data_storage = DataStorage {
+            default_exchange_time: (4, 2),
+            ..Default::default()
+        };
+        let mut stop = Stop::new(1, "A".to_string(), None, None, None);
+        assert_eq!(stop.effective_exchange_time(1, &data_storage), 4);
+        assert_eq!(stop.effective_exchange_time(5, &data_storage), 2);
+
+        stop.set_exchange_time(Some((5, 3)));
+        assert_eq!(stop.effective_exchange_time(0, &data_storage), 5);
+        assert_eq!(stop.effective_exchange_time(6, &data_storage), 3);
+    }
+
+    #[test]
+    fn search_stops_expands_meta_stops() {
+        let mut stops = FxHashMap::default();
+        for (id, name) in [
+            (1, "Basel SBB"),
+            (2, "Basel SBB, Bahnhofeingang Gundeldingen"),
+            (3, "Basel, Bahnhof SBB"),
+            (4, "Bern"),
+        ] {
+            stops.insert(id, Stop::new(id, name.to_string(), None, None, None));
+        }
+        let mut stop_connections = FxHashMap::default();
+        stop_connections.insert(1, StopConnection::new(1, 1, 3, 5));
+        let stop_connections = ResourceStorage::new(stop_connections);
+        let data_storage = DataStorage {
+            stops: ResourceStorage::new(stops),
+            stop_connections_by_stop_id: create_stop_connections_by_stop_id(&stop_connections),
+            stop_connections,
+            ..Default::default()
+        };
+
+        let ids = |stops: Vec<&Stop>| stops.iter().map(|stop| stop.id()).collect::<Vec<_>>();
+        assert_eq!(
+            ids(data_storage.search_stops("basel sbb", false)),
+            vec![1, 2]
+        );
+        assert_eq!(
+            ids(data_storage.search_stops(" Basel SBB", true)),
+            vec![1, 3, 2]
+        );
+        assert!(data_storage.search_stops("", true).is_empty());
+    }
+
     #[test]
     fn stop_connection_map_collects_ids() {
         let mut data = FxHashMap::default();
@@ -684,6 +2359,792 @@ mod tests {
         assert!(ids.contains(&2));
     }
 
+    #[test]
+    fn check_date_rejects_dates_outside_timetable_period() {
+        let start = NaiveDate::from_ymd_opt(2024, 12, 15).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 12, 13).unwrap();
+        let data_storage = DataStorage {
+            timetable_period: (start, end),
+            ..Default::default()
+        };
+
+        assert_eq!(data_storage.timetable_period(), start..=end);
+        assert!(data_storage.check_date(start).is_ok());
+        assert!(data_storage.check_date(end).is_ok());
+
+        let date = NaiveDate::from_ymd_opt(2025, 12, 14).unwrap();
+        match data_storage.check_date(date).unwrap_err() {
+            HrdfError::OutOfTimetablePeriod {
+                date: d,
+                start: s,
+                end: e,
+            } => {
+                assert_eq!(d, date);
+                assert_eq!(s, start);
+                assert_eq!(e, end);
+            }
+            other => panic!("Error should be OutOfTimetablePeriod but is: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn queries_reject_dates_outside_timetable_period() {
+        let start = NaiveDate::from_ymd_opt(2024, 12, 15).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 12, 13).unwrap();
+        let data_storage = DataStorage {
+            timetable_period: (start, end),
+            ..Default::default()
+        };
+        let inside = start.and_hms_opt(8, 0, 0).unwrap();
+        let outside = (end + TimeDelta::days(1)).and_hms_opt(8, 0, 0).unwrap();
+
+        assert!(data_storage.departures(1, inside, inside).is_ok());
+        assert!(matches!(
+            data_storage.departures(1, outside, outside),
+            Err(HrdfError::OutOfTimetablePeriod { .. })
+        ));
+        assert!(matches!(
+            data_storage.arrivals(1, outside, outside),
+            Err(HrdfError::OutOfTimetablePeriod { .. })
+        ));
+        assert!(matches!(
+            data_storage.transfer_statistics(1, outside.date()),
+            Err(HrdfError::OutOfTimetablePeriod { .. })
+        ));
+        assert!(data_storage.station_board(1, inside, inside, true).is_ok());
+        assert!(matches!(
+            data_storage.station_board(1, outside, outside, true),
+            Err(HrdfError::OutOfTimetablePeriod { .. })
+        ));
+        assert!(
+            data_storage
+                .earliest_arrival_matrix(&[1], &[2], inside, 2)
+                .is_ok()
+        );
+        assert!(matches!(
+            data_storage.earliest_arrival_matrix(&[1], &[2], outside, 2),
+            Err(HrdfError::OutOfTimetablePeriod { .. })
+        ));
+    }
+
+    #[test]
+    fn bit_index_and_date_round_trip() {
+        let start = NaiveDate::from_ymd_opt(2024, 12, 15).unwrap();
+        assert_eq!(bit_index_of_date(start, start), Some(BIT_FIELD_PADDING));
+        assert_eq!(date_of_bit_index(BIT_FIELD_PADDING, start), Some(start));
+        assert_eq!(date_of_bit_index(0, start), None);
+        assert_eq!(date_of_bit_index(1, start), None);
+        assert_eq!(
+            bit_index_of_date(NaiveDate::from_ymd_opt(2024, 12, 14).unwrap(), start),
+            None
+        );
+
+        for days in [0, 1, 17, 100, 363] {
+            let date = start.checked_add_days(Days::new(days)).unwrap();
+            let index = bit_index_of_date(date, start).unwrap();
+            assert_eq!(index, days as usize + 2);
+            assert_eq!(date_of_bit_index(index, start), Some(date));
+        }
+    }
+
+    #[test]
+    fn bit_fields_by_day_agrees_with_bit_field_is_active_on() {
+        let metadata = build_timetable_metadata("2024-12-15", "2024-12-21");
+        // Sunday 15.12 and Wednesday 18.12 and a bit beyond the end of the timetable.
+        let bit_fields = build_bit_field(vec![1, 1, 1, 0, 0, 1, 0, 0, 0, 1]);
+        let map = create_bit_fields_by_day(&bit_fields, &metadata).unwrap();
+        let start = NaiveDate::from_ymd_opt(2024, 12, 15).unwrap();
+        let bit_field = bit_fields.find(1).unwrap();
+
+        for days in 0..7 {
+            let date = start.checked_add_days(Days::new(days)).unwrap();
+            assert_eq!(
+                map.get(&date).unwrap().contains(&1),
+                bit_field.is_active_on(date, start),
+                "Mismatch on {date}"
+            );
+        }
+        assert!(bit_field.is_active_on(start, start));
+        assert!(bit_field.is_active_on(NaiveDate::from_ymd_opt(2024, 12, 18).unwrap(), start));
+        assert!(!bit_field.is_active_on(NaiveDate::from_ymd_opt(2024, 12, 16).unwrap(), start));
+        // The padding bits are not days.
+        assert!(!bit_field.is_active_on(NaiveDate::from_ymd_opt(2024, 12, 14).unwrap(), start));
+        assert_eq!(map.len(), 7);
+    }
+
+    #[test]
+    fn journey_operates_on_uses_bit_fields() {
+        let metadata = build_timetable_metadata("2024-01-01", "2024-01-03");
+        let bit_fields = build_bit_field(vec![0, 0, 1, 0, 1]);
+        let data_storage = DataStorage {
+            bit_fields_by_day: create_bit_fields_by_day(&bit_fields, &metadata).unwrap(),
+            bit_fields,
+            timetable_period: (
+                NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 1, 3).unwrap(),
+            ),
+            ..Default::default()
+        };
+
+        let journey = build_journey_with_bitfield(1, 100, Some(1), &[10, 20]);
+        let day1 = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let day2 = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        assert!(journey.operates_on(day1, &data_storage).unwrap());
+        assert!(!journey.operates_on(day2, &data_storage).unwrap());
+
+        let every_day = build_journey_with_bitfield(2, 200, None, &[10, 20]);
+        assert!(every_day.operates_on(day2, &data_storage).unwrap());
+
+        let out_of_period = NaiveDate::from_ymd_opt(2024, 1, 4).unwrap();
+        assert!(journey.operates_on(out_of_period, &data_storage).is_err());
+    }
+
+    #[test]
+    fn missing_bit_fields_operate_every_day() {
+        let bit_fields = build_bit_field(vec![0, 0, 1, 0, 1]);
+        let build_journeys = || {
+            let mut data = FxHashMap::default();
+            for journey in [
+                build_journey_with_bitfield(1, 100, Some(1), &[10, 20]),
+                build_journey_with_bitfield(2, 200, Some(7), &[10, 20]),
+                build_journey_with_bitfield(3, 300, None, &[10, 20]),
+            ] {
+                data.insert(journey.id(), journey);
+            }
+            ResourceStorage::new(data)
+        };
+
+        let result = resolve_missing_bit_fields(
+            &mut build_journeys(),
+            &bit_fields,
+            ParsingMode::Strict,
+            &mut ParseReport::new(),
+        );
+        assert!(matches!(result, Err(HrdfError::BitFieldIdNotFound(7))));
+
+        let mut journeys = build_journeys();
+        let mut report = ParseReport::new();
+        resolve_missing_bit_fields(
+            &mut journeys,
+            &bit_fields,
+            ParsingMode::Lenient,
+            &mut report,
+        )
+        .unwrap();
+        assert_eq!(journeys.find(1).unwrap().bit_field_id().unwrap(), Some(1));
+        assert_eq!(journeys.find(2).unwrap().bit_field_id().unwrap(), None);
+        assert_eq!(
+            report.issues(),
+            &vec![ParseIssue::MissingBitField {
+                journey_id: 2,
+                legacy_id: 200,
+                administration: "CH".to_string(),
+                bit_field_id: 7,
+            }]
+        );
+    }
+
+    #[test]
+    fn journeys_active_at_includes_journeys_from_previous_service_day() {
+        let metadata = build_timetable_metadata("2024-01-01", "2024-01-03");
+        let bit_fields = build_bit_field(vec![0, 0, 1, 0, 1]);
+
+        // Operates on 2024-01-01 and 2024-01-03, from 23:50 to 00:30 on the next day.
+        let mut night_journey = build_journey_with_bitfield(1, 100, Some(1), &[]);
+        night_journey.add_route_entry(JourneyRouteEntry::new(
+            10,
+            None,
+            Some(ServiceTime::new(23 * 60 + 50)),
+        ));
+        night_journey.add_route_entry(JourneyRouteEntry::new(
+            20,
+            Some(ServiceTime::new(24 * 60 + 30)),
+            None,
+        ));
+        // Operates every day, from 08:00 to 08:05.
+        let day_journey = build_journey_with_bitfield(2, 200, None, &[10, 20]);
+
+        let mut journeys = FxHashMap::default();
+        journeys.insert(1, night_journey);
+        journeys.insert(2, day_journey);
+        let data_storage = DataStorage {
+            bit_fields_by_day: create_bit_fields_by_day(&bit_fields, &metadata).unwrap(),
+            bit_fields,
+            journeys: ResourceStorage::new(journeys),
+            timetable_period: (
+                NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 1, 3).unwrap(),
+            ),
+            ..Default::default()
+        };
+
+        let active_ids_at = |date: (i32, u32, u32), time: (u32, u32)| {
+            let datetime = NaiveDate::from_ymd_opt(date.0, date.1, date.2)
+                .unwrap()
+                .and_hms_opt(time.0, time.1, 0)
+                .unwrap();
+            let mut ids: Vec<i32> = data_storage
+                .journeys_active_at(datetime)
+                .unwrap()
+                .iter()
+                .map(|journey| journey.id())
+                .collect();
+            ids.sort_unstable();
+            ids
+        };
+
+        assert_eq!(active_ids_at((2024, 1, 1), (23, 55)), vec![1]);
+        // Started on the previous service day.
+        assert_eq!(active_ids_at((2024, 1, 2), (0, 10)), vec![1]);
+        // The night journey does not operate on 2024-01-02.
+        assert_eq!(active_ids_at((2024, 1, 3), (0, 10)), Vec::<i32>::new());
+        assert_eq!(active_ids_at((2024, 1, 2), (8, 3)), vec![2]);
+        assert_eq!(active_ids_at((2024, 1, 2), (8, 10)), Vec::<i32>::new());
+        // The previous service day, 2023-12-31, is not covered by the timetable.
+        assert_eq!(active_ids_at((2024, 1, 1), (0, 10)), Vec::<i32>::new());
+
+        let outside = NaiveDate::from_ymd_opt(2024, 1, 4)
+            .unwrap()
+            .and_hms_opt(0, 10, 0)
+            .unwrap();
+        assert!(matches!(
+            data_storage.journeys_active_at(outside),
+            Err(HrdfError::OutOfTimetablePeriod { .. })
+        ));
+    }
+
+    #[test]
+    fn validate_reports_dangling_references() {
+        let mut journeys = FxHashMap::default();
+        journeys.insert(1, build_journey_with_bitfield(1, 100, Some(1), &[10, 20]));
+        journeys.insert(
+            2,
+            build_journey_with_bitfield(2, 200, Some(0), &[10, 30, 40]),
+        );
+        journeys.insert(3, build_journey_with_bitfield(3, 300, Some(7), &[20]));
+
+        let mut stops = FxHashMap::default();
+        stops.insert(10, Stop::new(10, "A".to_string(), None, None, None));
+        stops.insert(20, Stop::new(20, "B".to_string(), None, None, None));
+
+        let journey_platform = JourneyPlatform::new(100, "CH".to_string(), 5, None, None);
+        let mut through_service = FxHashMap::default();
+        through_service.insert(
+            1,
+            ThroughService::new(
+                1,
+                (100, "CH".to_string()),
+                20,
+                (900, "CH".to_string()),
+                10,
+                1,
+            ),
+        );
+
+        let data_storage = DataStorage {
+            bit_fields: build_bit_field(vec![1]),
+            stops: ResourceStorage::new(stops),
+            journeys: ResourceStorage::new(journeys),
+            journey_platform: ResourceStorage::new(FxHashMap::from_iter([(
+                journey_platform.id(),
+                journey_platform,
+            )])),
+            through_service: ResourceStorage::new(through_service),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            data_storage.validate().issues(),
+            &vec![
+                ValidationIssue::DanglingReferences {
+                    reference: ReferenceKind::JourneyToStop,
+                    count: 2,
+                    examples: vec![
+                        "Journey 2 (200, CH) -> 30".to_string(),
+                        "Journey 2 (200, CH) -> 40".to_string(),
+                    ],
+                },
+                ValidationIssue::DanglingReferences {
+                    reference: ReferenceKind::JourneyToBitField,
+                    count: 1,
+                    examples: vec!["Journey 3 (300, CH) -> 7".to_string()],
+                },
+                ValidationIssue::DanglingReferences {
+                    reference: ReferenceKind::JourneyPlatformToPlatform,
+                    count: 1,
+                    examples: vec!["JourneyPlatform (100, CH, 5) -> 5".to_string()],
+                },
+                ValidationIssue::DanglingReferences {
+                    reference: ReferenceKind::ThroughServiceToJourney,
+                    count: 1,
+                    examples: vec!["ThroughService 1 -> (900, CH)".to_string()],
+                },
+                ValidationIssue::ShortJourneys {
+                    count: 1,
+                    examples: vec!["Journey 3 (300, CH) has 1 route entries".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_reports_unresolved_raw_codes() {
+        let mut journey = build_journey_with_bitfield(1, 100, None, &[10, 20]);
+        for (metadata_type, raw_code) in [
+            (JourneyMetadataType::Line, "#S1X"),
+            (JourneyMetadataType::Direction, "R000099"),
+        ] {
+            let mut entry =
+                JourneyMetadataEntry::new(None, None, None, None, None, None, None, None);
+            entry.set_raw_code(raw_code.to_string());
+            journey.add_metadata_entry(metadata_type, entry);
+        }
+
+        let mut stops = FxHashMap::default();
+        stops.insert(10, Stop::new(10, "A".to_string(), None, None, None));
+        stops.insert(20, Stop::new(20, "B".to_string(), None, None, None));
+
+        let data_storage = DataStorage {
+            stops: ResourceStorage::new(stops),
+            journeys: ResourceStorage::new(FxHashMap::from_iter([(1, journey)])),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            data_storage.validate().issues(),
+            &vec![
+                ValidationIssue::DanglingReferences {
+                    reference: ReferenceKind::JourneyToDirection,
+                    count: 1,
+                    examples: vec!["Journey 1 (100, CH) -> R000099".to_string()],
+                },
+                ValidationIssue::DanglingReferences {
+                    reference: ReferenceKind::JourneyToLine,
+                    count: 1,
+                    examples: vec!["Journey 1 (100, CH) -> #S1X".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_reports_unused_resources() {
+        let mut journeys = FxHashMap::default();
+        journeys.insert(1, build_journey_with_bitfield(1, 100, Some(1), &[10, 20]));
+
+        let mut stops = FxHashMap::default();
+        for (id, name) in [(10, "A"), (20, "B"), (30, "C")] {
+            stops.insert(id, Stop::new(id, name.to_string(), None, None, None));
+        }
+        let mut bit_fields = FxHashMap::default();
+        bit_fields.insert(1, BitField::new(1, vec![1]));
+        bit_fields.insert(2, BitField::new(2, vec![1]));
+        let mut platforms = FxHashMap::default();
+        platforms.insert(5, Platform::new(5, "1".to_string(), Vec::new(), 10));
+
+        let data_storage = DataStorage {
+            bit_fields: ResourceStorage::new(bit_fields),
+            stops: ResourceStorage::new(stops),
+            journeys: ResourceStorage::new(journeys),
+            platforms: ResourceStorage::new(platforms),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            data_storage.validate().issues(),
+            &vec![
+                ValidationIssue::UnusedResources {
+                    resource: ResourceKind::BitField,
+                    count: 1,
+                    examples: vec!["BitField 2".to_string()],
+                },
+                ValidationIssue::UnusedResources {
+                    resource: ResourceKind::Platform,
+                    count: 1,
+                    examples: vec!["Platform 5".to_string()],
+                },
+                ValidationIssue::UnusedResources {
+                    resource: ResourceKind::Stop,
+                    count: 1,
+                    examples: vec!["Stop 30 (C)".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_keeps_a_limited_number_of_examples() {
+        let stop_ids: Vec<i32> = (1..=8).collect();
+        let mut journeys = FxHashMap::default();
+        journeys.insert(1, build_journey_with_bitfield(1, 100, None, &stop_ids));
+        let data_storage = DataStorage {
+            journeys: ResourceStorage::new(journeys),
+            ..Default::default()
+        };
+
+        match &data_storage.validate().issues()[..] {
+            [
+                ValidationIssue::DanglingReferences {
+                    count, examples, ..
+                },
+            ] => {
+                assert_eq!(*count, 8);
+                assert_eq!(examples.len(), 5);
+            }
+            issues => panic!("Unexpected issues: {issues:?}"),
+        }
+    }
+
+    #[cfg(feature = "spatial")]
+    #[test]
+    fn nearest_stops_are_sorted_by_distance() {
+        let mut stops = FxHashMap::default();
+        // Stop 6 is at the same place as stop 3.
+        for (id, latitude, longitude) in [
+            (1, 46.948, 7.439),
+            (2, 46.96, 7.445),
+            (3, 47.03, 7.52),
+            (6, 47.03, 7.52),
+        ] {
+            let mut stop = Stop::new(id, id.to_string(), None, None, None);
+            stop.set_wgs84_coordinates(Coordinates::new(
+                CoordinateSystem::WGS84,
+                latitude,
+                longitude,
+            ));
+            stops.insert(id, stop);
+        }
+        // Only LV95 coordinates, close to stop 3.
+        let mut stop = Stop::new(4, "4".to_string(), None, None, None);
+        stop.set_lv95_coordinates(Coordinates::new(
+            CoordinateSystem::LV95,
+            2_606_000.0,
+            1_208_500.0,
+        ));
+        stops.insert(4, stop);
+        // No coordinates.
+        stops.insert(5, Stop::new(5, "5".to_string(), None, None, None));
+        let data_storage = DataStorage {
+            stops: ResourceStorage::new(stops),
+            ..Default::default()
+        };
+
+        let coordinates = Coordinates::new(CoordinateSystem::WGS84, 47.03, 7.52);
+        let nearest = data_storage.nearest_stops(coordinates, 3);
+        let ids: Vec<_> = nearest.iter().map(|(stop, _)| stop.id()).collect();
+        assert_eq!(ids, vec![3, 6, 4]);
+        assert_eq!(nearest[0].1, 0.0);
+        assert!(nearest[2].1 > 0.0);
+
+        let ids: Vec<_> = data_storage
+            .nearest_stops(coordinates, 1)
+            .iter()
+            .map(|(stop, _)| stop.id())
+            .collect();
+        assert_eq!(ids, vec![3]);
+        assert!(data_storage.nearest_stops(coordinates, 0).is_empty());
+        assert_eq!(data_storage.nearest_stops(coordinates, 10).len(), 5);
+    }
+
+    #[test]
+    fn journey_polyline() {
+        let mut stops = FxHashMap::default();
+        for (id, latitude, longitude) in
+            [(1, 38.5, -120.2), (2, 40.7, -120.95), (3, 43.252, -126.453)]
+        {
+            let mut stop = Stop::new(id, id.to_string(), None, None, None);
+            stop.set_wgs84_coordinates(Coordinates::new(
+                CoordinateSystem::WGS84,
+                latitude,
+                longitude,
+            ));
+            stops.insert(id, stop);
+        }
+        let data_storage = DataStorage {
+            stops: ResourceStorage::new(stops),
+            ..Default::default()
+        };
+
+        let mut journey = Journey::new(1, 100, "CH".to_string());
+        for stop_id in [1, 2, 9, 3] {
+            journey.add_route_entry(JourneyRouteEntry::new(stop_id, None, None));
+        }
+
+        // The unknown stop 9 is skipped.
+        let polyline = journey.polyline(&data_storage);
+        assert_eq!(polyline.len(), 3);
+        assert_eq!(polyline[1].latitude(), Some(40.7));
+        // Example of the documentation of Google.
+        assert_eq!(
+            journey.encoded_polyline(&data_storage),
+            "_p~iF~ps|U_ulLnnqC_mqNvxq`@"
+        );
+    }
+
+    #[test]
+    fn platform_best_coordinates_fall_back_to_the_stop() {
+        let wgs84 = Coordinates::new(CoordinateSystem::WGS84, 46.948, 7.439);
+        let lv95 = Coordinates::new(CoordinateSystem::LV95, 2_600_000.0, 1_200_000.0);
+
+        let mut stops = FxHashMap::default();
+        let mut stop = Stop::new(1, "A".to_string(), None, None, None);
+        stop.set_wgs84_coordinates(wgs84);
+        stop.set_lv95_coordinates(lv95);
+        stops.insert(1, stop);
+        let mut stop = Stop::new(2, "B".to_string(), None, None, None);
+        stop.set_lv95_coordinates(lv95);
+        stops.insert(2, stop);
+        let data_storage = DataStorage {
+            stops: ResourceStorage::new(stops),
+            ..Default::default()
+        };
+
+        let mut platform = Platform::new(1, "1".to_string(), Vec::new(), 1);
+        platform.set_lv95_coordinates(lv95);
+        assert_eq!(
+            platform.best_coordinates(&data_storage).unwrap().latitude(),
+            Some(46.948)
+        );
+        platform.set_wgs84_coordinates(Coordinates::new(CoordinateSystem::WGS84, 46.9, 7.4));
+        assert_eq!(
+            platform.best_coordinates(&data_storage).unwrap().latitude(),
+            Some(46.9)
+        );
+
+        let platform = Platform::new(2, "2".to_string(), Vec::new(), 2);
+        let coordinates = platform.best_coordinates(&data_storage).unwrap();
+        assert_eq!(coordinates.coordinate_system(), CoordinateSystem::WGS84);
+        assert!((coordinates.latitude().unwrap() - 46.95108).abs() < 1e-5);
+
+        let platform = Platform::new(3, "3".to_string(), Vec::new(), 3);
+        assert!(platform.best_coordinates(&data_storage).is_none());
+    }
+
+    #[test]
+    fn line_geometry_with_a_branch() {
+        let mut journeys = FxHashMap::default();
+        for (id, stop_ids) in [
+            (1, vec![1, 2, 3]),
+            (2, vec![3, 2, 1]),
+            (3, vec![1, 2, 3, 4, 5]),
+            (4, vec![1, 2, 6, 7]),
+            (5, vec![6, 2]),
+        ] {
+            let mut journey = Journey::new(id, id, "CH".to_string());
+            journey.add_metadata_entry(
+                JourneyMetadataType::Line,
+                JourneyMetadataEntry::new(None, None, Some(10), None, None, None, None, None),
+            );
+            for stop_id in stop_ids {
+                journey.add_route_entry(JourneyRouteEntry::new(stop_id, None, None));
+            }
+            journeys.insert(id, journey);
+        }
+        // Without line.
+        let mut journey = Journey::new(6, 6, "CH".to_string());
+        journey.add_route_entry(JourneyRouteEntry::new(8, None, None));
+        journey.add_route_entry(JourneyRouteEntry::new(9, None, None));
+        journeys.insert(6, journey);
+        let data_storage = DataStorage {
+            journeys: ResourceStorage::new(journeys),
+            ..Default::default()
+        };
+
+        let line_geometries = data_storage.line_geometries();
+        assert_eq!(line_geometries.len(), 1);
+        assert_eq!(line_geometries[0].line_id(), 10);
+        assert_eq!(line_geometries[0].main_variant(), &[1, 2, 3, 4, 5]);
+        assert_eq!(line_geometries[0].branches(), &[vec![2, 6, 7]]);
+    }
+
+    #[test]
+    fn segment_statistics_of_consecutive_stops() {
+        let mut stops = FxHashMap::default();
+        for id in 1..=3 {
+            stops.insert(id, Stop::new(id, id.to_string(), None, None, None));
+        }
+        let mut journeys = FxHashMap::default();
+        for (id, times) in [
+            (1, [(0, 10), (15, 16), (20, 20)]),
+            (2, [(0, 60), (64, 65), (70, 70)]),
+            (3, [(0, 120), (128, 129), (135, 135)]),
+        ] {
+            let mut journey = Journey::new(id, id, "CH".to_string());
+            for (stop_id, (arrival, departure)) in (1..=3).zip(times) {
+                journey.add_route_entry(JourneyRouteEntry::new(
+                    stop_id,
+                    Some(ServiceTime::new(arrival)),
+                    Some(ServiceTime::new(departure)),
+                ));
+            }
+            journeys.insert(id, journey);
+        }
+        // Unknown stop.
+        let mut journey = Journey::new(4, 4, "CH".to_string());
+        journey.add_route_entry(JourneyRouteEntry::new(3, None, Some(ServiceTime::new(0))));
+        journey.add_route_entry(JourneyRouteEntry::new(9, Some(ServiceTime::new(5)), None));
+        journeys.insert(4, journey);
+        let data_storage = DataStorage {
+            stops: ResourceStorage::new(stops),
+            journeys: ResourceStorage::new(journeys),
+            ..Default::default()
+        };
+
+        let segment_statistics = data_storage.segment_statistics();
+        assert_eq!(segment_statistics.len(), 2);
+        assert_eq!(
+            (
+                segment_statistics[0].from_stop_id(),
+                segment_statistics[0].to_stop_id()
+            ),
+            (1, 2)
+        );
+        assert_eq!(segment_statistics[0].service_count(), 3);
+        assert_eq!(segment_statistics[0].min_travel_time(), 4);
+        assert_eq!(segment_statistics[0].median_travel_time(), 5.0);
+        assert_eq!(segment_statistics[0].mean_travel_time(), 17.0 / 3.0);
+        assert_eq!(segment_statistics[1].min_travel_time(), 4);
+        assert_eq!(segment_statistics[1].median_travel_time(), 5.0);
+    }
+
+    #[test]
+    fn stop_clusters_group_meta_stops_and_sloids() {
+        let mut stops = FxHashMap::default();
+        for (id, sloid, latitude) in [
+            (1, "ch:1:sloid:10", 47.0),
+            (2, "", 47.2),
+            (3, "ch:1:sloid:10:1:1", 47.4),
+            (4, "ch:1:sloid:40", 46.0),
+        ] {
+            let mut stop = Stop::new(id, id.to_string(), None, None, None);
+            stop.set_sloid(sloid.to_string());
+            stop.set_wgs84_coordinates(Coordinates::new(CoordinateSystem::WGS84, latitude, 7.0));
+            stops.insert(id, stop);
+        }
+        let mut stop_connections = FxHashMap::default();
+        stop_connections.insert(1, StopConnection::new(1, 2, 1, 5));
+        // Unknown stop.
+        stop_connections.insert(2, StopConnection::new(2, 4, 99, 5));
+        let data_storage = DataStorage {
+            stops: ResourceStorage::new(stops),
+            stop_connections: ResourceStorage::new(stop_connections),
+            ..Default::default()
+        };
+
+        let clusters = data_storage.stop_clusters();
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].stop_ids(), &[1, 2, 3]);
+        let centroid = clusters[0].centroid().unwrap();
+        assert!((centroid.latitude().unwrap() - 47.2).abs() < 1e-9);
+        assert_eq!(centroid.longitude(), Some(7.0));
+        assert_eq!(clusters[1].stop_ids(), &[4]);
+    }
+
+    #[test]
+    fn bounding_box_and_centroid_of_the_stops() {
+        let data_storage = DataStorage::default();
+        assert!(data_storage.bounding_box().is_none());
+        assert!(data_storage.centroid().is_none());
+
+        let mut stops = FxHashMap::default();
+        for (id, latitude, longitude) in [(1, 46.0, 7.0), (2, 47.0, 6.0), (3, 46.5, 8.0)] {
+            let mut stop = Stop::new(id, id.to_string(), None, None, None);
+            stop.set_wgs84_coordinates(Coordinates::new(
+                CoordinateSystem::WGS84,
+                latitude,
+                longitude,
+            ));
+            stops.insert(id, stop);
+        }
+        stops.insert(4, Stop::new(4, "4".to_string(), None, None, None));
+        let data_storage = DataStorage {
+            stops: ResourceStorage::new(stops),
+            ..Default::default()
+        };
+
+        let bounding_box = data_storage.bounding_box().unwrap();
+        assert_eq!(bounding_box.south_west().latitude(), Some(46.0));
+        assert_eq!(bounding_box.south_west().longitude(), Some(6.0));
+        assert_eq!(bounding_box.north_east().latitude(), Some(47.0));
+        assert_eq!(bounding_box.north_east().longitude(), Some(8.0));
+
+        let centroid = data_storage.centroid().unwrap();
+        assert!((centroid.latitude().unwrap() - 46.5).abs() < 1e-9);
+        assert!((centroid.longitude().unwrap() - 7.0).abs() < 1e-9);
+
+        let stop_ids = |stops: Vec<&Stop>| stops.iter().map(|stop| stop.id()).collect::<Vec<_>>();
+        assert_eq!(
+            stop_ids(data_storage.stops_in_bounding_box(bounding_box)),
+            vec![1, 2, 3]
+        );
+        let south = BoundingBox::new(
+            Coordinates::new(CoordinateSystem::WGS84, 45.9, 6.5),
+            Coordinates::new(CoordinateSystem::WGS84, 46.6, 8.5),
+        );
+        assert_eq!(
+            stop_ids(data_storage.stops_in_bounding_box(south)),
+            vec![1, 3]
+        );
+    }
+
+    #[cfg(feature = "geo")]
+    #[test]
+    fn geo_rect_as_bounding_box() {
+        let mut stops = FxHashMap::default();
+        let mut stop = Stop::new(1, "Bern".to_string(), None, None, None);
+        stop.set_lv95_coordinates(Coordinates::new(
+            CoordinateSystem::LV95,
+            2_600_000.0,
+            1_200_000.0,
+        ));
+        stops.insert(1, stop);
+        let data_storage = DataStorage {
+            stops: ResourceStorage::new(stops),
+            ..Default::default()
+        };
+
+        let rect = geo::Rect::new(
+            geo::coord! { x: 7.0, y: 46.5 },
+            geo::coord! { x: 8.0, y: 47.5 },
+        );
+        assert_eq!(data_storage.stops_in_bounding_box(rect).len(), 1);
+
+        let point = geo::Point::from(
+            data_storage
+                .stops()
+                .find(1)
+                .unwrap()
+                .lv95_coordinates()
+                .unwrap(),
+        );
+        assert!((point.x() - 7.4386).abs() < 1e-3);
+        assert!((point.y() - 46.9511).abs() < 1e-3);
+    }
+
+    #[test]
+    fn placeholder_stops_are_created_for_unknown_stops() {
+        let mut stops = FxHashMap::default();
+        stops.insert(10, Stop::new(10, "Bern".to_string(), None, None, None));
+        let mut stops = ResourceStorage::new(stops);
+        let stop_names = FxHashMap::from_iter([
+            (10, "Bern".to_string()),
+            (8000105, "Frankfurt(Main)Hbf".to_string()),
+        ]);
+        let mut parse_report = ParseReport::new();
+
+        add_placeholder_stops(&mut stops, stop_names, &mut parse_report);
+
+        assert_eq!(stops.data().len(), 2);
+        assert_eq!(stops.find(8000105).unwrap().name(), "Frankfurt(Main)Hbf");
+        assert_eq!(
+            parse_report.issues(),
+            &vec![ParseIssue::PlaceholderStop {
+                stop_id: 8000105,
+                name: "Frankfurt(Main)Hbf".to_string(),
+            }]
+        );
+    }
+
     #[test]
     fn through_service_map_keys_by_journeys_and_stop() {
         let mut data = FxHashMap::default();
@@ -0,0 +1,488 @@
+/// # Synthetic dataset
+///
+/// Generates a tiny, fully consistent HRDF dataset on disk, to be used as a fixture in tests which
+/// can't depend on the multi-GB exports of opentransportdata.swiss.
+///
+/// The dataset contains:
+///
+/// - 5 stops, Aarstadt having 2 platforms
+/// - 2 lines: the S1 (Aarstadt - Seeburg) and the bus 12 (Aarstadt - Talwil)
+/// - 3 bit fields: every day, Monday to Friday, Saturday and Sunday
+/// - 6 journeys, one of them running past midnight
+///
+/// All references (stops, bit fields, lines, directions, transport types, attributes, platforms)
+/// resolve, so DataStorage::validate returns an empty report for it.
+use std::{fs, path::Path};
+
+use chrono::{Datelike, NaiveDate, Weekday};
+
+use crate::{error::HResult, models::Version, utils::bit_index_of_date};
+
+/// Version of the HRDF format written by generate_sample_dataset.
+pub const SAMPLE_DATASET_VERSION: Version = Version::V_5_40_41_2_0_7;
+
+const ADMINISTRATION: &str = "000001";
+const NUM_BITS: usize = 96 * 4;
+
+struct SampleStop {
+    id: i32,
+    name: &'static str,
+    lv95: (f64, f64),
+    wgs84: (f64, f64),
+}
+
+#[rustfmt::skip]
+const STOPS: [SampleStop; 5] = [
+    SampleStop { id: 8500101, name: "Aarstadt", lv95: (2600028.0, 1199657.0), wgs84: (7.439, 46.948) },
+    SampleStop { id: 8500102, name: "Aarstadt Nord", lv95: (2600485.0, 1200991.0), wgs84: (7.445, 46.96) },
+    SampleStop { id: 8500103, name: "Bergdorf", lv95: (2603147.0, 1204327.0), wgs84: (7.48, 46.99) },
+    SampleStop { id: 8500104, name: "Seeburg", lv95: (2606185.0, 1208777.0), wgs84: (7.52, 47.03) },
+    SampleStop { id: 8500105, name: "Talwil", lv95: (2597060.0, 1202104.0), wgs84: (7.4, 46.97) },
+];
+
+struct SampleJourney {
+    number: i32,
+    transport_type: &'static str,
+    line_id: i32,
+    direction_id: i32,
+    bit_field_id: i32,
+    // (stop_id, arrival, departure), the times are written as HHMM.
+    route: &'static [(i32, Option<i32>, Option<i32>)],
+    // (stop_id, platform index) at which the journey departs.
+    platform: Option<(i32, i32)>,
+}
+
+const JOURNEYS: [SampleJourney; 6] = [
+    SampleJourney {
+        number: 1,
+        transport_type: "S",
+        line_id: 1,
+        direction_id: 1,
+        bit_field_id: 1,
+        route: &[
+            (8500101, None, Some(600)),
+            (8500102, Some(603), Some(604)),
+            (8500103, Some(610), Some(611)),
+            (8500104, Some(620), None),
+        ],
+        platform: Some((8500101, 1)),
+    },
+    SampleJourney {
+        number: 3,
+        transport_type: "S",
+        line_id: 1,
+        direction_id: 1,
+        bit_field_id: 1,
+        route: &[
+            (8500101, None, Some(700)),
+            (8500102, Some(703), Some(704)),
+            (8500103, Some(710), Some(711)),
+            (8500104, Some(720), None),
+        ],
+        platform: Some((8500101, 1)),
+    },
+    SampleJourney {
+        number: 2,
+        transport_type: "S",
+        line_id: 1,
+        direction_id: 2,
+        bit_field_id: 1,
+        route: &[
+            (8500104, None, Some(640)),
+            (8500103, Some(649), Some(650)),
+            (8500102, Some(656), Some(657)),
+            (8500101, Some(700), None),
+        ],
+        platform: None,
+    },
+    SampleJourney {
+        number: 5,
+        transport_type: "S",
+        line_id: 1,
+        direction_id: 1,
+        bit_field_id: 1,
+        route: &[
+            (8500101, None, Some(2350)),
+            (8500102, Some(2353), Some(2354)),
+            (8500103, Some(2400), Some(2401)),
+            (8500104, Some(2410), None),
+        ],
+        platform: Some((8500101, 2)),
+    },
+    SampleJourney {
+        number: 101,
+        transport_type: "B",
+        line_id: 2,
+        direction_id: 3,
+        bit_field_id: 2,
+        route: &[(8500101, None, Some(715)), (8500105, Some(727), None)],
+        platform: None,
+    },
+    SampleJourney {
+        number: 103,
+        transport_type: "B",
+        line_id: 2,
+        direction_id: 3,
+        bit_field_id: 3,
+        route: &[(8500101, None, Some(915)), (8500105, Some(927), None)],
+        platform: None,
+    },
+];
+
+/// Writes the synthetic dataset into the directory (which is created if needed).
+/// The files are in the format of SAMPLE_DATASET_VERSION and can be loaded with DataStorage::new.
+pub fn generate_sample_dataset(path: &Path) -> HResult<()> {
+    // unwrap: Both dates are valid.
+    let start_date = NaiveDate::from_ymd_opt(2025, 12, 14).unwrap();
+    let end_date = NaiveDate::from_ymd_opt(2026, 12, 12).unwrap();
+
+    fs::create_dir_all(path)?;
+    let files = [
+        // Time-relevant data
+        ("ECKDATEN", eckdaten(start_date, end_date)),
+        ("BITFELD", bitfeld(start_date, end_date)),
+        (
+            "FEIERTAG",
+            vec![
+                "01.08.2026 Bundesfeier<deu>Fête nationale<fra>Festa nazionale<ita>National Day<eng>"
+                    .to_string(),
+            ],
+        ),
+        // Basic data
+        ("ATTRIBUT", attribut()),
+        ("BETRIEB_DE", betrieb()),
+        ("BETRIEB_EN", betrieb()),
+        ("BETRIEB_FR", betrieb()),
+        ("BETRIEB_IT", betrieb()),
+        ("INFOTEXT_DE", Vec::new()),
+        ("INFOTEXT_EN", Vec::new()),
+        ("INFOTEXT_FR", Vec::new()),
+        ("INFOTEXT_IT", Vec::new()),
+        ("LINIE", linie()),
+        ("RICHTUNG", richtung()),
+        ("ZUGART", zugart()),
+        // Stop data
+        ("BAHNHOF", bahnhof()),
+        ("BFKOORD_LV95", bfkoord(|stop| stop.lv95)),
+        ("BFKOORD_WGS", bfkoord(|stop| stop.wgs84)),
+        ("BFPRIOS", bfprios()),
+        ("BHFART", bhfart()),
+        ("KMINFO", kminfo()),
+        ("METABHF", Vec::new()),
+        ("UMSTEIGB", umsteigb()),
+        // Timetable data
+        ("DURCHBI", Vec::new()),
+        ("FPLAN", fplan()),
+        ("GLEISE_LV95", gleise(|stop| stop.lv95)),
+        ("GLEISE_WGS", gleise(|stop| stop.wgs84)),
+        // Exchange times
+        ("UMSTEIGL", Vec::new()),
+        ("UMSTEIGV", Vec::new()),
+        ("UMSTEIGZ", Vec::new()),
+    ];
+
+    for (name, lines) in files {
+        let mut content = lines.join("\n");
+        content.push('\n');
+        fs::write(path.join(name), content)?;
+    }
+    Ok(())
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Files
+// ------------------------------------------------------------------------------------------------
+
+fn eckdaten(start_date: NaiveDate, end_date: NaiveDate) -> Vec<String> {
+    vec![
+        start_date.format("%d.%m.%Y").to_string(),
+        end_date.format("%d.%m.%Y").to_string(),
+        "Synthetic timetable$01.06.2025$1.00$hrdf-parser".to_string(),
+    ]
+}
+
+fn bitfeld(start_date: NaiveDate, end_date: NaiveDate) -> Vec<String> {
+    let every_day = |_: Weekday| true;
+    let working_days = |weekday: Weekday| weekday.num_days_from_monday() < 5;
+    let weekends = |weekday: Weekday| weekday.num_days_from_monday() >= 5;
+
+    vec![
+        format!("{:06} {}", 1, bit_field(start_date, end_date, every_day)),
+        format!("{:06} {}", 2, bit_field(start_date, end_date, working_days)),
+        format!("{:06} {}", 3, bit_field(start_date, end_date, weekends)),
+    ]
+}
+
+fn attribut() -> Vec<String> {
+    [
+        "NF 0   5  5",
+        "VR 0   5  5",
+        "# NF NF NF",
+        "# VR VR VR",
+        "<text>",
+        "<deu>",
+        "NF Niederflureinstieg",
+        "VR Velos: Reservierung obligatorisch",
+        "<fra>",
+        "NF Accès au plancher surbaissé",
+        "VR Vélos: réservation obligatoire",
+        "<ita>",
+        "NF Accesso a pianale ribassato",
+        "VR Bici: prenotazione obbligatoria",
+        "<eng>",
+        "NF Low-floor access",
+        "VR Bicycles: reservation compulsory",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+fn betrieb() -> Vec<String> {
+    vec![
+        r#"00001 K "STB" L "STB" V "Synthetic Transport Company""#.to_string(),
+        format!("00001 : {ADMINISTRATION}"),
+    ]
+}
+
+fn linie() -> Vec<String> {
+    [
+        "0000001 K 1",
+        "0000001 N T S1",
+        "0000001 F 255 255 255",
+        "0000001 B 000 102 204",
+        "0000002 K 12",
+        "0000002 N T 12",
+        "0000002 F 000 000 000",
+        "0000002 B 255 204 000",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+fn richtung() -> Vec<String> {
+    ["R000001 Seeburg", "R000002 Aarstadt", "R000003 Talwil"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+fn zugart() -> Vec<String> {
+    [
+        "S   5 A 0 S        0 N",
+        "B   6 A 0 B        0 N",
+        "<text>",
+        "<Deutsch>",
+        "class05 S-Bahn",
+        "class06 Bus",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+fn bahnhof() -> Vec<String> {
+    STOPS
+        .iter()
+        .map(|stop| format!("{:07}     {}$<1>", stop.id, stop.name))
+        .collect()
+}
+
+fn bfkoord(coordinates: impl Fn(&SampleStop) -> (f64, f64)) -> Vec<String> {
+    STOPS
+        .iter()
+        .map(|stop| {
+            let (x, y) = coordinates(stop);
+            format!("{:07}    {x}    {y}    0", stop.id)
+        })
+        .collect()
+}
+
+fn bfprios() -> Vec<String> {
+    STOPS
+        .iter()
+        .map(|stop| format!("{:07} {:>2} {}", stop.id, 16, stop.name))
+        .collect()
+}
+
+fn bhfart() -> Vec<String> {
+    let mut lines = vec!["% Globale IDs".to_string()];
+    lines.extend(
+        STOPS
+            .iter()
+            .map(|stop| format!("{:07} G A {}", stop.id, sloid(stop.id))),
+    );
+    lines
+}
+
+fn kminfo() -> Vec<String> {
+    STOPS
+        .iter()
+        .map(|stop| format!("{:07} {:>5} {}", stop.id, 30000, stop.name))
+        .collect()
+}
+
+fn umsteigb() -> Vec<String> {
+    let mut lines = vec!["9999999 02 02 STANDARD".to_string()];
+    lines.extend(
+        STOPS
+            .iter()
+            .filter(|stop| stop.id == 8500101)
+            .map(|stop| format!("{:07} 03 03 {}", stop.id, stop.name)),
+    );
+    lines
+}
+
+fn fplan() -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for journey in &JOURNEYS {
+        let first_stop_id = journey.route[0].0;
+        let last_stop_id = journey.route[journey.route.len() - 1].0;
+
+        lines.push(format!(
+            "*Z {:06} {ADMINISTRATION}   101         %",
+            journey.number
+        ));
+        lines.push(format!(
+            "*G {:<3} {first_stop_id:07} {last_stop_id:07} %",
+            journey.transport_type
+        ));
+        lines.push(format!(
+            "*A VE {first_stop_id:07} {last_stop_id:07} {:06} %",
+            journey.bit_field_id
+        ));
+        if journey.transport_type == "B" {
+            lines.push(format!(
+                "*A NF {first_stop_id:07} {last_stop_id:07}        %"
+            ));
+        }
+        lines.push(format!(
+            "*L #{:07} {first_stop_id:07} {last_stop_id:07}               %",
+            journey.line_id
+        ));
+        lines.push(format!(
+            "*R H R{:06} {first_stop_id:07} {last_stop_id:07}               %",
+            journey.direction_id
+        ));
+
+        for &(stop_id, arrival, departure) in journey.route {
+            let name = STOPS
+                .iter()
+                .find(|stop| stop.id == stop_id)
+                .map_or("", |stop| stop.name);
+            lines.push(format!(
+                "{stop_id:07} {name:<20} {:>6} {:>6} {:06} {ADMINISTRATION} %",
+                format_time(arrival),
+                format_time(departure),
+                journey.number,
+            ));
+        }
+    }
+    lines
+}
+
+fn gleise(coordinates: impl Fn(&SampleStop) -> (f64, f64)) -> Vec<String> {
+    let mut lines: Vec<_> = JOURNEYS
+        .iter()
+        .filter_map(|journey| {
+            let (stop_id, index) = journey.platform?;
+            Some(format!(
+                "{stop_id:07} {:06} {ADMINISTRATION} #{index:07}      {:06}",
+                journey.number, journey.bit_field_id
+            ))
+        })
+        .collect();
+
+    let stop = &STOPS[0];
+    let (x, y) = coordinates(stop);
+    for index in 1..=2 {
+        lines.push(format!("{:07} #{index:07} G '{index}'", stop.id));
+        lines.push(format!(
+            "{:07} #{index:07} g A {}:{index}:{index}",
+            stop.id,
+            sloid(stop.id)
+        ));
+        lines.push(format!("{:07} #{index:07} k {x} {y} 540", stop.id));
+    }
+    lines
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Helper Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Encodes the days of the timetable matching the predicate as a BITFELD hexadecimal number.
+fn bit_field(
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    predicate: impl Fn(Weekday) -> bool,
+) -> String {
+    let mut bits = [0u8; NUM_BITS];
+
+    for date in start_date.iter_days().take_while(|date| *date <= end_date) {
+        let bit = bit_index_of_date(date, start_date).and_then(|index| bits.get_mut(index));
+        if let (Some(bit), true) = (bit, predicate(date.weekday())) {
+            *bit = 1;
+        }
+    }
+
+    bits.chunks(4)
+        .map(|chunk| {
+            let value = chunk
+                .iter()
+                .fold(0, |acc, bit| (acc << 1) | u32::from(*bit));
+            // unwrap: A chunk of 4 bits is always a valid hexadecimal digit.
+            char::from_digit(value, 16).unwrap().to_ascii_uppercase()
+        })
+        .collect()
+}
+
+fn format_time(time: Option<i32>) -> String {
+    time.map(|time| format!("{time:05}")).unwrap_or_default()
+}
+
+fn sloid(stop_id: i32) -> String {
+    format!("ch:1:sloid:{}", stop_id % 100_000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::DataStorage;
+    use chrono::Days;
+    use pretty_assertions::assert_eq;
+    use std::env;
+
+    #[test]
+    fn sample_dataset_is_consistent() {
+        let path = env::temp_dir().join("hrdf-parser-sample-dataset");
+        generate_sample_dataset(&path).unwrap();
+
+        let data_storage = DataStorage::new(SAMPLE_DATASET_VERSION, &path).unwrap();
+        assert_eq!(data_storage.stops().data().len(), 5);
+        assert_eq!(data_storage.lines().data().len(), 2);
+        assert_eq!(data_storage.bit_fields().data().len(), 3);
+        assert_eq!(data_storage.journeys().data().len(), 6);
+        assert_eq!(data_storage.platforms().data().len(), 2);
+        assert_eq!(data_storage.journey_platform().data().len(), 3);
+        assert!(data_storage.parse_report().is_empty());
+        assert!(data_storage.validate().is_empty());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn bit_field_of_working_days() {
+        // 14.12.2025 is a Sunday.
+        let start_date = NaiveDate::from_ymd_opt(2025, 12, 14).unwrap();
+        let end_date = start_date.checked_add_days(Days::new(7)).unwrap();
+        let working_days = |weekday: Weekday| weekday.num_days_from_monday() < 5;
+
+        let hex = bit_field(start_date, end_date, working_days);
+        // Padding (2 bits), Sunday, Monday to Friday, Saturday, Sunday.
+        assert_eq!(&hex[..4], "1F00");
+        assert_eq!(hex.len(), 96);
+    }
+}
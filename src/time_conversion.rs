@@ -0,0 +1,126 @@
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+// ------------------------------------------------------------------------------------------------
+// --- ToChrono
+// ------------------------------------------------------------------------------------------------
+
+/// Converts the time crate types to the chrono types taken by the API
+/// (e.g. data_storage.departures(stop_id, from.to_chrono(), until.to_chrono())).
+pub trait ToChrono {
+    type Output;
+
+    fn to_chrono(&self) -> Self::Output;
+}
+
+impl ToChrono for time::Date {
+    type Output = NaiveDate;
+
+    fn to_chrono(&self) -> NaiveDate {
+        // unwrap: The range of chrono covers the range of time.
+        NaiveDate::from_ymd_opt(
+            self.year(),
+            u8::from(self.month()).into(),
+            self.day().into(),
+        )
+        .unwrap()
+    }
+}
+
+impl ToChrono for time::Time {
+    type Output = NaiveTime;
+
+    fn to_chrono(&self) -> NaiveTime {
+        // unwrap: The components of a time::Time are valid.
+        NaiveTime::from_hms_nano_opt(
+            self.hour().into(),
+            self.minute().into(),
+            self.second().into(),
+            self.nanosecond(),
+        )
+        .unwrap()
+    }
+}
+
+impl ToChrono for time::PrimitiveDateTime {
+    type Output = NaiveDateTime;
+
+    fn to_chrono(&self) -> NaiveDateTime {
+        self.date().to_chrono().and_time(self.time().to_chrono())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- ToTime
+// ------------------------------------------------------------------------------------------------
+
+/// Converts the chrono types returned by the API to the time crate types. The dates outside of
+/// the range of the time crate (years -9999 to 9999) give None.
+pub trait ToTime {
+    type Output;
+
+    fn to_time(&self) -> Option<Self::Output>;
+}
+
+impl ToTime for NaiveDate {
+    type Output = time::Date;
+
+    fn to_time(&self) -> Option<time::Date> {
+        // unwrap: The month of a NaiveDate is between 1 and 12.
+        let month = time::Month::try_from(u8::try_from(self.month()).unwrap()).unwrap();
+        // unwrap: The day of a NaiveDate is between 1 and 31.
+        time::Date::from_calendar_date(self.year(), month, u8::try_from(self.day()).unwrap()).ok()
+    }
+}
+
+impl ToTime for NaiveTime {
+    type Output = time::Time;
+
+    fn to_time(&self) -> Option<time::Time> {
+        // A leap second is represented by chrono with more than 10^9 nanoseconds.
+        let nanosecond = self.nanosecond().min(999_999_999);
+        // unwrap: The components of a NaiveTime fit into a u8.
+        time::Time::from_hms_nano(
+            u8::try_from(self.hour()).unwrap(),
+            u8::try_from(self.minute()).unwrap(),
+            u8::try_from(self.second()).unwrap(),
+            nanosecond,
+        )
+        .ok()
+    }
+}
+
+impl ToTime for NaiveDateTime {
+    type Output = time::PrimitiveDateTime;
+
+    fn to_time(&self) -> Option<time::PrimitiveDateTime> {
+        Some(time::PrimitiveDateTime::new(
+            self.date().to_time()?,
+            self.time().to_time()?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn round_trip_between_chrono_and_time() {
+        let datetime = NaiveDate::from_ymd_opt(2025, 12, 15)
+            .unwrap()
+            .and_hms_opt(23, 50, 30)
+            .unwrap();
+
+        let converted = datetime.to_time().unwrap();
+        assert_eq!(
+            converted,
+            time::PrimitiveDateTime::new(
+                time::Date::from_calendar_date(2025, time::Month::December, 15).unwrap(),
+                time::Time::from_hms(23, 50, 30).unwrap(),
+            )
+        );
+        assert_eq!(converted.to_chrono(), datetime);
+        assert_eq!(NaiveDate::MAX.to_time(), None);
+    }
+}
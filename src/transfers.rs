@@ -0,0 +1,407 @@
+use std::collections::BTreeMap;
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use rustc_hash::FxHashSet;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::HResult,
+    models::{Journey, Model},
+    storage::DataStorage,
+};
+
+/// Longest wait considered as a transfer, in minutes.
+const MAX_TRANSFER_TIME: i64 = 60;
+/// Product class of the journeys without transport type, which are not InterCity journeys.
+pub(crate) const UNKNOWN_PRODUCT_CLASS: i16 = -1;
+
+// ------------------------------------------------------------------------------------------------
+// --- Transfer
+// ------------------------------------------------------------------------------------------------
+
+/// Change from an arriving journey to a departing journey at a stop.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Transfer {
+    arriving_journey_id: i32,
+    departing_journey_id: i32,
+    arrival_at: NaiveDateTime,
+    departure_at: NaiveDateTime,
+    exchange_time: i16,
+    is_guaranteed: bool,
+}
+
+impl Transfer {
+    pub fn new(
+        arriving_journey_id: i32,
+        departing_journey_id: i32,
+        arrival_at: NaiveDateTime,
+        departure_at: NaiveDateTime,
+        exchange_time: i16,
+    ) -> Self {
+        Self {
+            arriving_journey_id,
+            departing_journey_id,
+            arrival_at,
+            departure_at,
+            exchange_time,
+            is_guaranteed: false,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn arriving_journey_id(&self) -> i32 {
+        self.arriving_journey_id
+    }
+
+    pub fn departing_journey_id(&self) -> i32 {
+        self.departing_journey_id
+    }
+
+    pub fn arrival_at(&self) -> NaiveDateTime {
+        self.arrival_at
+    }
+
+    pub fn departure_at(&self) -> NaiveDateTime {
+        self.departure_at
+    }
+
+    /// Minimum exchange time in minutes (see DataStorage::exchange_time).
+    pub fn exchange_time(&self) -> i16 {
+        self.exchange_time
+    }
+
+    /// True if the departing journey waits for the arriving journey (UMSTEIGZ or UMSTEIGL
+    /// marked with "!").
+    pub fn is_guaranteed(&self) -> bool {
+        self.is_guaranteed
+    }
+
+    pub fn set_is_guaranteed(&mut self, value: bool) {
+        self.is_guaranteed = value;
+    }
+
+    // Functions
+
+    /// Time between the arrival and the departure, in minutes.
+    pub fn transfer_time(&self) -> i64 {
+        (self.departure_at - self.arrival_at).num_minutes()
+    }
+
+    /// Transfer time left over the exchange time, in minutes. Negative if the transfer is too short.
+    pub fn slack(&self) -> i64 {
+        self.transfer_time() - i64::from(self.exchange_time)
+    }
+
+    /// True if the transfer time covers the exchange time.
+    pub fn is_feasible(&self) -> bool {
+        self.slack() >= 0
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- TransferStatistics
+// ------------------------------------------------------------------------------------------------
+
+/// Transfers realized at a stop on a date (see DataStorage::transfer_statistics).
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TransferStatistics {
+    stop_id: i32,
+    date: NaiveDate,
+    transfers: Vec<Transfer>,
+}
+
+impl TransferStatistics {
+    pub fn new(stop_id: i32, date: NaiveDate, transfers: Vec<Transfer>) -> Self {
+        Self {
+            stop_id,
+            date,
+            transfers,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn stop_id(&self) -> i32 {
+        self.stop_id
+    }
+
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    /// Sorted by arrival, then by departure.
+    pub fn transfers(&self) -> &[Transfer] {
+        &self.transfers
+    }
+
+    // Functions
+
+    /// Transfer times in minutes, sorted.
+    pub fn transfer_times(&self) -> Vec<i64> {
+        let mut transfer_times: Vec<_> = self
+            .transfers
+            .iter()
+            .map(|transfer| transfer.transfer_time())
+            .collect();
+        transfer_times.sort_unstable();
+        transfer_times
+    }
+
+    pub fn min_transfer_time(&self) -> Option<i64> {
+        self.transfer_times().first().copied()
+    }
+
+    pub fn median_transfer_time(&self) -> Option<f64> {
+        let transfer_times = self.transfer_times();
+        let middle = transfer_times.len() / 2;
+        match transfer_times.len() {
+            0 => None,
+            len if len % 2 == 0 => {
+                Some((transfer_times[middle - 1] + transfer_times[middle]) as f64 / 2.0)
+            }
+            _ => Some(transfer_times[middle] as f64),
+        }
+    }
+
+    /// Number of transfers for each transfer time in minutes.
+    pub fn histogram(&self) -> BTreeMap<i64, usize> {
+        self.transfers
+            .iter()
+            .fold(BTreeMap::new(), |mut histogram, transfer| {
+                *histogram.entry(transfer.transfer_time()).or_default() += 1;
+                histogram
+            })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Serves DataStorage::exchange_time.
+pub(crate) fn exchange_time(
+    data_storage: &DataStorage,
+    stop_id: i32,
+    journey_1: &Journey,
+    journey_2: &Journey,
+    service_day: NaiveDate,
+) -> i16 {
+    exchange_rule(data_storage, stop_id, journey_1, journey_2, service_day).0
+}
+
+/// The exchange time in minutes and whether the transfer is guaranteed.
+fn exchange_rule(
+    data_storage: &DataStorage,
+    stop_id: i32,
+    journey_1: &Journey,
+    journey_2: &Journey,
+    service_day: NaiveDate,
+) -> (i16, bool) {
+    let journey_id_1 = (
+        journey_1.legacy_id(),
+        journey_1.administration().to_string(),
+    );
+    let journey_id_2 = (
+        journey_2.legacy_id(),
+        journey_2.administration().to_string(),
+    );
+
+    // UMSTEIGZ
+    let exchange_time = data_storage
+        .exchange_times_journey_map()
+        .get(&(stop_id, journey_id_1, journey_id_2))
+        .into_iter()
+        .flatten()
+        .filter_map(|&id| data_storage.exchange_times_journey().find(id))
+        .find(|exchange_time| {
+            data_storage.is_bit_field_active(exchange_time.bit_field_id(), service_day)
+        });
+    if let Some(exchange_time) = exchange_time {
+        return (exchange_time.duration(), exchange_time.is_guaranteed());
+    }
+
+    // UMSTEIGL, the exchange times specific to the stop first.
+    let exchange_time = data_storage
+        .line_exchange_times_at(stop_id)
+        .into_iter()
+        .find(|exchange_time| {
+            exchange_time.applies_to(stop_id, journey_1, journey_2, data_storage)
+        });
+    if let Some(exchange_time) = exchange_time {
+        return (exchange_time.duration(), exchange_time.is_guaranteed());
+    }
+
+    // UMSTEIGV, the exchange times specific to the stop first.
+    for key_stop_id in [Some(stop_id), None] {
+        let exchange_time = data_storage
+            .exchange_times_administration_map()
+            .get(&(
+                key_stop_id,
+                journey_1.administration().to_string(),
+                journey_2.administration().to_string(),
+            ))
+            .and_then(|&id| data_storage.exchange_times_administration().find(id));
+        if let Some(exchange_time) = exchange_time {
+            return (exchange_time.duration(), false);
+        }
+    }
+
+    // UMSTEIGB. The InterCity exchange time only applies between two InterCity journeys,
+    // so the product class of a journey which is not an InterCity is used if there is one.
+    let transport_types =
+        [journey_1, journey_2].map(|journey| journey.transport_type(data_storage).ok());
+    let product_class_id = transport_types
+        .iter()
+        .find(|transport_type| {
+            !transport_type.is_some_and(|transport_type| transport_type.is_inter_city())
+        })
+        .unwrap_or(&transport_types[1])
+        .map_or(UNKNOWN_PRODUCT_CLASS, |transport_type| {
+            transport_type.product_class_id()
+        });
+    let exchange_time = data_storage.stops().find(stop_id).map_or(
+        // Unknown stop, the default exchange time for all the other journey types applies.
+        data_storage.default_exchange_time().1,
+        |stop| stop.effective_exchange_time(product_class_id, data_storage),
+    );
+    (exchange_time, false)
+}
+
+/// Serves DataStorage::can_transfer.
+pub(crate) fn can_transfer(
+    data_storage: &DataStorage,
+    arriving_journey: &Journey,
+    departing_journey: &Journey,
+    stop_id: i32,
+    service_day: NaiveDate,
+) -> HResult<Option<Transfer>> {
+    if !arriving_journey.operates_on(service_day, data_storage)? {
+        return Ok(None);
+    }
+    let Ok(arrival_at) = arriving_journey.arrival_at_of(stop_id, service_day) else {
+        return Ok(None);
+    };
+
+    // The service day of the departing journey can differ around midnight. The first departure
+    // after the arrival is kept, otherwise the last one less than an hour before it.
+    let service_days = [
+        service_day.pred_opt(),
+        Some(service_day),
+        service_day.succ_opt(),
+    ];
+    let departure_at = service_days
+        .into_iter()
+        .flatten()
+        .filter(|&day| {
+            departing_journey
+                .operates_on(day, data_storage)
+                .unwrap_or(false)
+        })
+        .filter_map(|day| departing_journey.departure_at_of(stop_id, day).ok())
+        .filter(|&departure_at| departure_at >= arrival_at - TimeDelta::minutes(MAX_TRANSFER_TIME))
+        .min_by_key(|&departure_at| (departure_at < arrival_at, (departure_at - arrival_at).abs()));
+    let Some(departure_at) = departure_at else {
+        return Ok(None);
+    };
+
+    let (exchange_time, is_guaranteed) = exchange_rule(
+        data_storage,
+        stop_id,
+        arriving_journey,
+        departing_journey,
+        service_day,
+    );
+    let mut transfer = Transfer::new(
+        arriving_journey.id(),
+        departing_journey.id(),
+        arrival_at,
+        departure_at,
+        exchange_time,
+    );
+    transfer.set_is_guaranteed(is_guaranteed);
+    Ok(Some(transfer))
+}
+
+/// Serves DataStorage::transfer_statistics.
+pub(crate) fn transfer_statistics(
+    data_storage: &DataStorage,
+    stop_id: i32,
+    date: NaiveDate,
+) -> HResult<TransferStatistics> {
+    let start_of_day = date.and_time(NaiveTime::MIN);
+    let end_of_day = start_of_day + TimeDelta::days(1) - TimeDelta::minutes(1);
+    let arrivals = data_storage.arrivals(stop_id, start_of_day, end_of_day)?;
+    let departures = data_storage.departures(
+        stop_id,
+        start_of_day,
+        end_of_day + TimeDelta::minutes(MAX_TRANSFER_TIME),
+    )?;
+
+    let mut transfers = Vec::new();
+    for arrival in &arrivals {
+        let Some(arriving_journey) = arrival.journey(data_storage) else {
+            continue;
+        };
+        let arriving_journey_id = (
+            arriving_journey.legacy_id(),
+            arriving_journey.administration().to_string(),
+        );
+        // (line_id, destination_stop_id) of the departing journeys already reached.
+        let mut reached = FxHashSet::default();
+
+        let reachable_departures = departures.iter().filter(|departure| {
+            let transfer_time = (departure.departure_at() - arrival.arrival_at()).num_minutes();
+            (0..=MAX_TRANSFER_TIME).contains(&transfer_time)
+        });
+        for departure in reachable_departures {
+            let Some(departing_journey) = departure.journey(data_storage) else {
+                continue;
+            };
+            let departing_journey_id = (
+                departing_journey.legacy_id(),
+                departing_journey.administration().to_string(),
+            );
+            // The passengers of a through service stay seated.
+            let is_through_service = data_storage
+                .bit_field_id_for_through_service_by_journey_id_stop_id()
+                .contains_key(&(arriving_journey_id.clone(), departing_journey_id, stop_id));
+            if departing_journey.id() == arriving_journey.id() || is_through_service {
+                continue;
+            }
+
+            let route = (
+                departing_journey.line_id(),
+                departing_journey.last_stop_id().ok(),
+            );
+            if reached.contains(&route) {
+                continue;
+            }
+
+            let (exchange_time, is_guaranteed) = exchange_rule(
+                data_storage,
+                stop_id,
+                arriving_journey,
+                departing_journey,
+                arrival.service_day(),
+            );
+            let mut transfer = Transfer::new(
+                arriving_journey.id(),
+                departing_journey.id(),
+                arrival.arrival_at(),
+                departure.departure_at(),
+                exchange_time,
+            );
+            transfer.set_is_guaranteed(is_guaranteed);
+            if transfer.transfer_time() >= i64::from(exchange_time) {
+                reached.insert(route);
+                transfers.push(transfer);
+            }
+        }
+    }
+
+    Ok(TransferStatistics::new(stop_id, date, transfers))
+}
@@ -4,11 +4,11 @@
 
 use std::cell::RefCell;
 
-use chrono::{Days, NaiveDate, NaiveTime};
+use chrono::{DateTime, Datelike, Days, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
 
 use crate::{
     error::{HResult, HrdfError},
-    models::TimetableMetadataEntry,
+    models::{Coordinates, ServiceTime, TimetableMetadataEntry},
     parsing::error::{PResult, ParsingError},
     storage::ResourceStorage,
 };
@@ -34,14 +34,14 @@ impl AutoIncrement {
     }
 }
 
-pub fn add_1_day(date: NaiveDate) -> HResult<NaiveDate> {
-    date.checked_add_days(Days::new(1))
-        .ok_or(HrdfError::FailedToAddDays(date, 1))
+pub fn add_days(date: NaiveDate, days: u64) -> HResult<NaiveDate> {
+    date.checked_add_days(Days::new(days))
+        .ok_or(HrdfError::FailedToAddDays(date, days))
 }
 
-pub fn sub_1_day(date: NaiveDate) -> HResult<NaiveDate> {
-    date.checked_sub_days(Days::new(1))
-        .ok_or(HrdfError::FailedToSubDays(date, 1))
+pub fn sub_days(date: NaiveDate, days: u64) -> HResult<NaiveDate> {
+    date.checked_sub_days(Days::new(days))
+        .ok_or(HrdfError::FailedToSubDays(date, days))
 }
 
 pub fn count_days_between_two_dates(date_1: NaiveDate, date_2: NaiveDate) -> usize {
@@ -49,14 +49,91 @@ pub fn count_days_between_two_dates(date_1: NaiveDate, date_2: NaiveDate) -> usi
         + 1
 }
 
+// ------------------------------------------------------------------------------------------------
+// --- Bit fields
+// ------------------------------------------------------------------------------------------------
+
+/// The first two bits of every BITFELD entry do not correspond to a day of the timetable.
+pub const BIT_FIELD_PADDING: usize = 2;
+
+/// Returns the index of the bit corresponding to the date in BitField::bits.
+/// The first day of the timetable (ECKDATEN) corresponds to the bit BIT_FIELD_PADDING.
+/// None is returned if the date is before the first day of the timetable.
+pub fn bit_index_of_date(date: NaiveDate, timetable_start_date: NaiveDate) -> Option<usize> {
+    let days = usize::try_from((date - timetable_start_date).num_days()).ok()?;
+    days.checked_add(BIT_FIELD_PADDING)
+}
+
+/// Returns the date corresponding to a bit of BitField::bits (inverse of bit_index_of_date).
+/// None is returned for the padding bits.
+pub fn date_of_bit_index(index: usize, timetable_start_date: NaiveDate) -> Option<NaiveDate> {
+    let days = index.checked_sub(BIT_FIELD_PADDING)?;
+    timetable_start_date.checked_add_days(Days::new(u64::try_from(days).ok()?))
+}
+
 pub fn create_time(hour: u32, minute: u32) -> PResult<NaiveTime> {
     NaiveTime::from_hms_opt(hour, minute, 0).ok_or(ParsingError::UnableToBuildTime(hour, minute, 0))
 }
 
-pub fn create_time_from_value(value: u32) -> PResult<NaiveTime> {
+/// Builds a time from a HHMM value (e.g. 1435 for 14:35).
+pub fn create_time_from_value(value: i32) -> PResult<NaiveTime> {
+    let value = u32::try_from(value).map_err(|_| ParsingError::InvalidTimeValue(value))?;
     create_time(value / 100, value % 100)
 }
 
+/// Builds a service time from a HHMM value whose hours can exceed 24 (e.g. 2530 for 01:30 on the next day).
+pub fn create_service_time_from_value(value: i32) -> PResult<ServiceTime> {
+    let value = u32::try_from(value).map_err(|_| ParsingError::InvalidTimeValue(value))?;
+    let (hours, minutes) = (value / 100, value % 100);
+    if minutes >= 60 {
+        return Err(ParsingError::UnableToBuildTime(hours, minutes, 0));
+    }
+    Ok(ServiceTime::new(hours * 60 + minutes))
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Daylight saving time (Europe/Zurich)
+// ------------------------------------------------------------------------------------------------
+
+const CET_OFFSET_IN_SECONDS: i32 = 3600;
+const CEST_OFFSET_IN_SECONDS: i32 = 7200;
+
+/// Returns the last Sunday of the given month.
+fn last_sunday_of(year: i32, month: u32) -> Option<NaiveDate> {
+    let first_day_of_next_month = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)?
+    };
+    let last_day = first_day_of_next_month.pred_opt()?;
+    let days_since_sunday = last_day.weekday().num_days_from_sunday();
+    last_day.checked_sub_days(Days::new(days_since_sunday.into()))
+}
+
+/// Resolves a Swiss local date-time (Europe/Zurich) into a date-time with a fixed UTC offset.
+/// Summer time starts on the last Sunday of March at 02:00 and ends on the last Sunday of October at 03:00.
+/// - Local times which do not exist (02:00 to 02:59 in March) are interpreted as winter time, i.e. they are moved one hour forward.
+/// - Local times which exist twice (02:00 to 02:59 in October) are resolved to the earlier instant (summer time).
+pub fn swiss_local_to_fixed_offset(datetime: NaiveDateTime) -> HResult<DateTime<FixedOffset>> {
+    let year = datetime.year();
+    let summer_time_start = last_sunday_of(year, 3)
+        .and_then(|date| date.and_hms_opt(3, 0, 0))
+        .ok_or(HrdfError::InvalidLocalDateTime(datetime))?;
+    let summer_time_end = last_sunday_of(year, 10)
+        .and_then(|date| date.and_hms_opt(3, 0, 0))
+        .ok_or(HrdfError::InvalidLocalDateTime(datetime))?;
+
+    let offset_in_seconds = if datetime >= summer_time_start && datetime < summer_time_end {
+        CEST_OFFSET_IN_SECONDS
+    } else {
+        CET_OFFSET_IN_SECONDS
+    };
+
+    FixedOffset::east_opt(offset_in_seconds)
+        .and_then(|offset| datetime.and_local_timezone(offset).single())
+        .ok_or(HrdfError::InvalidLocalDateTime(datetime))
+}
+
 pub fn timetable_start_date(
     timetable_metadata: &ResourceStorage<TimetableMetadataEntry>,
 ) -> HResult<NaiveDate> {
@@ -80,3 +157,37 @@ pub fn timetable_end_date(
         .value_as_naive_date();
     Ok(result)
 }
+
+// ------------------------------------------------------------------------------------------------
+// --- Polyline
+// ------------------------------------------------------------------------------------------------
+
+/// Encodes WGS84 coordinates with the Encoded Polyline Algorithm Format of Google (precision of 5 decimals).
+/// LV95 coordinates are converted to WGS84 first.
+pub fn encode_polyline(coordinates: &[Coordinates]) -> String {
+    let mut result = String::new();
+    let mut previous = (0, 0);
+
+    for coordinates in coordinates {
+        let wgs84 = coordinates.to_wgs84();
+        // unwrap: The coordinates are in WGS84.
+        let current = (
+            (wgs84.latitude().unwrap() * 1e5).round() as i64,
+            (wgs84.longitude().unwrap() * 1e5).round() as i64,
+        );
+        encode_polyline_value(current.0 - previous.0, &mut result);
+        encode_polyline_value(current.1 - previous.1, &mut result);
+        previous = current;
+    }
+    result
+}
+
+fn encode_polyline_value(value: i64, result: &mut String) {
+    let mut value = if value < 0 { !(value << 1) } else { value << 1 };
+
+    while value >= 0x20 {
+        result.push(char::from((((value & 0x1f) | 0x20) + 63) as u8));
+        value >>= 5;
+    }
+    result.push(char::from((value + 63) as u8));
+}
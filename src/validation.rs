@@ -0,0 +1,672 @@
+use std::{fmt::Display, hash::Hash, ops::RangeInclusive};
+
+use rustc_hash::{FxHashMap, FxHashSet};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "serde")]
+use crate::error::HResult;
+use crate::{
+    JourneyId,
+    models::{CoordinateSystem, Coordinates, JourneyMetadataType, Model},
+    storage::DataStorage,
+};
+
+/// Maximum number of examples kept for each kind of issue.
+const MAX_EXAMPLES: usize = 5;
+
+// Swiss territory in LV95 (easting, northing), with a margin.
+const LV95_EASTING_BOUNDS: RangeInclusive<f64> = 2_420_000.0..=2_900_000.0;
+const LV95_NORTHING_BOUNDS: RangeInclusive<f64> = 1_030_000.0..=1_350_000.0;
+// Switzerland in WGS84 (latitude, longitude), with a margin.
+const SWISS_LATITUDE_BOUNDS: RangeInclusive<f64> = 45.5..=48.0;
+const SWISS_LONGITUDE_BOUNDS: RangeInclusive<f64> = 5.5..=11.0;
+
+// ------------------------------------------------------------------------------------------------
+// --- ValidationReport
+// ------------------------------------------------------------------------------------------------
+
+/// Issues found by cross-checking the parsed resources (see DataStorage::validate).
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ValidationReport {
+    issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Getters/Setters
+
+    pub fn issues(&self) -> &Vec<ValidationIssue> {
+        &self.issues
+    }
+
+    // Functions
+
+    pub(crate) fn add(&mut self, issue: ValidationIssue) {
+        self.issues.push(issue);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Serializes the issues as a JSON array of {"code", "severity", "payload"} objects.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> HResult<String> {
+        issues_to_json(&self.issues)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Issue
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// An issue found while parsing or validating the data.
+pub trait Issue {
+    /// Stable identifier of the kind of issue, which does not change between releases.
+    fn code(&self) -> &'static str;
+
+    fn severity(&self) -> Severity;
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct IssueRecord<'a, T> {
+    code: &'static str,
+    severity: Severity,
+    payload: &'a T,
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn issues_to_json<T: Issue + Serialize>(issues: &[T]) -> HResult<String> {
+    let records: Vec<_> = issues
+        .iter()
+        .map(|issue| IssueRecord {
+            code: issue.code(),
+            severity: issue.severity(),
+            payload: issue,
+        })
+        .collect();
+    Ok(serde_json::to_string(&records)?)
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- ValidationIssue
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ValidationIssue {
+    /// Some resources refer to resources which do not exist.
+    /// Only the first few references (in sorted order) are kept as examples.
+    DanglingReferences {
+        reference: ReferenceKind,
+        count: usize,
+        examples: Vec<String>,
+    },
+    /// Some journeys have fewer than two route entries.
+    ShortJourneys { count: usize, examples: Vec<String> },
+    /// Some stops or platforms have implausible coordinates.
+    InvalidCoordinates {
+        problem: CoordinateProblem,
+        count: usize,
+        examples: Vec<String>,
+    },
+    /// Some resources are never referenced (platforms without journeys, unused bit fields, stops never served).
+    UnusedResources {
+        resource: ResourceKind,
+        count: usize,
+        examples: Vec<String>,
+    },
+}
+
+impl Issue for ValidationIssue {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::DanglingReferences { .. } => "dangling_references",
+            Self::ShortJourneys { .. } => "short_journeys",
+            Self::InvalidCoordinates { .. } => "invalid_coordinates",
+            Self::UnusedResources { .. } => "unused_resources",
+        }
+    }
+
+    fn severity(&self) -> Severity {
+        match self {
+            Self::DanglingReferences { .. } => Severity::Error,
+            Self::ShortJourneys { .. } | Self::InvalidCoordinates { .. } => Severity::Warning,
+            Self::UnusedResources { .. } => Severity::Info,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Reference
+// ------------------------------------------------------------------------------------------------
+
+/// A kind of reference from one resource to another.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ReferenceKind {
+    JourneyToStop,
+    JourneyToAttribute,
+    JourneyToBitField,
+    JourneyToDirection,
+    JourneyToInformationText,
+    JourneyToLine,
+    JourneyToTransportType,
+    JourneyPlatformToJourney,
+    JourneyPlatformToPlatform,
+    ThroughServiceToJourney,
+    ThroughServiceToBitField,
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- ResourceKind
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ResourceKind {
+    BitField,
+    Platform,
+    Stop,
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- CoordinateProblem
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CoordinateProblem {
+    /// Both coordinates are 0.
+    Zero,
+    /// The coordinates are in the expected bounds once swapped.
+    Swapped,
+    /// LV95 coordinates outside of the Swiss projection bounds (foreign stops can end up here too)
+    /// or WGS84 coordinates which are not a valid latitude/longitude.
+    OutOfBounds,
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Validation
+// ------------------------------------------------------------------------------------------------
+
+pub(crate) fn validate(data_storage: &DataStorage) -> ValidationReport {
+    let mut dangling_references = DanglingReferences::default();
+
+    check_journeys(data_storage, &mut dangling_references);
+    check_journey_platforms(data_storage, &mut dangling_references);
+    check_through_services(data_storage, &mut dangling_references);
+
+    let mut report = ValidationReport::new();
+    dangling_references.add_to(&mut report, |reference, count, examples| {
+        ValidationIssue::DanglingReferences {
+            reference,
+            count,
+            examples,
+        }
+    });
+    check_short_journeys(data_storage, &mut report);
+    check_coordinates(data_storage, &mut report);
+    check_unused_resources(data_storage, &mut report);
+    report
+}
+
+fn check_journeys(data_storage: &DataStorage, dangling_references: &mut DanglingReferences) {
+    for journey in data_storage.journeys().entries() {
+        let source = format!(
+            "Journey {} ({}, {})",
+            journey.id(),
+            journey.legacy_id(),
+            journey.administration()
+        );
+
+        for route_entry in journey.route() {
+            let stop_id = route_entry.stop_id();
+            if data_storage.stops().find(stop_id).is_none() {
+                dangling_references.add_reference(ReferenceKind::JourneyToStop, &source, stop_id);
+            }
+        }
+
+        for (metadata_type, entries) in journey.metadata() {
+            for entry in entries {
+                if let Some(bit_field_id) = entry.bit_field_id() {
+                    // The bit field 0 means every day and does not exist in BITFELD.
+                    if bit_field_id != 0 && data_storage.bit_fields().find(bit_field_id).is_none() {
+                        dangling_references.add_reference(
+                            ReferenceKind::JourneyToBitField,
+                            &source,
+                            bit_field_id,
+                        );
+                    }
+                }
+
+                let Some(resource_id) = entry.resource_id() else {
+                    // The *L and *R references which are not in LINIE or RICHTUNG are kept as their
+                    // raw codes.
+                    let reference = match metadata_type {
+                        JourneyMetadataType::Line => ReferenceKind::JourneyToLine,
+                        JourneyMetadataType::Direction => ReferenceKind::JourneyToDirection,
+                        _ => continue,
+                    };
+                    if let Some(raw_code) = entry.raw_code() {
+                        dangling_references.add_reference(reference, &source, raw_code);
+                    }
+                    continue;
+                };
+                let (reference, exists) = match metadata_type {
+                    JourneyMetadataType::Attribute => (
+                        ReferenceKind::JourneyToAttribute,
+                        data_storage.attributes().find(resource_id).is_some(),
+                    ),
+                    JourneyMetadataType::Direction => (
+                        ReferenceKind::JourneyToDirection,
+                        data_storage.directions().find(resource_id).is_some(),
+                    ),
+                    JourneyMetadataType::InformationText => (
+                        ReferenceKind::JourneyToInformationText,
+                        data_storage.information_texts().find(resource_id).is_some(),
+                    ),
+                    JourneyMetadataType::Line => (
+                        ReferenceKind::JourneyToLine,
+                        data_storage.lines().find(resource_id).is_some(),
+                    ),
+                    JourneyMetadataType::TransportType => (
+                        ReferenceKind::JourneyToTransportType,
+                        data_storage.transport_types().find(resource_id).is_some(),
+                    ),
+                    JourneyMetadataType::BitField
+                    | JourneyMetadataType::ExchangeTimeBoarding
+                    | JourneyMetadataType::ExchangeTimeDisembarking
+                    | JourneyMetadataType::Coupling
+                    | JourneyMetadataType::SeasonalStop
+                    | JourneyMetadataType::Unknown => continue,
+                };
+                if !exists {
+                    dangling_references.add_reference(reference, &source, resource_id);
+                }
+            }
+        }
+    }
+}
+
+fn check_journey_platforms(
+    data_storage: &DataStorage,
+    dangling_references: &mut DanglingReferences,
+) {
+    let journey_ids = journey_ids(data_storage);
+
+    for journey_platform in data_storage.journey_platform().entries() {
+        let journey_id = (
+            journey_platform.journey_legacy_id(),
+            journey_platform.administration().to_string(),
+        );
+        let source = format!(
+            "JourneyPlatform ({}, {}, {})",
+            journey_id.0,
+            journey_id.1,
+            journey_platform.platform_id()
+        );
+
+        if !journey_ids.contains(&journey_id) {
+            dangling_references.add_reference(
+                ReferenceKind::JourneyPlatformToJourney,
+                &source,
+                format!("({}, {})", journey_id.0, journey_id.1),
+            );
+        }
+
+        let platform_id = journey_platform.platform_id();
+        if data_storage.platforms().find(platform_id).is_none() {
+            dangling_references.add_reference(
+                ReferenceKind::JourneyPlatformToPlatform,
+                &source,
+                platform_id,
+            );
+        }
+    }
+}
+
+fn check_through_services(
+    data_storage: &DataStorage,
+    dangling_references: &mut DanglingReferences,
+) {
+    let journey_ids = journey_ids(data_storage);
+
+    for through_service in data_storage.through_service().entries() {
+        let source = format!("ThroughService {}", through_service.id());
+
+        for journey_id in [
+            through_service.journey_1_id(),
+            through_service.journey_2_id(),
+        ] {
+            if !journey_ids.contains(journey_id) {
+                dangling_references.add_reference(
+                    ReferenceKind::ThroughServiceToJourney,
+                    &source,
+                    format!("({}, {})", journey_id.0, journey_id.1),
+                );
+            }
+        }
+
+        let bit_field_id = through_service.bit_field_id();
+        if bit_field_id != 0 && data_storage.bit_fields().find(bit_field_id).is_none() {
+            dangling_references.add_reference(
+                ReferenceKind::ThroughServiceToBitField,
+                &source,
+                bit_field_id,
+            );
+        }
+    }
+}
+
+fn check_short_journeys(data_storage: &DataStorage, report: &mut ValidationReport) {
+    let mut short_journeys = Findings::default();
+
+    for journey in data_storage.journeys().entries() {
+        if journey.route().len() < 2 {
+            short_journeys.add(
+                (),
+                format!(
+                    "Journey {} ({}, {}) has {} route entries",
+                    journey.id(),
+                    journey.legacy_id(),
+                    journey.administration(),
+                    journey.route().len()
+                ),
+            );
+        }
+    }
+
+    short_journeys.add_to(report, |_, count, examples| {
+        ValidationIssue::ShortJourneys { count, examples }
+    });
+}
+
+fn check_coordinates(data_storage: &DataStorage, report: &mut ValidationReport) {
+    let mut invalid_coordinates = Findings::default();
+
+    let mut add = |source: String, coordinates: Option<Coordinates>| {
+        let Some(coordinates) = coordinates else {
+            return;
+        };
+        if let Some(problem) = coordinate_problem(&coordinates) {
+            invalid_coordinates.add(problem, format!("{source}: {coordinates:?}"));
+        }
+    };
+
+    for stop in data_storage.stops().entries() {
+        add(format!("Stop {}", stop.id()), stop.lv95_coordinates());
+        add(format!("Stop {}", stop.id()), stop.wgs84_coordinates());
+    }
+
+    for platform in data_storage.platforms().entries() {
+        add(
+            format!("Platform {}", platform.id()),
+            platform.lv95_coordinates(),
+        );
+        add(
+            format!("Platform {}", platform.id()),
+            platform.wgs84_coordinates(),
+        );
+    }
+
+    invalid_coordinates.add_to(report, |problem, count, examples| {
+        ValidationIssue::InvalidCoordinates {
+            problem,
+            count,
+            examples,
+        }
+    });
+}
+
+fn coordinate_problem(coordinates: &Coordinates) -> Option<CoordinateProblem> {
+    let (first, second, is_plausible): (f64, f64, fn(f64, f64) -> bool) = match coordinates
+        .coordinate_system()
+    {
+        CoordinateSystem::LV95 => (
+            coordinates.easting()?,
+            coordinates.northing()?,
+            |easting, northing| {
+                LV95_EASTING_BOUNDS.contains(&easting) && LV95_NORTHING_BOUNDS.contains(&northing)
+            },
+        ),
+        CoordinateSystem::WGS84 => (
+            coordinates.latitude()?,
+            coordinates.longitude()?,
+            |latitude, longitude| {
+                (-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude)
+            },
+        ),
+    };
+
+    if first == 0.0 && second == 0.0 {
+        return Some(CoordinateProblem::Zero);
+    }
+
+    let is_swapped = match coordinates.coordinate_system() {
+        CoordinateSystem::LV95 => !is_plausible(first, second) && is_plausible(second, first),
+        // Swapped WGS84 coordinates are usually still valid, so only Swiss coordinates are recognized.
+        CoordinateSystem::WGS84 => {
+            SWISS_LATITUDE_BOUNDS.contains(&second) && SWISS_LONGITUDE_BOUNDS.contains(&first)
+        }
+    };
+
+    if is_swapped {
+        Some(CoordinateProblem::Swapped)
+    } else if !is_plausible(first, second) {
+        Some(CoordinateProblem::OutOfBounds)
+    } else {
+        None
+    }
+}
+
+fn check_unused_resources(data_storage: &DataStorage, report: &mut ValidationReport) {
+    let journeys = data_storage.journeys().entries();
+
+    let served_stop_ids: FxHashSet<i32> = journeys
+        .iter()
+        .flat_map(|journey| journey.route())
+        .map(|route_entry| route_entry.stop_id())
+        .collect();
+
+    let used_platform_ids: FxHashSet<i32> = data_storage
+        .journey_platform()
+        .entries()
+        .into_iter()
+        .map(|journey_platform| journey_platform.platform_id())
+        .collect();
+
+    let used_bit_field_ids: FxHashSet<i32> = journeys
+        .iter()
+        .flat_map(|journey| journey.metadata().values().flatten())
+        .filter_map(|entry| entry.bit_field_id())
+        .chain(
+            data_storage
+                .journey_platform()
+                .entries()
+                .into_iter()
+                .filter_map(|journey_platform| journey_platform.bit_field_id()),
+        )
+        .chain(
+            data_storage
+                .through_service()
+                .entries()
+                .into_iter()
+                .map(|through_service| through_service.bit_field_id()),
+        )
+        .chain(
+            data_storage
+                .exchange_times_journey()
+                .entries()
+                .into_iter()
+                .filter_map(|exchange_time| exchange_time.bit_field_id()),
+        )
+        .collect();
+
+    let mut unused_resources = Findings::default();
+
+    for stop in data_storage.stops().entries() {
+        if !served_stop_ids.contains(&stop.id()) {
+            unused_resources.add(
+                ResourceKind::Stop,
+                format!("Stop {} ({})", stop.id(), stop.name()),
+            );
+        }
+    }
+
+    for platform in data_storage.platforms().entries() {
+        if !used_platform_ids.contains(&platform.id()) {
+            unused_resources.add(
+                ResourceKind::Platform,
+                format!("Platform {}", platform.id()),
+            );
+        }
+    }
+
+    for bit_field in data_storage.bit_fields().entries() {
+        if !used_bit_field_ids.contains(&bit_field.id()) {
+            unused_resources.add(
+                ResourceKind::BitField,
+                format!("BitField {}", bit_field.id()),
+            );
+        }
+    }
+
+    unused_resources.add_to(report, |resource, count, examples| {
+        ValidationIssue::UnusedResources {
+            resource,
+            count,
+            examples,
+        }
+    });
+}
+
+fn journey_ids(data_storage: &DataStorage) -> FxHashSet<JourneyId> {
+    data_storage
+        .journeys()
+        .entries()
+        .into_iter()
+        .map(|journey| (journey.legacy_id(), journey.administration().to_string()))
+        .collect()
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Findings
+// ------------------------------------------------------------------------------------------------
+
+/// Descriptions of the problems found, grouped by kind.
+struct Findings<K> {
+    data: FxHashMap<K, Vec<String>>,
+}
+
+impl<K> Default for Findings<K> {
+    fn default() -> Self {
+        Self {
+            data: FxHashMap::default(),
+        }
+    }
+}
+
+impl<K: Copy + Eq + Hash + Ord> Findings<K> {
+    fn add(&mut self, kind: K, description: String) {
+        self.data.entry(kind).or_default().push(description);
+    }
+
+    /// Adds one issue per kind to the report, keeping only the first descriptions (in sorted order) as examples.
+    fn add_to(
+        self,
+        report: &mut ValidationReport,
+        to_issue: impl Fn(K, usize, Vec<String>) -> ValidationIssue,
+    ) {
+        let mut data: Vec<_> = self.data.into_iter().collect();
+        data.sort_unstable_by_key(|(kind, _)| *kind);
+
+        for (kind, mut descriptions) in data {
+            descriptions.sort_unstable();
+            let count = descriptions.len();
+            descriptions.truncate(MAX_EXAMPLES);
+            report.add(to_issue(kind, count, descriptions));
+        }
+    }
+}
+
+type DanglingReferences = Findings<ReferenceKind>;
+
+impl DanglingReferences {
+    fn add_reference(&mut self, reference: ReferenceKind, source: &str, target: impl Display) {
+        self.add(reference, format!("{source} -> {target}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn validation_report_to_json() {
+        let mut report = ValidationReport::new();
+        report.add(ValidationIssue::ShortJourneys {
+            count: 1,
+            examples: vec!["Journey 1 (100, CH) has 1 route entries".to_string()],
+        });
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([{
+                "code": "short_journeys",
+                "severity": "Warning",
+                "payload": {
+                    "ShortJourneys": {
+                        "count": 1,
+                        "examples": ["Journey 1 (100, CH) has 1 route entries"]
+                    }
+                }
+            }])
+        );
+    }
+
+    #[test]
+    fn coordinate_problem_lv95() {
+        let check = |x, y| coordinate_problem(&Coordinates::new(CoordinateSystem::LV95, x, y));
+
+        assert_eq!(check(2600000.0, 1200000.0), None);
+        assert_eq!(check(0.0, 0.0), Some(CoordinateProblem::Zero));
+        assert_eq!(
+            check(1200000.0, 2600000.0),
+            Some(CoordinateProblem::Swapped)
+        );
+        assert_eq!(
+            check(600000.0, 200000.0),
+            Some(CoordinateProblem::OutOfBounds)
+        );
+    }
+
+    #[test]
+    fn coordinate_problem_wgs84() {
+        let check = |x, y| coordinate_problem(&Coordinates::new(CoordinateSystem::WGS84, x, y));
+
+        assert_eq!(check(46.948, 7.447), None);
+        // Paris.
+        assert_eq!(check(48.880, 2.355), None);
+        assert_eq!(check(0.0, 0.0), Some(CoordinateProblem::Zero));
+        assert_eq!(check(7.447, 46.948), Some(CoordinateProblem::Swapped));
+        assert_eq!(check(146.948, 7.447), Some(CoordinateProblem::OutOfBounds));
+    }
+}
@@ -0,0 +1,224 @@
+//! Tests of the hrdf command-line tool over the small HRDF extract of tests/data/mini.
+#![cfg(feature = "cli")]
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use assert_cmd::Command;
+use predicates::prelude::predicate;
+use pretty_assertions::assert_eq;
+
+fn mini_dataset() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/mini")
+}
+
+/// A copy of the extract in which the file is modified, e.g. to introduce issues.
+fn modified_mini_dataset(name: &str, file_name: &str, modify: impl Fn(&str) -> String) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("hrdf-parser-cli-{name}"));
+    let _ = fs::remove_dir_all(&path);
+    fs::create_dir_all(&path).unwrap();
+    for entry in fs::read_dir(mini_dataset()).unwrap() {
+        let entry = entry.unwrap();
+        fs::copy(entry.path(), path.join(entry.file_name())).unwrap();
+    }
+    let content = fs::read_to_string(path.join(file_name)).unwrap();
+    fs::write(path.join(file_name), modify(&content)).unwrap();
+    path
+}
+
+fn hrdf() -> Command {
+    Command::cargo_bin("hrdf").unwrap()
+}
+
+fn validate_json(dataset: &Path, extra_args: &[&str]) -> (Option<i32>, serde_json::Value) {
+    let output = hrdf()
+        .arg("validate")
+        .arg(dataset)
+        .args(["--format", "json"])
+        .args(extra_args)
+        .output()
+        .unwrap();
+    (
+        output.status.code(),
+        serde_json::from_slice(&output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn validate_consistent_dataset() {
+    hrdf()
+        .arg("validate")
+        .arg(mini_dataset())
+        .args(["--format", "json", "--deny-warnings"])
+        .assert()
+        .code(0)
+        .stdout("{\"parse_issues\":[],\"validation_issues\":[]}\n");
+
+    hrdf()
+        .arg("validate")
+        .arg(mini_dataset())
+        .assert()
+        .code(0)
+        .stdout(predicate::str::contains("Parse issues: none"))
+        .stdout(predicate::str::contains("Validation issues: none"));
+}
+
+#[test]
+fn validate_errors() {
+    // The first journey serves a stop which is not in BAHNHOF.
+    let dataset = modified_mini_dataset("validate-errors", "FPLAN", |content| {
+        content.replacen("8500103 Bergdorf", "8500199 Bergdorf", 1)
+    });
+
+    let (code, report) = validate_json(&dataset, &[]);
+    assert_eq!(code, Some(1));
+    assert_eq!(report["parse_issues"], serde_json::json!([]));
+    assert_eq!(
+        report["validation_issues"][0]["code"],
+        "dangling_references"
+    );
+    assert_eq!(report["validation_issues"][0]["severity"], "Error");
+}
+
+#[test]
+fn validate_warnings() {
+    let dataset = modified_mini_dataset("validate-warnings", "BFKOORD_WGS", |content| {
+        content.replace("8500105    7.4    46.97", "8500105    0.0    0.0")
+    });
+
+    let (code, report) = validate_json(&dataset, &[]);
+    assert_eq!(code, Some(0));
+    assert_eq!(
+        report["validation_issues"][0]["code"],
+        "invalid_coordinates"
+    );
+    assert_eq!(report["validation_issues"][0]["severity"], "Warning");
+
+    let (code, _) = validate_json(&dataset, &["--deny-warnings"]);
+    assert_eq!(code, Some(1));
+}
+
+#[test]
+fn validate_unloadable_dataset() {
+    hrdf()
+        .args(["validate", "/nonexistent/hrdf", "--format", "json"])
+        .assert()
+        .code(2)
+        .stdout("")
+        .stderr(predicate::str::starts_with("Error: "));
+}
+
+#[test]
+fn inspect() {
+    hrdf()
+        .arg("inspect")
+        .arg(mini_dataset())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Timetable period: 2025-12-14 - 2026-12-12",
+        ))
+        .stdout(predicate::str::is_match(r"Journeys +6\n").unwrap());
+}
+
+#[test]
+fn departures() {
+    // 15.12.2025 is a Monday.
+    hrdf()
+        .arg("departures")
+        .arg(mini_dataset())
+        .args(["Aarstadt", "--at", "2025-12-15T06:00"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Departures from Aarstadt (8500101)",
+        ))
+        .stdout(predicate::str::is_match(r"06:00 +S1 +Seeburg +1").unwrap());
+
+    hrdf()
+        .arg("departures")
+        .arg(mini_dataset())
+        .args(["Aarstadt", "--at", "2025-12-15 6h"])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn route() {
+    hrdf()
+        .arg("route")
+        .arg(mini_dataset())
+        .args(["Aarstadt", "Seeburg", "--depart", "2025-12-15T06:00"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("arrival at 2025-12-15 06:20"))
+        .stdout(predicate::str::contains(
+            "06:00 Aarstadt (platform 1) -> 06:20 Seeburg  S1",
+        ));
+}
+
+#[test]
+fn route_with_a_transfer() {
+    hrdf()
+        .arg("route")
+        .arg(mini_dataset())
+        .args(["Seeburg", "Talwil", "--depart", "2025-12-15T06:30"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "    06:40 Seeburg -> 07:00 Aarstadt  S1\n      transfer at Aarstadt\n    07:15 Aarstadt -> 07:27 Talwil  12\n",
+        ));
+}
+
+#[test]
+fn diff() {
+    let dataset = modified_mini_dataset("diff", "BAHNHOF", |content| {
+        content.replace("Bergdorf", "Bergdorf Dorf")
+    });
+
+    let output = hrdf()
+        .arg("diff")
+        .arg(mini_dataset())
+        .arg(&dataset)
+        .arg("--json")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let diff: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(diff["added_journeys"], serde_json::json!([]));
+    assert_eq!(diff["removed_journeys"], serde_json::json!([]));
+    assert_eq!(diff["renamed_stops"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn export_gtfs() {
+    let out = std::env::temp_dir().join("hrdf-parser-cli-export-gtfs");
+    let _ = fs::remove_dir_all(&out);
+
+    hrdf()
+        .arg("export")
+        .arg(mini_dataset())
+        .args(["--format", "gtfs", "--out"])
+        .arg(&out)
+        .assert()
+        .success();
+    for file_name in [
+        "agency.txt",
+        "stops.txt",
+        "routes.txt",
+        "trips.txt",
+        "stop_times.txt",
+        "calendar_dates.txt",
+    ] {
+        assert!(out.join(file_name).exists(), "{file_name} is missing");
+    }
+    assert_eq!(
+        fs::read_to_string(out.join("trips.txt"))
+            .unwrap()
+            .lines()
+            .count(),
+        1 + 6
+    );
+}
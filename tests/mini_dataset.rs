@@ -0,0 +1,1062 @@
+//! End-to-end tests over the small HRDF extract of tests/data/mini.
+//!
+//! The extract contains 5 stops, 2 lines (the S1 Aarstadt - Seeburg and the bus 12 Aarstadt - Talwil),
+//! 3 bit fields (every day, Monday to Friday, Saturday and Sunday) and 6 journeys.
+use std::path::Path;
+
+use chrono::{NaiveDate, NaiveTime};
+use hrdf_parser::{DataStorage, Hrdf, Journey, Language, LocationType, Model, Version};
+use pretty_assertions::assert_eq;
+
+fn load() -> Hrdf {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/mini");
+    Hrdf::from_directory(Version::V_5_40_41_2_0_7, &path).unwrap()
+}
+
+fn find_journey(data_storage: &DataStorage, legacy_id: i32) -> &Journey {
+    data_storage
+        .journeys()
+        .entries()
+        .into_iter()
+        .find(|journey| journey.legacy_id() == legacy_id && journey.administration() == "000001")
+        .unwrap()
+}
+
+fn date(day: u32, month: u32, year: i32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+#[test]
+fn resource_counts() {
+    let hrdf = load();
+    let data_storage = hrdf.data_storage();
+
+    assert_eq!(data_storage.attributes().data().len(), 2);
+    assert_eq!(data_storage.bit_fields().data().len(), 3);
+    assert_eq!(data_storage.directions().data().len(), 3);
+    assert_eq!(data_storage.journeys().data().len(), 6);
+    assert_eq!(data_storage.journey_platform().data().len(), 3);
+    assert_eq!(data_storage.lines().data().len(), 2);
+    assert_eq!(data_storage.platforms().data().len(), 2);
+    assert_eq!(data_storage.stops().data().len(), 5);
+    assert_eq!(data_storage.transport_types().data().len(), 2);
+    assert_eq!(data_storage.default_exchange_time(), (2, 2));
+    assert_eq!(
+        hrdf.timetable_period(),
+        date(14, 12, 2025)..=date(12, 12, 2026)
+    );
+}
+
+#[test]
+fn dataset_is_consistent() {
+    let hrdf = load();
+
+    assert!(hrdf.data_storage().parse_report().is_empty());
+    assert!(hrdf.data_storage().validate().is_empty());
+}
+
+#[test]
+fn journey_route() {
+    let hrdf = load();
+    let journey = find_journey(hrdf.data_storage(), 1);
+
+    let stop_ids: Vec<_> = journey
+        .route()
+        .iter()
+        .map(|entry| entry.stop_id())
+        .collect();
+    assert_eq!(stop_ids, vec![8500101, 8500102, 8500103, 8500104]);
+    assert_eq!(
+        journey.route()[0].departure_time(),
+        NaiveTime::from_hms_opt(6, 0, 0)
+    );
+    assert_eq!(
+        journey.route()[3].arrival_time(),
+        NaiveTime::from_hms_opt(6, 20, 0)
+    );
+    assert_eq!(
+        journey
+            .transport_type(hrdf.data_storage())
+            .unwrap()
+            .designation(),
+        "S"
+    );
+}
+
+#[test]
+fn journey_running_past_midnight() {
+    let hrdf = load();
+    let journey = find_journey(hrdf.data_storage(), 5);
+    let last = &journey.route()[3];
+
+    assert_eq!(last.arrival_time(), NaiveTime::from_hms_opt(0, 10, 0));
+    assert_eq!(last.arrival_day_offset(), 1);
+}
+
+#[test]
+fn journey_description() {
+    let hrdf = load();
+    let data_storage = hrdf.data_storage();
+    let description = find_journey(data_storage, 1).describe(data_storage, Language::German);
+    let lines: Vec<_> = description.lines().collect();
+
+    assert_eq!(lines[0], "S 1 (000001)");
+    assert!(lines.contains(&"Line: S1"));
+    assert!(lines.contains(&"Operator: STB"));
+    assert!(lines.contains(&"Direction: Seeburg"));
+    assert!(lines.iter().any(|line| line.starts_with("Operates: ")));
+    assert_eq!(lines.len(), 9);
+    assert!(lines[5].contains("Aarstadt (8500101)") && lines[5].ends_with("06:00"));
+    assert!(lines[8].contains("Seeburg (8500104)") && lines[8].trim_end().ends_with("06:20"));
+}
+
+#[test]
+fn journeys_operating_days() {
+    let hrdf = load();
+    let data_storage = hrdf.data_storage();
+    let working_days_journey = find_journey(data_storage, 101);
+    let weekends_journey = find_journey(data_storage, 103);
+
+    // 15.12.2025 is a Monday, 20.12.2025 a Saturday.
+    let monday = date(15, 12, 2025);
+    let saturday = date(20, 12, 2025);
+    assert!(
+        working_days_journey
+            .operates_on(monday, data_storage)
+            .unwrap()
+    );
+    assert!(
+        !working_days_journey
+            .operates_on(saturday, data_storage)
+            .unwrap()
+    );
+    assert!(!weekends_journey.operates_on(monday, data_storage).unwrap());
+    assert!(
+        weekends_journey
+            .operates_on(saturday, data_storage)
+            .unwrap()
+    );
+}
+
+#[test]
+fn swiss_journey_ids() {
+    let hrdf = load();
+    let data_storage = hrdf.data_storage();
+
+    assert_eq!(
+        find_journey(data_storage, 1).swiss_journey_id(),
+        Some("ch:1:sjyid:100001:1-001")
+    );
+    assert_eq!(find_journey(data_storage, 101).swiss_journey_id(), None);
+
+    let journey = data_storage
+        .journey_by_sjyid("ch:1:sjyid:100001:3-001")
+        .unwrap();
+    assert_eq!(journey.legacy_id(), 3);
+    assert!(data_storage.journey_by_sjyid("ch:1:sjyid:1:1").is_none());
+}
+
+#[test]
+fn journey_regions() {
+    let hrdf = load();
+    let data_storage = hrdf.data_storage();
+
+    assert_eq!(
+        find_journey(data_storage, 101).region(),
+        Some("Aarstadt-Land")
+    );
+    assert_eq!(find_journey(data_storage, 103).region(), None);
+    // The region is not taken for the Swiss Journey ID.
+    assert_eq!(find_journey(data_storage, 101).swiss_journey_id(), None);
+}
+
+#[test]
+fn journey_attributes_on() {
+    let hrdf = load();
+    let data_storage = hrdf.data_storage();
+    let journey = find_journey(data_storage, 101);
+
+    // VR only applies on weekends.
+    let designations = |date| {
+        journey
+            .attributes_on(date, data_storage)
+            .into_iter()
+            .map(|attribute| attribute.designation())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(designations(date(15, 12, 2025)), vec!["NF"]);
+    assert_eq!(designations(date(20, 12, 2025)), vec!["NF", "VR"]);
+}
+
+#[test]
+fn stop_hierarchy() {
+    let hrdf = load();
+    let data_storage = hrdf.data_storage();
+    let hierarchy = data_storage.stop_hierarchy();
+
+    let boarding_areas = hierarchy.boarding_areas_of_stop(8500101);
+    let sloids: Vec<_> = boarding_areas
+        .iter()
+        .map(|boarding_area| boarding_area.sloid())
+        .collect();
+    assert_eq!(
+        sloids,
+        vec!["ch:1:sloid:101:1", "ch:1:sloid:101:2", "ch:1:sloid:101:3"]
+    );
+    assert!(hierarchy.boarding_areas_of_stop(8500104).is_empty());
+
+    let platforms = boarding_areas[0].platforms(data_storage);
+    assert_eq!(platforms.len(), 1);
+    assert_eq!(platforms[0].sloid(), "ch:1:sloid:101:1:1");
+    assert!(boarding_areas[2].platform_ids().is_empty());
+    assert_eq!(
+        boarding_areas[0].stop(data_storage).unwrap().name(),
+        "Aarstadt"
+    );
+
+    let platform_id = platforms[0].id();
+    assert_eq!(
+        hierarchy
+            .boarding_area_of_platform(platform_id)
+            .unwrap()
+            .sloid(),
+        "ch:1:sloid:101:1"
+    );
+    assert_eq!(hierarchy.platform_ids_of_stop(8500101).len(), 2);
+
+    assert_eq!(
+        hierarchy.location_type("ch:1:sloid:101"),
+        Some(LocationType::Station)
+    );
+    assert_eq!(
+        hierarchy.location_type("ch:1:sloid:101:2"),
+        Some(LocationType::BoardingArea)
+    );
+    assert_eq!(
+        hierarchy.location_type("ch:1:sloid:101:2:2"),
+        Some(LocationType::Platform)
+    );
+    assert_eq!(LocationType::BoardingArea.gtfs_location_type(), 4);
+    assert_eq!(hierarchy.location_type("ch:1:sloid:999"), None);
+}
+
+#[test]
+fn administrations() {
+    let hrdf = load();
+    let data_storage = hrdf.data_storage();
+    let administrations = data_storage.administrations();
+
+    assert_eq!(administrations.len(), 1);
+    let administration = &administrations[0];
+    assert_eq!(administration.code(), "000001");
+    assert_eq!(
+        administration.journey_count(),
+        data_storage.journeys().data().len()
+    );
+    let transport_company = administration.transport_company(data_storage).unwrap();
+    assert_eq!(transport_company.short_name(Language::German), Some("STB"));
+    assert_eq!(transport_company.sboid(), Some("ch:1:sboid:100001"));
+    assert_eq!(
+        data_storage
+            .transport_company_by_sboid("ch:1:sboid:100001")
+            .map(|transport_company| transport_company.id()),
+        Some(transport_company.id())
+    );
+    assert!(
+        data_storage
+            .transport_company_by_sboid("ch:1:sboid:1")
+            .is_none()
+    );
+}
+
+#[test]
+fn legacy_codes() {
+    let hrdf = load();
+    let data_storage = hrdf.data_storage();
+
+    let attribute_id = data_storage.attribute_id_by_code("NF").unwrap();
+    assert_eq!(
+        data_storage
+            .attributes()
+            .find(attribute_id)
+            .unwrap()
+            .designation(),
+        "NF"
+    );
+    let direction_id = data_storage.direction_id_by_code("R000001").unwrap();
+    assert_eq!(
+        data_storage.directions().find(direction_id).unwrap().name(),
+        "Seeburg"
+    );
+    let transport_type_id = data_storage.transport_type_id_by_designation("B").unwrap();
+    assert_eq!(
+        data_storage
+            .transport_types()
+            .find(transport_type_id)
+            .unwrap()
+            .designation(),
+        "B"
+    );
+    assert_eq!(data_storage.attribute_id_by_code("XX"), None);
+    assert_eq!(data_storage.transport_type_id_by_designation("ICE"), None);
+
+    assert!(data_storage.contains_journey(1, "000001"));
+    assert!(!data_storage.contains_journey(1, "000011"));
+    assert!(!data_storage.contains_journey(4, "000001"));
+}
+
+#[test]
+fn journeys_by_train_number() {
+    let hrdf = load();
+    let data_storage = hrdf.data_storage();
+    let monday = date(15, 12, 2025);
+    let saturday = date(20, 12, 2025);
+    let legacy_ids = |train_number, date| {
+        data_storage
+            .find_journeys_by_train_number(train_number, date)
+            .unwrap()
+            .iter()
+            .map(|journey| journey.legacy_id())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(legacy_ids("S 1", monday), vec![1]);
+    assert_eq!(legacy_ids("s1", monday), vec![1]);
+    assert_eq!(legacy_ids("1", monday), vec![1]);
+    assert_eq!(legacy_ids("B 101", monday), vec![101]);
+    assert!(legacy_ids("B 101", saturday).is_empty());
+    assert!(legacy_ids("S 101", monday).is_empty());
+    assert!(legacy_ids("IC", monday).is_empty());
+    assert!(
+        data_storage
+            .find_journeys_by_train_number("S 1", date(1, 1, 2030))
+            .is_err()
+    );
+}
+
+#[test]
+fn journeys_active_after_midnight() {
+    let hrdf = load();
+    let datetime = date(16, 12, 2025).and_hms_opt(0, 5, 0).unwrap();
+
+    let legacy_ids: Vec<_> = hrdf
+        .data_storage()
+        .journeys_active_at(datetime)
+        .unwrap()
+        .into_iter()
+        .map(|journey| journey.legacy_id())
+        .collect();
+    assert_eq!(legacy_ids, vec![5]);
+}
+
+#[test]
+fn graphml_export() {
+    let hrdf = load();
+    let graphml = hrdf.data_storage().to_graphml();
+
+    assert!(graphml.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+    assert_eq!(graphml.matches("<node id=").count(), 5);
+    // The S1 serves 3 segments in each direction, the bus 12 serves 1 segment in one direction.
+    assert_eq!(graphml.matches("<edge source=").count(), 7);
+    assert!(graphml.contains(r#"<data key="name">Aarstadt Nord</data>"#));
+}
+
+#[test]
+fn gtfs_export() {
+    let hrdf = load();
+    let files: std::collections::HashMap<_, _> =
+        hrdf.data_storage().to_gtfs().into_iter().collect();
+
+    assert_eq!(files["agency.txt"].lines().count(), 1 + 1);
+    assert_eq!(files["stops.txt"].lines().count(), 1 + 5);
+    // The S1 is a rail route and the bus 12 a bus route.
+    assert_eq!(
+        files["routes.txt"].lines().skip(1).collect::<Vec<_>>(),
+        vec!["000001:1,000001,1,2", "000001:2,000001,12,3"]
+    );
+    assert_eq!(files["trips.txt"].lines().count(), 1 + 6);
+    assert!(files["stop_times.txt"].contains("1,06:03:00,06:04:00,8500102,1,0,0\n"));
+    // 14.12.2025 is a Sunday, on which the journeys of the bit field 2 do not operate.
+    assert!(files["calendar_dates.txt"].contains("1,20251214,1\n"));
+    assert!(!files["calendar_dates.txt"].contains("2,20251214,1\n"));
+}
+
+#[test]
+fn gtfs_export_without_wgs84_coordinates() {
+    let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/mini");
+    let path = std::env::temp_dir().join("hrdf-parser-gtfs-export-without-wgs84-coordinates");
+    std::fs::create_dir_all(&path).unwrap();
+    for entry in std::fs::read_dir(&source).unwrap() {
+        let entry = entry.unwrap();
+        std::fs::copy(entry.path(), path.join(entry.file_name())).unwrap();
+    }
+    // Talwil only has LV95 coordinates and Seeburg has no coordinates at all.
+    let remove_rows = |file: &str, stop_ids: &[&str]| {
+        let rows = std::fs::read_to_string(source.join(file)).unwrap();
+        let rows: String = rows
+            .lines()
+            .filter(|row| !stop_ids.iter().any(|stop_id| row.starts_with(stop_id)))
+            .map(|row| format!("{row}\n"))
+            .collect();
+        std::fs::write(path.join(file), rows).unwrap();
+    };
+    remove_rows("BFKOORD_WGS", &["8500104", "8500105"]);
+    remove_rows("BFKOORD_LV95", &["8500104"]);
+
+    let hrdf = Hrdf::from_directory(Version::V_5_40_41_2_0_7, &path).unwrap();
+    let files: std::collections::HashMap<_, _> =
+        hrdf.data_storage().to_gtfs().into_iter().collect();
+
+    assert_eq!(files["stops.txt"].lines().count(), 1 + 4);
+    assert!(files["stops.txt"].contains("8500105,Talwil,"));
+    assert!(!files["stops.txt"].contains("8500104"));
+    assert!(!files["stop_times.txt"].contains("8500104"));
+    assert!(files["stop_times.txt"].contains("1,06:10:00,06:11:00,8500103,2,0,0\n"));
+    std::fs::remove_dir_all(&path).unwrap();
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_export() {
+    let hrdf = load();
+    let path = std::env::temp_dir().join("hrdf-parser-sqlite-export.sqlite");
+    // Exporting twice replaces the tables.
+    hrdf.data_storage().to_sqlite(&path).unwrap();
+    hrdf.data_storage().to_sqlite(&path).unwrap();
+
+    let connection = rusqlite::Connection::open(&path).unwrap();
+    let count = |table: &str| -> i64 {
+        connection
+            .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+    };
+    assert_eq!(count("stops"), 5);
+    assert_eq!(count("journeys"), 6);
+    assert_eq!(count("stop_times"), 20);
+    let departure: i64 = connection
+        .query_row(
+            "SELECT departure_minutes FROM stop_times JOIN journeys ON journey_id = id \
+            WHERE legacy_id = 1 AND sequence = 1",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(departure, 6 * 60 + 4);
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_export_cycles_and_boarding() {
+    let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/mini");
+    let directory = std::env::temp_dir().join("hrdf-parser-sqlite-export-cycles-and-boarding");
+    std::fs::create_dir_all(&directory).unwrap();
+    for entry in std::fs::read_dir(&source).unwrap() {
+        let entry = entry.unwrap();
+        std::fs::copy(entry.path(), directory.join(entry.file_name())).unwrap();
+    }
+    // The journey 1 runs 2 more times every 30 minutes and does not let passengers board at
+    // Aarstadt Nord.
+    let fplan = std::fs::read_to_string(source.join("FPLAN")).unwrap();
+    let fplan = fplan
+        .replacen(
+            "*Z 000001 000001   101         %",
+            "*Z 000001 000001   101 002 030 %",
+            1,
+        )
+        .replacen(
+            "00603  00604 000001 000001",
+            "00603 -00604 000001 000001",
+            1,
+        );
+    std::fs::write(directory.join("FPLAN"), fplan).unwrap();
+
+    let hrdf = Hrdf::from_directory(Version::V_5_40_41_2_0_7, &directory).unwrap();
+    let path = directory.join("hrdf.sqlite");
+    hrdf.data_storage().to_sqlite(&path).unwrap();
+
+    let connection = rusqlite::Connection::open(&path).unwrap();
+    let cycles: (i64, i64) = connection
+        .query_row(
+            "SELECT num_cycles, cycle_duration FROM journeys WHERE legacy_id = 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!(cycles, (2, 30));
+    let allowed: (bool, bool) = connection
+        .query_row(
+            "SELECT pickup_allowed, drop_off_allowed FROM stop_times JOIN journeys \
+            ON journey_id = id WHERE legacy_id = 1 AND sequence = 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!(allowed, (false, true));
+    drop(connection);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn line_geometries() {
+    let hrdf = load();
+    let line_geometries = hrdf.data_storage().line_geometries();
+
+    assert_eq!(line_geometries.len(), 2);
+    // The journeys in the opposite direction do not add a branch.
+    assert_eq!(
+        line_geometries[0].main_variant(),
+        &[8500101, 8500102, 8500103, 8500104]
+    );
+    assert!(line_geometries[0].branches().is_empty());
+    assert_eq!(line_geometries[1].main_variant(), &[8500101, 8500105]);
+
+    let geojson: serde_json::Value =
+        serde_json::from_str(&hrdf.data_storage().line_geometries_to_geojson().unwrap()).unwrap();
+    assert_eq!(geojson["features"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        geojson["features"][0]["geometry"]["coordinates"][0]
+            .as_array()
+            .unwrap()
+            .len(),
+        4
+    );
+}
+
+#[test]
+fn station_board() {
+    let hrdf = load();
+    let data_storage = hrdf.data_storage();
+    // 15.12.2025 is a Monday.
+    let from = date(15, 12, 2025).and_hms_opt(6, 0, 0).unwrap();
+    let until = date(15, 12, 2025).and_hms_opt(7, 30, 0).unwrap();
+
+    let departures: Vec<_> = data_storage
+        .departures(8500101, from, until)
+        .unwrap()
+        .iter()
+        .map(|departure| departure.journey(data_storage).unwrap().legacy_id())
+        .collect();
+    assert_eq!(departures, vec![1, 3, 101]);
+
+    let station_board = data_storage
+        .station_board(8500101, from, until, true)
+        .unwrap();
+    assert_eq!(station_board.stop_ids(), &[8500101]);
+    let platforms = station_board.platforms();
+    assert_eq!(platforms.len(), 2);
+
+    assert_eq!(platforms[0].name(), Some("1"));
+    assert_eq!(platforms[0].entries().len(), 2);
+    let entry = &platforms[0].entries()[0];
+    assert_eq!(entry.departure_at(), from);
+    assert_eq!(entry.destination_stop_id(), Some(8500104));
+    assert_eq!(entry.line_name(), Some("S1"));
+    assert_eq!(entry.background_color().unwrap().b(), 204);
+
+    // The bus has no platform.
+    assert_eq!(platforms[1].platform_id(), None);
+    let entry = &platforms[1].entries()[0];
+    assert_eq!(entry.line_name(), Some("12"));
+    assert_eq!(entry.attributes(), &["NF".to_string()]);
+    assert_eq!(entry.continues_as(), None);
+}
+
+#[test]
+fn lines_overview() {
+    let hrdf = load();
+    let lines_overview = hrdf.data_storage().lines_overview();
+
+    assert_eq!(lines_overview.len(), 2);
+    let bus = &lines_overview[1];
+    assert_eq!(bus.administrations(), &["000001".to_string()]);
+    assert_eq!(bus.transport_type_ids().len(), 1);
+    assert_eq!(bus.terminal_stop_ids(), &[8500101, 8500105]);
+    // The working days and weekends journeys together cover the whole timetable period.
+    assert_eq!(bus.active_period(), Some(hrdf.timetable_period()));
+}
+
+#[test]
+fn transfer_statistics() {
+    let hrdf = load();
+    let data_storage = hrdf.data_storage();
+    let s1 = find_journey(data_storage, 2);
+    let bus = find_journey(data_storage, 101);
+
+    // UMSTEIGB defines 3 minutes at Aarstadt and 2 minutes elsewhere.
+    assert_eq!(
+        data_storage.exchange_time(8500101, s1, bus, date(15, 12, 2025)),
+        3
+    );
+    assert_eq!(
+        data_storage.exchange_time(8500104, s1, bus, date(15, 12, 2025)),
+        2
+    );
+
+    // The S1 arriving at 07:00 misses the S1 leaving at 07:00, but not the bus leaving at 07:15.
+    let transfer_statistics = data_storage
+        .transfer_statistics(8500101, date(15, 12, 2025))
+        .unwrap();
+    assert_eq!(transfer_statistics.transfers().len(), 1);
+    let transfer = &transfer_statistics.transfers()[0];
+    assert_eq!(transfer.arriving_journey_id(), s1.id());
+    assert_eq!(transfer.departing_journey_id(), bus.id());
+    assert_eq!(transfer.exchange_time(), 3);
+    assert_eq!(transfer_statistics.transfer_times(), vec![15]);
+    assert_eq!(transfer_statistics.median_transfer_time(), Some(15.0));
+
+    // On Saturday, the bus leaves at 09:15, more than an hour later.
+    assert!(
+        data_storage
+            .transfer_statistics(8500101, date(20, 12, 2025))
+            .unwrap()
+            .transfers()
+            .is_empty()
+    );
+}
+
+#[test]
+fn journey_segments() {
+    let hrdf = load();
+    let data_storage = hrdf.data_storage();
+    let s1 = find_journey(data_storage, 1);
+
+    let segments = s1.segments();
+    assert_eq!(segments.len(), 3);
+    assert_eq!(
+        (segments[0].from_stop_id(), segments[0].to_stop_id()),
+        (8500101, 8500102)
+    );
+    assert_eq!(segments[0].run_time(), 3);
+    assert_eq!(segments[0].dwell_time(), 0);
+    assert_eq!(segments[1].dwell_time(), 1);
+    assert_eq!(segments[1].run_time(), 6);
+
+    // The last S1 crosses midnight between Aarstadt Nord and Bergdorf.
+    let late_s1 = find_journey(data_storage, 5);
+    let segments = late_s1.segments();
+    let monday = date(15, 12, 2025);
+    assert_eq!(segments[1].run_time(), 6);
+    assert_eq!(
+        segments[1].departure_at(monday),
+        monday.and_hms_opt(23, 54, 0).unwrap()
+    );
+    assert_eq!(
+        segments[1].arrival_at(monday),
+        date(16, 12, 2025).and_hms_opt(0, 0, 0).unwrap()
+    );
+    assert_eq!(segments[2].dwell_time(), 1);
+}
+
+#[test]
+fn line_exchange_times() {
+    let hrdf = load();
+    let data_storage = hrdf.data_storage();
+    let s1 = find_journey(data_storage, 1);
+    let bus = find_journey(data_storage, 101);
+    let monday = date(15, 12, 2025);
+
+    // Bergdorf has its own line exchange time, the one from the buses to the S-Bahn applies at
+    // all the stops.
+    let exchange_times = data_storage.line_exchange_times_at(8500103);
+    assert_eq!(exchange_times.len(), 2);
+    let (specific, general) = (exchange_times[0], exchange_times[1]);
+    assert_eq!(specific.stop_id(), Some(8500103));
+    assert_eq!(specific.duration(), 4);
+    assert!(specific.is_guaranteed());
+    assert_eq!(specific.line_1().administration(), Some("000001"));
+    assert_eq!(specific.line_1().line_id(), Some("#0000001"));
+    assert_eq!(
+        specific.line_1().transport_type_id(),
+        data_storage.transport_type_id_by_designation("S")
+    );
+    assert_eq!(specific.line_2().line_id(), None);
+    assert_eq!(general.stop_id(), None);
+    assert_eq!(general.line_1().administration(), None);
+    assert_eq!(data_storage.line_exchange_times_at(8500101).len(), 1);
+
+    assert!(specific.applies_to(8500103, s1, bus, data_storage));
+    assert!(!specific.applies_to(8500103, bus, s1, data_storage));
+    assert!(general.applies_to(8500101, bus, s1, data_storage));
+    assert_eq!(data_storage.exchange_time(8500103, s1, bus, monday), 4);
+    assert_eq!(data_storage.exchange_time(8500101, bus, s1, monday), 5);
+}
+
+#[test]
+fn can_transfer() {
+    let hrdf = load();
+    let data_storage = hrdf.data_storage();
+    let s1 = find_journey(data_storage, 2);
+    let next_s1 = find_journey(data_storage, 3);
+    let bus = find_journey(data_storage, 101);
+    let weekend_bus = find_journey(data_storage, 103);
+    let monday = date(15, 12, 2025);
+
+    // 15 minutes for the 3 minutes of UMSTEIGB.
+    let transfer = data_storage
+        .can_transfer(s1, bus, 8500101, monday)
+        .unwrap()
+        .unwrap();
+    assert_eq!(transfer.transfer_time(), 15);
+    assert_eq!(transfer.slack(), 12);
+    assert!(transfer.is_feasible());
+    assert!(!transfer.is_guaranteed());
+
+    let transfer = data_storage
+        .can_transfer(s1, next_s1, 8500101, monday)
+        .unwrap()
+        .unwrap();
+    assert_eq!(transfer.slack(), -3);
+    assert!(!transfer.is_feasible());
+
+    // UMSTEIGZ guarantees the connection to the weekend bus with 10 minutes.
+    let transfer = data_storage
+        .can_transfer(s1, weekend_bus, 8500101, date(20, 12, 2025))
+        .unwrap()
+        .unwrap();
+    assert_eq!(transfer.exchange_time(), 10);
+    assert_eq!(transfer.slack(), 125);
+    assert!(transfer.is_guaranteed());
+
+    // The S1 starts at Seeburg, the weekend bus doesn't run on working days.
+    assert!(
+        data_storage
+            .can_transfer(s1, bus, 8500104, monday)
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        data_storage
+            .can_transfer(s1, weekend_bus, 8500101, monday)
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        data_storage
+            .can_transfer(s1, bus, 8500101, date(1, 1, 2030))
+            .is_err()
+    );
+}
+
+#[test]
+fn earliest_arrival_matrix() {
+    let hrdf = load();
+    let data_storage = hrdf.data_storage();
+    let departure_at = date(15, 12, 2025).and_hms_opt(6, 30, 0).unwrap();
+    let at = |hour, minute| date(15, 12, 2025).and_hms_opt(hour, minute, 0).unwrap();
+
+    let matrix = data_storage
+        .earliest_arrival_matrix(
+            &[8500104, 8500101],
+            &[8500102, 8500105, 8500104],
+            departure_at,
+            1,
+        )
+        .unwrap();
+    assert_eq!(
+        matrix.arrival_times(),
+        &[
+            vec![Some(at(6, 56)), Some(at(7, 27)), Some(departure_at)],
+            vec![Some(at(7, 3)), Some(at(7, 27)), Some(at(7, 20))],
+        ]
+    );
+    assert_eq!(matrix.travel_time(8500104, 8500105), Some(57));
+
+    // Talwil requires a transfer at Aarstadt when coming from Seeburg.
+    let matrix = data_storage
+        .earliest_arrival_matrix(&[8500104], &[8500105], departure_at, 0)
+        .unwrap();
+    assert_eq!(matrix.arrival_at(8500104, 8500105), None);
+}
+
+#[test]
+fn earliest_itinerary() {
+    let hrdf = load();
+    let data_storage = hrdf.data_storage();
+    let departure_at = date(15, 12, 2025).and_hms_opt(6, 30, 0).unwrap();
+
+    // The S1 to Aarstadt, then the bus 12 to Talwil.
+    let legs = data_storage
+        .earliest_itinerary(8500104, 8500105, departure_at, 1)
+        .unwrap()
+        .unwrap();
+    let rides: Vec<_> = legs
+        .iter()
+        .map(|leg| {
+            let journey = data_storage.journeys().find(leg.journey_id()).unwrap();
+            (
+                journey.legacy_id(),
+                leg.from_stop_id(),
+                leg.to_stop_id(),
+                leg.departure_at().time(),
+                leg.arrival_at().time(),
+            )
+        })
+        .collect();
+    let at = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+    assert_eq!(
+        rides,
+        vec![
+            (2, 8500104, 8500101, at(6, 40), at(7, 0)),
+            (101, 8500101, 8500105, at(7, 15), at(7, 27)),
+        ]
+    );
+
+    assert!(
+        data_storage
+            .earliest_itinerary(8500104, 8500105, departure_at, 0)
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[test]
+fn raw_file() {
+    let hrdf = load();
+    let lines: Vec<_> = hrdf.raw_file("ECKDATEN").unwrap().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "14.12.2025",
+            "12.12.2026",
+            "Mini timetable$01.06.2025$1.00$hrdf-parser"
+        ]
+    );
+
+    assert!(matches!(
+        hrdf.raw_file("../mini/ECKDATEN"),
+        Err(hrdf_parser::Error::InvalidFileName(_))
+    ));
+    assert!(hrdf.raw_file("UNKNOWN").is_err());
+}
+
+#[test]
+fn collect_all_parsing_errors() {
+    use hrdf_parser::{Error, ParsingOptions};
+
+    let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/mini");
+    let path = std::env::temp_dir().join("hrdf-parser-collect-all-parsing-errors");
+    std::fs::create_dir_all(&path).unwrap();
+    for entry in std::fs::read_dir(&source).unwrap() {
+        let entry = entry.unwrap();
+        std::fs::copy(entry.path(), path.join(entry.file_name())).unwrap();
+    }
+    // An invalid hexadecimal digit and an unknown transport type.
+    let bit_fields = std::fs::read_to_string(source.join("BITFELD")).unwrap();
+    std::fs::write(
+        path.join("BITFELD"),
+        bit_fields.replacen("000003 20C", "000003 2GC", 1),
+    )
+    .unwrap();
+    let fplan = std::fs::read_to_string(source.join("FPLAN")).unwrap();
+    std::fs::write(path.join("FPLAN"), fplan.replacen("*G S  ", "*G XX ", 1)).unwrap();
+
+    let error = Hrdf::from_directory(Version::V_5_40_41_2_0_7, &path).unwrap_err();
+    assert!(matches!(error, Error::Parsing { .. }));
+
+    let mut options = ParsingOptions::new();
+    options.set_collect_errors(true);
+    let error =
+        Hrdf::from_directory_with_options(Version::V_5_40_41_2_0_7, &path, options).unwrap_err();
+    let Error::ParsingErrors(errors) = error else {
+        panic!("expected all the parsing errors, got {error}");
+    };
+    let files: Vec<_> = errors
+        .iter()
+        .map(|error| match error {
+            Error::Parsing { file, .. } => file.rsplit('/').next().unwrap().to_string(),
+            _ => panic!("expected a line error, got {error}"),
+        })
+        .collect();
+    assert_eq!(files, ["BITFELD", "FPLAN"]);
+    std::fs::remove_dir_all(&path).unwrap();
+}
+
+#[test]
+fn file_rules_skip_headers_and_comments() {
+    use hrdf_parser::{FileRules, ParsingOptions};
+
+    let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/mini");
+    let path = std::env::temp_dir().join("hrdf-parser-file-rules-skip-headers-and-comments");
+    std::fs::create_dir_all(&path).unwrap();
+    for entry in std::fs::read_dir(&source).unwrap() {
+        let entry = entry.unwrap();
+        std::fs::copy(entry.path(), path.join(entry.file_name())).unwrap();
+    }
+    let eckdaten = std::fs::read_to_string(source.join("ECKDATEN")).unwrap();
+    std::fs::write(
+        path.join("ECKDATEN"),
+        format!("Timetable export\nGenerated on 01.06.2025\n{eckdaten}"),
+    )
+    .unwrap();
+    let attributes = std::fs::read_to_string(source.join("ATTRIBUT")).unwrap();
+    std::fs::write(
+        path.join("ATTRIBUT"),
+        format!(
+            "%% Attributes\n{}",
+            attributes.replacen('\n', "\n  %% Texts\n", 1)
+        ),
+    )
+    .unwrap();
+
+    assert!(Hrdf::from_directory(Version::V_5_40_41_2_0_7, &path).is_err());
+
+    let mut options = ParsingOptions::new();
+    let mut header = FileRules::new();
+    header.set_skip_lines(2);
+    options.set_file_rules("ECKDATEN", header);
+    let mut comments = FileRules::new();
+    comments.set_comment_prefix(Some("%%".to_string()));
+    options.set_file_rules("ATTRIBUT", comments);
+    let hrdf = Hrdf::from_directory_with_options(Version::V_5_40_41_2_0_7, &path, options).unwrap();
+    assert_eq!(*hrdf.timetable_period().start(), date(14, 12, 2025));
+    assert_eq!(
+        hrdf.data_storage().attributes().data().len(),
+        load().data_storage().attributes().data().len()
+    );
+    std::fs::remove_dir_all(&path).unwrap();
+}
+
+#[test]
+fn administration_scoped_parsing() {
+    use hrdf_parser::ParsingOptions;
+    use rustc_hash::FxHashSet;
+
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/mini");
+    let load_administrations = |administrations: &[&str]| {
+        let mut options = ParsingOptions::new();
+        options.set_administrations(Some(FxHashSet::from_iter(
+            administrations
+                .iter()
+                .map(|administration| administration.to_string()),
+        )));
+        Hrdf::from_directory_with_options(Version::V_5_40_41_2_0_7, &path, options).unwrap()
+    };
+
+    let hrdf = load_administrations(&["000001"]);
+    assert_eq!(hrdf.data_storage().journeys().data().len(), 6);
+    assert_eq!(hrdf.data_storage().journey_platform().data().len(), 3);
+    assert_eq!(hrdf.data_storage().platforms().data().len(), 2);
+
+    let hrdf = load_administrations(&["000011"]);
+    assert!(hrdf.data_storage().journeys().data().is_empty());
+    assert!(hrdf.data_storage().journey_platform().data().is_empty());
+    assert!(hrdf.data_storage().platforms().data().is_empty());
+    assert_eq!(hrdf.data_storage().stops().data().len(), 5);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn bytes_round_trip() {
+    let hrdf = load();
+    let decoded = Hrdf::from_bytes(&hrdf.to_bytes().unwrap()).unwrap();
+
+    assert_eq!(decoded.data_storage().journeys().data().len(), 6);
+    assert_eq!(decoded.timetable_period(), hrdf.timetable_period());
+    assert_eq!(decoded.version(), Version::V_5_40_41_2_0_7);
+
+    let error = Hrdf::from_bytes(b"not a snapshot").unwrap_err();
+    assert!(matches!(error, hrdf_parser::Error::IncompatibleSnapshot(_)));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn cache_segments() {
+    use hrdf_parser::CacheSegment;
+
+    let hrdf = load();
+    let path = std::env::temp_dir().join("hrdf-parser-cache-segments.cache");
+    hrdf.build_cache(&path).unwrap();
+
+    let geocoder = Hrdf::load_from_cache_segments(&path, &[CacheSegment::Stops]).unwrap();
+    assert_eq!(geocoder.data_storage().stops().data().len(), 5);
+    assert!(geocoder.data_storage().journeys().data().is_empty());
+    assert!(geocoder.data_storage().platforms().data().is_empty());
+    assert_eq!(geocoder.timetable_period(), hrdf.timetable_period());
+
+    let complete = Hrdf::load_from_cache(&path).unwrap();
+    assert_eq!(complete.data_storage().journeys().data().len(), 6);
+    assert!(complete.data_storage().contains_journey(1, "000001"));
+    assert_eq!(
+        complete.data_storage().attribute_id_by_code("NF"),
+        hrdf.data_storage().attribute_id_by_code("NF")
+    );
+    assert_eq!(complete.version(), Version::V_5_40_41_2_0_7);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "polars")]
+#[test]
+fn dataframes() {
+    let hrdf = load();
+    let data_storage = hrdf.data_storage();
+
+    assert_eq!(data_storage.stops_to_dataframe().unwrap().shape(), (5, 4));
+    assert_eq!(
+        data_storage.journeys_to_dataframe().unwrap().shape(),
+        (6, 7)
+    );
+    // On a Monday, the S1 journeys 1, 2, 3 and 5 have 4 stops and the bus 101 has 2 stops.
+    let stop_times = data_storage
+        .stop_times_to_dataframe(date(15, 12, 2025))
+        .unwrap();
+    assert_eq!(stop_times.shape(), (18, 5));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn dto_json() {
+    use hrdf_parser::dto::{DepartureDto, ItineraryDto, LegDto};
+
+    let hrdf = load();
+    let data_storage = hrdf.data_storage();
+    let monday = date(15, 12, 2025);
+    let from = monday.and_hms_opt(6, 0, 0).unwrap();
+    let until = monday.and_hms_opt(6, 0, 0).unwrap();
+
+    let station_board = data_storage
+        .station_board(8500101, from, until, false)
+        .unwrap();
+    let departures = DepartureDto::from_station_board(&station_board, data_storage);
+    let json = serde_json::to_value(&departures).unwrap();
+    assert_eq!(json[0]["departureAt"], "2025-12-15T06:00:00");
+    assert_eq!(json[0]["destinationName"], "Seeburg");
+    assert_eq!(json[0]["lineName"], "S1");
+    assert_eq!(json[0]["backgroundColor"], "#0066CC");
+
+    let legs = vec![
+        LegDto::new(
+            find_journey(data_storage, 2),
+            8500104,
+            8500101,
+            monday,
+            data_storage,
+        )
+        .unwrap(),
+        LegDto::new(
+            find_journey(data_storage, 101),
+            8500101,
+            8500105,
+            monday,
+            data_storage,
+        )
+        .unwrap(),
+    ];
+    let itinerary = ItineraryDto::new(legs).unwrap();
+    assert_eq!(itinerary.duration, 47);
+    assert_eq!(itinerary.transfers, 1);
+    let json = serde_json::to_value(&itinerary).unwrap();
+    assert_eq!(json["legs"][1]["toStopName"], "Talwil");
+    // The bus 101 does not serve Seeburg.
+    assert!(
+        LegDto::new(
+            find_journey(data_storage, 101),
+            8500101,
+            8500104,
+            monday,
+            data_storage
+        )
+        .is_none()
+    );
+}